@@ -0,0 +1,277 @@
+//! Toolkit diagnostics for bug reports.
+//!
+//! [`Diagnostics`] collects the Verovio version, the resource directory and
+//! its health, and the state of the loaded document into a single value that
+//! can be serialized and attached to a bug report. [`LayoutSummary`] is a
+//! smaller snapshot of the current layout, suited to an editor status line.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::{BreakMode, Error, Result};
+
+/// The font Verovio uses to build its glyph name table.
+pub(crate) const BASELINE_FONT: &str = "Bravura";
+
+/// Text font definitions Verovio loads on startup.
+const TEXT_RESOURCES: &[&str] = &["text/Times.xml"];
+
+/// A snapshot of a toolkit's configuration and state.
+///
+/// # Example
+///
+/// ```no_run
+/// use verovioxide::Toolkit;
+///
+/// let toolkit = Toolkit::new().expect("Failed to create toolkit");
+/// let diagnostics = toolkit.diagnostics();
+/// println!("{}", serde_json::to_string_pretty(&diagnostics).unwrap());
+/// ```
+///
+/// # See also
+///
+/// - [`Toolkit::diagnostics`](crate::Toolkit::diagnostics) - Collect diagnostics
+/// - [`Toolkit::verify_resources`](crate::Toolkit::verify_resources) - Check the resource directory
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Diagnostics {
+    /// The Verovio version string.
+    pub verovio_version: String,
+    /// The resource directory, or `None` if the toolkit has none.
+    pub resource_path: Option<PathBuf>,
+    /// The fonts installed in the resource directory.
+    pub available_fonts: Vec<String>,
+    /// The number of pages in the loaded document.
+    pub page_count: u32,
+    /// Whether a document is loaded.
+    pub is_loaded: bool,
+    /// Required resources missing from the resource directory.
+    pub missing_resources: Vec<String>,
+}
+
+/// A summary of the current layout of a loaded document.
+///
+/// # See also
+///
+/// - [`Toolkit::layout_summary`](crate::Toolkit::layout_summary) - Compute the summary
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LayoutSummary {
+    /// The number of pages.
+    pub pages: u32,
+    /// The number of systems across all pages.
+    pub systems: u32,
+    /// The number of measures.
+    pub measures: u32,
+    /// The rendering scale as a percentage.
+    pub scale: i32,
+    /// The break mode, or `None` if it cannot be read from the options.
+    pub breaks: Option<BreakMode>,
+}
+
+/// Returns the fonts installed in `dir`, sorted by name.
+///
+/// A font is installed when both its `<name>.xml` definition and its `<name>/`
+/// glyph directory are present.
+pub(crate) fn installed_fonts(dir: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut fonts: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "xml") && path.is_file())
+        .filter_map(|path| {
+            let name = path.file_stem()?.to_str()?.to_string();
+            dir.join(&name).is_dir().then_some(name)
+        })
+        .collect();
+    fonts.sort();
+    fonts
+}
+
+/// Returns the resources required to render with `font` that are missing
+/// from `dir`, as paths relative to `dir`.
+pub(crate) fn missing_resources(dir: &Path, font: &str) -> Vec<String> {
+    let mut required = vec![
+        format!("{}.xml", BASELINE_FONT),
+        format!("{}/", BASELINE_FONT),
+    ];
+    if font != BASELINE_FONT {
+        required.push(format!("{}.xml", font));
+        required.push(format!("{}/", font));
+    }
+    required.extend(TEXT_RESOURCES.iter().map(|s| s.to_string()));
+
+    required
+        .into_iter()
+        .filter(|resource| match resource.strip_suffix('/') {
+            Some(directory) => !dir.join(directory).is_dir(),
+            None => !dir.join(resource).is_file(),
+        })
+        .collect()
+}
+
+/// Returns the glyphs defined by `font` in `dir`, mapping SMuFL code points
+/// (e.g. `E0A4`) to glyph names (e.g. `noteheadBlack`).
+pub(crate) fn font_glyphs(dir: &Path, font: &str) -> Result<BTreeMap<String, String>> {
+    let path = dir.join(format!("{}.xml", font));
+    let xml = fs::read_to_string(&path)?;
+    let doc = roxmltree::Document::parse(&xml).map_err(|e| {
+        Error::RenderError(format!("invalid font definition {}: {}", path.display(), e))
+    })?;
+
+    Ok(doc
+        .descendants()
+        .filter(|node| node.has_tag_name("g"))
+        .filter_map(|node| {
+            let code = node.attribute("c")?;
+            let name = node.attribute("n").unwrap_or(code);
+            Some((code.to_string(), name.to_string()))
+        })
+        .collect())
+}
+
+/// Returns the names of the glyphs in `used` that `font` does not define, in
+/// code point order.
+///
+/// Names are looked up in the baseline font; code points it does not know
+/// either are returned as is.
+pub(crate) fn missing_glyphs(
+    dir: &Path,
+    font: &str,
+    used: &BTreeSet<String>,
+) -> Result<Vec<String>> {
+    let available = font_glyphs(dir, font)?;
+    let names = font_glyphs(dir, BASELINE_FONT).unwrap_or_default();
+
+    Ok(used
+        .iter()
+        .filter(|code| !available.contains_key(*code))
+        .map(|code| names.get(code).unwrap_or(code).clone())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resource_dir(entries: &[&str]) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().expect("Failed to create temp dir");
+        for entry in entries {
+            let path = dir.path().join(entry);
+            if let Some(directory) = entry.strip_suffix('/') {
+                fs::create_dir_all(dir.path().join(directory)).unwrap();
+            } else {
+                fs::create_dir_all(path.parent().unwrap()).unwrap();
+                fs::write(path, "<bBoxes/>").unwrap();
+            }
+        }
+        dir
+    }
+
+    #[test]
+    fn test_installed_fonts() {
+        let dir = resource_dir(&[
+            "Bravura.xml",
+            "Bravura/",
+            "Leipzig.xml",
+            "Leipzig/",
+            "Orphan.xml",
+            "text/Times.xml",
+        ]);
+        assert_eq!(installed_fonts(dir.path()), vec!["Bravura", "Leipzig"]);
+    }
+
+    #[test]
+    fn test_installed_fonts_missing_dir() {
+        assert!(installed_fonts(Path::new("/nonexistent/verovio/data")).is_empty());
+    }
+
+    #[test]
+    fn test_missing_resources_complete() {
+        let dir = resource_dir(&[
+            "Bravura.xml",
+            "Bravura/",
+            "Leipzig.xml",
+            "Leipzig/",
+            "text/Times.xml",
+        ]);
+        assert!(missing_resources(dir.path(), "Leipzig").is_empty());
+        assert!(missing_resources(dir.path(), "Bravura").is_empty());
+    }
+
+    #[test]
+    fn test_missing_resources_reports_missing() {
+        let dir = resource_dir(&["Bravura.xml", "Leipzig.xml"]);
+        assert_eq!(
+            missing_resources(dir.path(), "Leipzig"),
+            vec!["Bravura/", "Leipzig/", "text/Times.xml"]
+        );
+    }
+
+    fn font_dir(fonts: &[(&str, &str)]) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().expect("Failed to create temp dir");
+        for (font, glyphs) in fonts {
+            let xml = format!(r#"<bBoxes font-family="{}">{}</bBoxes>"#, font, glyphs);
+            fs::write(dir.path().join(format!("{}.xml", font)), xml).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn test_font_glyphs() {
+        let dir = font_dir(&[(
+            "Mini",
+            r#"<g c="E0A3" n="noteheadHalf"/><g c="E0A4" n="noteheadBlack"><a n="stemUpSE"/></g>"#,
+        )]);
+        let glyphs = font_glyphs(dir.path(), "Mini").unwrap();
+        assert_eq!(glyphs.len(), 2);
+        assert_eq!(glyphs["E0A4"], "noteheadBlack");
+
+        assert!(matches!(
+            font_glyphs(dir.path(), "Missing"),
+            Err(Error::IoError(_))
+        ));
+    }
+
+    #[test]
+    fn test_missing_glyphs() {
+        let dir = font_dir(&[
+            (
+                "Bravura",
+                r#"<g c="E050" n="gClef"/><g c="E0A3" n="noteheadHalf"/><g c="E0A4" n="noteheadBlack"/>"#,
+            ),
+            ("Mini", r#"<g c="E050" n="gClef"/>"#),
+        ]);
+        let used: BTreeSet<String> = ["E050", "E0A4", "F400"].map(String::from).into();
+
+        assert_eq!(
+            missing_glyphs(dir.path(), "Mini", &used).unwrap(),
+            vec!["noteheadBlack", "F400"]
+        );
+        assert_eq!(
+            missing_glyphs(dir.path(), "Bravura", &used).unwrap(),
+            vec!["F400"]
+        );
+    }
+
+    #[test]
+    fn test_diagnostics_serialize() {
+        let diagnostics = Diagnostics {
+            verovio_version: "5.0.0".into(),
+            resource_path: Some(PathBuf::from("/data")),
+            available_fonts: vec!["Leipzig".into()],
+            page_count: 2,
+            is_loaded: true,
+            missing_resources: Vec::new(),
+        };
+        let value = serde_json::to_value(&diagnostics).unwrap();
+        assert_eq!(value["verovio_version"], "5.0.0");
+        assert_eq!(value["resource_path"], "/data");
+        assert_eq!(value["page_count"], 2);
+        assert_eq!(value["is_loaded"], true);
+    }
+}
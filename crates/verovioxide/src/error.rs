@@ -21,12 +21,14 @@ pub enum Error {
     /// This typically occurs when:
     /// - Memory allocation fails
     /// - Resource initialization fails
+    /// - Rendering is requested from a toolkit without resources
     ///
     /// # Returned by
     ///
     /// - [`Toolkit::new`](crate::Toolkit::new)
     /// - [`Toolkit::with_resource_path`](crate::Toolkit::with_resource_path)
     /// - [`Toolkit::without_resources`](crate::Toolkit::without_resources)
+    /// - [`Toolkit::render_to_svg`](crate::Toolkit::render_to_svg), when the toolkit has no resources
     #[error("failed to initialize toolkit: {0}")]
     InitializationError(String),
 
@@ -103,12 +105,28 @@ pub enum Error {
     /// # Returned by
     ///
     /// - [`Toolkit::load_file`](crate::Toolkit::load_file)
+    /// - [`Toolkit::set_svg_css_file`](crate::Toolkit::set_svg_css_file)
     #[error("file not found: {}", .0.display())]
     FileNotFound(PathBuf),
 
-    /// A string contained invalid UTF-8.
-    #[error("invalid UTF-8 in string")]
-    InvalidUtf8,
+    /// A path or string contained invalid UTF-8.
+    ///
+    /// Verovio only accepts UTF-8 paths, and its output is expected to be
+    /// UTF-8. `context` names what was being converted, e.g. `"resource path"`
+    /// or `"SVG output"`.
+    ///
+    /// # Returned by
+    ///
+    /// - [`Toolkit::with_resource_path`](crate::Toolkit::with_resource_path)
+    /// - [`Toolkit::load_file`](crate::Toolkit::load_file)
+    /// - [`Toolkit::load_reader`](crate::Toolkit::load_reader)
+    /// - Methods returning Verovio output, such as
+    ///   [`Toolkit::render_to_svg`](crate::Toolkit::render_to_svg)
+    #[error("invalid UTF-8 in {context}")]
+    InvalidUtf8 {
+        /// What contained the invalid UTF-8.
+        context: String,
+    },
 
     /// A string contained a null byte.
     ///
@@ -161,8 +179,10 @@ mod tests {
 
     #[test]
     fn test_error_display_invalid_utf8() {
-        let err = Error::InvalidUtf8;
-        assert_eq!(err.to_string(), "invalid UTF-8 in string");
+        let err = Error::InvalidUtf8 {
+            context: "resource path".into(),
+        };
+        assert_eq!(err.to_string(), "invalid UTF-8 in resource path");
     }
 
     #[test]
@@ -246,8 +246,9 @@
 //!
 //! - **Create separate toolkit instances** for each thread
 //! - **Each instance needs its own loaded document copy**
-//! - For rendering different pages of the same document concurrently, you would
-//!   need multiple toolkits each with the document loaded
+//! - For rendering different pages of the same document concurrently, use
+//!   [`Toolkit::render_all_pages_parallel`], which loads the document into one
+//!   toolkit per worker thread
 //!
 //! ## Memory Usage
 //!
@@ -256,30 +257,51 @@
 //! - SVG output strings can be large; consider streaming to files for big documents
 //! - Dropping a toolkit releases all associated memory and temporary files
 
+mod diagnostics;
 mod error;
+mod mei;
+mod midi;
+mod musicxml;
 mod options;
 mod query;
 mod render;
+mod schema;
+mod svg;
 mod toolkit;
+mod validation;
 
+pub use diagnostics::{Diagnostics, LayoutSummary};
 pub use error::{Error, Result};
+pub use mei::{DocumentMetadata, TextKind, TextSpan, TranspositionResult};
 pub use options::{
-    BreakMode, CondenseMode, FooterMode, HeaderMode, Options, OptionsBuilder, TextFont,
+    BreakMode, CondenseMode, Font, FooterMode, HeaderMode, Options, OptionsBuilder,
+    OptionsValidationError, PedalStyle, ScaleMode, TextFont,
 };
 pub use query::{
     Attrs, Elements, ExpansionIds, Features, FeaturesOptionsBuilder, MidiValues, NotatedId, Page,
     QueryOutput, Time, Times,
 };
 pub use render::{
-    ExpansionMap, Humdrum, Mei, MeiOptionsBuilder, Midi, Pae, RenderOutput, RenderSpec, Svg,
-    SvgAllPages, SvgPage, SvgPages, Timemap, TimemapOptionsBuilder,
+    ExpansionMap, Humdrum, Mei, MeiOptionsBuilder, Midi, OutputFormat, Pae, RenderOutput,
+    RenderSpec, Svg, SvgAllPages, SvgPage, SvgPages, Timemap, TimemapOptionsBuilder,
 };
-pub use toolkit::{LoadSource, Toolkit, ZipBase64, ZipBuffer};
+pub use schema::{OptionDescriptor, OptionKind};
+pub use svg::{CoordinateMapper, DARK_THEME_CSS, ElementAnchor, OptimizeLevel, ViewBox};
+pub use toolkit::{LoadSource, PageBundle, PageCursor, Toolkit, ZipBase64, ZipBuffer};
+pub use validation::{Severity, ValidationIssue};
 
 // PNG exports (feature-gated)
 #[cfg(feature = "png")]
 pub use render::{Png, PngAllPages, PngOptions, PngPage, PngPages};
 
+// PDF exports (feature-gated)
+#[cfg(feature = "pdf")]
+pub use render::Pdf;
+
+// Test helper exports (feature-gated)
+#[cfg(any(test, feature = "test-utils"))]
+pub use svg::GoldenResult;
+
 // Re-export data crate types when bundled-data feature is enabled
 #[cfg(feature = "bundled-data")]
 pub use verovioxide_data::{
@@ -303,6 +325,28 @@ mod tests {
         let _ = std::any::type_name::<FooterMode>();
         let _ = std::any::type_name::<HeaderMode>();
         let _ = std::any::type_name::<TextFont>();
+        let _ = std::any::type_name::<Font>();
+        let _ = std::any::type_name::<ScaleMode>();
+        let _ = std::any::type_name::<PedalStyle>();
+        let _ = std::any::type_name::<ViewBox>();
+        let _ = std::any::type_name::<OptimizeLevel>();
+        let _ = std::any::type_name::<OutputFormat>();
+        let _ = std::any::type_name::<Diagnostics>();
+        let _ = std::any::type_name::<LayoutSummary>();
+        let _ = std::any::type_name::<PageBundle>();
+        let _ = std::any::type_name::<PageCursor>();
+        let _ = std::any::type_name::<ElementAnchor>();
+        let _ = std::any::type_name::<CoordinateMapper>();
+        let _ = std::any::type_name::<DocumentMetadata>();
+        let _ = std::any::type_name::<TextSpan>();
+        let _ = std::any::type_name::<TextKind>();
+        let _ = std::any::type_name::<TranspositionResult>();
+        let _ = std::any::type_name::<OptionsValidationError>();
+        let _ = std::any::type_name::<OptionDescriptor>();
+        let _ = std::any::type_name::<OptionKind>();
+        let _ = std::any::type_name::<ValidationIssue>();
+        let _ = std::any::type_name::<Severity>();
+        let _ = std::any::type_name::<GoldenResult>();
     }
 
     #[test]
@@ -0,0 +1,1174 @@
+//! MEI inspection helpers.
+//!
+//! This module contains helpers for inspecting MEI exported by Verovio,
+//! parsed with [`roxmltree`].
+
+use serde::Serialize;
+
+use crate::{Error, Result};
+
+/// The MEI namespace.
+const MEI_NS: &str = "http://www.music-encoding.org/ns/mei";
+
+/// The XML namespace of the `xml:id` attribute.
+const XML_NS: &str = "http://www.w3.org/XML/1998/namespace";
+
+/// Parses an MEI document, mapping parse failures to [`Error::RenderError`].
+pub(crate) fn parse(mei: &str) -> Result<roxmltree::Document<'_>> {
+    roxmltree::Document::parse(mei)
+        .map_err(|e| Error::RenderError(format!("failed to parse MEI: {}", e)))
+}
+
+/// Returns the `xml:id`s of all `<measure>` elements in document order.
+///
+/// Measures without an `xml:id` are skipped.
+pub(crate) fn measure_ids(mei: &str) -> Result<Vec<String>> {
+    let doc = parse(mei)?;
+    Ok(doc
+        .descendants()
+        .filter(|node| node.has_tag_name("measure"))
+        .filter_map(|node| node.attribute((XML_NS, "id")))
+        .map(String::from)
+        .collect())
+}
+
+/// Counts the elements named in `names` that have no `xml:id`.
+pub(crate) fn elements_without_id(mei: &str, names: &[&str]) -> Result<usize> {
+    let doc = parse(mei)?;
+    Ok(doc
+        .descendants()
+        .filter(|node| node.is_element() && names.contains(&node.tag_name().name()))
+        .filter(|node| !node.has_attribute((XML_NS, "id")))
+        .count())
+}
+
+/// Returns the `xml:id` of the measure with the given number.
+///
+/// The number is the measure's `@n`; measures without `@n` are numbered by
+/// their position among all measures, starting at 1.
+pub(crate) fn measure_id_for_number(mei: &str, number: u32) -> Result<Option<String>> {
+    let doc = parse(mei)?;
+    Ok(doc
+        .descendants()
+        .filter(|node| node.has_tag_name("measure"))
+        .zip(1..)
+        .find(|(node, position)| match node.attribute("n") {
+            Some(n) => n.trim().parse() == Ok(number),
+            None => *position == number,
+        })
+        .and_then(|(node, _)| node.attribute((XML_NS, "id")))
+        .map(String::from))
+}
+
+/// Title, authorship and rights information from an MEI header.
+///
+/// Fields are `None` when the header does not contain them.
+///
+/// # See also
+///
+/// - [`Toolkit::metadata`](crate::Toolkit::metadata) - Read the metadata of the loaded document
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct DocumentMetadata {
+    /// The first title in `<fileDesc>/<titleStmt>`.
+    pub title: Option<String>,
+    /// The composer named in the title statement.
+    pub composer: Option<String>,
+    /// The lyricist named in the title statement.
+    pub lyricist: Option<String>,
+    /// The rights statement in `<pubStmt>/<availability>`.
+    pub copyright: Option<String>,
+}
+
+/// Reads the [`DocumentMetadata`] from the `<fileDesc>` of an MEI header.
+///
+/// Composers and lyricists are read from `<composer>`/`<lyricist>` children of
+/// `<titleStmt>`, or from any element inside it whose `@role` is `composer` or
+/// `lyricist`, as in `<respStmt><persName role="composer">`. Text is joined
+/// with whitespace collapsed, and empty values are reported as `None`.
+pub(crate) fn metadata(mei: &str) -> Result<DocumentMetadata> {
+    let doc = parse(mei)?;
+    let Some(file_desc) = doc.descendants().find(|node| node.has_tag_name("fileDesc")) else {
+        return Ok(DocumentMetadata::default());
+    };
+
+    let non_empty = |text: String| Some(text).filter(|text| !text.is_empty());
+    let title_stmt = file_desc
+        .children()
+        .find(|node| node.has_tag_name("titleStmt"));
+    let person = |role: &str| {
+        let title_stmt = title_stmt?;
+        title_stmt
+            .children()
+            .find(|node| node.has_tag_name(role))
+            .or_else(|| {
+                title_stmt
+                    .descendants()
+                    .find(|node| node.attribute("role") == Some(role))
+            })
+            .map(text_content)
+            .and_then(non_empty)
+    };
+
+    Ok(DocumentMetadata {
+        title: title_stmt
+            .and_then(|node| node.children().find(|node| node.has_tag_name("title")))
+            .map(text_content)
+            .and_then(non_empty),
+        composer: person("composer"),
+        lyricist: person("lyricist"),
+        copyright: file_desc
+            .children()
+            .filter(|node| node.has_tag_name("pubStmt"))
+            .flat_map(|node| node.children())
+            .find(|node| node.has_tag_name("availability"))
+            .map(text_content)
+            .and_then(non_empty),
+    })
+}
+
+/// The outcome of [`Toolkit::transpose`](crate::Toolkit::transpose).
+///
+/// # See also
+///
+/// - [`Toolkit::transpose`](crate::Toolkit::transpose) - Transpose the loaded document
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TranspositionResult {
+    /// The interval the document was transposed by, as passed to Verovio.
+    pub interval: String,
+    /// The first key signature of the transposed document as a position on the
+    /// circle of fifths: sharps are positive, flats negative.
+    pub fifths: Option<i32>,
+    /// The key of the transposed document, such as `G` or `F#m`.
+    ///
+    /// `None` when the document has no key signature. The key is major unless
+    /// the key signature's mode is minor.
+    pub new_key: Option<String>,
+}
+
+/// Major keys by number of sharps (positive) or flats (negative), from 7 flats
+/// to 7 sharps.
+const MAJOR_KEYS: [&str; 15] = [
+    "Cb", "Gb", "Db", "Ab", "Eb", "Bb", "F", "C", "G", "D", "A", "E", "B", "F#", "C#",
+];
+
+/// Minor keys by number of sharps (positive) or flats (negative), from 7 flats
+/// to 7 sharps.
+const MINOR_KEYS: [&str; 15] = [
+    "Ab", "Eb", "Bb", "F", "C", "G", "D", "A", "E", "B", "F#", "C#", "G#", "D#", "A#",
+];
+
+/// Reads the first key signature of a document.
+///
+/// The signature comes from the first `<keySig>` element or `@keysig`/
+/// `@key.sig` attribute in document order, and is returned as a circle of
+/// fifths position with the mode (`major`, `minor`, ...) if one is given.
+pub(crate) fn key_signature(mei: &str) -> Result<Option<(i32, Option<String>)>> {
+    let doc = parse(mei)?;
+    for node in doc.descendants().filter(|node| node.is_element()) {
+        let (sig, mode) = if node.has_tag_name("keySig") {
+            (node.attribute("sig"), node.attribute("mode"))
+        } else if let Some(sig) = node.attribute("keysig").or(node.attribute("key.sig")) {
+            (Some(sig), node.attribute("key.mode"))
+        } else {
+            continue;
+        };
+
+        let Some(fifths) = sig.and_then(parse_fifths) else {
+            continue;
+        };
+        return Ok(Some((fifths, mode.map(String::from))));
+    }
+    Ok(None)
+}
+
+/// Parses a key signature value such as `0`, `2s` or `3f`.
+fn parse_fifths(sig: &str) -> Option<i32> {
+    let sig = sig.trim();
+    if sig == "0" {
+        return Some(0);
+    }
+    if let Some(sharps) = sig.strip_suffix('s') {
+        sharps.parse().ok()
+    } else {
+        sig.strip_suffix('f')?.parse().ok().map(|flats: i32| -flats)
+    }
+}
+
+/// Names the key with `fifths` sharps or flats, such as `G` or `Em`.
+///
+/// Returns `None` if `fifths` is outside -7..=7.
+pub(crate) fn key_name(fifths: i32, minor: bool) -> Option<String> {
+    let keys = if minor { &MINOR_KEYS } else { &MAJOR_KEYS };
+    let key = keys.get(usize::try_from(fifths + 7).ok()?)?;
+    Some(if minor {
+        format!("{}m", key)
+    } else {
+        key.to_string()
+    })
+}
+
+/// Returns the text of `node` and its descendants with whitespace collapsed.
+fn text_content(node: roxmltree::Node<'_, '_>) -> String {
+    node.descendants()
+        .filter_map(|node| node.text().filter(|_| node.is_text()))
+        .flat_map(str::split_whitespace)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// What a [`TextSpan`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TextKind {
+    /// A lyric syllable (`<syl>`).
+    Lyric,
+    /// A dynamic marking (`<dynam>`), such as `mf` or `cresc.`.
+    Dynamic,
+    /// A tempo indication (`<tempo>`).
+    Tempo,
+    /// Other text on the score: directions, harmony labels, rehearsal marks,
+    /// fingerings and anchored text.
+    Other,
+}
+
+/// A piece of text in the music of a document.
+///
+/// # See also
+///
+/// - [`Toolkit::extract_text`](crate::Toolkit::extract_text) - Extract the text of the loaded document
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TextSpan {
+    /// What the text is.
+    pub kind: TextKind,
+    /// The text, with whitespace collapsed and SMuFL glyphs replaced by their
+    /// Unicode equivalents.
+    pub text: String,
+    /// The xml:id of the element holding the text, or empty if it has none.
+    pub element_id: String,
+}
+
+/// SMuFL code points that have a Unicode equivalent, as used in text such as
+/// tempo markings (`♩ = 60`).
+const SMUFL_TO_UNICODE: &[(char, char)] = &[
+    ('\u{E1D2}', '\u{1D15D}'), // metNoteWhole
+    ('\u{E1D3}', '\u{1D15E}'), // metNoteHalfUp
+    ('\u{E1D5}', '\u{1D15F}'), // metNoteQuarterUp
+    ('\u{E1D7}', '\u{1D160}'), // metNote8thUp
+    ('\u{E1D9}', '\u{1D161}'), // metNote16thUp
+    ('\u{E1E7}', '.'),         // metAugmentationDot
+    ('\u{E260}', '\u{266D}'),  // accidentalFlat
+    ('\u{E261}', '\u{266E}'),  // accidentalNatural
+    ('\u{E262}', '\u{266F}'),  // accidentalSharp
+];
+
+/// Replaces SMuFL glyphs in `text` with their Unicode equivalents and drops
+/// other characters of the Private Use Area, which only render in music fonts.
+fn smufl_to_unicode(text: &str) -> String {
+    text.chars()
+        .filter_map(
+            |c| match SMUFL_TO_UNICODE.iter().find(|(smufl, _)| *smufl == c) {
+                Some((_, unicode)) => Some(*unicode),
+                None if ('\u{E000}'..='\u{F8FF}').contains(&c) => None,
+                None => Some(c),
+            },
+        )
+        .collect()
+}
+
+/// Returns the text of the `<music>` part of a document, in document order.
+///
+/// Each `<syl>`, `<dynam>`, `<tempo>`, `<dir>`, `<harm>`, `<reh>`, `<fing>` and
+/// `<anchoredText>` yields one span; spans whose text is empty are skipped.
+pub(crate) fn text_spans(mei: &str) -> Result<Vec<TextSpan>> {
+    let doc = parse(mei)?;
+    let Some(music) = doc.descendants().find(|node| node.has_tag_name("music")) else {
+        return Ok(Vec::new());
+    };
+
+    Ok(music
+        .descendants()
+        .filter_map(|node| {
+            let kind = match node.tag_name().name() {
+                "syl" => TextKind::Lyric,
+                "dynam" => TextKind::Dynamic,
+                "tempo" => TextKind::Tempo,
+                "dir" | "harm" | "reh" | "fing" | "anchoredText" => TextKind::Other,
+                _ => return None,
+            };
+            let text = smufl_to_unicode(&text_content(node))
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ");
+            (!text.is_empty()).then(|| TextSpan {
+                kind,
+                text,
+                element_id: node
+                    .attribute((XML_NS, "id"))
+                    .unwrap_or_default()
+                    .to_string(),
+            })
+        })
+        .collect())
+}
+
+/// Returns the number of `<measure>` elements in the document.
+pub(crate) fn measure_count(mei: &str) -> Result<usize> {
+    let doc = parse(mei)?;
+    Ok(doc
+        .descendants()
+        .filter(|node| node.has_tag_name("measure"))
+        .count())
+}
+
+/// Elements that put sounding or silent events on a staff.
+const EVENT_ELEMENTS: &[&str] = &["note", "rest", "mRest", "multiRest"];
+
+/// Returns `true` if the document contains at least one note or rest.
+pub(crate) fn has_events(mei: &str) -> Result<bool> {
+    let doc = parse(mei)?;
+    Ok(doc
+        .descendants()
+        .any(|node| node.is_element() && EVENT_ELEMENTS.contains(&node.tag_name().name())))
+}
+
+/// Returns the number of `<system>` and `<measure>` elements in the document.
+///
+/// Systems are only present in page-based MEI.
+pub(crate) fn layout_counts(mei: &str) -> Result<(usize, usize)> {
+    let doc = parse(mei)?;
+    Ok(doc.descendants().fold((0, 0), |(systems, measures), node| {
+        if node.has_tag_name("system") {
+            (systems + 1, measures)
+        } else if node.has_tag_name("measure") {
+            (systems, measures + 1)
+        } else {
+            (systems, measures)
+        }
+    }))
+}
+
+/// Inserts the `<measure>` elements of `fragment` at the end of the music in
+/// `mei`, returning the new document.
+///
+/// The measures go into the outermost `<section>`, after the child holding
+/// the last measure, so a document ending with an `<ending>` or a nested
+/// section is extended after it rather than inside it.
+///
+/// The fragment is parsed in the MEI namespace and must consist of one or more
+/// `<measure>` elements; anything else is rejected with [`Error::LoadError`].
+pub(crate) fn append_measures(mei: &str, fragment: &str) -> Result<String> {
+    let invalid = |reason: String| Error::LoadError(format!("invalid MEI fragment: {}", reason));
+
+    let wrapped = format!(r#"<section xmlns="{}">{}</section>"#, MEI_NS, fragment);
+    let fragment_doc = roxmltree::Document::parse(&wrapped).map_err(|e| invalid(e.to_string()))?;
+
+    let mut measures = 0;
+    for child in fragment_doc.root_element().children() {
+        if child.is_element() {
+            if !child.has_tag_name((MEI_NS, "measure")) {
+                return Err(invalid(format!(
+                    "expected <measure>, found <{}>",
+                    child.tag_name().name()
+                )));
+            }
+            measures += 1;
+        } else if child.is_text() && !child.text().unwrap_or_default().trim().is_empty() {
+            return Err(invalid("unexpected text outside <measure>".into()));
+        }
+    }
+    if measures == 0 {
+        return Err(invalid("no <measure> elements".into()));
+    }
+
+    let doc = parse(mei)?;
+    let last = doc
+        .descendants()
+        .rfind(|node| node.has_tag_name("measure"))
+        .ok_or_else(|| Error::LoadError("document has no measures to append to".into()))?;
+    let is_outermost_section = |node: roxmltree::Node<'_, '_>| {
+        node.has_tag_name("section")
+            && !node
+                .ancestors()
+                .skip(1)
+                .any(|ancestor| ancestor.has_tag_name("section"))
+    };
+    let end = last
+        .ancestors()
+        .find(|node| node.parent().is_some_and(is_outermost_section))
+        .unwrap_or(last)
+        .range()
+        .end;
+
+    Ok(format!("{}{}{}", &mei[..end], fragment, &mei[end..]))
+}
+
+/// Inserts `element` immediately before the `<measure>` with the given
+/// `xml:id`, returning the new document.
+///
+/// # Errors
+///
+/// Returns [`Error::RenderError`] if no measure has the given `xml:id`.
+pub(crate) fn insert_before_measure(mei: &str, measure_id: &str, element: &str) -> Result<String> {
+    let doc = parse(mei)?;
+    let start = doc
+        .descendants()
+        .find(|node| {
+            node.has_tag_name("measure") && node.attribute((XML_NS, "id")) == Some(measure_id)
+        })
+        .map(|node| node.range().start)
+        .ok_or_else(|| Error::RenderError(format!("measure not found: {}", measure_id)))?;
+
+    Ok(format!("{}{}{}", &mei[..start], element, &mei[start..]))
+}
+
+/// Sets the first title in the header's `<fileDesc>/<titleStmt>`, returning
+/// the new document.
+///
+/// An existing title keeps its attributes and has its content replaced;
+/// otherwise a `<title>` is inserted at the start of the title statement.
+pub(crate) fn set_title(mei: &str, title: &str) -> Result<String> {
+    let text = crate::svg::escape_text(title);
+    edit_title_stmt(
+        mei,
+        &format!("<title>{}</title>", text),
+        |title_stmt| match title_stmt
+            .children()
+            .find(|node| node.has_tag_name("title"))
+        {
+            Some(node) => replace_content(mei, node, &text),
+            None => insert_first_child(mei, title_stmt, &format!("<title>{}</title>", text)),
+        },
+    )
+}
+
+/// Sets the person with the given `@role` in the header's title statement,
+/// returning the new document.
+///
+/// A `<composer>`/`<lyricist>` child of `<titleStmt>` or an element with the
+/// matching `@role` inside it has its content replaced; otherwise
+/// `<respStmt><persName role="...">` is appended to the title statement.
+pub(crate) fn set_person(mei: &str, role: &str, name: &str) -> Result<String> {
+    let text = crate::svg::escape_text(name);
+    let resp_stmt = format!(
+        r#"<respStmt><persName role="{}">{}</persName></respStmt>"#,
+        role, text
+    );
+    edit_title_stmt(mei, &resp_stmt, |title_stmt| {
+        let existing = title_stmt
+            .children()
+            .find(|node| node.has_tag_name(role))
+            .or_else(|| {
+                title_stmt
+                    .descendants()
+                    .find(|node| node.attribute("role") == Some(role))
+            });
+        match existing {
+            Some(node) => replace_content(mei, node, &text),
+            None => insert_last_child(mei, title_stmt, &resp_stmt),
+        }
+    })
+}
+
+/// Applies `edit` to the `<titleStmt>` of the header.
+///
+/// A document without `<meiHead>` gets a minimal header containing
+/// `title_stmt_content`, inserted before `<music>`.
+fn edit_title_stmt(
+    mei: &str,
+    title_stmt_content: &str,
+    edit: impl FnOnce(roxmltree::Node<'_, '_>) -> String,
+) -> Result<String> {
+    let doc = parse(mei)?;
+    if let Some(title_stmt) = doc
+        .descendants()
+        .filter(|node| node.has_tag_name("titleStmt"))
+        .find(|node| node.parent().is_some_and(|p| p.has_tag_name("fileDesc")))
+    {
+        return Ok(edit(title_stmt));
+    }
+    if doc.descendants().any(|node| node.has_tag_name("meiHead")) {
+        return Err(Error::RenderError(
+            "MEI header has no title statement".into(),
+        ));
+    }
+
+    let music = doc
+        .descendants()
+        .find(|node| node.has_tag_name("music"))
+        .ok_or_else(|| Error::RenderError("MEI document has no <music> element".into()))?;
+    let start = music.range().start;
+    Ok(format!(
+        "{}<meiHead><fileDesc><titleStmt>{}</titleStmt><pubStmt/></fileDesc></meiHead>{}",
+        &mei[..start],
+        title_stmt_content,
+        &mei[start..]
+    ))
+}
+
+/// Replaces the content of `node` with `content`, keeping its start tag.
+fn replace_content(mei: &str, node: roxmltree::Node<'_, '_>, content: &str) -> String {
+    let range = node.range();
+    let element = &mei[range.clone()];
+    match element.strip_suffix("/>") {
+        Some(start_tag) => {
+            let name_end = element[1..]
+                .find(|c: char| c.is_whitespace() || c == '/' || c == '>')
+                .map_or(element.len(), |offset| offset + 1);
+            format!(
+                "{}{}>{}</{}>{}",
+                &mei[..range.start],
+                start_tag.trim_end(),
+                content,
+                &element[1..name_end],
+                &mei[range.end..]
+            )
+        }
+        None => {
+            let open_end = range.start + element.find('>').map_or(0, |i| i + 1);
+            let close_start = range.start + element.rfind("</").unwrap_or(element.len());
+            format!("{}{}{}", &mei[..open_end], content, &mei[close_start..])
+        }
+    }
+}
+
+/// Inserts `content` as the first child of `node`.
+fn insert_first_child(mei: &str, node: roxmltree::Node<'_, '_>, content: &str) -> String {
+    let element = &mei[node.range()];
+    if element.ends_with("/>") {
+        return replace_content(mei, node, content);
+    }
+    let open_end = node.range().start + element.find('>').map_or(0, |i| i + 1);
+    format!("{}{}{}", &mei[..open_end], content, &mei[open_end..])
+}
+
+/// Inserts `content` as the last child of `node`.
+fn insert_last_child(mei: &str, node: roxmltree::Node<'_, '_>, content: &str) -> String {
+    let element = &mei[node.range()];
+    if element.ends_with("/>") {
+        return replace_content(mei, node, content);
+    }
+    let close_start = node.range().start + element.rfind("</").unwrap_or(element.len());
+    format!("{}{}{}", &mei[..close_start], content, &mei[close_start..])
+}
+
+/// Gives every staff its own MIDI channel, returning the new document.
+///
+/// Staves are taken in the order of their first `<staffDef>`. A staff whose
+/// definition already has an `<instrDef>` with `@midi.channel` keeps it; every
+/// other staff gets an `<instrDef>` with the lowest channel not yet in use.
+/// Channel 9 (General MIDI percussion) is skipped, and channels are reused
+/// round-robin once all 15 others are taken.
+pub(crate) fn assign_midi_channels(mei: &str) -> Result<String> {
+    let doc = parse(mei)?;
+
+    let mut seen = Vec::new();
+    let mut staff_defs = Vec::new();
+    for staff_def in doc
+        .descendants()
+        .filter(|node| node.has_tag_name("staffDef"))
+    {
+        let n = staff_def.attribute("n").unwrap_or_default();
+        if !seen.contains(&n) {
+            seen.push(n);
+            staff_defs.push(staff_def);
+        }
+    }
+
+    let explicit = |node: roxmltree::Node<'_, '_>| {
+        node.descendants()
+            .filter(|child| child.has_tag_name("instrDef"))
+            .find_map(|child| child.attribute("midi.channel")?.parse::<u8>().ok())
+    };
+    let used: Vec<u8> = staff_defs
+        .iter()
+        .filter_map(|node| explicit(*node))
+        .collect();
+    let mut available = (0..16u8)
+        .filter(|channel| *channel != 9 && !used.contains(channel))
+        .collect::<Vec<_>>()
+        .into_iter()
+        .chain((0..16u8).filter(|channel| *channel != 9).cycle());
+
+    let mut output = mei.to_string();
+    let mut edits = Vec::new();
+    for staff_def in staff_defs {
+        if explicit(staff_def).is_some() {
+            continue;
+        }
+        let channel = available.next().unwrap_or_default();
+        edits.push((
+            staff_def,
+            format!(r#"<instrDef midi.channel="{}"/>"#, channel),
+        ));
+    }
+    // Apply from the end so earlier offsets stay valid
+    for (staff_def, instr_def) in edits.into_iter().rev() {
+        output = insert_last_child(&output, staff_def, &instr_def);
+    }
+    Ok(output)
+}
+
+/// Removes every staff not listed in `staff_ns` from the document.
+///
+/// `<staff>` and `<staffDef>` elements are dropped by their `@n`, `<staffGrp>`s
+/// are dropped once none of their staff definitions remain, and control events
+/// are dropped when every staff in their `@staff` attribute is removed.
+///
+/// # Errors
+///
+/// Returns [`Error::RenderError`] if `staff_ns` is empty or names a staff that
+/// has no `<staffDef>` in the document.
+pub(crate) fn retain_staves(mei: &str, staff_ns: &[u32]) -> Result<String> {
+    let doc = parse(mei)?;
+
+    if staff_ns.is_empty() {
+        return Err(Error::RenderError("no staves selected".into()));
+    }
+    let defined: Vec<u32> = doc
+        .descendants()
+        .filter(|node| node.has_tag_name("staffDef"))
+        .filter_map(|node| node.attribute("n")?.parse().ok())
+        .collect();
+    if let Some(missing) = staff_ns.iter().find(|n| !defined.contains(n)) {
+        return Err(Error::RenderError(format!("staff not found: {}", missing)));
+    }
+
+    let mut removed = Vec::new();
+    collect_excluded(doc.root_element(), staff_ns, &mut removed);
+
+    let mut retained = String::with_capacity(mei.len());
+    let mut start = 0;
+    for range in removed {
+        retained.push_str(&mei[start..range.start]);
+        start = range.end;
+    }
+    retained.push_str(&mei[start..]);
+    Ok(retained)
+}
+
+/// Collects the byte ranges of the outermost elements below `node` that
+/// belong only to staves outside `staff_ns`.
+fn collect_excluded(
+    node: roxmltree::Node<'_, '_>,
+    staff_ns: &[u32],
+    removed: &mut Vec<std::ops::Range<usize>>,
+) {
+    for child in node.children().filter(|child| child.is_element()) {
+        if is_excluded(child, staff_ns) {
+            removed.push(child.range());
+        } else {
+            collect_excluded(child, staff_ns, removed);
+        }
+    }
+}
+
+/// Returns whether `node` belongs only to staves outside `staff_ns`.
+fn is_excluded(node: roxmltree::Node<'_, '_>, staff_ns: &[u32]) -> bool {
+    let outside = |n: &u32| !staff_ns.contains(n);
+
+    match node.tag_name().name() {
+        "staff" | "staffDef" => node
+            .attribute("n")
+            .and_then(|n| n.parse().ok())
+            .is_some_and(|n| outside(&n)),
+        "staffGrp" => {
+            let defs: Vec<_> = node
+                .descendants()
+                .filter(|def| def.has_tag_name("staffDef"))
+                .collect();
+            !defs.is_empty() && defs.into_iter().all(|def| is_excluded(def, staff_ns))
+        }
+        _ => {
+            let staves: Vec<u32> = node
+                .attribute("staff")
+                .unwrap_or_default()
+                .split_whitespace()
+                .filter_map(|n| n.parse().ok())
+                .collect();
+            !staves.is_empty() && staves.iter().all(outside)
+        }
+    }
+}
+
+/// Removes every `<verse>` whose `@n` is not `verse`, returning the new
+/// document. Verses without `@n` count as verse 1.
+///
+/// # Errors
+///
+/// Returns [`Error::OptionsError`] if the document has no verse `verse`.
+pub(crate) fn retain_verse(mei: &str, verse: u32) -> Result<String> {
+    let doc = parse(mei)?;
+    let verses: Vec<_> = doc
+        .descendants()
+        .filter(|node| node.has_tag_name("verse"))
+        .map(|node| {
+            let n = node.attribute("n").map_or(Some(1), |n| n.parse().ok());
+            (node, n)
+        })
+        .collect();
+    if !verses.iter().any(|(_, n)| *n == Some(verse)) {
+        return Err(Error::OptionsError(format!("verse not found: {}", verse)));
+    }
+
+    let mut retained = String::with_capacity(mei.len());
+    let mut start = 0;
+    for (node, _) in verses.iter().filter(|(_, n)| *n != Some(verse)) {
+        retained.push_str(&mei[start..node.range().start]);
+        start = node.range().end;
+    }
+    retained.push_str(&mei[start..]);
+    Ok(retained)
+}
+
+/// Returns the `xml:id`s of all `<expansion>` elements in document order.
+pub(crate) fn expansion_ids(mei: &str) -> Result<Vec<String>> {
+    let doc = parse(mei)?;
+    Ok(doc
+        .descendants()
+        .filter(|node| node.has_tag_name("expansion"))
+        .filter_map(|node| node.attribute((XML_NS, "id")))
+        .map(String::from)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_measure_ids_in_document_order() {
+        let mei = r#"<mei xmlns="http://www.music-encoding.org/ns/mei">
+            <section>
+                <measure xml:id="m1" n="1"/>
+                <ending><measure xml:id="m2" n="2"/></ending>
+                <measure n="3"/>
+                <measure xml:id="m4" n="4"/>
+            </section>
+        </mei>"#;
+        assert_eq!(measure_ids(mei).unwrap(), vec!["m1", "m2", "m4"]);
+    }
+
+    #[test]
+    fn test_measure_ids_ignores_plain_id_attribute() {
+        let mei = r#"<mei><measure id="not-an-xml-id"/></mei>"#;
+        assert!(measure_ids(mei).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_measure_ids_invalid_xml() {
+        let err = measure_ids("<mei>").unwrap_err();
+        assert!(err.to_string().contains("failed to parse MEI"));
+    }
+
+    #[test]
+    fn test_elements_without_id() {
+        let mei = r#"<mei><measure xml:id="m1"><note xml:id="n1"/><note/><rest/></measure>
+            <measure id="not-an-xml-id"/></mei>"#;
+        assert_eq!(elements_without_id(mei, &["measure", "note"]).unwrap(), 2);
+        assert_eq!(elements_without_id(mei, &["note"]).unwrap(), 1);
+        assert_eq!(elements_without_id(mei, &["chord"]).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_measure_id_for_number() {
+        let mei = r#"<mei><section><measure xml:id="m1" n="1"/><measure xml:id="m2" n="2a"/><measure xml:id="m3" n="3"/></section></mei>"#;
+        assert_eq!(
+            measure_id_for_number(mei, 1).unwrap().as_deref(),
+            Some("m1")
+        );
+        assert_eq!(
+            measure_id_for_number(mei, 3).unwrap().as_deref(),
+            Some("m3")
+        );
+        assert_eq!(measure_id_for_number(mei, 2).unwrap(), None);
+
+        let unnumbered = r#"<mei><measure xml:id="a"/><measure xml:id="b"/></mei>"#;
+        assert_eq!(
+            measure_id_for_number(unnumbered, 2).unwrap().as_deref(),
+            Some("b")
+        );
+        assert_eq!(measure_id_for_number(unnumbered, 0).unwrap(), None);
+    }
+
+    #[test]
+    fn test_measure_count_includes_measures_without_ids() {
+        let mei = r#"<mei><section><measure xml:id="m1"/><measure/></section></mei>"#;
+        assert_eq!(measure_count(mei).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_has_events() {
+        let skeleton = r#"<mei><section><measure><staff n="1"><layer n="1"/></staff></measure></section></mei>"#;
+        assert!(!has_events(skeleton).unwrap());
+
+        let rest = skeleton.replace("<layer n=\"1\"/>", "<layer n=\"1\"><mRest/></layer>");
+        assert!(has_events(&rest).unwrap());
+
+        let note = skeleton.replace(
+            "<layer n=\"1\"/>",
+            "<layer n=\"1\"><chord><note/></chord></layer>",
+        );
+        assert!(has_events(&note).unwrap());
+    }
+
+    #[test]
+    fn test_metadata() {
+        let mei = r#"<mei><meiHead><fileDesc><titleStmt>
+            <title>Sonata <title type="subordinate">in C</title></title>
+            <title>Second title</title>
+            <composer>W. A. Mozart</composer>
+            <respStmt><persName role="lyricist">Anon.</persName></respStmt></titleStmt>
+            <pubStmt><availability><useRestrict>Public domain</useRestrict></availability></pubStmt>
+            </fileDesc></meiHead><music><title>Not the header</title></music></mei>"#;
+        assert_eq!(
+            metadata(mei).unwrap(),
+            DocumentMetadata {
+                title: Some("Sonata in C".into()),
+                composer: Some("W. A. Mozart".into()),
+                lyricist: Some("Anon.".into()),
+                copyright: Some("Public domain".into()),
+            }
+        );
+    }
+
+    #[test]
+    fn test_metadata_composer_in_resp_stmt() {
+        let mei = r#"<mei><meiHead><fileDesc><titleStmt><title>Song</title>
+            <respStmt><persName role="composer">Clara Schumann</persName></respStmt>
+            </titleStmt></fileDesc></meiHead></mei>"#;
+        let metadata = metadata(mei).unwrap();
+        assert_eq!(metadata.composer.as_deref(), Some("Clara Schumann"));
+        assert_eq!(metadata.lyricist, None);
+    }
+
+    #[test]
+    fn test_key_signature() {
+        let mei = r#"<mei><scoreDef><staffDef n="1"><keySig sig="1s" mode="major"/></staffDef>
+            </scoreDef><keySig sig="3f"/></mei>"#;
+        assert_eq!(key_signature(mei).unwrap(), Some((1, Some("major".into()))));
+
+        let mei = r#"<mei><scoreDef key.sig="2f" key.mode="minor"/></mei>"#;
+        assert_eq!(
+            key_signature(mei).unwrap(),
+            Some((-2, Some("minor".into())))
+        );
+
+        let mei = r#"<mei><staffDef keysig="0"/></mei>"#;
+        assert_eq!(key_signature(mei).unwrap(), Some((0, None)));
+
+        let mei = r#"<mei><keySig sig="mixed"/><staffDef n="1"/></mei>"#;
+        assert_eq!(key_signature(mei).unwrap(), None);
+    }
+
+    #[test]
+    fn test_key_name() {
+        assert_eq!(key_name(0, false).as_deref(), Some("C"));
+        assert_eq!(key_name(1, false).as_deref(), Some("G"));
+        assert_eq!(key_name(-3, false).as_deref(), Some("Eb"));
+        assert_eq!(key_name(0, true).as_deref(), Some("Am"));
+        assert_eq!(key_name(3, true).as_deref(), Some("F#m"));
+        assert_eq!(key_name(8, false), None);
+        assert_eq!(key_name(-8, true), None);
+    }
+
+    #[test]
+    fn test_metadata_missing_or_empty() {
+        assert_eq!(
+            metadata("<mei><music/></mei>").unwrap(),
+            DocumentMetadata::default()
+        );
+        let mei = "<mei><meiHead><fileDesc><titleStmt><title> </title></titleStmt></fileDesc></meiHead></mei>";
+        assert_eq!(metadata(mei).unwrap().title, None);
+    }
+
+    #[test]
+    fn test_set_title_replaces_content() {
+        let mei = r#"<mei><meiHead><fileDesc><titleStmt><title type="main">Old <rend>title</rend></title></titleStmt></fileDesc></meiHead></mei>"#;
+        assert_eq!(
+            set_title(mei, "Tom & Jerry").unwrap(),
+            r#"<mei><meiHead><fileDesc><titleStmt><title type="main">Tom &amp; Jerry</title></titleStmt></fileDesc></meiHead></mei>"#
+        );
+    }
+
+    #[test]
+    fn test_set_title_empty_elements() {
+        let mei =
+            "<mei><meiHead><fileDesc><titleStmt><title /></titleStmt></fileDesc></meiHead></mei>";
+        assert_eq!(
+            set_title(mei, "New").unwrap(),
+            "<mei><meiHead><fileDesc><titleStmt><title>New</title></titleStmt></fileDesc></meiHead></mei>"
+        );
+
+        let mei = "<mei><meiHead><fileDesc><titleStmt/></fileDesc></meiHead></mei>";
+        assert_eq!(
+            set_title(mei, "New").unwrap(),
+            "<mei><meiHead><fileDesc><titleStmt><title>New</title></titleStmt></fileDesc></meiHead></mei>"
+        );
+    }
+
+    #[test]
+    fn test_set_title_without_header() {
+        let mei = "<mei><music/></mei>";
+        let updated = set_title(mei, "New").unwrap();
+        assert_eq!(
+            updated,
+            "<mei><meiHead><fileDesc><titleStmt><title>New</title></titleStmt><pubStmt/></fileDesc></meiHead><music/></mei>"
+        );
+        assert_eq!(metadata(&updated).unwrap().title.as_deref(), Some("New"));
+    }
+
+    #[test]
+    fn test_set_person() {
+        let mei = r#"<mei><meiHead><fileDesc><titleStmt><title>T</title><respStmt><persName role="composer">Old</persName></respStmt></titleStmt></fileDesc></meiHead></mei>"#;
+        let updated = set_person(mei, "composer", "New").unwrap();
+        assert_eq!(metadata(&updated).unwrap().composer.as_deref(), Some("New"));
+
+        let updated = set_person(&updated, "lyricist", "Poet").unwrap();
+        assert!(updated.contains(
+            r#"<respStmt><persName role="lyricist">Poet</persName></respStmt></titleStmt>"#
+        ));
+        let metadata = metadata(&updated).unwrap();
+        assert_eq!(metadata.composer.as_deref(), Some("New"));
+        assert_eq!(metadata.lyricist.as_deref(), Some("Poet"));
+    }
+
+    #[test]
+    fn test_assign_midi_channels() {
+        let mei = concat!(
+            r#"<mei><staffGrp><staffDef n="1"/>"#,
+            r#"<staffDef n="2"><instrDef midi.channel="0"/></staffDef>"#,
+            r#"<staffDef n="3" lines="5"></staffDef></staffGrp>"#,
+            r#"<staffDef n="1"/></mei>"#
+        );
+        assert_eq!(
+            assign_midi_channels(mei).unwrap(),
+            concat!(
+                r#"<mei><staffGrp><staffDef n="1"><instrDef midi.channel="1"/></staffDef>"#,
+                r#"<staffDef n="2"><instrDef midi.channel="0"/></staffDef>"#,
+                r#"<staffDef n="3" lines="5"><instrDef midi.channel="2"/></staffDef></staffGrp>"#,
+                r#"<staffDef n="1"/></mei>"#
+            )
+        );
+    }
+
+    #[test]
+    fn test_assign_midi_channels_skips_percussion() {
+        let staff_defs: String = (1..=17)
+            .map(|n| format!(r#"<staffDef n="{}"/>"#, n))
+            .collect();
+        let mei = format!("<mei>{}</mei>", staff_defs);
+        let assigned = assign_midi_channels(&mei).unwrap();
+
+        let doc = parse(&assigned).unwrap();
+        let channels: Vec<&str> = doc
+            .descendants()
+            .filter_map(|node| node.attribute("midi.channel"))
+            .collect();
+        assert_eq!(channels.len(), 17);
+        assert!(!channels.contains(&"9"));
+        assert_eq!(channels[8], "8");
+        assert_eq!(channels[9], "10");
+        assert_eq!(channels[15], "0");
+    }
+
+    #[test]
+    fn test_layout_counts() {
+        let mei = r#"<mei><pages><page><system><measure/><measure/></system><system><measure/></system></page>
+            <page><system><measure/></system></page></pages></mei>"#;
+        assert_eq!(layout_counts(mei).unwrap(), (3, 4));
+
+        let score_based = r#"<mei><section><measure/><measure/></section></mei>"#;
+        assert_eq!(layout_counts(score_based).unwrap(), (0, 2));
+    }
+
+    #[test]
+    fn test_append_measures_after_last_measure() {
+        let mei = r#"<mei xmlns="http://www.music-encoding.org/ns/mei"><section><measure n="0"/><measure n="1"/><sb/></section><pb/></mei>"#;
+        let appended = append_measures(mei, r#"<measure n="2"/> <measure n="3"/>"#).unwrap();
+        assert_eq!(
+            appended,
+            r#"<mei xmlns="http://www.music-encoding.org/ns/mei"><section><measure n="0"/><measure n="1"/><measure n="2"/> <measure n="3"/><sb/></section><pb/></mei>"#
+        );
+        assert_eq!(measure_count(&appended).unwrap(), 4);
+    }
+
+    #[test]
+    fn test_append_measures_after_ending() {
+        let mei =
+            r#"<mei><section><measure n="0"/><ending><measure n="1"/></ending></section></mei>"#;
+        assert_eq!(
+            append_measures(mei, r#"<measure n="2"/>"#).unwrap(),
+            r#"<mei><section><measure n="0"/><ending><measure n="1"/></ending><measure n="2"/></section></mei>"#
+        );
+
+        let mei = r#"<mei><section><section><measure n="1"/><ending><measure n="2"/></ending></section></section></mei>"#;
+        assert_eq!(
+            append_measures(mei, r#"<measure n="3"/>"#).unwrap(),
+            r#"<mei><section><section><measure n="1"/><ending><measure n="2"/></ending></section><measure n="3"/></section></mei>"#
+        );
+    }
+
+    #[test]
+    fn test_append_measures_rejects_malformed_fragment() {
+        let mei = r#"<mei><section><measure/></section></mei>"#;
+        let err = append_measures(mei, "<measure>").unwrap_err();
+        assert!(matches!(err, Error::LoadError(_)));
+        assert!(err.to_string().contains("invalid MEI fragment"));
+    }
+
+    #[test]
+    fn test_append_measures_rejects_non_measure_content() {
+        let mei = r#"<mei><section><measure/></section></mei>"#;
+        let err = append_measures(mei, "<staff/>").unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("expected <measure>, found <staff>")
+        );
+
+        let err = append_measures(mei, "<measure/>stray").unwrap_err();
+        assert!(err.to_string().contains("unexpected text"));
+
+        let err = append_measures(mei, "  ").unwrap_err();
+        assert!(err.to_string().contains("no <measure> elements"));
+    }
+
+    #[test]
+    fn test_append_measures_requires_existing_measure() {
+        let err = append_measures("<mei><section/></mei>", "<measure/>").unwrap_err();
+        assert!(err.to_string().contains("no measures to append to"));
+    }
+
+    #[test]
+    fn test_insert_before_measure() {
+        let mei = r#"<mei><section><measure xml:id="m1"/><measure xml:id="m2"/></section></mei>"#;
+        assert_eq!(
+            insert_before_measure(mei, "m2", "<sb/>").unwrap(),
+            r#"<mei><section><measure xml:id="m1"/><sb/><measure xml:id="m2"/></section></mei>"#
+        );
+    }
+
+    #[test]
+    fn test_insert_before_measure_not_found() {
+        let mei = r#"<mei><section><measure xml:id="m1"/><note xml:id="n1"/></section></mei>"#;
+        let err = insert_before_measure(mei, "n1", "<sb/>").unwrap_err();
+        assert!(err.to_string().contains("measure not found: n1"));
+    }
+
+    const TWO_STAFF_MEI: &str = r#"<mei><scoreDef><staffGrp><staffDef n="1"/><staffGrp><staffDef n="2"/></staffGrp></staffGrp></scoreDef><section><measure><staff n="1"/><staff n="2"/><dynam staff="2"/><slur staff="1 2"/></measure></section></mei>"#;
+
+    #[test]
+    fn test_retain_staves_removes_other_staves() {
+        let retained = retain_staves(TWO_STAFF_MEI, &[1]).unwrap();
+        assert_eq!(
+            retained,
+            r#"<mei><scoreDef><staffGrp><staffDef n="1"/></staffGrp></scoreDef><section><measure><staff n="1"/><slur staff="1 2"/></measure></section></mei>"#
+        );
+    }
+
+    #[test]
+    fn test_retain_staves_all_staves_unchanged() {
+        assert_eq!(
+            retain_staves(TWO_STAFF_MEI, &[2, 1]).unwrap(),
+            TWO_STAFF_MEI
+        );
+    }
+
+    #[test]
+    fn test_retain_staves_unknown_staff() {
+        let err = retain_staves(TWO_STAFF_MEI, &[1, 3]).unwrap_err();
+        assert!(err.to_string().contains("staff not found: 3"));
+    }
+
+    #[test]
+    fn test_retain_staves_empty_selection() {
+        let err = retain_staves(TWO_STAFF_MEI, &[]).unwrap_err();
+        assert!(err.to_string().contains("no staves selected"));
+    }
+
+    #[test]
+    fn test_retain_verse() {
+        let mei = r#"<mei><note><verse n="1"><syl>Ho</syl></verse><verse n="2"><syl>Glo</syl></verse></note><note><verse><syl>ly</syl></verse></note></mei>"#;
+        assert_eq!(
+            retain_verse(mei, 2).unwrap(),
+            r#"<mei><note><verse n="2"><syl>Glo</syl></verse></note><note></note></mei>"#
+        );
+        assert_eq!(
+            retain_verse(mei, 1).unwrap(),
+            r#"<mei><note><verse n="1"><syl>Ho</syl></verse></note><note><verse><syl>ly</syl></verse></note></mei>"#
+        );
+    }
+
+    #[test]
+    fn test_retain_verse_not_found() {
+        let mei = r#"<mei><note><verse n="1"><syl>Ho</syl></verse></note></mei>"#;
+        let err = retain_verse(mei, 3).unwrap_err();
+        assert!(matches!(err, Error::OptionsError(_)));
+        assert!(err.to_string().contains("verse not found: 3"));
+    }
+
+    #[test]
+    fn test_expansion_ids() {
+        let mei = r##"<mei xmlns="http://www.music-encoding.org/ns/mei">
+            <section>
+                <expansion xml:id="exp-full" plist="#a #a #b"/>
+                <expansion xml:id="exp-short" plist="#a #b"/>
+                <section xml:id="a"/>
+                <section xml:id="b"/>
+            </section>
+        </mei>"##;
+        assert_eq!(expansion_ids(mei).unwrap(), vec!["exp-full", "exp-short"]);
+    }
+
+    #[test]
+    fn test_text_spans() {
+        let mei = r#"<mei xmlns="http://www.music-encoding.org/ns/mei" xmlns:xml="http://www.w3.org/XML/1998/namespace">
+            <meiHead><fileDesc><titleStmt><title>Not music text</title></titleStmt></fileDesc></meiHead>
+            <music><body><mdiv><score><section><measure>
+                <staff><layer><note><verse><syl xml:id="s1">Ky</syl></verse></note>
+                <note><verse><syl xml:id="s2"> ri </syl></verse></note></layer></staff>
+                <dynam xml:id="d1">mf</dynam>
+                <tempo xml:id="t1">Allegro <rend fontfam="VerovioText">&#xE1D5;</rend> = 120</tempo>
+                <dir>dolce</dir>
+                <dir xml:id="empty"> </dir>
+            </measure></section></score></mdiv></body></music></mei>"#;
+
+        let spans = text_spans(mei).unwrap();
+        assert_eq!(
+            spans,
+            vec![
+                TextSpan {
+                    kind: TextKind::Lyric,
+                    text: "Ky".into(),
+                    element_id: "s1".into(),
+                },
+                TextSpan {
+                    kind: TextKind::Lyric,
+                    text: "ri".into(),
+                    element_id: "s2".into(),
+                },
+                TextSpan {
+                    kind: TextKind::Dynamic,
+                    text: "mf".into(),
+                    element_id: "d1".into(),
+                },
+                TextSpan {
+                    kind: TextKind::Tempo,
+                    text: "Allegro \u{1D15F} = 120".into(),
+                    element_id: "t1".into(),
+                },
+                TextSpan {
+                    kind: TextKind::Other,
+                    text: "dolce".into(),
+                    element_id: String::new(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_smufl_to_unicode() {
+        assert_eq!(smufl_to_unicode("\u{E1D7}\u{E1E7} = 90"), "\u{1D160}. = 90");
+        assert_eq!(smufl_to_unicode("B\u{E260}"), "B\u{266D}");
+        assert_eq!(smufl_to_unicode("a\u{E4E5}b"), "ab");
+    }
+}
@@ -0,0 +1,182 @@
+//! MIDI helpers.
+//!
+//! Verovio returns MIDI as base64-encoded Standard MIDI File data. This module
+//! decodes it; tests also use it to read the channels used by a file.
+
+#[cfg(test)]
+use std::collections::BTreeSet;
+
+use crate::{Error, Result};
+
+/// Decodes standard (RFC 4648) base64, ignoring whitespace.
+pub(crate) fn decode_base64(encoded: &str) -> Result<Vec<u8>> {
+    let invalid = || Error::RenderError("invalid base64 in MIDI output".into());
+    let value = |c: u8| match c {
+        b'A'..=b'Z' => Some(c - b'A'),
+        b'a'..=b'z' => Some(c - b'a' + 26),
+        b'0'..=b'9' => Some(c - b'0' + 52),
+        b'+' => Some(62),
+        b'/' => Some(63),
+        _ => None,
+    };
+
+    let symbols: Vec<u8> = encoded
+        .bytes()
+        .filter(|c| !c.is_ascii_whitespace())
+        .collect();
+    let data = symbols
+        .strip_suffix(b"==")
+        .or_else(|| symbols.strip_suffix(b"="))
+        .unwrap_or(&symbols);
+    if symbols.len() % 4 != 0 {
+        return Err(invalid());
+    }
+
+    let mut bytes = Vec::with_capacity(data.len() * 3 / 4);
+    for chunk in data.chunks(4) {
+        let mut buffer = 0u32;
+        for (i, &c) in chunk.iter().enumerate() {
+            buffer |= u32::from(value(c).ok_or_else(invalid)?) << (18 - 6 * i);
+        }
+        let decoded = buffer.to_be_bytes();
+        bytes.extend_from_slice(&decoded[1..chunk.len()]);
+    }
+    Ok(bytes)
+}
+
+/// Returns the channels (0-15) that have note-on events in a Standard MIDI
+/// File.
+#[cfg(test)]
+pub(crate) fn note_channels(midi: &[u8]) -> Result<BTreeSet<u8>> {
+    let invalid = || Error::RenderError("invalid MIDI data".into());
+    let mut channels = BTreeSet::new();
+    let mut rest = midi;
+
+    while rest.len() >= 8 {
+        let length = u32::from_be_bytes([rest[4], rest[5], rest[6], rest[7]]) as usize;
+        let chunk = rest.get(8..8 + length).ok_or_else(invalid)?;
+        if &rest[..4] == b"MTrk" {
+            scan_track(chunk, &mut channels).ok_or_else(invalid)?;
+        }
+        rest = &rest[8 + length..];
+    }
+    Ok(channels)
+}
+
+/// Adds the channels of the note-on events in a track to `channels`.
+///
+/// Returns `None` if the track is truncated.
+#[cfg(test)]
+fn scan_track(track: &[u8], channels: &mut BTreeSet<u8>) -> Option<()> {
+    let mut position = 0;
+    let mut running_status = 0u8;
+
+    while position < track.len() {
+        read_variable_length(track, &mut position)?;
+        let mut status = *track.get(position)?;
+        if status < 0x80 {
+            status = running_status;
+        } else {
+            position += 1;
+        }
+
+        match status {
+            0xFF => {
+                position += 1;
+                position += read_variable_length(track, &mut position)?;
+            }
+            0xF0 | 0xF7 => {
+                position += read_variable_length(track, &mut position)?;
+            }
+            0x80..=0xEF => {
+                running_status = status;
+                let data_length = if matches!(status & 0xF0, 0xC0 | 0xD0) {
+                    1
+                } else {
+                    2
+                };
+                let data = track.get(position..position + data_length)?;
+                if status & 0xF0 == 0x90 && data[1] > 0 {
+                    channels.insert(status & 0x0F);
+                }
+                position += data_length;
+            }
+            _ => return None,
+        }
+    }
+    Some(())
+}
+
+/// Reads a MIDI variable-length quantity, advancing `position` past it.
+#[cfg(test)]
+fn read_variable_length(data: &[u8], position: &mut usize) -> Option<usize> {
+    let mut value = 0usize;
+    for _ in 0..4 {
+        let byte = *data.get(*position)?;
+        *position += 1;
+        value = (value << 7) | usize::from(byte & 0x7F);
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_base64() {
+        assert_eq!(decode_base64("TVRoZA==").unwrap(), b"MThd");
+        assert_eq!(decode_base64("TVRo").unwrap(), b"MTh");
+        assert_eq!(decode_base64("TVRoZAA=").unwrap(), b"MThd\0");
+        assert_eq!(decode_base64("TVRo\nZA==").unwrap(), b"MThd");
+        assert!(decode_base64("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_decode_base64_invalid() {
+        assert!(decode_base64("TVR").is_err());
+        assert!(decode_base64("TV!o").is_err());
+    }
+
+    fn track(events: &[u8]) -> Vec<u8> {
+        let mut chunk = b"MTrk".to_vec();
+        chunk.extend_from_slice(&(events.len() as u32).to_be_bytes());
+        chunk.extend_from_slice(events);
+        chunk
+    }
+
+    #[test]
+    fn test_note_channels() {
+        let mut midi = b"MThd\0\0\0\x06\0\x01\0\x02\x01\xE0".to_vec();
+        midi.extend(track(&[
+            0x00, 0xFF, 0x51, 0x03, 0x07, 0xA1, 0x20, // tempo
+            0x00, 0xC0, 0x00, // program change, channel 0
+            0x00, 0x90, 0x3C, 0x5A, // note on, channel 0
+            0x83, 0x60, 0x3C, 0x00, // running status note on with velocity 0
+            0x00, 0xFF, 0x2F, 0x00, // end of track
+        ]));
+        midi.extend(track(&[
+            0x00, 0x93, 0x40, 0x5A, // note on, channel 3
+            0x60, 0x83, 0x40, 0x00, // note off, channel 3
+            0x00, 0x95, 0x40, 0x00, // note on with velocity 0, channel 5
+            0x00, 0xFF, 0x2F, 0x00,
+        ]));
+
+        let channels = note_channels(&midi).unwrap();
+        assert_eq!(channels.into_iter().collect::<Vec<_>>(), vec![0, 3]);
+    }
+
+    #[test]
+    fn test_note_channels_truncated() {
+        let mut midi = b"MThd\0\0\0\x06\0\x01\0\x01\x01\xE0".to_vec();
+        midi.extend(track(&[0x00, 0x90, 0x3C]));
+        assert!(note_channels(&midi).is_err());
+
+        let mut midi = b"MTrk\0\0\0\x10".to_vec();
+        midi.push(0);
+        assert!(note_channels(&midi).is_err());
+    }
+}
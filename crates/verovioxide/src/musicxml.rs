@@ -0,0 +1,176 @@
+//! MusicXML helpers.
+//!
+//! MusicXML comes in two layouts: `score-partwise`, where measures are nested
+//! in parts, and `score-timewise`, where parts are nested in measures. Verovio
+//! only reads the partwise layout, so this module converts timewise documents
+//! by reordering their elements.
+
+use std::borrow::Cow;
+
+use crate::{Error, Result};
+
+/// Converts a `score-timewise` document to `score-partwise`.
+///
+/// Other documents are returned unchanged. The header (work, identification,
+/// part list, ...) and the music data of each part in each measure are copied
+/// verbatim. Parts are ordered as in the part list, followed by parts that
+/// only appear in measures, in order of appearance. The converted document
+/// starts with a new XML declaration and has no document type declaration.
+pub(crate) fn to_partwise(xml: &str) -> Result<Cow<'_, str>> {
+    if !xml.contains("<score-timewise") {
+        return Ok(Cow::Borrowed(xml));
+    }
+
+    let options = roxmltree::ParsingOptions {
+        allow_dtd: true,
+        ..Default::default()
+    };
+    let doc = roxmltree::Document::parse_with_options(xml, options)
+        .map_err(|e| Error::LoadError(format!("failed to parse MusicXML: {}", e)))?;
+    let root = doc.root_element();
+    if !root.has_tag_name("score-timewise") {
+        return Ok(Cow::Borrowed(xml));
+    }
+
+    let measures: Vec<_> = root
+        .children()
+        .filter(|node| node.has_tag_name("measure"))
+        .collect();
+
+    let mut part_ids: Vec<&str> = root
+        .children()
+        .filter(|node| node.has_tag_name("part-list"))
+        .flat_map(|list| list.children())
+        .filter(|node| node.has_tag_name("score-part"))
+        .filter_map(|node| node.attribute("id"))
+        .collect();
+    for part in measures
+        .iter()
+        .flat_map(|measure| measure.children())
+        .filter(|node| node.has_tag_name("part"))
+    {
+        let id = part.attribute("id").unwrap_or_default();
+        if !part_ids.contains(&id) {
+            part_ids.push(id);
+        }
+    }
+
+    let mut output = String::with_capacity(xml.len());
+    output.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    output.push_str(&format!("<score-partwise{}>\n", attributes(root)));
+    for header in root
+        .children()
+        .filter(|node| node.is_element() && !node.has_tag_name("measure"))
+    {
+        output.push_str(&xml[header.range()]);
+        output.push('\n');
+    }
+
+    for id in part_ids {
+        output.push_str(&format!("<part id=\"{}\">\n", escape_attribute(id)));
+        for measure in &measures {
+            output.push_str(&format!("<measure{}>", attributes(*measure)));
+            let part = measure.children().find(|node| {
+                node.has_tag_name("part") && node.attribute("id").unwrap_or_default() == id
+            });
+            if let Some((first, last)) =
+                part.and_then(|part| Some((part.first_child()?, part.last_child()?)))
+            {
+                output.push_str(&xml[first.range().start..last.range().end]);
+            }
+            output.push_str("</measure>\n");
+        }
+        output.push_str("</part>\n");
+    }
+    output.push_str("</score-partwise>\n");
+
+    Ok(Cow::Owned(output))
+}
+
+/// Formats the attributes of `node` as they appear in a start tag, each
+/// preceded by a space.
+fn attributes(node: roxmltree::Node<'_, '_>) -> String {
+    node.attributes()
+        .map(|attr| format!(" {}=\"{}\"", attr.name(), escape_attribute(attr.value())))
+        .collect()
+}
+
+/// Escapes text for use in a double-quoted attribute value.
+fn escape_attribute(value: &str) -> String {
+    crate::svg::escape_text(value).replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TIMEWISE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE score-timewise PUBLIC "-//Recordare//DTD MusicXML 4.0 Timewise//EN"
+  "http://www.musicxml.org/dtds/timewise.dtd">
+<score-timewise version="4.0">
+  <work><work-title>Duet &amp; Co</work-title></work>
+  <part-list>
+    <score-part id="P1"><part-name>Flute</part-name></score-part>
+    <score-part id="P2"><part-name>Oboe</part-name></score-part>
+  </part-list>
+  <measure number="1">
+    <part id="P2"><note><rest/><duration>4</duration></note></part>
+    <part id="P1"><note><pitch><step>C</step><octave>5</octave></pitch><duration>4</duration></note></part>
+  </measure>
+  <measure number="2" implicit="no">
+    <part id="P1"><note><rest/><duration>4</duration></note></part>
+    <part id="P2"><note><pitch><step>E</step><octave>4</octave></pitch><duration>4</duration></note></part>
+  </measure>
+</score-timewise>
+"#;
+
+    #[test]
+    fn test_to_partwise() {
+        let partwise = to_partwise(TIMEWISE).unwrap();
+        let doc = roxmltree::Document::parse(&partwise).unwrap();
+        let root = doc.root_element();
+        assert!(root.has_tag_name("score-partwise"));
+        assert_eq!(root.attribute("version"), Some("4.0"));
+        assert!(partwise.contains("<work-title>Duet &amp; Co</work-title>"));
+
+        let parts: Vec<_> = root
+            .children()
+            .filter(|node| node.has_tag_name("part"))
+            .collect();
+        assert_eq!(
+            parts
+                .iter()
+                .map(|part| part.attribute("id").unwrap())
+                .collect::<Vec<_>>(),
+            vec!["P1", "P2"]
+        );
+
+        let measures: Vec<_> = parts[1]
+            .children()
+            .filter(|node| node.has_tag_name("measure"))
+            .collect();
+        assert_eq!(measures.len(), 2);
+        assert_eq!(measures[1].attribute("number"), Some("2"));
+        assert_eq!(measures[1].attribute("implicit"), Some("no"));
+        let step = measures[1]
+            .descendants()
+            .find(|node| node.has_tag_name("step"))
+            .and_then(|node| node.text());
+        assert_eq!(step, Some("E"));
+    }
+
+    #[test]
+    fn test_to_partwise_keeps_other_documents() {
+        let partwise = r#"<score-partwise version="4.0"><part id="P1"/></score-partwise>"#;
+        assert!(matches!(to_partwise(partwise).unwrap(), Cow::Borrowed(_)));
+
+        let mentioned = "<mei><!-- no <score-timewise here --></mei>";
+        assert!(matches!(to_partwise(mentioned).unwrap(), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_to_partwise_invalid_xml() {
+        let err = to_partwise("<score-timewise><measure>").unwrap_err();
+        assert!(matches!(err, Error::LoadError(_)));
+    }
+}
@@ -30,7 +30,11 @@
 //! For best performance, set layout-affecting options before loading data, or batch
 //! option changes together to minimize layout recalculations.
 
-use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize};
 
 /// Break mode for page and system breaks.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
@@ -90,6 +94,24 @@ pub enum HeaderMode {
     Encoded,
 }
 
+/// Pedal marking style.
+///
+/// Leaving [`Options::pedal_style`] unset uses the style encoded in the
+/// input, or Verovio's default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PedalStyle {
+    /// A continuous line, with the release and re-pedaling marked by hooks.
+    Line,
+    /// "Ped." followed by a bracket line to the release (Verovio's `pedline`).
+    #[serde(rename = "pedline")]
+    Bracket,
+    /// "Ped." and "*" markings.
+    PedStar,
+    /// "Ped." markings with bracketed releases.
+    AltPedStar,
+}
+
 /// SMuFL text font to use for text rendering.
 #[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub enum TextFont {
@@ -111,6 +133,55 @@ impl TextFont {
     }
 }
 
+/// A SMuFL music font bundled with Verovio.
+///
+/// # See also
+///
+/// - [`Toolkit::set_fonts`](crate::Toolkit::set_fonts) - Set the music and fallback fonts
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Font {
+    /// Leipzig, Verovio's default font.
+    #[default]
+    Leipzig,
+    /// Bravura, the SMuFL reference font.
+    Bravura,
+    /// Gootville.
+    Gootville,
+    /// Leland.
+    Leland,
+    /// Petaluma, a handwritten-style font.
+    Petaluma,
+}
+
+impl Font {
+    /// Returns the font name as used in options and resource files.
+    #[must_use]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Leipzig => "Leipzig",
+            Self::Bravura => "Bravura",
+            Self::Gootville => "Gootville",
+            Self::Leland => "Leland",
+            Self::Petaluma => "Petaluma",
+        }
+    }
+}
+
+/// How to choose the rendering scale.
+///
+/// # See also
+///
+/// - [`Toolkit::apply_scale_mode`](crate::Toolkit::apply_scale_mode) - Apply a scale mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScaleMode {
+    /// A fixed scale, as a percentage.
+    Percent(u32),
+    /// The scale at which a page is the given number of pixels wide.
+    FitWidth(u32),
+    /// The scale at which a page is the given number of pixels high.
+    FitHeight(u32),
+}
+
 /// Rendering options for the Verovio toolkit.
 ///
 /// This struct provides a type-safe way to configure Verovio rendering options.
@@ -124,43 +195,108 @@ impl TextFont {
 /// - [`OptionsBuilder`] - Builder for constructing options
 /// - [`Toolkit::set_options`](crate::Toolkit::set_options) - Apply options to a toolkit
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase", default)]
 pub struct Options {
     // =========================================================================
     // General Options
     // =========================================================================
     /// Rendering scale as a percentage (e.g., 100 for 100%).
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_lenient"
+    )]
     pub scale: Option<u32>,
 
     /// Page width in MEI units.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_lenient"
+    )]
     pub page_width: Option<u32>,
 
     /// Page height in MEI units.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_lenient"
+    )]
     pub page_height: Option<u32>,
 
     /// Whether to adjust the page height to the content.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_lenient"
+    )]
     pub adjust_page_height: Option<bool>,
 
+    /// Whether to adjust the page width to the content.
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_lenient"
+    )]
+    pub adjust_page_width: Option<bool>,
+
     /// Top page margin (in MEI units).
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_lenient"
+    )]
     pub page_margin_top: Option<u32>,
 
     /// Bottom page margin (in MEI units).
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_lenient"
+    )]
     pub page_margin_bottom: Option<u32>,
 
     /// Left page margin (in MEI units).
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_lenient"
+    )]
     pub page_margin_left: Option<u32>,
 
     /// Right page margin (in MEI units).
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_lenient"
+    )]
     pub page_margin_right: Option<u32>,
 
+    /// Whether to remove `xml:id`s that are not referenced when exporting MEI.
+    ///
+    /// Applies to [`Toolkit::get_mei`](crate::Toolkit::get_mei) and
+    /// [`Toolkit::save_file`](crate::Toolkit::save_file). A `removeIds` value
+    /// passed as MEI export options (for example through
+    /// [`MeiOptionsBuilder::remove_ids`](crate::MeiOptionsBuilder::remove_ids))
+    /// takes precedence for that export.
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_lenient"
+    )]
+    pub remove_ids: Option<bool>,
+
+    /// Number of spaces per indentation level in MEI and Humdrum output.
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_lenient"
+    )]
+    pub output_indent: Option<u32>,
+
+    /// Whether to indent MEI and Humdrum output with tabs instead of spaces.
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_lenient"
+    )]
+    pub output_indent_tab: Option<bool>,
+
+    /// Whether to write MEI output without line breaks or indentation.
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_lenient"
+    )]
+    pub output_format_raw: Option<bool>,
+
     // =========================================================================
     // Font Options
     // =========================================================================
@@ -168,10 +304,37 @@ pub struct Options {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub font: Option<String>,
 
+    /// The music font used for glyphs missing from [`font`](Self::font)
+    /// (Verovio `fontFallback`).
+    ///
+    /// Verovio accepts only "Leipzig" and "Bravura" here.
+    #[serde(rename = "fontFallback", skip_serializing_if = "Option::is_none")]
+    pub fallback_font: Option<String>,
+
     /// Lyric size as a percentage of the staff size.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_lenient"
+    )]
     pub lyric_size: Option<f64>,
 
+    /// Whether to close the gap left by verses without a syllable.
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_lenient"
+    )]
+    pub lyric_verse_collapse: Option<bool>,
+
+    /// The language of the score's text, as a BCP 47 tag (e.g. `he`, `ar-EG`).
+    ///
+    /// Verovio has no such option, so this one is applied by
+    /// [`Toolkit::render_to_svg`](crate::Toolkit::render_to_svg), which marks
+    /// every `<text>` element with `xml:lang`, and with `direction="rtl"` for
+    /// right-to-left scripts such as Hebrew and Arabic. An empty string
+    /// clears a language set before.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text_lang: Option<String>,
+
     // =========================================================================
     // Layout Options
     // =========================================================================
@@ -179,24 +342,56 @@ pub struct Options {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub breaks: Option<BreakMode>,
 
+    /// Proportion of the page a system must fill before a smart break is
+    /// preferred over an encoded system break (0.0–1.0).
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_lenient"
+    )]
+    pub breaks_smart_sb: Option<f64>,
+
+    /// Maximum number of systems per page (0 for no limit).
+    ///
+    /// Pages still break early when the systems do not fit the page height.
+    /// With `adjust_page_height`, each page is then shrunk to its systems, so
+    /// the limit alone decides how dense pages are. The limit has no effect
+    /// when breaks are disabled.
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_lenient"
+    )]
+    pub system_max_per_page: Option<u32>,
+
     /// Condense mode for dense layouts.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub condense: Option<CondenseMode>,
 
     /// Whether to condense the first page.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_lenient"
+    )]
     pub condense_first_page: Option<bool>,
 
     /// Minimum width for condensed scores.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_lenient"
+    )]
     pub condense_tempo_pages: Option<bool>,
 
     /// Whether to even note spacing.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_lenient"
+    )]
     pub even_note_spacing: Option<bool>,
 
     /// The minimum measure width.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_lenient"
+    )]
     pub min_measure_width: Option<u32>,
 
     /// Header display mode.
@@ -207,23 +402,49 @@ pub struct Options {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub footer: Option<FooterMode>,
 
+    /// Pedal marking style. Verovio's `"auto"` reads as `None`.
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_pedal_style"
+    )]
+    pub pedal_style: Option<PedalStyle>,
+
+    /// Default distance of harmony indications from the staff (in MEI units).
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_lenient"
+    )]
+    pub harm_dist: Option<f64>,
+
     // =========================================================================
     // SVG Output Options
     // =========================================================================
     /// Whether to include the XML declaration in SVG output.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_lenient"
+    )]
     pub svg_xml_declaration: Option<bool>,
 
     /// Whether to include bounding boxes in SVG output.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_lenient"
+    )]
     pub svg_bounding_boxes: Option<bool>,
 
     /// Whether to use viewBox attribute in SVG output.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_lenient"
+    )]
     pub svg_view_box: Option<bool>,
 
     /// Whether to remove xlink namespace from SVG output.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_lenient"
+    )]
     pub svg_remove_xlink: Option<bool>,
 
     /// CSS stylesheet to embed in SVG output.
@@ -231,22 +452,41 @@ pub struct Options {
     pub svg_css: Option<String>,
 
     /// Whether to format SVG output with indentation.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_lenient"
+    )]
     pub svg_format_raw: Option<bool>,
 
     /// Whether to include font fallback in SVG output.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_lenient"
+    )]
     pub svg_font_face_include: Option<bool>,
 
+    /// Whether SVG dimensions are given in millimeters instead of pixels.
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_lenient"
+    )]
+    pub mm_output: Option<bool>,
+
     // =========================================================================
     // MIDI Options
     // =========================================================================
     /// Default MIDI tempo.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_lenient"
+    )]
     pub midi_tempo: Option<f64>,
 
     /// MIDI velocity for notes without dynamics.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_lenient"
+    )]
     pub midi_velocity: Option<u8>,
 
     // =========================================================================
@@ -263,6 +503,18 @@ pub struct Options {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub mdiv_x_path_query: Option<String>,
 
+    /// XPath queries selecting `<rdg>` or `<lem>` children of `<app>` elements.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub app_x_path_query: Option<Vec<String>>,
+
+    /// XPath queries selecting children of `<choice>` elements.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub choice_x_path_query: Option<Vec<String>>,
+
+    /// XPath queries selecting children of `<subst>` elements.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subst_x_path_query: Option<Vec<String>>,
+
     /// Expansion to use from the MEI document.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub expansion: Option<String>,
@@ -275,31 +527,145 @@ pub struct Options {
     pub transpose: Option<String>,
 
     /// Whether to transpose the written/sounding selection.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_lenient"
+    )]
     pub transpose_selected_only: Option<bool>,
 
     /// Whether to transpose to written or sounding pitch.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_lenient"
+    )]
     pub transpose_to_sounding_pitch: Option<bool>,
 
     // =========================================================================
     // Spacing Options
     // =========================================================================
     /// Spacing between staff lines.
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub spacing_staff: Option<u32>,
+    ///
+    /// Fractional values are accepted for fine tuning. This field was an
+    /// `Option<u32>` before; code assigning it directly needs a float literal
+    /// (`Some(12.0)`), while the builder still accepts integers.
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_lenient"
+    )]
+    pub spacing_staff: Option<f64>,
 
     /// Spacing between systems.
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub spacing_system: Option<u32>,
-
-    /// Linear spacing factor.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    ///
+    /// Fractional values are accepted for fine tuning. This field was an
+    /// `Option<u32>` before; code assigning it directly needs a float literal
+    /// (`Some(6.0)`), while the builder still accepts integers.
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_lenient"
+    )]
+    pub spacing_system: Option<f64>,
+
+    /// Linear spacing factor, from 0.0 to 1.0 (see [`Options::validate`]).
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_lenient"
+    )]
     pub spacing_linear: Option<f64>,
 
-    /// Non-linear spacing factor.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Non-linear spacing factor, from 0.0 to 1.0 (see [`Options::validate`]).
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_lenient"
+    )]
     pub spacing_non_linear: Option<f64>,
+
+    /// Whether to spread systems and staves vertically to fill the page.
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_lenient"
+    )]
+    pub justify_vertically: Option<bool>,
+
+    // =========================================================================
+    // Slur and Tie Options
+    // =========================================================================
+    /// Slur curve factor; higher values give flatter slurs (Verovio `slurCurveFactor`).
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_lenient"
+    )]
+    pub slur_curve_factor: Option<f64>,
+
+    /// Maximum slur slope in degrees (Verovio `slurMaxSlope`).
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_lenient"
+    )]
+    pub slur_max_slope: Option<f64>,
+
+    /// Minimum tie length in MEI units (Verovio `tieMinLength`).
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_lenient"
+    )]
+    pub tie_min_length: Option<f64>,
+
+    // =========================================================================
+    // Element Size Options
+    // =========================================================================
+    /// Size of clef changes relative to the initial clef (Verovio `clefChangeFactor`).
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_lenient"
+    )]
+    pub clef_change_factor: Option<f64>,
+
+    /// Size of grace notes relative to normal notes (Verovio `graceFactor`).
+    #[serde(
+        rename = "graceFactor",
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_lenient"
+    )]
+    pub graced_note_factor: Option<f64>,
+
+    // =========================================================================
+    // Early Music Options
+    // =========================================================================
+    /// Whether to render neumes as note heads instead of neume shapes
+    /// (Verovio `neumeAsNote`).
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_lenient"
+    )]
+    pub neume_as_note: Option<bool>,
+
+    /// Whether to render mensural ligatures as brackets above the notes
+    /// (Verovio `ligatureAsBracket`).
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_lenient"
+    )]
+    pub ligature_as_bracket: Option<bool>,
+
+    /// Whether to convert mensural sections into measure-based sections when
+    /// loading (Verovio `mensuralToMeasure`).
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_lenient"
+    )]
+    pub mensural_to_measure: Option<bool>,
+
+    // =========================================================================
+    // Unmodeled Options
+    // =========================================================================
+    /// Options not modeled by this struct, keyed by their Verovio name.
+    ///
+    /// Unknown keys found when deserializing end up here and are written back
+    /// unchanged when serializing, so options read from
+    /// [`Toolkit::current_options`](crate::Toolkit::current_options) survive a
+    /// round trip.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
 impl Options {
@@ -325,12 +691,311 @@ impl Options {
 
     /// Deserializes options from a JSON string.
     ///
+    /// Parsing is lenient so that the output of
+    /// [`Toolkit::get_options`](crate::Toolkit::get_options) can always be read
+    /// back: numbers and booleans may also be given as strings (`"100"`,
+    /// `"true"`), integral floats are accepted for integer options, and keys
+    /// that are not modeled are kept in [`extra`](Self::extra).
+    ///
     /// # Errors
     ///
     /// Returns an error if deserialization fails.
     pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
         serde_json::from_str(json)
     }
+
+    /// Asserts that the options serialize to the same JSON as `expected`.
+    ///
+    /// Both sides are normalized before comparing: object keys are sorted,
+    /// and numbers are compared by value, so `120` matches `120.0`. Enum
+    /// options compare by their Verovio names, e.g. `"breaks": "auto"`. This
+    /// lets crates snapshot their option configurations without depending on
+    /// field order or number formatting.
+    ///
+    /// Available in this crate's tests and with the `test-utils` feature.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `expected` is not valid JSON, or if the JSON differs. The
+    /// message shows both sides, normalized and pretty-printed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # #[cfg(feature = "test-utils")]
+    /// # {
+    /// use verovioxide::{BreakMode, Options};
+    ///
+    /// let options = Options::builder()
+    ///     .scale(40)
+    ///     .breaks(BreakMode::Encoded)
+    ///     .build();
+    /// options.assert_json_eq(r#"{"breaks": "encoded", "scale": 40}"#);
+    /// # }
+    /// ```
+    #[cfg(any(test, feature = "test-utils"))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "test-utils")))]
+    #[track_caller]
+    pub fn assert_json_eq(&self, expected: &str) {
+        let actual = serde_json::to_value(self).expect("options should serialize to JSON");
+        let expected: serde_json::Value = serde_json::from_str(expected)
+            .unwrap_or_else(|e| panic!("expected options JSON is invalid: {}", e));
+
+        let (actual, expected) = (normalize_json(actual), normalize_json(expected));
+        if actual != expected {
+            let pretty = |value: &serde_json::Value| {
+                serde_json::to_string_pretty(value).unwrap_or_else(|_| value.to_string())
+            };
+            panic!(
+                "options JSON differs\n  actual: {}\nexpected: {}",
+                pretty(&actual),
+                pretty(&expected)
+            );
+        }
+    }
+
+    /// Checks the options for combinations Verovio cannot honor together.
+    ///
+    /// Validation is opt-in: [`Toolkit::set_options`](crate::Toolkit::set_options)
+    /// passes options through unchanged, and Verovio silently lets one setting
+    /// win over the other. Call this to surface such mistakes early.
+    ///
+    /// The following combinations are rejected:
+    ///
+    /// - `adjust_page_height(true)` with a fixed `page_height`
+    /// - `adjust_page_width(true)` with a fixed `page_width`
+    /// - `breaks_smart_sb` with any break mode other than [`BreakMode::Smart`]
+    /// - a non-zero `system_max_per_page` with [`BreakMode::None`]
+    /// - `condense_first_page(true)` or `condense_tempo_pages(true)` with
+    ///   [`CondenseMode::None`]
+    ///
+    /// An option left at Verovio's default value does not take part in a
+    /// conflict, so the full set of options reported by
+    /// [`Toolkit::current_options`](crate::Toolkit::current_options), which
+    /// always includes `page_height`, `breaks` and `breaks_smart_sb`, passes
+    /// unless a contradictory value was actually set.
+    ///
+    /// Spacing factors outside Verovio's documented range, where layout
+    /// degenerates or takes unbounded time, are rejected too:
+    ///
+    /// - `spacing_linear` or `spacing_non_linear` outside 0.0..=1.0, or NaN
+    ///
+    /// # Errors
+    ///
+    /// Returns the first [`OptionsValidationError`] found.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use verovioxide::{Options, OptionsValidationError};
+    ///
+    /// let options = Options::builder()
+    ///     .page_height(1500)
+    ///     .adjust_page_height(true)
+    ///     .build();
+    ///
+    /// assert!(matches!(
+    ///     options.validate(),
+    ///     Err(OptionsValidationError::Conflict { .. })
+    /// ));
+    /// ```
+    pub fn validate(&self) -> Result<(), OptionsValidationError> {
+        let page_height = non_default(self.page_height, DEFAULT_PAGE_HEIGHT);
+        let page_width = non_default(self.page_width, DEFAULT_PAGE_WIDTH);
+        let breaks = non_default(self.breaks, BreakMode::Auto);
+        let breaks_smart_sb = non_default(self.breaks_smart_sb, DEFAULT_BREAKS_SMART_SB);
+
+        if self.adjust_page_height == Some(true) && page_height.is_some() {
+            return Err(OptionsValidationError::Conflict {
+                a: "adjust_page_height",
+                b: "page_height",
+            });
+        }
+
+        if self.adjust_page_width == Some(true) && page_width.is_some() {
+            return Err(OptionsValidationError::Conflict {
+                a: "adjust_page_width",
+                b: "page_width",
+            });
+        }
+
+        if breaks_smart_sb.is_some() && breaks.is_some_and(|mode| mode != BreakMode::Smart) {
+            return Err(OptionsValidationError::Conflict {
+                a: "breaks",
+                b: "breaks_smart_sb",
+            });
+        }
+
+        if self.system_max_per_page.is_some_and(|max| max > 0)
+            && self.breaks == Some(BreakMode::None)
+        {
+            return Err(OptionsValidationError::Conflict {
+                a: "breaks",
+                b: "system_max_per_page",
+            });
+        }
+
+        if self.condense == Some(CondenseMode::None) {
+            if self.condense_first_page == Some(true) {
+                return Err(OptionsValidationError::Conflict {
+                    a: "condense",
+                    b: "condense_first_page",
+                });
+            }
+            if self.condense_tempo_pages == Some(true) {
+                return Err(OptionsValidationError::Conflict {
+                    a: "condense",
+                    b: "condense_tempo_pages",
+                });
+            }
+        }
+
+        for (option, value) in [
+            ("spacing_linear", self.spacing_linear),
+            ("spacing_non_linear", self.spacing_non_linear),
+        ] {
+            if let Some(value) = value {
+                if !(0.0..=1.0).contains(&value) {
+                    return Err(OptionsValidationError::OutOfRange {
+                        option,
+                        value,
+                        min: 0.0,
+                        max: 1.0,
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Verovio's default `pageHeight`.
+const DEFAULT_PAGE_HEIGHT: u32 = 2970;
+
+/// Verovio's default `pageWidth`.
+const DEFAULT_PAGE_WIDTH: u32 = 2100;
+
+/// Verovio's default `breaksSmartSb`.
+const DEFAULT_BREAKS_SMART_SB: f64 = 0.66;
+
+/// Returns `value` unless it is Verovio's `default`, for conflict checks.
+fn non_default<T: PartialEq>(value: Option<T>, default: T) -> Option<T> {
+    value.filter(|value| *value != default)
+}
+
+/// An error returned by [`Options::validate`].
+///
+/// Option names are given as their [`OptionsBuilder`] method names.
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum OptionsValidationError {
+    /// Two options were set that contradict each other.
+    #[error("options `{a}` and `{b}` cannot be used together")]
+    Conflict {
+        /// The first conflicting option.
+        a: &'static str,
+        /// The second conflicting option.
+        b: &'static str,
+    },
+
+    /// An option was set outside the range Verovio handles.
+    #[error("option `{option}` must be between {min} and {max}, got {value}")]
+    OutOfRange {
+        /// The option.
+        option: &'static str,
+        /// The value it was set to.
+        value: f64,
+        /// The smallest accepted value.
+        min: f64,
+        /// The largest accepted value.
+        max: f64,
+    },
+}
+
+/// Returns the entries of the JSON object `current` whose value differs from
+/// the same key in `defaults`, or that `defaults` does not have.
+pub(crate) fn options_diff(
+    current: &str,
+    defaults: &str,
+) -> Result<serde_json::Map<String, serde_json::Value>, serde_json::Error> {
+    let current: serde_json::Map<String, serde_json::Value> = serde_json::from_str(current)?;
+    let defaults: serde_json::Map<String, serde_json::Value> = serde_json::from_str(defaults)?;
+
+    Ok(current
+        .into_iter()
+        .filter(|(key, value)| defaults.get(key) != Some(value))
+        .collect())
+}
+
+/// Sorts the keys of every object in `value` and turns every number into a
+/// float, so that equal options compare equal however they were written.
+#[cfg(any(test, feature = "test-utils"))]
+fn normalize_json(value: serde_json::Value) -> serde_json::Value {
+    use serde_json::Value;
+
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.into_iter().collect();
+            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+            Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key, normalize_json(value)))
+                    .collect(),
+            )
+        }
+        Value::Array(values) => Value::Array(values.into_iter().map(normalize_json).collect()),
+        Value::Number(n) => n
+            .as_f64()
+            .and_then(serde_json::Number::from_f64)
+            .map_or(Value::Number(n), Value::Number),
+        value => value,
+    }
+}
+
+/// Deserializes an optional number or boolean, accepting string values and
+/// integral floats for integer types.
+fn deserialize_lenient<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: DeserializeOwned + FromStr,
+    T::Err: fmt::Display,
+{
+    use serde::de::Error as _;
+
+    match serde_json::Value::deserialize(deserializer)? {
+        serde_json::Value::Null => Ok(None),
+        serde_json::Value::String(s) => s.trim().parse().map(Some).map_err(D::Error::custom),
+        serde_json::Value::Number(n) => match serde_json::from_value(n.clone().into()) {
+            Ok(value) => Ok(Some(value)),
+            Err(e) => match n.as_f64() {
+                Some(f) if f.fract() == 0.0 => format!("{}", f as i64)
+                    .parse()
+                    .map(Some)
+                    .map_err(D::Error::custom),
+                _ => Err(D::Error::custom(e)),
+            },
+        },
+        value => serde_json::from_value(value)
+            .map(Some)
+            .map_err(D::Error::custom),
+    }
+}
+
+/// Deserializes the pedal style, reading Verovio's default of `"auto"` as
+/// unset.
+fn deserialize_pedal_style<'de, D>(deserializer: D) -> Result<Option<PedalStyle>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    use serde::de::Error as _;
+
+    match Option::<String>::deserialize(deserializer)?.as_deref() {
+        None | Some("auto") => Ok(None),
+        Some(style) => serde_json::from_value(style.into())
+            .map(Some)
+            .map_err(D::Error::custom),
+    }
 }
 
 /// Builder for constructing [`Options`].
@@ -379,6 +1044,13 @@ impl OptionsBuilder {
         self
     }
 
+    /// Sets whether to adjust the page width to the content.
+    #[must_use]
+    pub fn adjust_page_width(mut self, adjust: bool) -> Self {
+        self.options.adjust_page_width = Some(adjust);
+        self
+    }
+
     /// Sets the page margin for all sides in MEI units.
     ///
     /// # See also
@@ -439,6 +1111,34 @@ impl OptionsBuilder {
         self
     }
 
+    /// Sets whether to remove unreferenced `xml:id`s when exporting MEI.
+    #[must_use]
+    pub fn remove_ids(mut self, remove: bool) -> Self {
+        self.options.remove_ids = Some(remove);
+        self
+    }
+
+    /// Sets the number of spaces per indentation level in MEI and Humdrum output.
+    #[must_use]
+    pub fn output_indent(mut self, spaces: u32) -> Self {
+        self.options.output_indent = Some(spaces);
+        self
+    }
+
+    /// Sets whether to indent MEI and Humdrum output with tabs.
+    #[must_use]
+    pub fn output_indent_tab(mut self, tab: bool) -> Self {
+        self.options.output_indent_tab = Some(tab);
+        self
+    }
+
+    /// Sets whether to write MEI output without line breaks or indentation.
+    #[must_use]
+    pub fn output_format_raw(mut self, raw: bool) -> Self {
+        self.options.output_format_raw = Some(raw);
+        self
+    }
+
     /// Sets the SMuFL music font to use.
     #[must_use]
     pub fn font(mut self, font: impl Into<String>) -> Self {
@@ -446,6 +1146,13 @@ impl OptionsBuilder {
         self
     }
 
+    /// Sets the music font used for glyphs missing from the main font.
+    #[must_use]
+    pub fn fallback_font(mut self, font: impl Into<String>) -> Self {
+        self.options.fallback_font = Some(font.into());
+        self
+    }
+
     /// Sets the lyric size as a percentage of staff size.
     #[must_use]
     pub fn lyric_size(mut self, size: f64) -> Self {
@@ -453,6 +1160,21 @@ impl OptionsBuilder {
         self
     }
 
+    /// Sets whether to close the gap left by verses without a syllable.
+    #[must_use]
+    pub fn lyric_verse_collapse(mut self, collapse: bool) -> Self {
+        self.options.lyric_verse_collapse = Some(collapse);
+        self
+    }
+
+    /// Sets the language of the score's text (a BCP 47 tag such as `he`), or
+    /// clears it with an empty string.
+    #[must_use]
+    pub fn text_lang(mut self, lang: impl Into<String>) -> Self {
+        self.options.text_lang = Some(lang.into());
+        self
+    }
+
     /// Sets the break mode for page and system breaks.
     #[must_use]
     pub fn breaks(mut self, mode: BreakMode) -> Self {
@@ -460,6 +1182,20 @@ impl OptionsBuilder {
         self
     }
 
+    /// Sets the page fill threshold for smart system breaks.
+    #[must_use]
+    pub fn breaks_smart_sb(mut self, threshold: f64) -> Self {
+        self.options.breaks_smart_sb = Some(threshold);
+        self
+    }
+
+    /// Sets the maximum number of systems per page (0 for no limit).
+    #[must_use]
+    pub fn system_max_per_page(mut self, systems: u32) -> Self {
+        self.options.system_max_per_page = Some(systems);
+        self
+    }
+
     /// Sets the condense mode for dense layouts.
     #[must_use]
     pub fn condense(mut self, mode: CondenseMode) -> Self {
@@ -509,6 +1245,20 @@ impl OptionsBuilder {
         self
     }
 
+    /// Sets the pedal marking style.
+    #[must_use]
+    pub fn pedal_style(mut self, style: PedalStyle) -> Self {
+        self.options.pedal_style = Some(style);
+        self
+    }
+
+    /// Sets the default distance of harmony indications from the staff.
+    #[must_use]
+    pub fn harm_dist(mut self, dist: f64) -> Self {
+        self.options.harm_dist = Some(dist);
+        self
+    }
+
     /// Sets whether to include the XML declaration in SVG output.
     #[must_use]
     pub fn svg_xml_declaration(mut self, include: bool) -> Self {
@@ -558,6 +1308,13 @@ impl OptionsBuilder {
         self
     }
 
+    /// Sets whether SVG dimensions are given in millimeters.
+    #[must_use]
+    pub fn mm_output(mut self, mm: bool) -> Self {
+        self.options.mm_output = Some(mm);
+        self
+    }
+
     /// Sets the default MIDI tempo.
     #[must_use]
     pub fn midi_tempo(mut self, tempo: f64) -> Self {
@@ -586,6 +1343,39 @@ impl OptionsBuilder {
         self
     }
 
+    /// Sets the XPath queries for selecting readings within `<app>` elements.
+    #[must_use]
+    pub fn app_x_path_query<I, S>(mut self, queries: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.options.app_x_path_query = Some(queries.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Sets the XPath queries for selecting children of `<choice>` elements.
+    #[must_use]
+    pub fn choice_x_path_query<I, S>(mut self, queries: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.options.choice_x_path_query = Some(queries.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Sets the XPath queries for selecting children of `<subst>` elements.
+    #[must_use]
+    pub fn subst_x_path_query<I, S>(mut self, queries: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.options.subst_x_path_query = Some(queries.into_iter().map(Into::into).collect());
+        self
+    }
+
     /// Sets the expansion to use from the MEI document.
     #[must_use]
     pub fn expansion(mut self, expansion: impl Into<String>) -> Self {
@@ -614,17 +1404,17 @@ impl OptionsBuilder {
         self
     }
 
-    /// Sets the spacing between staff lines.
+    /// Sets the spacing between staff lines; integers and fractions are accepted.
     #[must_use]
-    pub fn spacing_staff(mut self, spacing: u32) -> Self {
-        self.options.spacing_staff = Some(spacing);
+    pub fn spacing_staff(mut self, spacing: impl Into<f64>) -> Self {
+        self.options.spacing_staff = Some(spacing.into());
         self
     }
 
-    /// Sets the spacing between systems.
+    /// Sets the spacing between systems; integers and fractions are accepted.
     #[must_use]
-    pub fn spacing_system(mut self, spacing: u32) -> Self {
-        self.options.spacing_system = Some(spacing);
+    pub fn spacing_system(mut self, spacing: impl Into<f64>) -> Self {
+        self.options.spacing_system = Some(spacing.into());
         self
     }
 
@@ -642,6 +1432,69 @@ impl OptionsBuilder {
         self
     }
 
+    /// Sets whether to spread systems and staves vertically to fill the page.
+    #[must_use]
+    pub fn justify_vertically(mut self, justify: bool) -> Self {
+        self.options.justify_vertically = Some(justify);
+        self
+    }
+
+    /// Sets the slur curve factor.
+    #[must_use]
+    pub fn slur_curve_factor(mut self, factor: f64) -> Self {
+        self.options.slur_curve_factor = Some(factor);
+        self
+    }
+
+    /// Sets the maximum slur slope in degrees.
+    #[must_use]
+    pub fn slur_max_slope(mut self, slope: f64) -> Self {
+        self.options.slur_max_slope = Some(slope);
+        self
+    }
+
+    /// Sets the minimum tie length.
+    #[must_use]
+    pub fn tie_min_length(mut self, length: f64) -> Self {
+        self.options.tie_min_length = Some(length);
+        self
+    }
+
+    /// Sets the size of clef changes relative to the initial clef.
+    #[must_use]
+    pub fn clef_change_factor(mut self, factor: f64) -> Self {
+        self.options.clef_change_factor = Some(factor);
+        self
+    }
+
+    /// Sets the size of grace notes relative to normal notes.
+    #[must_use]
+    pub fn graced_note_factor(mut self, factor: f64) -> Self {
+        self.options.graced_note_factor = Some(factor);
+        self
+    }
+
+    /// Sets whether to render neumes as note heads.
+    #[must_use]
+    pub fn neume_as_note(mut self, as_note: bool) -> Self {
+        self.options.neume_as_note = Some(as_note);
+        self
+    }
+
+    /// Sets whether to render mensural ligatures as brackets.
+    #[must_use]
+    pub fn ligature_as_bracket(mut self, as_bracket: bool) -> Self {
+        self.options.ligature_as_bracket = Some(as_bracket);
+        self
+    }
+
+    /// Sets whether to convert mensural sections into measures.
+    #[must_use]
+    pub fn mensural_to_measure(mut self, to_measure: bool) -> Self {
+        self.options.mensural_to_measure = Some(to_measure);
+        self
+    }
+
     /// Builds the options.
     ///
     /// # See also
@@ -680,6 +1533,15 @@ mod tests {
         assert_eq!(options.adjust_page_height, Some(true));
     }
 
+    #[test]
+    fn test_options_builder_adjust_page_width() {
+        let options = Options::builder().adjust_page_width(true).build();
+        assert_eq!(options.adjust_page_width, Some(true));
+
+        let json = options.to_json().unwrap();
+        assert!(json.contains("\"adjustPageWidth\":true"));
+    }
+
     #[test]
     fn test_options_builder_font() {
         let options = Options::builder().font("Bravura").build();
@@ -692,6 +1554,30 @@ mod tests {
         assert_eq!(options.breaks, Some(BreakMode::Encoded));
     }
 
+    #[test]
+    fn test_options_builder_breaks_smart_sb() {
+        let options = Options::builder()
+            .breaks(BreakMode::Smart)
+            .breaks_smart_sb(0.5)
+            .build();
+        assert_eq!(options.breaks_smart_sb, Some(0.5));
+
+        let json = options.to_json().unwrap();
+        assert!(json.contains("\"breaksSmartSb\":0.5"));
+    }
+
+    #[test]
+    fn test_options_builder_system_max_per_page() {
+        let options = Options::builder().system_max_per_page(3).build();
+        assert_eq!(options.system_max_per_page, Some(3));
+
+        let json = options.to_json().unwrap();
+        assert!(json.contains("\"systemMaxPerPage\":3"));
+
+        let parsed: Options = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.system_max_per_page, Some(3));
+    }
+
     #[test]
     fn test_options_builder_header_footer() {
         let options = Options::builder()
@@ -799,6 +1685,34 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_font_as_str() {
+        assert_eq!(Font::default(), Font::Leipzig);
+        assert_eq!(Font::Bravura.as_str(), "Bravura");
+        assert_eq!(Font::Petaluma.as_str(), "Petaluma");
+        assert_eq!(serde_json::to_string(&Font::Leland).unwrap(), r#""Leland""#);
+        let font: Font = serde_json::from_str(r#""Gootville""#).unwrap();
+        assert_eq!(font, Font::Gootville);
+    }
+
+    #[test]
+    fn test_options_fallback_font_json() {
+        let options = Options::builder()
+            .font("Leipzig")
+            .fallback_font("Bravura")
+            .build();
+        assert_eq!(options.fallback_font.as_deref(), Some("Bravura"));
+
+        let json = options.to_json().unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["font"], "Leipzig");
+        assert_eq!(value["fontFallback"], "Bravura");
+
+        let parsed = Options::from_json(r#"{"fontFallback": "Leipzig"}"#).unwrap();
+        assert_eq!(parsed.fallback_font.as_deref(), Some("Leipzig"));
+        assert!(parsed.extra.is_empty());
+    }
+
     #[test]
     fn test_text_font_default() {
         let font = TextFont::default();
@@ -847,16 +1761,59 @@ mod tests {
             .svg_font_face_include(true)
             .build();
 
-        assert_eq!(options.svg_xml_declaration, Some(false));
-        assert_eq!(options.svg_bounding_boxes, Some(true));
-        assert_eq!(options.svg_view_box, Some(true));
-        assert_eq!(options.svg_remove_xlink, Some(true));
-        assert_eq!(
-            options.svg_css,
-            Some("svg { background: white; }".to_string())
-        );
-        assert_eq!(options.svg_format_raw, Some(false));
-        assert_eq!(options.svg_font_face_include, Some(true));
+        assert_eq!(options.svg_xml_declaration, Some(false));
+        assert_eq!(options.svg_bounding_boxes, Some(true));
+        assert_eq!(options.svg_view_box, Some(true));
+        assert_eq!(options.svg_remove_xlink, Some(true));
+        assert_eq!(
+            options.svg_css,
+            Some("svg { background: white; }".to_string())
+        );
+        assert_eq!(options.svg_format_raw, Some(false));
+        assert_eq!(options.svg_font_face_include, Some(true));
+    }
+
+    #[test]
+    fn test_options_output_formatting_json() {
+        let options = Options::builder()
+            .output_indent(2)
+            .output_indent_tab(false)
+            .output_format_raw(true)
+            .build();
+        assert_eq!(options.output_indent, Some(2));
+        assert_eq!(options.output_indent_tab, Some(false));
+        assert_eq!(options.output_format_raw, Some(true));
+
+        let json = options.to_json().unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["outputIndent"], 2);
+        assert_eq!(value["outputIndentTab"], false);
+        assert_eq!(value["outputFormatRaw"], true);
+
+        let parsed = Options::from_json(
+            r#"{"outputIndent": "4", "outputIndentTab": true, "outputFormatRaw": "false"}"#,
+        )
+        .unwrap();
+        assert_eq!(parsed.output_indent, Some(4));
+        assert_eq!(parsed.output_indent_tab, Some(true));
+        assert_eq!(parsed.output_format_raw, Some(false));
+        assert!(parsed.extra.is_empty());
+    }
+
+    #[test]
+    fn test_options_builder_mm_output_and_remove_ids() {
+        let options = Options::builder().mm_output(true).remove_ids(true).build();
+        assert_eq!(options.mm_output, Some(true));
+        assert_eq!(options.remove_ids, Some(true));
+
+        let json = options.to_json().unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["mmOutput"], true);
+        assert_eq!(value["removeIds"], true);
+
+        let parsed = Options::from_json(r#"{"mmOutput": false, "removeIds": false}"#).unwrap();
+        assert_eq!(parsed.mm_output, Some(false));
+        assert_eq!(parsed.remove_ids, Some(false));
     }
 
     #[test]
@@ -877,12 +1834,33 @@ mod tests {
             .spacing_system(6)
             .spacing_linear(0.25)
             .spacing_non_linear(0.6)
+            .justify_vertically(true)
             .build();
 
-        assert_eq!(options.spacing_staff, Some(12));
-        assert_eq!(options.spacing_system, Some(6));
+        assert_eq!(options.spacing_staff, Some(12.0));
+        assert_eq!(options.spacing_system, Some(6.0));
         assert_eq!(options.spacing_linear, Some(0.25));
         assert_eq!(options.spacing_non_linear, Some(0.6));
+        assert_eq!(options.justify_vertically, Some(true));
+
+        let json = options.to_json().unwrap();
+        assert!(json.contains(r#""justifyVertically":true"#));
+    }
+
+    #[test]
+    fn test_options_fractional_spacing() {
+        let options = Options::builder()
+            .spacing_staff(8.25)
+            .spacing_system(6.5)
+            .build();
+        assert_eq!(options.spacing_system, Some(6.5));
+
+        let json: serde_json::Value = serde_json::from_str(&options.to_json().unwrap()).unwrap();
+        assert_eq!(json["spacingStaff"], 8.25);
+        assert_eq!(json["spacingSystem"], 6.5);
+
+        let parsed = Options::from_json(r#"{"spacingSystem": 6.5}"#).unwrap();
+        assert_eq!(parsed.spacing_system, Some(6.5));
     }
 
     #[test]
@@ -946,6 +1924,100 @@ mod tests {
         assert_eq!(options.scale, Some(100));
     }
 
+    #[test]
+    fn test_options_from_json_unknown_fields_preserved() {
+        let options =
+            Options::from_json(r#"{"unknownField": "value", "nested": {"a": [1, 2]}}"#).unwrap();
+        assert_eq!(options.extra["unknownField"], "value");
+
+        let value: serde_json::Value = serde_json::from_str(&options.to_json().unwrap()).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({"unknownField": "value", "nested": {"a": [1, 2]}})
+        );
+    }
+
+    #[test]
+    fn test_options_from_json_lenient_values() {
+        let options = Options::from_json(
+            r#"{"scale": "80", "pageWidth": 2100.0, "lyricSize": "4.5", "svgViewBox": "true", "midiVelocity": null}"#,
+        )
+        .unwrap();
+        assert_eq!(options.scale, Some(80));
+        assert_eq!(options.page_width, Some(2100));
+        assert_eq!(options.lyric_size, Some(4.5));
+        assert_eq!(options.svg_view_box, Some(true));
+        assert_eq!(options.midi_velocity, None);
+
+        assert!(Options::from_json(r#"{"pageWidth": 2100.5}"#).is_err());
+        assert!(Options::from_json(r#"{"scale": -1}"#).is_err());
+    }
+
+    #[test]
+    fn test_options_round_trip_verovio_output() {
+        // Shape of `vrvToolkit_getOptions` output, trimmed
+        let verovio = r#"{
+            "adjustPageHeight": false,
+            "breaks": "auto",
+            "condense": "auto",
+            "evenNoteSpacing": false,
+            "expansion": "",
+            "font": "Leipzig",
+            "footer": "auto",
+            "header": "auto",
+            "inputFrom": "mei",
+            "lyricSize": 4.5,
+            "mdivXPathQuery": "",
+            "appXPathQuery": [],
+            "minMeasureWidth": 15.0,
+            "mmOutput": false,
+            "pageHeight": 2970,
+            "pageMarginBottom": 50,
+            "pageMarginLeft": 50,
+            "pageMarginRight": 50,
+            "pageMarginTop": 50,
+            "pageWidth": 2100,
+            "removeIds": false,
+            "scale": 100,
+            "spacingLinear": 0.25,
+            "spacingNonLinear": 0.6,
+            "spacingStaff": "12",
+            "spacingSystem": 4,
+            "svgBoundingBoxes": false,
+            "svgCss": "",
+            "svgViewBox": false,
+            "transpose": "",
+            "barLineWidth": 0.3,
+            "beamMaxSlope": 10,
+            "justifyVertically": false,
+            "outputIndent": 3,
+            "smuflTextFont": "embedded"
+        }"#;
+
+        let options = Options::from_json(verovio).expect("Verovio output should parse");
+        assert_eq!(options.scale, Some(100));
+        assert_eq!(options.breaks, Some(BreakMode::Auto));
+        assert_eq!(options.min_measure_width, Some(15));
+        assert_eq!(options.spacing_staff, Some(12.0));
+        assert_eq!(options.app_x_path_query, Some(Vec::new()));
+        assert_eq!(options.output_indent, Some(3));
+        assert_eq!(options.justify_vertically, Some(false));
+        assert_eq!(options.extra.len(), 3);
+        assert_eq!(options.extra["smuflTextFont"], "embedded");
+
+        let original: serde_json::Value = serde_json::from_str(verovio).unwrap();
+        let round_trip: serde_json::Value =
+            serde_json::from_str(&options.to_json().unwrap()).unwrap();
+        let original = original.as_object().unwrap();
+        let round_trip = round_trip.as_object().unwrap();
+        assert_eq!(
+            original.keys().collect::<std::collections::BTreeSet<_>>(),
+            round_trip.keys().collect::<std::collections::BTreeSet<_>>()
+        );
+        assert_eq!(round_trip["barLineWidth"], 0.3);
+        assert_eq!(round_trip["spacingStaff"], 12.0);
+    }
+
     #[test]
     fn test_options_from_json_wrong_type() {
         let result = Options::from_json(r#"{"scale": "not a number"}"#);
@@ -1210,6 +2282,211 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_options_builder_editorial_x_path_queries() {
+        let options = Options::builder()
+            .app_x_path_query(["./rdg[contains(@source, 'A')]", "./lem"])
+            .choice_x_path_query(vec!["./orig".to_string()])
+            .subst_x_path_query(["./add"])
+            .build();
+        assert_eq!(
+            options.app_x_path_query,
+            Some(vec![
+                "./rdg[contains(@source, 'A')]".to_string(),
+                "./lem".to_string()
+            ])
+        );
+        assert_eq!(
+            options.choice_x_path_query,
+            Some(vec!["./orig".to_string()])
+        );
+        assert_eq!(options.subst_x_path_query, Some(vec!["./add".to_string()]));
+    }
+
+    #[test]
+    fn test_options_editorial_x_path_queries_json() {
+        let options = Options::builder()
+            .app_x_path_query(["./rdg[1]", "./lem"])
+            .choice_x_path_query(["./sic", "./orig"])
+            .subst_x_path_query(["./del"])
+            .build();
+        let json = options.to_json().unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            value["appXPathQuery"],
+            serde_json::json!(["./rdg[1]", "./lem"])
+        );
+        assert_eq!(
+            value["choiceXPathQuery"],
+            serde_json::json!(["./sic", "./orig"])
+        );
+        assert_eq!(value["substXPathQuery"], serde_json::json!(["./del"]));
+
+        let parsed = Options::from_json(&json).unwrap();
+        assert_eq!(parsed.app_x_path_query, options.app_x_path_query);
+        assert_eq!(parsed.choice_x_path_query, options.choice_x_path_query);
+        assert_eq!(parsed.subst_x_path_query, options.subst_x_path_query);
+    }
+
+    #[test]
+    fn test_options_builder_slur_tie_shaping() {
+        let options = Options::builder()
+            .slur_curve_factor(1.5)
+            .slur_max_slope(45.0)
+            .tie_min_length(2.0)
+            .build();
+        assert_eq!(options.slur_curve_factor, Some(1.5));
+        assert_eq!(options.slur_max_slope, Some(45.0));
+        assert_eq!(options.tie_min_length, Some(2.0));
+    }
+
+    #[test]
+    fn test_options_slur_tie_shaping_json() {
+        let options = Options::builder()
+            .slur_curve_factor(1.5)
+            .slur_max_slope(45.0)
+            .tie_min_length(2.0)
+            .build();
+        let json = options.to_json().unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["slurCurveFactor"], 1.5);
+        assert_eq!(value["slurMaxSlope"], 45.0);
+        assert_eq!(value["tieMinLength"], 2.0);
+
+        let parsed = Options::from_json(
+            r#"{"slurCurveFactor": 1.5, "slurMaxSlope": 45, "tieMinLength": "2"}"#,
+        )
+        .unwrap();
+        assert_eq!(parsed.slur_curve_factor, Some(1.5));
+        assert_eq!(parsed.slur_max_slope, Some(45.0));
+        assert_eq!(parsed.tie_min_length, Some(2.0));
+        assert!(parsed.extra.is_empty());
+    }
+
+    #[test]
+    fn test_options_element_size_json() {
+        let options = Options::builder()
+            .clef_change_factor(0.66)
+            .graced_note_factor(0.75)
+            .build();
+        assert_eq!(options.clef_change_factor, Some(0.66));
+        assert_eq!(options.graced_note_factor, Some(0.75));
+
+        let json = options.to_json().unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["clefChangeFactor"], 0.66);
+        assert_eq!(value["graceFactor"], 0.75);
+
+        let parsed =
+            Options::from_json(r#"{"clefChangeFactor": 0.66, "graceFactor": "0.75"}"#).unwrap();
+        assert_eq!(parsed.clef_change_factor, Some(0.66));
+        assert_eq!(parsed.graced_note_factor, Some(0.75));
+        assert!(parsed.extra.is_empty());
+    }
+
+    #[test]
+    fn test_options_early_music_json() {
+        let options = Options::builder()
+            .neume_as_note(true)
+            .ligature_as_bracket(false)
+            .mensural_to_measure(true)
+            .build();
+        assert_eq!(options.neume_as_note, Some(true));
+        assert_eq!(options.ligature_as_bracket, Some(false));
+        assert_eq!(options.mensural_to_measure, Some(true));
+
+        let json = options.to_json().unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["neumeAsNote"], true);
+        assert_eq!(value["ligatureAsBracket"], false);
+        assert_eq!(value["mensuralToMeasure"], true);
+
+        let parsed = Options::from_json(
+            r#"{"neumeAsNote": "true", "ligatureAsBracket": false, "mensuralToMeasure": true}"#,
+        )
+        .unwrap();
+        assert_eq!(parsed.neume_as_note, Some(true));
+        assert_eq!(parsed.ligature_as_bracket, Some(false));
+        assert_eq!(parsed.mensural_to_measure, Some(true));
+        assert!(parsed.extra.is_empty());
+    }
+
+    #[test]
+    fn test_options_assert_json_eq_ignores_key_order_and_number_format() {
+        let options = Options::builder()
+            .scale(40)
+            .midi_tempo(120.0)
+            .adjust_page_height(true)
+            .build();
+        options.assert_json_eq(r#"{"midiTempo": 120, "adjustPageHeight": true, "scale": 40.0}"#);
+        Options::default().assert_json_eq("{}");
+    }
+
+    #[test]
+    fn test_options_assert_json_eq_enum_fields() {
+        let options = Options::builder()
+            .breaks(BreakMode::Encoded)
+            .condense(CondenseMode::None)
+            .header(HeaderMode::None)
+            .build();
+        options.assert_json_eq(r#"{"header": "none", "condense": "none", "breaks": "encoded"}"#);
+    }
+
+    #[test]
+    fn test_options_assert_json_eq_extra_and_nested() {
+        let mut options = Options::builder().breaks(BreakMode::Smart).build();
+        options.extra.insert(
+            "appXPathQuery".into(),
+            serde_json::json!(["./rdg[contains(@source, 'A')]"]),
+        );
+        options.assert_json_eq(
+            r#"{"appXPathQuery": ["./rdg[contains(@source, 'A')]"], "breaks": "smart"}"#,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "options JSON differs")]
+    fn test_options_assert_json_eq_mismatch() {
+        Options::builder()
+            .breaks(BreakMode::Auto)
+            .build()
+            .assert_json_eq(r#"{"breaks": "none"}"#);
+    }
+
+    #[test]
+    #[should_panic(expected = "expected options JSON is invalid")]
+    fn test_options_assert_json_eq_invalid_expected() {
+        Options::default().assert_json_eq("{breaks}");
+    }
+
+    #[test]
+    fn test_options_lyric_verse_json() {
+        let options = Options::builder().lyric_verse_collapse(true).build();
+        assert_eq!(options.lyric_verse_collapse, Some(true));
+
+        let json = options.to_json().unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["lyricVerseCollapse"], true);
+
+        let parsed = Options::from_json(r#"{"lyricVerseCollapse": "true"}"#).unwrap();
+        assert_eq!(parsed.lyric_verse_collapse, Some(true));
+        assert!(parsed.extra.is_empty());
+    }
+
+    #[test]
+    fn test_options_text_lang_json() {
+        let options = Options::builder().text_lang("he").build();
+        assert_eq!(options.text_lang.as_deref(), Some("he"));
+
+        let json = options.to_json().unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["textLang"], "he");
+
+        let parsed = Options::from_json(r#"{"textLang": "ar-EG"}"#).unwrap();
+        assert_eq!(parsed.text_lang.as_deref(), Some("ar-EG"));
+        assert!(parsed.extra.is_empty());
+    }
+
     #[test]
     fn test_options_builder_expansion() {
         let options = Options::builder().expansion("expanded").build();
@@ -1517,6 +2794,50 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_pedal_style_serialize_all_variants() {
+        for (style, expected) in [
+            (PedalStyle::Line, r#""line""#),
+            (PedalStyle::Bracket, r#""pedline""#),
+            (PedalStyle::PedStar, r#""pedstar""#),
+            (PedalStyle::AltPedStar, r#""altpedstar""#),
+        ] {
+            assert_eq!(serde_json::to_string(&style).unwrap(), expected);
+            let parsed: PedalStyle = serde_json::from_str(expected).unwrap();
+            assert_eq!(parsed, style);
+        }
+    }
+
+    #[test]
+    fn test_pedal_style_auto_reads_as_unset() {
+        let options = Options::from_json(r#"{"pedalStyle": "auto"}"#).unwrap();
+        assert_eq!(options.pedal_style, None);
+
+        let options = Options::from_json(r#"{"pedalStyle": "pedline"}"#).unwrap();
+        assert_eq!(options.pedal_style, Some(PedalStyle::Bracket));
+    }
+
+    #[test]
+    fn test_pedal_style_invalid_deserialize() {
+        let result: std::result::Result<PedalStyle, _> = serde_json::from_str(r#""auto""#);
+        assert!(result.is_err());
+        assert!(Options::from_json(r#"{"pedalStyle": "sideways"}"#).is_err());
+    }
+
+    #[test]
+    fn test_options_builder_pedal_style_and_harm_dist() {
+        let options = Options::builder()
+            .pedal_style(PedalStyle::PedStar)
+            .harm_dist(1.5)
+            .build();
+        assert_eq!(options.pedal_style, Some(PedalStyle::PedStar));
+        assert_eq!(options.harm_dist, Some(1.5));
+
+        let value: serde_json::Value = serde_json::from_str(&options.to_json().unwrap()).unwrap();
+        assert_eq!(value["pedalStyle"], "pedstar");
+        assert_eq!(value["harmDist"], 1.5);
+    }
+
     #[test]
     fn test_footer_mode_invalid_deserialize() {
         let result: std::result::Result<FooterMode, _> = serde_json::from_str(r#""invalid_mode""#);
@@ -1596,4 +2917,235 @@ mod tests {
         assert_eq!(options2.spacing_linear, Some(1.0));
         assert_eq!(options2.spacing_non_linear, Some(1.0));
     }
+
+    // =========================================================================
+    // Options Diff Tests
+    // =========================================================================
+
+    #[test]
+    fn test_options_diff() {
+        let defaults = r#"{"scale": 100, "breaks": "auto", "svgCss": ""}"#;
+        let current = r#"{"scale": 50, "breaks": "auto", "svgCss": "", "newOption": true}"#;
+
+        let diff = options_diff(current, defaults).unwrap();
+        assert_eq!(
+            serde_json::Value::Object(diff),
+            serde_json::json!({"scale": 50, "newOption": true})
+        );
+        assert!(options_diff(defaults, defaults).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_options_diff_invalid_json() {
+        assert!(options_diff("not json", "{}").is_err());
+        assert!(options_diff("{}", "[]").is_err());
+    }
+
+    // =========================================================================
+    // Validation Tests
+    // =========================================================================
+
+    #[test]
+    fn test_options_validate_default_ok() {
+        assert_eq!(Options::default().validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_options_validate_adjust_page_height_with_page_height() {
+        let options = Options::builder()
+            .page_height(1500)
+            .adjust_page_height(true)
+            .build();
+        assert_eq!(
+            options.validate(),
+            Err(OptionsValidationError::Conflict {
+                a: "adjust_page_height",
+                b: "page_height",
+            })
+        );
+
+        let options = Options::builder()
+            .page_height(1500)
+            .adjust_page_height(false)
+            .build();
+        assert_eq!(options.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_options_validate_ignores_verovio_defaults() {
+        let options = Options::builder()
+            .page_height(2970)
+            .page_width(2100)
+            .adjust_page_height(true)
+            .adjust_page_width(true)
+            .breaks(BreakMode::Auto)
+            .breaks_smart_sb(0.66)
+            .build();
+        assert_eq!(options.validate(), Ok(()));
+
+        let options = Options::builder()
+            .breaks(BreakMode::Line)
+            .breaks_smart_sb(0.66)
+            .build();
+        assert_eq!(options.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_options_validate_adjust_page_width_with_page_width() {
+        let options = Options::builder()
+            .page_width(1500)
+            .adjust_page_width(true)
+            .build();
+        assert_eq!(
+            options.validate(),
+            Err(OptionsValidationError::Conflict {
+                a: "adjust_page_width",
+                b: "page_width",
+            })
+        );
+    }
+
+    #[test]
+    fn test_options_validate_breaks_none_with_breaks_smart_sb() {
+        let options = Options::builder()
+            .breaks(BreakMode::None)
+            .breaks_smart_sb(0.5)
+            .build();
+        assert_eq!(
+            options.validate(),
+            Err(OptionsValidationError::Conflict {
+                a: "breaks",
+                b: "breaks_smart_sb",
+            })
+        );
+    }
+
+    #[test]
+    fn test_options_validate_breaks_smart_with_breaks_smart_sb() {
+        let options = Options::builder()
+            .breaks(BreakMode::Smart)
+            .breaks_smart_sb(0.5)
+            .build();
+        assert_eq!(options.validate(), Ok(()));
+
+        let options = Options::builder().breaks_smart_sb(0.5).build();
+        assert_eq!(options.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_options_validate_breaks_none_with_system_max_per_page() {
+        let options = Options::builder()
+            .breaks(BreakMode::None)
+            .system_max_per_page(3)
+            .build();
+        assert_eq!(
+            options.validate(),
+            Err(OptionsValidationError::Conflict {
+                a: "breaks",
+                b: "system_max_per_page",
+            })
+        );
+
+        let options = Options::builder()
+            .breaks(BreakMode::None)
+            .system_max_per_page(0)
+            .build();
+        assert_eq!(options.validate(), Ok(()));
+
+        let options = Options::builder()
+            .breaks(BreakMode::Auto)
+            .system_max_per_page(3)
+            .adjust_page_height(true)
+            .build();
+        assert_eq!(options.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_options_validate_condense_none_with_condense_first_page() {
+        let options = Options::builder()
+            .condense(CondenseMode::None)
+            .condense_first_page(true)
+            .build();
+        assert_eq!(
+            options.validate(),
+            Err(OptionsValidationError::Conflict {
+                a: "condense",
+                b: "condense_first_page",
+            })
+        );
+    }
+
+    #[test]
+    fn test_options_validate_spacing_in_range() {
+        for factor in [0.0, 0.25, 0.6, 1.0] {
+            let options = Options::builder()
+                .spacing_linear(factor)
+                .spacing_non_linear(factor)
+                .build();
+            assert_eq!(options.validate(), Ok(()));
+        }
+    }
+
+    #[test]
+    fn test_options_validate_spacing_out_of_range() {
+        let options = Options::builder().spacing_linear(2.5).build();
+        assert_eq!(
+            options.validate(),
+            Err(OptionsValidationError::OutOfRange {
+                option: "spacing_linear",
+                value: 2.5,
+                min: 0.0,
+                max: 1.0,
+            })
+        );
+
+        let options = Options::builder()
+            .spacing_linear(0.25)
+            .spacing_non_linear(-0.1)
+            .build();
+        assert_eq!(
+            options.validate(),
+            Err(OptionsValidationError::OutOfRange {
+                option: "spacing_non_linear",
+                value: -0.1,
+                min: 0.0,
+                max: 1.0,
+            })
+        );
+
+        let options = Options::builder().spacing_non_linear(f64::NAN).build();
+        assert!(matches!(
+            options.validate(),
+            Err(OptionsValidationError::OutOfRange {
+                option: "spacing_non_linear",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_options_validation_error_display() {
+        let err = OptionsValidationError::Conflict {
+            a: "adjust_page_height",
+            b: "page_height",
+        };
+        assert_eq!(
+            err.to_string(),
+            "options `adjust_page_height` and `page_height` cannot be used together"
+        );
+    }
+
+    #[test]
+    fn test_options_validation_error_display_out_of_range() {
+        let err = OptionsValidationError::OutOfRange {
+            option: "spacing_linear",
+            value: 2.5,
+            min: 0.0,
+            max: 1.0,
+        };
+        assert_eq!(
+            err.to_string(),
+            "option `spacing_linear` must be between 0 and 1, got 2.5"
+        );
+    }
 }
@@ -48,7 +48,7 @@
 
 use crate::{Error, Result, Toolkit};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 // =============================================================================
 // Traits
@@ -500,6 +500,7 @@ pub struct MeiOptionsBuilder {
     remove_ids: Option<bool>,
     page_based: Option<bool>,
     scorebased_mei: Option<bool>,
+    score_based: Option<bool>,
 }
 
 impl MeiOptionsBuilder {
@@ -509,6 +510,15 @@ impl MeiOptionsBuilder {
         self
     }
 
+    /// Keep the `xml:id`s of the loaded document in the output.
+    ///
+    /// This is the default; `preserve_ids(true)` is the same as
+    /// `remove_ids(false)` but states the intent explicitly.
+    pub fn preserve_ids(mut self, v: bool) -> Self {
+        self.remove_ids = Some(!v);
+        self
+    }
+
     /// Generate page-based MEI output.
     pub fn page_based(mut self, v: bool) -> Self {
         self.page_based = Some(v);
@@ -521,8 +531,17 @@ impl MeiOptionsBuilder {
         self
     }
 
+    /// Export score-based MEI (`true`, the default) or page-based MEI (`false`).
+    ///
+    /// Page-based MEI wraps the music in `<page>` elements following the
+    /// current layout. This sets Verovio's `scoreBased` export option.
+    pub fn score_based(mut self, v: bool) -> Self {
+        self.score_based = Some(v);
+        self
+    }
+
     /// Build the MEI options JSON string.
-    fn to_json(&self) -> String {
+    pub(crate) fn to_json(&self) -> String {
         let mut parts = Vec::new();
         if let Some(v) = self.remove_ids {
             parts.push(format!("\"removeIds\":{}", v));
@@ -533,6 +552,9 @@ impl MeiOptionsBuilder {
         if let Some(v) = self.scorebased_mei {
             parts.push(format!("\"scoreBasedMei\":{}", v));
         }
+        if let Some(v) = self.score_based {
+            parts.push(format!("\"scoreBased\":{}", v));
+        }
         format!("{{{}}}", parts.join(","))
     }
 }
@@ -1000,6 +1022,201 @@ fn calculate_png_dimensions(
     }
 }
 
+// =============================================================================
+// PDF Format Types (feature-gated)
+// =============================================================================
+
+/// PDF export format.
+///
+/// Renders every page to SVG and composes them into a single vector PDF with
+/// one PDF page per score page. Returns the raw PDF bytes (`Vec<u8>`).
+///
+/// # Example
+///
+/// ```no_run
+/// use verovioxide::{Toolkit, Pdf};
+///
+/// let mut voxide = Toolkit::new().unwrap();
+/// voxide.load("score.mei").unwrap();
+///
+/// let pdf_bytes: Vec<u8> = voxide.render(Pdf).unwrap();
+/// voxide.render_to("score.pdf").unwrap();
+/// ```
+#[cfg(feature = "pdf")]
+#[cfg_attr(docsrs, doc(cfg(feature = "pdf")))]
+#[derive(Debug, Clone, Copy)]
+pub struct Pdf;
+
+#[cfg(feature = "pdf")]
+impl RenderOutput for Pdf {
+    type Output = Vec<u8>;
+
+    fn render(self, toolkit: &Toolkit) -> Result<Self::Output> {
+        toolkit.render_to_pdf()
+    }
+}
+
+#[cfg(feature = "pdf")]
+impl RenderSpec for Pdf {
+    fn render_to_file(self, toolkit: &Toolkit, path: &Path) -> Result<()> {
+        let pdf = toolkit.render_to_pdf()?;
+        fs::write(path, &pdf).map_err(Error::IoError)?;
+        Ok(())
+    }
+}
+
+/// Compose SVG pages into a multi-page PDF.
+///
+/// Each SVG becomes one PDF page sized to that SVG, so documents with mixed
+/// page sizes keep the size of every page.
+#[cfg(feature = "pdf")]
+pub(crate) fn svgs_to_pdf(svgs: &[String]) -> Result<Vec<u8>> {
+    use pdf_writer::{Content, Finish, Name, Rect, Ref};
+    use svg2pdf::usvg::{self, TreeParsing};
+
+    let svg_name = Name(b"S1");
+    let catalog_ref = Ref::new(1);
+    let page_tree_ref = Ref::new(2);
+    let mut next_ref = Ref::new(3);
+    let mut page_refs = Vec::with_capacity(svgs.len());
+
+    let mut pdf = pdf_writer::Pdf::new();
+
+    for svg in svgs {
+        let tree = usvg::Tree::from_str(svg, &usvg::Options::default()).map_err(|e| {
+            Error::RenderError(format!("failed to parse SVG for PDF conversion: {}", e))
+        })?;
+        let width = tree.size.width();
+        let height = tree.size.height();
+
+        let page_ref = next_ref;
+        let content_ref = Ref::new(page_ref.get() + 1);
+        let svg_ref = Ref::new(page_ref.get() + 2);
+        next_ref =
+            svg2pdf::convert_tree_into(&tree, svg2pdf::Options::default(), &mut pdf, svg_ref);
+
+        let mut page = pdf.page(page_ref);
+        page.media_box(Rect::new(0.0, 0.0, width, height));
+        page.parent(page_tree_ref);
+        page.contents(content_ref);
+        page.resources().x_objects().pair(svg_name, svg_ref);
+        page.finish();
+
+        // The converted SVG is a unit-sized form XObject; scale it to the page
+        let mut content = Content::new();
+        content
+            .transform([width, 0.0, 0.0, height, 0.0, 0.0])
+            .x_object(svg_name);
+        pdf.stream(content_ref, &content.finish());
+
+        page_refs.push(page_ref);
+    }
+
+    pdf.catalog(catalog_ref).pages(page_tree_ref);
+    pdf.pages(page_tree_ref)
+        .count(page_refs.len() as i32)
+        .kids(page_refs);
+
+    Ok(pdf.finish())
+}
+
+// =============================================================================
+// Multi-Format Export
+// =============================================================================
+
+/// An output format for [`Toolkit::export_all`](crate::Toolkit::export_all).
+///
+/// Each format is written with the default settings of its render spec.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum OutputFormat {
+    /// All SVG pages, in a directory as with [`Svg::all_pages`].
+    Svg,
+    /// All PNG pages, in a directory as with [`Png::all_pages`].
+    #[cfg(feature = "png")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "png")))]
+    Png,
+    /// A multi-page PDF.
+    #[cfg(feature = "pdf")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "pdf")))]
+    Pdf,
+    /// The MEI export.
+    Mei,
+    /// A Standard MIDI File.
+    Midi,
+    /// Plaine & Easie code.
+    Pae,
+    /// Humdrum.
+    Humdrum,
+    /// The timemap, as JSON.
+    Timemap,
+    /// The expansion map, as JSON.
+    ExpansionMap,
+}
+
+impl OutputFormat {
+    /// Returns the file extension written for this format, without the dot.
+    ///
+    /// The timemap and expansion map are both JSON, so their extensions carry
+    /// a distinguishing prefix.
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Svg => "svg",
+            #[cfg(feature = "png")]
+            Self::Png => "png",
+            #[cfg(feature = "pdf")]
+            Self::Pdf => "pdf",
+            Self::Mei => "mei",
+            Self::Midi => "mid",
+            Self::Pae => "pae",
+            Self::Humdrum => "krn",
+            Self::Timemap => "timemap.json",
+            Self::ExpansionMap => "expansion.json",
+        }
+    }
+
+    /// Writes this format to `dir/<basename>.<extension>` and returns the
+    /// paths of the files written.
+    ///
+    /// Paged formats are written to a `dir/<basename>/` directory with one
+    /// `page-001.<extension>` file per page.
+    pub(crate) fn export(
+        self,
+        toolkit: &Toolkit,
+        dir: &Path,
+        basename: &str,
+    ) -> Result<Vec<PathBuf>> {
+        let path = dir.join(format!("{}.{}", basename, self.extension()));
+        let pages = |extension: &str| {
+            let pages_dir = dir.join(basename);
+            (1..=toolkit.page_count())
+                .map(|page| pages_dir.join(format!("page-{:03}.{}", page, extension)))
+                .collect()
+        };
+
+        match self {
+            Self::Svg => {
+                Svg::all_pages().render_to_file(toolkit, &path)?;
+                return Ok(pages("svg"));
+            }
+            #[cfg(feature = "png")]
+            Self::Png => {
+                Png::all_pages().render_to_file(toolkit, &path)?;
+                return Ok(pages("png"));
+            }
+            #[cfg(feature = "pdf")]
+            Self::Pdf => Pdf.render_to_file(toolkit, &path)?,
+            Self::Mei => Mei.render_to_file(toolkit, &path)?,
+            Self::Midi => Midi.render_to_file(toolkit, &path)?,
+            Self::Pae => Pae.render_to_file(toolkit, &path)?,
+            Self::Humdrum => Humdrum.render_to_file(toolkit, &path)?,
+            Self::Timemap => Timemap.render_to_file(toolkit, &path)?,
+            Self::ExpansionMap => ExpansionMap.render_to_file(toolkit, &path)?,
+        }
+        Ok(vec![path])
+    }
+}
+
 // =============================================================================
 // Format Inference
 // =============================================================================
@@ -1026,6 +1243,8 @@ pub(crate) fn infer_format_and_render(toolkit: &Toolkit, path: &Path) -> Result<
             options: PngOptions::default(),
         }
         .render_to_file(toolkit, path),
+        #[cfg(feature = "pdf")]
+        Some("pdf") => Pdf.render_to_file(toolkit, path),
         Some("mid") | Some("midi") => Midi.render_to_file(toolkit, path),
         Some("pae") => Pae.render_to_file(toolkit, path),
         Some("mei") => Mei.render_to_file(toolkit, path),
@@ -1056,6 +1275,17 @@ mod tests {
         assert!(spec.declaration);
     }
 
+    #[test]
+    fn test_output_format_extensions() {
+        assert_eq!(OutputFormat::Svg.extension(), "svg");
+        assert_eq!(OutputFormat::Midi.extension(), "mid");
+        assert_eq!(OutputFormat::Humdrum.extension(), "krn");
+        assert_ne!(
+            OutputFormat::Timemap.extension(),
+            OutputFormat::ExpansionMap.extension()
+        );
+    }
+
     #[test]
     fn test_svg_pages_builder() {
         let spec = Svg::pages(2, 5);
@@ -1104,6 +1334,19 @@ mod tests {
         assert!(opts.to_json().contains("\"pageBasedMei\":false"));
     }
 
+    #[test]
+    fn test_mei_options_preserve_ids() {
+        let opts = Mei::with_options().preserve_ids(true);
+        assert_eq!(opts.to_json(), "{\"removeIds\":false}");
+
+        let opts = Mei::with_options().preserve_ids(false);
+        assert_eq!(opts.to_json(), "{\"removeIds\":true}");
+
+        // The last call wins when combined with remove_ids
+        let opts = Mei::with_options().remove_ids(true).preserve_ids(true);
+        assert_eq!(opts.to_json(), "{\"removeIds\":false}");
+    }
+
     #[test]
     fn test_format_types_are_send_sync() {
         fn assert_send<T: Send>() {}
@@ -1137,6 +1380,15 @@ mod tests {
         assert!(opts.to_json().contains("\"scoreBasedMei\":true"));
     }
 
+    #[test]
+    fn test_mei_options_score_based() {
+        let opts = Mei::with_options().score_based(false);
+        assert_eq!(opts.to_json(), "{\"scoreBased\":false}");
+
+        let opts = Mei::with_options().score_based(true);
+        assert_eq!(opts.to_json(), "{\"scoreBased\":true}");
+    }
+
     #[test]
     fn test_svg_page_accessors() {
         let spec = Svg::page(5);
@@ -1393,3 +1645,38 @@ mod png_tests {
         let _cloned = opts.clone();
     }
 }
+
+#[cfg(all(test, feature = "pdf"))]
+mod pdf_tests {
+    use super::*;
+
+    #[test]
+    fn test_svgs_to_pdf_mixed_page_sizes() {
+        let svgs = vec![
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="100" height="200" viewBox="0 0 1000 2000"><rect width="500" height="500"/></svg>"#.to_string(),
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="300" height="150" viewBox="0 0 3000 1500"><rect width="500" height="500"/></svg>"#.to_string(),
+        ];
+
+        let pdf = svgs_to_pdf(&svgs).unwrap();
+        let text = String::from_utf8_lossy(&pdf);
+        assert!(pdf.starts_with(b"%PDF"));
+        assert!(text.contains("/Count 2"));
+        assert!(text.contains("/MediaBox [0 0 100 200]"));
+        assert!(text.contains("/MediaBox [0 0 300 150]"));
+    }
+
+    #[test]
+    fn test_svgs_to_pdf_invalid_svg() {
+        let err = svgs_to_pdf(&["<svg".to_string()]).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("failed to parse SVG for PDF conversion")
+        );
+    }
+
+    #[test]
+    fn test_pdf_format_debug_clone() {
+        let pdf = Pdf;
+        let _ = format!("{:?}", pdf.clone());
+    }
+}
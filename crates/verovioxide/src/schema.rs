@@ -0,0 +1,301 @@
+//! Typed descriptions of Verovio's options.
+//!
+//! Verovio describes its options in a custom JSON format, returned by
+//! [`Toolkit::get_available_options`](crate::Toolkit::get_available_options).
+//! This module parses that format into [`OptionDescriptor`]s and converts them
+//! into a JSON Schema document.
+
+use serde::Serialize;
+use serde_json::{Map, Value, json};
+
+use crate::{Error, Result};
+
+/// The kind of value an option accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum OptionKind {
+    /// A boolean.
+    Bool,
+    /// An integer, optionally bounded.
+    Int,
+    /// A floating-point number, optionally bounded.
+    Double,
+    /// A free-form string.
+    String,
+    /// A list of strings.
+    Array,
+    /// One string out of a fixed set of [`values`](OptionDescriptor::values).
+    Enum,
+}
+
+impl OptionKind {
+    /// Maps a Verovio option type name to a kind.
+    ///
+    /// Unknown type names are treated as free-form strings.
+    fn from_verovio(name: &str) -> Self {
+        match name {
+            "bool" => Self::Bool,
+            "int" => Self::Int,
+            "double" => Self::Double,
+            "array" => Self::Array,
+            "std::string-list" => Self::Enum,
+            _ => Self::String,
+        }
+    }
+}
+
+/// Describes one option Verovio accepts.
+///
+/// # See also
+///
+/// - [`Toolkit::available_options`](crate::Toolkit::available_options) - List all options
+/// - [`Toolkit::options_schema_json`](crate::Toolkit::options_schema_json) - Options as JSON Schema
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OptionDescriptor {
+    /// The option's Verovio name, as used in options JSON (e.g. `pageWidth`).
+    pub name: String,
+    /// The label of the group the option belongs to.
+    pub group: String,
+    /// A short human-readable title.
+    pub title: String,
+    /// A longer description.
+    pub description: String,
+    /// The kind of value the option accepts.
+    pub kind: OptionKind,
+    /// The default value.
+    pub default: Value,
+    /// The minimum value for numeric options.
+    pub min: Option<f64>,
+    /// The maximum value for numeric options.
+    pub max: Option<f64>,
+    /// The accepted values for [`OptionKind::Enum`] options.
+    pub values: Vec<String>,
+}
+
+/// Parses the output of `getAvailableOptions` into descriptors, in group order.
+pub(crate) fn parse_available_options(json: &str) -> Result<Vec<OptionDescriptor>> {
+    let root: Value = serde_json::from_str(json).map_err(|e| Error::OptionsError(e.to_string()))?;
+    let groups = root
+        .get("groups")
+        .unwrap_or(&root)
+        .as_object()
+        .ok_or_else(|| Error::OptionsError("available options are not an object".into()))?;
+
+    let text = |value: &Value, key: &str| {
+        value
+            .get(key)
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string()
+    };
+
+    let mut descriptors = Vec::new();
+    for (group_id, group) in groups {
+        let Some(options) = group.get("options").and_then(Value::as_object) else {
+            continue;
+        };
+        let group_name = group
+            .get("name")
+            .and_then(Value::as_str)
+            .unwrap_or(group_id);
+
+        for (name, option) in options {
+            descriptors.push(OptionDescriptor {
+                name: name.clone(),
+                group: group_name.to_string(),
+                title: text(option, "title"),
+                description: text(option, "description"),
+                kind: OptionKind::from_verovio(
+                    option
+                        .get("type")
+                        .and_then(Value::as_str)
+                        .unwrap_or_default(),
+                ),
+                default: option.get("default").cloned().unwrap_or(Value::Null),
+                min: option.get("min").and_then(Value::as_f64),
+                max: option.get("max").and_then(Value::as_f64),
+                values: option
+                    .get("values")
+                    .and_then(Value::as_array)
+                    .map(|values| {
+                        values
+                            .iter()
+                            .filter_map(Value::as_str)
+                            .map(String::from)
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+            });
+        }
+    }
+    Ok(descriptors)
+}
+
+/// Builds a JSON Schema (draft 2020-12) object describing `descriptors`.
+pub(crate) fn to_json_schema(descriptors: &[OptionDescriptor]) -> Value {
+    let properties: Map<String, Value> = descriptors
+        .iter()
+        .map(|descriptor| (descriptor.name.clone(), property_schema(descriptor)))
+        .collect();
+
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": "Verovio options",
+        "type": "object",
+        "properties": properties,
+    })
+}
+
+/// Builds the schema of a single option.
+fn property_schema(descriptor: &OptionDescriptor) -> Value {
+    let mut schema = Map::new();
+    let bound = |value: f64| match descriptor.kind {
+        OptionKind::Int => json!(value as i64),
+        _ => json!(value),
+    };
+
+    match descriptor.kind {
+        OptionKind::Bool => {
+            schema.insert("type".into(), json!("boolean"));
+        }
+        OptionKind::Int => {
+            schema.insert("type".into(), json!("integer"));
+        }
+        OptionKind::Double => {
+            schema.insert("type".into(), json!("number"));
+        }
+        OptionKind::String => {
+            schema.insert("type".into(), json!("string"));
+        }
+        OptionKind::Array => {
+            schema.insert("type".into(), json!("array"));
+            schema.insert("items".into(), json!({ "type": "string" }));
+        }
+        OptionKind::Enum => {
+            schema.insert("type".into(), json!("string"));
+            schema.insert("enum".into(), json!(descriptor.values));
+        }
+    }
+    if !descriptor.title.is_empty() {
+        schema.insert("title".into(), json!(descriptor.title));
+    }
+    if !descriptor.description.is_empty() {
+        schema.insert("description".into(), json!(descriptor.description));
+    }
+    if !descriptor.default.is_null() {
+        schema.insert("default".into(), descriptor.default.clone());
+    }
+    if let Some(min) = descriptor.min {
+        schema.insert("minimum".into(), bound(min));
+    }
+    if let Some(max) = descriptor.max {
+        schema.insert("maximum".into(), bound(max));
+    }
+
+    Value::Object(schema)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AVAILABLE_OPTIONS: &str = r#"{
+        "groups": {
+            "1-general": {
+                "name": "General",
+                "options": {
+                    "scale": {
+                        "title": "Scale percent",
+                        "description": "Set the scale option",
+                        "type": "int",
+                        "default": 100,
+                        "min": 1,
+                        "max": 1000
+                    },
+                    "breaks": {
+                        "title": "Breaks",
+                        "description": "Define page and system breaks layout",
+                        "type": "std::string-list",
+                        "default": "auto",
+                        "values": ["none", "auto", "encoded"]
+                    },
+                    "appXPathQuery": {
+                        "title": "App xPath query",
+                        "type": "array",
+                        "default": []
+                    }
+                }
+            },
+            "2-layout": {
+                "name": "Layout",
+                "options": {
+                    "adjustPageHeight": {"type": "bool", "default": false},
+                    "spacingLinear": {"type": "double", "default": 0.25, "min": 0.0, "max": 1.0},
+                    "outputTo": {"type": "std::string", "default": "svg"}
+                }
+            }
+        }
+    }"#;
+
+    #[test]
+    fn test_parse_available_options() {
+        let descriptors = parse_available_options(AVAILABLE_OPTIONS).unwrap();
+        assert_eq!(descriptors.len(), 6);
+
+        let scale = descriptors.iter().find(|d| d.name == "scale").unwrap();
+        assert_eq!(scale.group, "General");
+        assert_eq!(scale.title, "Scale percent");
+        assert_eq!(scale.kind, OptionKind::Int);
+        assert_eq!(scale.default, json!(100));
+        assert_eq!(scale.min, Some(1.0));
+        assert_eq!(scale.max, Some(1000.0));
+
+        let breaks = descriptors.iter().find(|d| d.name == "breaks").unwrap();
+        assert_eq!(breaks.kind, OptionKind::Enum);
+        assert_eq!(breaks.values, vec!["none", "auto", "encoded"]);
+
+        let output_to = descriptors.iter().find(|d| d.name == "outputTo").unwrap();
+        assert_eq!(output_to.group, "Layout");
+        assert_eq!(output_to.kind, OptionKind::String);
+        assert!(output_to.description.is_empty());
+    }
+
+    #[test]
+    fn test_parse_available_options_invalid_json() {
+        let err = parse_available_options("not json").unwrap_err();
+        assert!(matches!(err, Error::OptionsError(_)));
+
+        let err = parse_available_options("[]").unwrap_err();
+        assert!(err.to_string().contains("not an object"));
+    }
+
+    #[test]
+    fn test_to_json_schema() {
+        let descriptors = parse_available_options(AVAILABLE_OPTIONS).unwrap();
+        let schema = to_json_schema(&descriptors);
+
+        assert_eq!(schema["type"], "object");
+        let properties = &schema["properties"];
+        assert_eq!(
+            properties["scale"],
+            json!({
+                "type": "integer",
+                "title": "Scale percent",
+                "description": "Set the scale option",
+                "default": 100,
+                "minimum": 1,
+                "maximum": 1000,
+            })
+        );
+        assert_eq!(properties["breaks"]["type"], "string");
+        assert_eq!(
+            properties["breaks"]["enum"],
+            json!(["none", "auto", "encoded"])
+        );
+        assert_eq!(properties["appXPathQuery"]["items"]["type"], "string");
+        assert_eq!(properties["adjustPageHeight"]["type"], "boolean");
+        assert_eq!(properties["spacingLinear"]["type"], "number");
+        assert_eq!(properties["spacingLinear"]["maximum"], json!(1.0));
+    }
+}
@@ -0,0 +1,2414 @@
+//! SVG inspection helpers.
+//!
+//! This module contains helpers for inspecting the SVG produced by Verovio.
+//! Parsing is done with [`roxmltree`], so the helpers work on the document
+//! structure rather than on raw text.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, Result};
+
+/// The XML namespace of the `xml:lang` attribute.
+const XML_NS: &str = "http://www.w3.org/XML/1998/namespace";
+
+/// CSS that draws Verovio's SVG light-on-transparent, for dark backgrounds.
+///
+/// Verovio draws notation in `currentColor`, inherited from the `color` of the
+/// nested `definition-scale` SVG, so most of the theme is a single `color`
+/// rule. The remaining rules cover text and lines that set their own color.
+///
+/// # See also
+///
+/// - [`Toolkit::render_to_svg_dark`](crate::Toolkit::render_to_svg_dark) - Render with this theme
+pub const DARK_THEME_CSS: &str = "svg { background: transparent; } \
+    svg.definition-scale { color: #f0f0f0; } \
+    g.page-margin text, g.page-margin tspan { fill: #f0f0f0; } \
+    g.page-margin path, g.page-margin rect, g.page-margin polygon { fill: #f0f0f0; } \
+    g.page-margin path[stroke], g.page-margin line, g.page-margin polyline { stroke: #f0f0f0; }";
+
+/// The `viewBox` of a rendered SVG page, in SVG user units.
+///
+/// # Example
+///
+/// ```
+/// use verovioxide::ViewBox;
+///
+/// let view_box: ViewBox = "0 0 21000 29700".parse().unwrap();
+/// assert_eq!(view_box.width, 21000.0);
+/// assert_eq!(view_box.height, 29700.0);
+/// ```
+///
+/// # See also
+///
+/// - [`Toolkit::page_view_box`](crate::Toolkit::page_view_box) - Get the viewBox of a page
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct ViewBox {
+    /// The minimum x coordinate.
+    pub min_x: f64,
+    /// The minimum y coordinate.
+    pub min_y: f64,
+    /// The width of the box.
+    pub width: f64,
+    /// The height of the box.
+    pub height: f64,
+}
+
+impl FromStr for ViewBox {
+    type Err = Error;
+
+    /// Parses a `viewBox` attribute value (`"min-x min-y width height"`).
+    ///
+    /// Values may be separated by whitespace and/or commas.
+    fn from_str(s: &str) -> Result<Self> {
+        let values = s
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|part| !part.is_empty())
+            .map(f64::from_str)
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|_| Error::RenderError(format!("invalid viewBox: {}", s)))?;
+
+        match values.as_slice() {
+            [min_x, min_y, width, height] => Ok(Self {
+                min_x: *min_x,
+                min_y: *min_y,
+                width: *width,
+                height: *height,
+            }),
+            _ => Err(Error::RenderError(format!("invalid viewBox: {}", s))),
+        }
+    }
+}
+
+impl fmt::Display for ViewBox {
+    /// Formats the box as a `viewBox` attribute value.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} {}",
+            self.min_x, self.min_y, self.width, self.height
+        )
+    }
+}
+
+/// Where an element is drawn on a rendered page.
+///
+/// # See also
+///
+/// - [`Toolkit::document_anchors`](crate::Toolkit::document_anchors) - Anchors of every page
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ElementAnchor {
+    /// The element's id.
+    pub id: String,
+    /// The element's type, from its SVG class (e.g. `note`, `measure`).
+    pub kind: String,
+    /// The element's bounding box, in the page's drawing coordinate system.
+    pub bounds: ViewBox,
+}
+
+/// The outcome of comparing a rendered page with a golden SVG file.
+///
+/// # See also
+///
+/// - [`Toolkit::compare_golden`](crate::Toolkit::compare_golden) - Compare a page with a golden file
+#[cfg(any(test, feature = "test-utils"))]
+#[cfg_attr(docsrs, doc(cfg(feature = "test-utils")))]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GoldenResult {
+    /// Whether the rendering matched the golden file, or the golden file was
+    /// updated to match it.
+    pub passed: bool,
+    /// The differences between the golden file and the rendering, in the
+    /// format of [`Toolkit::diff_render`](crate::Toolkit::diff_render).
+    /// Empty if they match.
+    pub differences: Vec<String>,
+    /// Whether the golden file was written with the rendering.
+    pub updated: bool,
+}
+
+/// Converts between SVG user units and pixels of a displayed page.
+///
+/// The mapping follows SVG's default `preserveAspectRatio` (`xMidYMid meet`):
+/// the viewBox is scaled uniformly to fit the display size and centered, so
+/// a display size with a different aspect ratio leaves empty bands on two
+/// sides. Pixel coordinates are relative to the top-left corner of the
+/// displayed SVG.
+///
+/// # Example
+///
+/// ```
+/// use verovioxide::{CoordinateMapper, ViewBox};
+///
+/// let view_box: ViewBox = "0 0 21000 29700".parse().unwrap();
+/// let mapper = CoordinateMapper::new(view_box, 700.0, 990.0).unwrap();
+/// assert_eq!(mapper.user_to_pixel(10500.0, 14850.0), (350.0, 495.0));
+/// assert_eq!(mapper.pixel_to_user(350.0, 495.0), (10500.0, 14850.0));
+/// ```
+///
+/// # See also
+///
+/// - [`Toolkit::coordinate_mapper`](crate::Toolkit::coordinate_mapper) - Mapper for the drawing coordinates of a page
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CoordinateMapper {
+    view_box: ViewBox,
+    scale: f64,
+    offset_x: f64,
+    offset_y: f64,
+}
+
+impl CoordinateMapper {
+    /// Creates a mapper for `view_box` displayed at `width` x `height` pixels.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::RenderError`] if the display size or the viewBox size
+    /// is not a positive finite number.
+    pub fn new(view_box: ViewBox, width: f64, height: f64) -> Result<Self> {
+        let positive = |value: f64| value.is_finite() && value > 0.0;
+        if !positive(width) || !positive(height) {
+            return Err(Error::RenderError(format!(
+                "invalid display size: {} x {}",
+                width, height
+            )));
+        }
+        if !positive(view_box.width) || !positive(view_box.height) {
+            return Err(Error::RenderError(format!("invalid viewBox: {}", view_box)));
+        }
+
+        let scale = (width / view_box.width).min(height / view_box.height);
+        Ok(Self {
+            view_box,
+            scale,
+            offset_x: (width - view_box.width * scale) / 2.0,
+            offset_y: (height - view_box.height * scale) / 2.0,
+        })
+    }
+
+    /// Converts a point in SVG user units to pixels.
+    #[must_use]
+    pub fn user_to_pixel(&self, x: f64, y: f64) -> (f64, f64) {
+        (
+            self.offset_x + (x - self.view_box.min_x) * self.scale,
+            self.offset_y + (y - self.view_box.min_y) * self.scale,
+        )
+    }
+
+    /// Converts a point in pixels to SVG user units.
+    #[must_use]
+    pub fn pixel_to_user(&self, x: f64, y: f64) -> (f64, f64) {
+        (
+            self.view_box.min_x + (x - self.offset_x) / self.scale,
+            self.view_box.min_y + (y - self.offset_y) / self.scale,
+        )
+    }
+
+    /// Returns the number of pixels per SVG user unit.
+    #[must_use]
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+}
+
+/// How much [`Toolkit::render_to_svg_optimized`](crate::Toolkit::render_to_svg_optimized)
+/// shrinks the SVG.
+///
+/// Every level keeps the rendering identical; only markup that does not affect
+/// the drawing is removed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum OptimizeLevel {
+    /// Leave the SVG unchanged.
+    #[default]
+    None,
+    /// Remove comments and the whitespace Verovio uses for indentation.
+    Basic,
+    /// Like [`Basic`](Self::Basic), and also remove `<use>` elements that
+    /// repeat an identical sibling, attributes with empty values, and identity
+    /// `translate(0, 0)` transforms.
+    Aggressive,
+}
+
+/// Parses an SVG document, mapping parse failures to [`Error::RenderError`].
+pub(crate) fn parse(svg: &str) -> Result<roxmltree::Document<'_>> {
+    roxmltree::Document::parse(svg)
+        .map_err(|e| Error::RenderError(format!("failed to parse SVG: {}", e)))
+}
+
+/// Returns the `viewBox` of the root `<svg>` element.
+pub(crate) fn root_view_box(svg: &str) -> Result<ViewBox> {
+    let doc = parse(svg)?;
+    doc.root_element()
+        .attribute("viewBox")
+        .ok_or_else(|| Error::RenderError("SVG root has no viewBox attribute".into()))?
+        .parse()
+}
+
+/// Prefixes every `id` attribute and every internal reference to it.
+///
+/// Internal references are `href`/`xlink:href` values of the form `#id` and
+/// `url(#id)` functional notation in any attribute. The prefix is inserted
+/// into the original text, so the rest of the document is left byte-for-byte
+/// unchanged.
+pub(crate) fn prefix_ids(svg: &str, prefix: &str) -> Result<String> {
+    prefix_ids_mapped(svg, prefix).map(|(output, _)| output)
+}
+
+/// Like [`prefix_ids`], but also returns a map from every original `id` to
+/// its prefixed form.
+pub(crate) fn prefix_ids_mapped(
+    svg: &str,
+    prefix: &str,
+) -> Result<(String, HashMap<String, String>)> {
+    let valid_start = prefix
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_');
+    let valid_rest = prefix
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if !valid_start || !valid_rest {
+        return Err(Error::RenderError(format!(
+            "invalid id prefix: {:?}",
+            prefix
+        )));
+    }
+
+    let doc = parse(svg)?;
+    let mut insertions = Vec::new();
+    let mut ids = HashMap::new();
+
+    for node in doc.descendants().filter(|node| node.is_element()) {
+        for attr in node.attributes() {
+            let range = attr.range_value();
+            let raw = &svg[range.clone()];
+
+            if attr.name() == "id" && attr.namespace().is_none() {
+                insertions.push(range.start);
+                ids.insert(
+                    attr.value().to_string(),
+                    format!("{}{}", prefix, attr.value()),
+                );
+            } else if attr.name() == "href" && raw.starts_with('#') {
+                insertions.push(range.start + 1);
+            } else {
+                insertions.extend(
+                    raw.match_indices("url(#")
+                        .map(|(offset, m)| range.start + offset + m.len()),
+                );
+            }
+        }
+    }
+
+    insertions.sort_unstable();
+
+    let mut output = String::with_capacity(svg.len() + insertions.len() * prefix.len());
+    let mut last = 0;
+    for position in insertions {
+        output.push_str(&svg[last..position]);
+        output.push_str(prefix);
+        last = position;
+    }
+    output.push_str(&svg[last..]);
+
+    Ok((output, ids))
+}
+
+/// Sets `fill` and `stroke` on the elements with the given ids.
+///
+/// Each pair is `(element_id, css_color)`. Existing `fill`/`stroke` attributes
+/// on a matching element are replaced; otherwise the attributes are added to
+/// its start tag. Ids that are not in the document are ignored. As with
+/// [`prefix_ids`], the rest of the document is left byte-for-byte unchanged.
+pub(crate) fn color_elements(svg: &str, colors: &[(&str, &str)]) -> Result<String> {
+    if let Some((_, color)) = colors
+        .iter()
+        .find(|(_, color)| color.is_empty() || color.contains(['"', '<', '>', '&']))
+    {
+        return Err(Error::RenderError(format!("invalid color: {:?}", color)));
+    }
+
+    let doc = parse(svg)?;
+    let mut edits: Vec<(std::ops::Range<usize>, String)> = Vec::new();
+
+    for node in doc.descendants().filter(|node| node.is_element()) {
+        let Some(id) = node.attribute("id") else {
+            continue;
+        };
+        let Some((_, color)) = colors.iter().rev().find(|(wanted, _)| *wanted == id) else {
+            continue;
+        };
+
+        let mut added = String::new();
+        for name in ["fill", "stroke"] {
+            match node.attributes().find(|attr| attr.name() == name) {
+                Some(attr) => edits.push((attr.range_value(), color.to_string())),
+                None => added.push_str(&format!(" {}=\"{}\"", name, color)),
+            }
+        }
+        if !added.is_empty() {
+            // Insert right after the tag name, e.g. `<g` in `<g id="...">`
+            let start = node.range().start;
+            let name_end = svg[start + 1..]
+                .find(|c: char| c.is_whitespace() || c == '>' || c == '/')
+                .map_or(svg.len(), |offset| start + 1 + offset);
+            edits.push((name_end..name_end, added));
+        }
+    }
+
+    edits.sort_unstable_by_key(|(range, _)| range.start);
+
+    let mut output = String::with_capacity(svg.len() + edits.len() * 32);
+    let mut last = 0;
+    for (range, text) in edits {
+        output.push_str(&svg[last..range.start]);
+        output.push_str(&text);
+        last = range.end;
+    }
+    output.push_str(&svg[last..]);
+
+    Ok(output)
+}
+
+/// Returns the id and staff number of every staff group in an SVG rendered
+/// with `staff@n` in `svgAdditionalAttribute`, in document order.
+///
+/// Staff groups are `<g class="staff">` elements; Verovio writes their `@n`
+/// as `data-n`. Groups without an id or a numeric `data-n` are skipped.
+pub(crate) fn staff_groups(svg: &str) -> Result<Vec<(u32, String)>> {
+    let doc = parse(svg)?;
+    Ok(doc
+        .descendants()
+        .filter(|node| {
+            node.has_tag_name("g")
+                && node
+                    .attribute("class")
+                    .is_some_and(|class| class.split_whitespace().any(|c| c == "staff"))
+        })
+        .filter_map(|node| {
+            let n = node.attribute("data-n")?.trim().parse().ok()?;
+            Some((n, node.attribute("id")?.to_string()))
+        })
+        .collect())
+}
+
+/// Adds `class` to the `class` attribute of the element with id `id`.
+///
+/// The class is appended to an existing `class` attribute, or the attribute
+/// is added to the element's start tag. The rest of the document is left
+/// byte-for-byte unchanged.
+///
+/// # Errors
+///
+/// Returns [`Error::RenderError`] if `class` is empty or contains `"`, `<`,
+/// `>` or `&`, or if no element has the id.
+pub(crate) fn add_class(svg: &str, id: &str, class: &str) -> Result<String> {
+    if class.trim().is_empty() || class.contains(['"', '<', '>', '&']) {
+        return Err(Error::RenderError(format!("invalid class: {:?}", class)));
+    }
+
+    let doc = parse(svg)?;
+    let node = doc
+        .descendants()
+        .find(|node| node.attribute("id") == Some(id))
+        .ok_or_else(|| Error::RenderError(format!("element not found: {}", id)))?;
+
+    let (position, text) = match node.attributes().find(|attr| attr.name() == "class") {
+        Some(attr) => (attr.range_value().end, format!(" {}", class)),
+        None => {
+            let start = node.range().start;
+            let name_end = svg[start + 1..]
+                .find(|c: char| c.is_whitespace() || c == '>' || c == '/')
+                .map_or(svg.len(), |offset| start + 1 + offset);
+            (name_end, format!(" class=\"{}\"", class))
+        }
+    };
+
+    let mut output = String::with_capacity(svg.len() + text.len());
+    output.push_str(&svg[..position]);
+    output.push_str(&text);
+    output.push_str(&svg[position..]);
+    Ok(output)
+}
+
+/// Removes the `<g>` groups whose `class` list contains any of `classes`.
+///
+/// Each matching group is removed with everything inside it; the rest of the
+/// document is left byte-for-byte unchanged.
+///
+/// # Errors
+///
+/// Returns [`Error::RenderError`] if a class is empty or contains characters
+/// other than ASCII letters, digits, `-` and `_`.
+pub(crate) fn remove_groups(svg: &str, classes: &[&str]) -> Result<String> {
+    check_classes(classes)?;
+
+    let doc = parse(svg)?;
+    let matches = |node: &roxmltree::Node<'_, '_>| {
+        node.has_tag_name("g")
+            && node
+                .attribute("class")
+                .is_some_and(|class| class.split_whitespace().any(|c| classes.contains(&c)))
+    };
+
+    let mut output = String::with_capacity(svg.len());
+    let mut last = 0;
+    for node in doc.descendants().filter(matches) {
+        let range = node.range();
+        if range.start < last {
+            continue;
+        }
+        output.push_str(&svg[last..range.start]);
+        last = range.end;
+    }
+    output.push_str(&svg[last..]);
+
+    Ok(output)
+}
+
+/// Checks that `classes` are plain class names: non-empty, with only ASCII
+/// letters, digits, `-` and `_`.
+pub(crate) fn check_classes(classes: &[&str]) -> Result<()> {
+    match classes.iter().find(|class| {
+        class.is_empty()
+            || !class
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_'))
+    }) {
+        Some(class) => Err(Error::RenderError(format!(
+            "invalid element type: {:?}",
+            class
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Marks the root `<svg>` as an image for assistive technology.
+///
+/// Adds `role="img"` and `aria-labelledby` to the root element and inserts
+/// `<title>` and `<desc>` elements as its first children, with the ids
+/// `{id_prefix}-title` and `{id_prefix}-desc`.
+pub(crate) fn add_accessibility(
+    svg: &str,
+    id_prefix: &str,
+    title: &str,
+    description: &str,
+) -> Result<String> {
+    let doc = parse(svg)?;
+    let root = doc.root_element();
+    let start = root.range().start;
+    let attributes_end = root
+        .attributes()
+        .map(|attr| attr.range_value().end + 1)
+        .max()
+        .unwrap_or(start + 1);
+    let tag_end = svg[attributes_end..]
+        .find('>')
+        .map(|offset| attributes_end + offset)
+        .ok_or_else(|| Error::RenderError("SVG root start tag is not closed".into()))?;
+    let self_closing = svg[..tag_end].ends_with('/');
+
+    let id_prefix = escape_text(id_prefix).replace('"', "&quot;");
+    let attributes = format!(
+        r#" role="img" aria-labelledby="{0}-title {0}-desc""#,
+        id_prefix
+    );
+    let children = format!(
+        r#"<title id="{}-title">{}</title><desc id="{}-desc">{}</desc>"#,
+        id_prefix,
+        escape_text(title),
+        id_prefix,
+        escape_text(description)
+    );
+
+    let mut output = String::with_capacity(svg.len() + attributes.len() + children.len() + 8);
+    if self_closing {
+        output.push_str(&svg[..tag_end - 1]);
+        output.push_str(&attributes);
+        output.push('>');
+        output.push_str(&children);
+        output.push_str("</svg>");
+    } else {
+        output.push_str(&svg[..attributes_end]);
+        output.push_str(&attributes);
+        output.push_str(&svg[attributes_end..=tag_end]);
+        output.push_str(&children);
+    }
+    output.push_str(&svg[tag_end + 1..]);
+
+    Ok(output)
+}
+
+/// Primary language subtags of languages written right to left.
+const RTL_LANGUAGES: &[&str] = &[
+    "ar", "arc", "ckb", "dv", "fa", "he", "iw", "ji", "ku", "ps", "sd", "syr", "ug", "ur", "yi",
+];
+
+/// Checks that `lang` looks like a BCP 47 language tag: subtags of 1 to 8
+/// ASCII letters or digits, separated by `-`.
+pub(crate) fn check_lang(lang: &str) -> Result<()> {
+    let valid = lang.split('-').all(|subtag| {
+        (1..=8).contains(&subtag.len()) && subtag.chars().all(|c| c.is_ascii_alphanumeric())
+    });
+    if valid {
+        Ok(())
+    } else {
+        Err(Error::OptionsError(format!(
+            "invalid language tag: {:?}",
+            lang
+        )))
+    }
+}
+
+/// Returns whether `lang` is written right to left, judging by its primary
+/// language subtag.
+fn is_rtl(lang: &str) -> bool {
+    let primary = lang
+        .split('-')
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    RTL_LANGUAGES.contains(&primary.as_str())
+}
+
+/// Marks every `<text>` element with `xml:lang="lang"`, and with
+/// `direction="rtl"` if the language is written right to left.
+pub(crate) fn set_text_lang(svg: &str, lang: &str) -> Result<String> {
+    let doc = parse(svg)?;
+    let rtl = is_rtl(lang);
+    let mut edits = Vec::new();
+
+    for node in doc.descendants().filter(|node| node.has_tag_name("text")) {
+        match node
+            .attributes()
+            .find(|attr| attr.namespace() == Some(XML_NS) && attr.name() == "lang")
+        {
+            Some(attr) => edits.push((attr.range_value(), lang.to_string())),
+            None => {
+                let start = node.range().start + "<text".len();
+                edits.push((start..start, format!(" xml:lang=\"{}\"", lang)));
+            }
+        }
+        if rtl {
+            set_attribute(svg, node, "direction", "rtl", &mut edits);
+        }
+    }
+
+    Ok(apply_edits(svg, edits))
+}
+
+/// Escapes text for use as XML character data.
+pub(crate) fn escape_text(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Removes markup that does not affect the drawing, as selected by `level`.
+///
+/// Whitespace inside text-bearing elements (`<text>`, `<tspan>`, `<style>`,
+/// `<title>`, `<desc>`) is kept.
+pub(crate) fn optimize(svg: &str, level: OptimizeLevel) -> Result<String> {
+    if level == OptimizeLevel::None {
+        return Ok(svg.to_string());
+    }
+
+    let doc = parse(svg)?;
+    let mut removals: Vec<std::ops::Range<usize>> = Vec::new();
+    let preserves_space = |node: roxmltree::Node<'_, '_>| {
+        node.ancestors().any(|ancestor| {
+            ["text", "tspan", "style", "title", "desc"]
+                .iter()
+                .any(|name| ancestor.has_tag_name(*name))
+        })
+    };
+
+    for node in doc.descendants() {
+        if node.is_comment()
+            || (node.is_text()
+                && node.text().is_some_and(|text| text.trim().is_empty())
+                && !preserves_space(node))
+        {
+            removals.push(node.range());
+        }
+        if level != OptimizeLevel::Aggressive || !node.is_element() {
+            continue;
+        }
+
+        if node.has_tag_name("use")
+            && node
+                .prev_siblings()
+                .skip(1)
+                .filter(|sibling| sibling.has_tag_name("use"))
+                .any(|sibling| svg[sibling.range()] == svg[node.range()])
+        {
+            removals.push(node.range());
+            continue;
+        }
+        for attr in node.attributes() {
+            let redundant = attr.value().is_empty()
+                || (attr.name() == "transform"
+                    && parse_translate(attr.value()) == Some((0.0, 0.0))
+                    && is_single_translate(attr.value()));
+            if redundant {
+                let range = attr.range();
+                let start = svg[..range.start].trim_end().len();
+                removals.push(start..range.end);
+            }
+        }
+    }
+
+    removals.sort_unstable_by_key(|range| range.start);
+
+    let mut output = String::with_capacity(svg.len());
+    let mut last = 0;
+    for range in removals {
+        // Skip ranges inside an element that is already removed
+        if range.start < last {
+            continue;
+        }
+        output.push_str(&svg[last..range.start]);
+        last = range.end;
+    }
+    output.push_str(&svg[last..]);
+
+    Ok(output)
+}
+
+/// Attributes whose values are coordinates, lengths or transforms.
+const GEOMETRY_ATTRIBUTES: &[&str] = &[
+    "cx",
+    "cy",
+    "d",
+    "font-size",
+    "height",
+    "points",
+    "r",
+    "rx",
+    "ry",
+    "stroke-width",
+    "transform",
+    "viewBox",
+    "width",
+    "x",
+    "x1",
+    "x2",
+    "y",
+    "y1",
+    "y2",
+];
+
+/// Rounds the numbers in geometry attributes (path data, transforms,
+/// coordinates and lengths) to at most `precision` decimal places.
+///
+/// Trailing zeros are dropped, so `9.000000` becomes `9`. Integers, units and
+/// all other attributes are left unchanged.
+pub(crate) fn round_numbers(svg: &str, precision: u8) -> Result<String> {
+    let doc = parse(svg)?;
+    let mut edits = Vec::new();
+
+    for node in doc.descendants().filter(|node| node.is_element()) {
+        for attr in node.attributes() {
+            if !GEOMETRY_ATTRIBUTES.contains(&attr.name()) || attr.namespace().is_some() {
+                continue;
+            }
+            let rounded = round_value(attr.value(), precision);
+            if rounded != attr.value() {
+                edits.push((attr.range_value(), rounded));
+            }
+        }
+    }
+
+    Ok(apply_edits(svg, edits))
+}
+
+/// Rounds every number with a fraction or exponent in an attribute value.
+///
+/// Numbers are only replaced when rounding makes them shorter, so `.5` is
+/// not rewritten as `0.5`.
+fn round_value(value: &str, precision: u8) -> String {
+    let bytes = value.as_bytes();
+    let mut output = String::with_capacity(value.len());
+    let mut last = 0;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let start = i;
+        if matches!(bytes[i], b'-' | b'+') {
+            i += 1;
+        }
+        let digits_start = i;
+        while i < bytes.len() && bytes[i].is_ascii_digit() {
+            i += 1;
+        }
+        let mut fractional = false;
+        if i < bytes.len() && bytes[i] == b'.' {
+            i += 1;
+            while i < bytes.len() && bytes[i].is_ascii_digit() {
+                i += 1;
+            }
+            fractional = true;
+        }
+        if i == digits_start || (fractional && i == digits_start + 1) {
+            // Not a number: skip one character
+            i = start + value[start..].chars().next().map_or(1, char::len_utf8);
+            continue;
+        }
+        if i < bytes.len() && matches!(bytes[i], b'e' | b'E') {
+            let mut end = i + 1;
+            if end < bytes.len() && matches!(bytes[end], b'-' | b'+') {
+                end += 1;
+            }
+            if end < bytes.len() && bytes[end].is_ascii_digit() {
+                while end < bytes.len() && bytes[end].is_ascii_digit() {
+                    end += 1;
+                }
+                i = end;
+                fractional = true;
+            }
+        }
+        if !fractional {
+            continue;
+        }
+
+        if let Ok(number) = value[start..i].parse::<f64>() {
+            let mut text = format!("{:.*}", usize::from(precision), number);
+            if text.contains('.') {
+                text.truncate(text.trim_end_matches('0').trim_end_matches('.').len());
+            }
+            if text == "-0" {
+                text = "0".into();
+            }
+            // Keep `1.001.5` from becoming the single number `1.5`
+            if !text.contains('.') && bytes.get(i) == Some(&b'.') {
+                text.push(' ');
+            }
+            if text.len() < i - start {
+                output.push_str(&value[last..start]);
+                output.push_str(&text);
+                last = i;
+            }
+        }
+    }
+    output.push_str(&value[last..]);
+    output
+}
+
+/// Returns the `viewBox` of the coordinate system Verovio draws in.
+///
+/// Verovio nests a `<svg class="definition-scale">` carrying the viewBox inside
+/// the root element; the root's own viewBox is used if there is none.
+fn drawing_view_box(doc: &roxmltree::Document<'_>) -> Result<ViewBox> {
+    drawing_svg(doc)
+        .attribute("viewBox")
+        .ok_or_else(|| Error::RenderError("SVG has no viewBox".into()))?
+        .parse()
+}
+
+/// Returns the `viewBox` of the coordinate system Verovio draws in, as for
+/// [`drawing_view_box`].
+pub(crate) fn page_drawing_view_box(svg: &str) -> Result<ViewBox> {
+    drawing_view_box(&parse(svg)?)
+}
+
+/// Returns the `<svg>` element carrying the viewBox Verovio draws in.
+fn drawing_svg<'a, 'input>(doc: &'a roxmltree::Document<'input>) -> roxmltree::Node<'a, 'input> {
+    doc.descendants()
+        .find(|node| {
+            node.has_tag_name("svg") && node.attribute("class") == Some("definition-scale")
+        })
+        .unwrap_or_else(|| doc.root_element())
+}
+
+/// Parses the offset of a `translate(x[, y])` transform.
+fn parse_translate(transform: &str) -> Option<(f64, f64)> {
+    let start = transform.find("translate(")? + "translate(".len();
+    let end = start + transform[start..].find(')')?;
+    let mut values = transform[start..end]
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|part| !part.is_empty())
+        .map(f64::from_str);
+    let x = values.next()?.ok()?;
+    let y = values.next().transpose().ok()?.unwrap_or(0.0);
+    Some((x, y))
+}
+
+/// Returns `true` if `transform` consists of a single `translate(...)`.
+fn is_single_translate(transform: &str) -> bool {
+    let transform = transform.trim();
+    transform.starts_with("translate(") && transform.find(')') == Some(transform.len() - 1)
+}
+
+/// Returns the x offset applied by `translate` transforms on `node` and its
+/// ancestors, up to the nearest enclosing `<svg>`.
+fn translation_x(node: roxmltree::Node<'_, '_>) -> f64 {
+    translation(node).0
+}
+
+/// Returns the offset applied by `translate` transforms on `node` and its
+/// ancestors, up to the nearest enclosing `<svg>`.
+fn translation(node: roxmltree::Node<'_, '_>) -> (f64, f64) {
+    node.ancestors()
+        .take_while(|ancestor| !ancestor.has_tag_name("svg"))
+        .filter_map(|ancestor| ancestor.attribute("transform"))
+        .filter_map(parse_translate)
+        .fold((0.0, 0.0), |(x, y), (dx, dy)| (x + dx, y + dy))
+}
+
+/// Returns the leftmost `x` position drawn by any of the elements with the
+/// given ids, in the drawing coordinate system.
+///
+/// Returns `None` if none of the elements are on the page.
+pub(crate) fn elements_min_x(svg: &str, ids: &[String]) -> Result<Option<f64>> {
+    let doc = parse(svg)?;
+    Ok(doc
+        .descendants()
+        .filter(|node| {
+            node.attribute("id")
+                .is_some_and(|id| ids.iter().any(|wanted| wanted == id))
+        })
+        .flat_map(|node| node.descendants())
+        .filter_map(|node| {
+            let x: f64 = node.attribute("x")?.parse().ok()?;
+            Some(x + translation_x(node))
+        })
+        .reduce(f64::min))
+}
+
+/// Returns the SMuFL code points (e.g. `E0A4`) of the glyphs defined in an
+/// SVG rendered by Verovio.
+///
+/// Verovio defines each glyph once per page as a `<symbol>` whose id is the
+/// code point followed by a generated suffix, as in `E0A4-1a2b3c`.
+pub(crate) fn glyph_codes(svg: &str) -> Result<BTreeSet<String>> {
+    let doc = parse(svg)?;
+    Ok(doc
+        .descendants()
+        .filter(|node| node.has_tag_name("symbol"))
+        .filter_map(|node| node.attribute("id"))
+        .filter_map(glyph_code)
+        .map(String::from)
+        .collect())
+}
+
+/// Adds a `<title>` with the glyph name to every `<use>` element referencing
+/// a glyph, so that viewers show the name on hover.
+///
+/// Glyph references are `href`/`xlink:href` values of the form `#E0A4-1a2b3c`
+/// (see [`glyph_codes`]); `names` maps code points to names. References to
+/// code points without a name are left unchanged.
+pub(crate) fn annotate_glyphs(svg: &str, names: &BTreeMap<String, String>) -> Result<String> {
+    let doc = parse(svg)?;
+    let mut edits = Vec::new();
+
+    for node in doc.descendants().filter(|node| node.has_tag_name("use")) {
+        let Some(name) = node
+            .attributes()
+            .find(|attr| attr.name() == "href")
+            .and_then(|attr| attr.value().strip_prefix('#'))
+            .and_then(|id| names.get(id.split('-').next().unwrap_or(id)))
+        else {
+            continue;
+        };
+        let title = format!("<title>{}</title>", escape_text(name));
+
+        let range = node.range();
+        if svg[range.clone()].ends_with("/>") {
+            edits.push((range.end - 2..range.end, format!(">{}</use>", title)));
+        } else {
+            let start = node
+                .first_child()
+                .map_or(range.end - "</use>".len(), |child| child.range().start);
+            edits.push((start..start, title));
+        }
+    }
+
+    Ok(apply_edits(svg, edits))
+}
+
+/// Returns the id of the innermost element whose bounding box contains the
+/// point (`x`, `y`), in the drawing coordinate system.
+///
+/// The SVG must be rendered with `svgBoundingBoxes`, which makes Verovio draw
+/// each element's box as a `<g class="bounding-box">` holding a `<rect>`,
+/// inside the element's own group. The box belongs to the nearest enclosing
+/// element with an id; its `bbox-` id, when present, names the element too.
+/// Of several boxes containing the point, the most deeply nested one wins,
+/// then the smallest.
+pub(crate) fn element_at_point(svg: &str, x: f64, y: f64) -> Result<Option<String>> {
+    let doc = parse(svg)?;
+    let mut best: Option<(usize, f64, &str)> = None;
+
+    for (group, bounds) in bounding_boxes(&doc) {
+        if x < bounds.min_x
+            || x > bounds.min_x + bounds.width
+            || y < bounds.min_y
+            || y > bounds.min_y + bounds.height
+        {
+            continue;
+        }
+
+        let Some(owner) = box_owner(group) else {
+            continue;
+        };
+
+        let depth = group.ancestors().count();
+        let area = bounds.width * bounds.height;
+        let better = best.is_none_or(|(best_depth, best_area, _)| {
+            depth > best_depth || (depth == best_depth && area < best_area)
+        });
+        if better {
+            best = Some((depth, area, owner));
+        }
+    }
+
+    Ok(best.map(|(_, _, id)| id.to_string()))
+}
+
+/// Returns the anchor of every element with a bounding box in an SVG rendered
+/// with `svgBoundingBoxes`, in document order.
+///
+/// An element with several boxes is anchored at the first one.
+pub(crate) fn element_anchors(svg: &str) -> Result<Vec<ElementAnchor>> {
+    let doc = parse(svg)?;
+    let mut seen = HashSet::new();
+
+    Ok(bounding_boxes(&doc)
+        .filter_map(|(group, bounds)| {
+            let id = box_owner(group)?;
+            if !seen.insert(id) {
+                return None;
+            }
+            let kind = group
+                .ancestors()
+                .skip(1)
+                .find(|ancestor| ancestor.attribute("id") == Some(id))
+                .and_then(|owner| owner.attribute("class"))
+                .and_then(|class| class.split_whitespace().next())
+                .unwrap_or_default();
+            Some(ElementAnchor {
+                id: id.to_string(),
+                kind: kind.to_string(),
+                bounds,
+            })
+        })
+        .collect())
+}
+
+/// Returns the id of the element a `<g class="bounding-box">` belongs to.
+///
+/// The box belongs to the nearest enclosing element with an id; its `bbox-`
+/// id, when present, names the element too.
+fn box_owner<'a>(group: roxmltree::Node<'a, '_>) -> Option<&'a str> {
+    group
+        .attribute("id")
+        .and_then(|id| id.strip_prefix("bbox-"))
+        .or_else(|| {
+            group
+                .ancestors()
+                .skip(1)
+                .find_map(|ancestor| ancestor.attribute("id"))
+        })
+}
+
+/// Returns the union of the bounding boxes in an SVG rendered with
+/// `svgBoundingBoxes`, in the drawing coordinate system.
+///
+/// Returns `None` if the SVG has no bounding boxes.
+pub(crate) fn content_bounds(svg: &str) -> Result<Option<ViewBox>> {
+    let doc = parse(svg)?;
+    Ok(bounding_boxes(&doc)
+        .map(|(_, bounds)| bounds)
+        .reduce(|a, b| {
+            let min_x = a.min_x.min(b.min_x);
+            let min_y = a.min_y.min(b.min_y);
+            ViewBox {
+                min_x,
+                min_y,
+                width: (a.min_x + a.width).max(b.min_x + b.width) - min_x,
+                height: (a.min_y + a.height).max(b.min_y + b.height) - min_y,
+            }
+        }))
+}
+
+/// Returns the `<g class="bounding-box">` groups drawn by `svgBoundingBoxes`
+/// with the box of their `<rect>`, in the drawing coordinate system.
+fn bounding_boxes<'a, 'input>(
+    doc: &'a roxmltree::Document<'input>,
+) -> impl Iterator<Item = (roxmltree::Node<'a, 'input>, ViewBox)> {
+    doc.descendants()
+        .filter(|node| {
+            node.has_tag_name("g")
+                && node
+                    .attribute("class")
+                    .is_some_and(|class| class.split_whitespace().any(|c| c == "bounding-box"))
+        })
+        .filter_map(|group| {
+            let rect = group.descendants().find(|node| node.has_tag_name("rect"))?;
+            let number = |name: &str| -> Option<f64> { rect.attribute(name)?.parse().ok() };
+            let (x, y, width, height) = (
+                number("x")?,
+                number("y")?,
+                number("width")?,
+                number("height")?,
+            );
+            let (dx, dy) = translation(rect);
+            Some((
+                group,
+                ViewBox {
+                    min_x: x.min(x + width) + dx,
+                    min_y: y.min(y + height) + dy,
+                    width: width.abs(),
+                    height: height.abs(),
+                },
+            ))
+        })
+}
+
+/// Crops a rendered page to `bounds` plus `padding` on every side.
+///
+/// `bounds` and `padding` are in the drawing coordinate system. The drawing
+/// viewBox is set to the padded box, and the root's `width` and `height` (when
+/// present) shrink in proportion, keeping their unit. The root gets a
+/// `viewBox` matching its new size, so the crop also scales when printed.
+pub(crate) fn crop(svg: &str, bounds: ViewBox, padding: f64) -> Result<String> {
+    let doc = parse(svg)?;
+    let root = doc.root_element();
+    let drawing = drawing_svg(&doc);
+    let view_box = drawing_view_box(&doc)?;
+    let cropped = ViewBox {
+        min_x: bounds.min_x - padding,
+        min_y: bounds.min_y - padding,
+        width: bounds.width + 2.0 * padding,
+        height: bounds.height + 2.0 * padding,
+    };
+
+    let root_view_box: Option<ViewBox> = root
+        .attribute("viewBox")
+        .and_then(|value| value.parse().ok());
+    let root_size = |name: &str, from_view_box: fn(&ViewBox) -> f64| {
+        root.attribute(name)
+            .map(|value| length(Some(value)))
+            .or_else(|| root_view_box.as_ref().map(from_view_box))
+            .unwrap_or_else(|| from_view_box(&view_box))
+    };
+    let width = round(cropped.width * root_size("width", |v| v.width) / view_box.width);
+    let height = round(cropped.height * root_size("height", |v| v.height) / view_box.height);
+
+    let mut edits = Vec::new();
+    if drawing == root {
+        set_attribute(svg, root, "viewBox", &cropped.to_string(), &mut edits);
+    } else {
+        set_attribute(svg, drawing, "viewBox", &cropped.to_string(), &mut edits);
+        let root_box = ViewBox {
+            min_x: 0.0,
+            min_y: 0.0,
+            width,
+            height,
+        };
+        set_attribute(svg, root, "viewBox", &root_box.to_string(), &mut edits);
+    }
+    for (name, size) in [("width", width), ("height", height)] {
+        if let Some(value) = root.attribute(name) {
+            let unit = value.trim_start_matches(|c: char| c.is_ascii_digit() || c == '.');
+            set_attribute(svg, root, name, &format!("{}{}", size, unit), &mut edits);
+        }
+    }
+
+    Ok(apply_edits(svg, edits))
+}
+
+/// Sets `data-*` attributes on the root `<svg>`, or on every `<g>` group whose
+/// `class` list contains `class` when it is given.
+///
+/// Existing attributes of the same name are overwritten; values are escaped.
+/// The rest of the document is left byte-for-byte unchanged.
+///
+/// # Errors
+///
+/// Returns [`Error::RenderError`] if an attribute name does not start with
+/// `data-` followed by ASCII letters, digits, `-`, `_` or `.`.
+pub(crate) fn set_data_attributes(
+    svg: &str,
+    class: Option<&str>,
+    attributes: &[(&str, &str)],
+) -> Result<String> {
+    check_data_attribute_names(attributes)?;
+
+    let doc = parse(svg)?;
+    let targets: Vec<_> = match class {
+        None => vec![doc.root_element()],
+        Some(class) => doc
+            .descendants()
+            .filter(|node| {
+                node.has_tag_name("g")
+                    && node
+                        .attribute("class")
+                        .is_some_and(|classes| classes.split_whitespace().any(|c| c == class))
+            })
+            .collect(),
+    };
+
+    let mut edits = Vec::new();
+    for node in targets {
+        for (name, value) in attributes {
+            let value = escape_text(value).replace('"', "&quot;");
+            set_attribute(svg, node, name, &value, &mut edits);
+        }
+    }
+    Ok(apply_edits(svg, edits))
+}
+
+/// Checks that the names of `attributes` are `data-` followed by ASCII letters,
+/// digits, `-`, `_` or `.`.
+pub(crate) fn check_data_attribute_names(attributes: &[(&str, &str)]) -> Result<()> {
+    match attributes.iter().find(|(name, _)| {
+        !name.strip_prefix("data-").is_some_and(|rest| {
+            !rest.is_empty()
+                && rest
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        })
+    }) {
+        Some((name, _)) => Err(Error::RenderError(format!(
+            "invalid data attribute name: {:?}",
+            name
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Applies edits recorded by [`set_attribute`], which must not overlap.
+///
+/// Insertions at the same position are applied in the order they were
+/// recorded.
+fn apply_edits(svg: &str, mut edits: Vec<(std::ops::Range<usize>, String)>) -> String {
+    edits.sort_by_key(|(range, _)| range.start);
+    let mut output = String::with_capacity(svg.len());
+    let mut last = 0;
+    for (range, text) in edits {
+        output.push_str(&svg[last..range.start]);
+        output.push_str(&text);
+        last = range.end;
+    }
+    output.push_str(&svg[last..]);
+    output
+}
+
+/// Records the edit that sets attribute `name` on `node` to `value`: the
+/// existing value is replaced, or the attribute is added after the tag name.
+fn set_attribute(
+    svg: &str,
+    node: roxmltree::Node<'_, '_>,
+    name: &str,
+    value: &str,
+    edits: &mut Vec<(std::ops::Range<usize>, String)>,
+) {
+    match node.attributes().find(|attr| attr.name() == name) {
+        Some(attr) => edits.push((attr.range_value(), value.to_string())),
+        None => {
+            let start = node.range().start;
+            let name_end = svg[start + 1..]
+                .find(|c: char| c.is_whitespace() || c == '>' || c == '/')
+                .map_or(svg.len(), |offset| start + 1 + offset);
+            edits.push((name_end..name_end, format!(" {}=\"{}\"", name, value)));
+        }
+    }
+}
+
+/// Rounds a length to two decimal places.
+fn round(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+/// An element with an `id` in a rendered page, as compared by [`diff`].
+struct DiffElement<'a> {
+    /// The element's first class, or its tag name if it has none.
+    kind: &'a str,
+    /// The top-left corner of what the element draws, if it draws anything
+    /// positioned.
+    position: Option<(f64, f64)>,
+    /// The element's attributes and text, and those of its descendants up to
+    /// the next element with an id, with attributes sorted by name.
+    content: String,
+}
+
+/// Classes of the elements Verovio creates during layout, whose ids are
+/// generated anew by every layout.
+const LAYOUT_CLASSES: &[&str] = &["page", "system", "pgHead", "pgFoot"];
+
+/// Returns the code point of a glyph id such as `E0A4-1a2b3c` (see
+/// [`glyph_codes`]), or `None` if `id` is not a glyph id.
+fn glyph_code(id: &str) -> Option<&str> {
+    let code = id.split('-').next().unwrap_or(id);
+    ((4..=5).contains(&code.len()) && code.chars().all(|c| c.is_ascii_hexdigit())).then_some(code)
+}
+
+/// Collects the elements with an `id` in a rendered page, in document order.
+///
+/// Elements are keyed by their id, except those of [`LAYOUT_CLASSES`], which
+/// are keyed by their class and position among the elements of that class,
+/// as in `system[2]`. Glyph references are reduced to the glyph's code
+/// point. Elements inside `<defs>` (the glyph definitions) are skipped.
+fn diff_elements<'a>(doc: &'a roxmltree::Document<'_>) -> Vec<(String, DiffElement<'a>)> {
+    let mut layout_counts: HashMap<&str, usize> = HashMap::new();
+    doc.descendants()
+        .filter(|node| node.is_element())
+        .filter(|node| {
+            !node
+                .ancestors()
+                .any(|ancestor| ancestor.has_tag_name("defs"))
+        })
+        .filter_map(|node| {
+            let id = node.attribute("id")?;
+            let kind = node
+                .attribute("class")
+                .and_then(|class| class.split_whitespace().next())
+                .unwrap_or(node.tag_name().name());
+            let key = if LAYOUT_CLASSES.contains(&kind) {
+                let count = layout_counts.entry(kind).or_default();
+                *count += 1;
+                format!("{}[{}]", kind, count)
+            } else {
+                id.to_string()
+            };
+
+            let position = node
+                .descendants()
+                .filter_map(|child| {
+                    let x: f64 = child.attribute("x")?.parse().ok()?;
+                    let y: f64 = child.attribute("y")?.parse().ok()?;
+                    let (dx, dy) = translation(child);
+                    Some((x + dx, y + dy))
+                })
+                .reduce(|(x1, y1), (x2, y2)| (x1.min(x2), y1.min(y2)));
+
+            let mut content = String::new();
+            let mut stack = vec![node];
+            while let Some(current) = stack.pop() {
+                if current.is_text() {
+                    content.push_str(current.text().unwrap_or_default().trim());
+                    continue;
+                }
+                let mut attributes: Vec<_> = current
+                    .attributes()
+                    .filter(|attr| !matches!(attr.name(), "id" | "x" | "y" | "transform"))
+                    .map(|attr| {
+                        let value = attr.value().trim();
+                        let value = match attr.name() {
+                            "href" => value
+                                .strip_prefix('#')
+                                .and_then(glyph_code)
+                                .unwrap_or(value),
+                            _ => value,
+                        };
+                        format!("{}={}", attr.name(), value)
+                    })
+                    .collect();
+                attributes.sort();
+                content.push_str(&format!(
+                    "<{} {}>",
+                    current.tag_name().name(),
+                    attributes.join(" ")
+                ));
+                stack.extend(
+                    current
+                        .children()
+                        .filter(|child| {
+                            child.is_text() || (child.is_element() && !child.has_attribute("id"))
+                        })
+                        .collect::<Vec<_>>()
+                        .into_iter()
+                        .rev(),
+                );
+            }
+
+            Some((
+                key,
+                DiffElement {
+                    kind,
+                    position,
+                    content,
+                },
+            ))
+        })
+        .collect()
+}
+
+/// Compares two rendered pages element by element.
+///
+/// Elements are matched by `id`, except systems, pages and page headers and
+/// footers, which are matched by position (see [`diff_elements`]). The
+/// result lists, in document order, the elements only in `baseline`
+/// (removed), those only in `current` (added), those drawn at another
+/// position (moved, by more than half a unit), and those whose attributes or
+/// text differ otherwise (changed). Attribute order, surrounding whitespace
+/// and the generated suffixes of glyph ids are ignored.
+pub(crate) fn diff(baseline: &str, current: &str) -> Result<Vec<String>> {
+    let baseline_doc = parse(baseline)?;
+    let current_doc = parse(current)?;
+    let before = diff_elements(&baseline_doc);
+    let after = diff_elements(&current_doc);
+    let before_ids: HashMap<&str, &DiffElement<'_>> = before
+        .iter()
+        .map(|(id, element)| (id.as_str(), element))
+        .collect();
+    let after_ids: HashSet<&str> = after.iter().map(|(id, _)| id.as_str()).collect();
+
+    let mut differences: Vec<String> = before
+        .iter()
+        .filter(|(id, _)| !after_ids.contains(id.as_str()))
+        .map(|(id, element)| format!("removed {} #{}", element.kind, id))
+        .collect();
+
+    for (id, element) in &after {
+        let Some(old) = before_ids.get(id.as_str()) else {
+            differences.push(format!("added {} #{}", element.kind, id));
+            continue;
+        };
+        match (old.position, element.position) {
+            (Some((x1, y1)), Some((x2, y2))) if (x1 - x2).abs() > 0.5 || (y1 - y2).abs() > 0.5 => {
+                differences.push(format!(
+                    "moved {} #{} from ({}, {}) to ({}, {})",
+                    element.kind, id, x1, y1, x2, y2
+                ));
+            }
+            _ if old.content != element.content => {
+                differences.push(format!("changed {} #{}", element.kind, id));
+            }
+            _ => {}
+        }
+    }
+
+    Ok(differences)
+}
+
+/// Normalizes a rendered page for comparison with a golden file.
+///
+/// Comments and indentation are removed (as with [`OptimizeLevel::Basic`]),
+/// the generated suffixes of glyph ids (see [`glyph_codes`]) are dropped
+/// from the `<symbol>` ids and the references to them, and the Verovio
+/// version is dropped from the `Engraved by Verovio` description. Two
+/// renderings of the same document by different runs or Verovio versions
+/// then normalize to the same text if they draw the same thing.
+#[cfg(any(test, feature = "test-utils"))]
+pub(crate) fn normalize_golden(svg: &str) -> Result<String> {
+    let svg = optimize(svg, OptimizeLevel::Basic)?;
+    let doc = parse(&svg)?;
+    let mut edits = Vec::new();
+
+    for node in doc.descendants() {
+        if node.is_text()
+            && node
+                .parent()
+                .is_some_and(|parent| parent.has_tag_name("desc"))
+        {
+            let text = node.text().unwrap_or_default();
+            if text.starts_with("Engraved by Verovio") {
+                edits.push((node.range(), "Engraved by Verovio".to_string()));
+            }
+            continue;
+        }
+        for attr in node.attributes() {
+            let code = match attr.name() {
+                "id" if node.has_tag_name("symbol") => glyph_code(attr.value()).map(String::from),
+                "href" => attr
+                    .value()
+                    .strip_prefix('#')
+                    .and_then(glyph_code)
+                    .map(|code| format!("#{}", code)),
+                _ => None,
+            };
+            if let Some(code) = code {
+                edits.push((attr.range_value(), code));
+            }
+        }
+    }
+
+    Ok(apply_edits(&svg, edits))
+}
+
+/// Builds a standalone SVG overlay with a vertical cursor line at `x`.
+///
+/// The overlay has the same size and drawing coordinate system as `svg`, so it
+/// can be stacked on top of the rendered page.
+pub(crate) fn cursor_overlay(svg: &str, x: f64) -> Result<String> {
+    let doc = parse(svg)?;
+    let root = doc.root_element();
+    let view_box = drawing_view_box(&doc)?;
+
+    let size: String = ["width", "height"]
+        .iter()
+        .filter_map(|name| Some(format!(" {}=\"{}\"", name, root.attribute(*name)?)))
+        .collect();
+
+    Ok(format!(
+        concat!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg"{} viewBox="{}">"#,
+            r#"<line class="cursor" x1="{x}" y1="{}" x2="{x}" y2="{}" "#,
+            r#"stroke="currentColor" stroke-width="{}"/></svg>"#
+        ),
+        size,
+        view_box,
+        view_box.min_y,
+        view_box.min_y + view_box.height,
+        view_box.width / 500.0,
+        x = x,
+    ))
+}
+
+/// Combines rendered pages into one SVG, stacking them vertically.
+///
+/// The definitions in each page's top-level `<defs>` are hoisted into a single
+/// shared `<defs>`. A definition whose id was already seen with the same markup
+/// is dropped; one whose id was seen with different markup is renamed to
+/// `<id>-p<page>` and the references in its page are rewritten.
+pub(crate) fn combine_pages(pages: &[String]) -> Result<String> {
+    let mut definitions: Vec<(String, String)> = Vec::new();
+    let mut bodies = String::new();
+    let mut width: f64 = 0.0;
+    let mut height: f64 = 0.0;
+
+    for (index, page) in pages.iter().enumerate() {
+        let doc = parse(page)?;
+        let root = doc.root_element();
+        let page_width = length(root.attribute("width"));
+        let page_height = length(root.attribute("height"));
+
+        let mut renames = HashMap::new();
+        let mut kept = Vec::new();
+        for definition in root
+            .children()
+            .filter(|node| node.has_tag_name("defs"))
+            .flat_map(|defs| defs.children())
+            .filter(|node| node.is_element())
+        {
+            let markup = &page[definition.range()];
+            let id = definition.attribute("id").unwrap_or_default();
+            match definitions.iter().find(|(existing, _)| existing == id) {
+                Some((_, existing)) if !id.is_empty() && existing == markup => {}
+                Some(_) if !id.is_empty() => {
+                    renames.insert(id.to_string(), format!("{}-p{}", id, index + 1));
+                    kept.push(definition);
+                }
+                _ => kept.push(definition),
+            }
+        }
+
+        for definition in kept {
+            let id = definition.attribute("id").unwrap_or_default();
+            let id = renames.get(id).map_or(id, String::as_str);
+            let markup = rename_references(page, definition, &renames);
+            definitions.push((id.to_string(), markup));
+        }
+
+        let body: String = root
+            .children()
+            .filter(|child| !child.has_tag_name("defs"))
+            .map(|child| rename_references(page, child, &renames))
+            .collect();
+
+        bodies.push_str(&format!(
+            r#"<svg x="0" y="{}" width="{}" height="{}">{}</svg>"#,
+            height, page_width, page_height, body
+        ));
+        width = width.max(page_width);
+        height += page_height;
+    }
+
+    let defs: String = definitions.into_iter().map(|(_, markup)| markup).collect();
+    Ok(format!(
+        concat!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" "#,
+            r#"xmlns:xlink="http://www.w3.org/1999/xlink" "#,
+            r#"width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+            "<defs>{}</defs>{}</svg>"
+        ),
+        defs,
+        bodies,
+        w = width,
+        h = height,
+    ))
+}
+
+/// Returns the markup of `node` in `page` with the ids in `renames` renamed:
+/// in `id` attributes, in `#id` references and in `url(#id)` references.
+fn rename_references(
+    page: &str,
+    node: roxmltree::Node<'_, '_>,
+    renames: &HashMap<String, String>,
+) -> String {
+    let range = node.range();
+    if renames.is_empty() {
+        return page[range].to_string();
+    }
+
+    let mut edits = Vec::new();
+    for attr in node
+        .descendants()
+        .filter(|node| node.is_element())
+        .flat_map(|node| node.attributes())
+    {
+        let value = attr.value();
+        let renamed = if attr.name() == "id" {
+            renames.get(value).cloned()
+        } else if let Some(new) = value.strip_prefix('#').and_then(|id| renames.get(id)) {
+            Some(format!("#{}", new))
+        } else if value.contains("url(#") {
+            let replaced = renames.iter().fold(value.to_string(), |value, (old, new)| {
+                value.replace(&format!("url(#{})", old), &format!("url(#{})", new))
+            });
+            (replaced != value).then_some(replaced)
+        } else {
+            None
+        };
+        if let Some(renamed) = renamed {
+            let value_range = attr.range_value();
+            edits.push((
+                value_range.start - range.start..value_range.end - range.start,
+                renamed,
+            ));
+        }
+    }
+
+    apply_edits(&page[range], edits)
+}
+
+/// Parses an SVG length such as `2100px`, ignoring the unit.
+fn length(value: Option<&str>) -> f64 {
+    value
+        .map(|v| v.trim_end_matches(|c: char| c.is_ascii_alphabetic() || c == '%'))
+        .and_then(|v| v.trim().parse().ok())
+        .unwrap_or(0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_view_box_parse_spaces() {
+        let view_box: ViewBox = "0 0 21000 29700".parse().unwrap();
+        assert_eq!(
+            view_box,
+            ViewBox {
+                min_x: 0.0,
+                min_y: 0.0,
+                width: 21000.0,
+                height: 29700.0,
+            }
+        );
+    }
+
+    #[test]
+    fn test_view_box_parse_commas_and_fractions() {
+        let view_box: ViewBox = "-10.5, 2,100.25 ,50".parse().unwrap();
+        assert_eq!(view_box.min_x, -10.5);
+        assert_eq!(view_box.min_y, 2.0);
+        assert_eq!(view_box.width, 100.25);
+        assert_eq!(view_box.height, 50.0);
+    }
+
+    #[test]
+    fn test_view_box_parse_wrong_count() {
+        assert!("0 0 100".parse::<ViewBox>().is_err());
+        assert!("".parse::<ViewBox>().is_err());
+    }
+
+    #[test]
+    fn test_view_box_parse_not_a_number() {
+        let err = "0 0 wide 100".parse::<ViewBox>().unwrap_err();
+        assert!(err.to_string().contains("invalid viewBox"));
+    }
+
+    #[test]
+    fn test_root_view_box() {
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 800 600"><g/></svg>"#;
+        let view_box = root_view_box(svg).unwrap();
+        assert_eq!(view_box.width, 800.0);
+        assert_eq!(view_box.height, 600.0);
+    }
+
+    #[test]
+    fn test_root_view_box_missing() {
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" width="800px"><g/></svg>"#;
+        assert!(root_view_box(svg).is_err());
+    }
+
+    #[test]
+    fn test_parse_invalid_svg() {
+        let err = parse("<svg").unwrap_err();
+        assert!(err.to_string().contains("failed to parse SVG"));
+    }
+
+    #[test]
+    fn test_prefix_ids_rewrites_ids_and_references() {
+        let svg = concat!(
+            r##"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink">"##,
+            r##"<defs><symbol id="E0A4-abc"><path d="M0 0"/></symbol>"##,
+            r##"<clipPath id="clip"/></defs>"##,
+            r##"<g id="note-1" class="note" clip-path="url(#clip)">"##,
+            r##"<use xlink:href="#E0A4-abc"/><a href="https://example.com/#top"/></g></svg>"##,
+        );
+
+        let prefixed = prefix_ids(svg, "score1-").unwrap();
+        let doc = parse(&prefixed).unwrap();
+
+        let ids: Vec<&str> = doc
+            .descendants()
+            .filter_map(|node| node.attribute("id"))
+            .collect();
+        assert_eq!(ids, vec!["score1-E0A4-abc", "score1-clip", "score1-note-1"]);
+
+        assert!(prefixed.contains(r##"xlink:href="#score1-E0A4-abc""##));
+        assert!(prefixed.contains(r##"clip-path="url(#score1-clip)""##));
+        // External links and classes are left alone
+        assert!(prefixed.contains(r##"href="https://example.com/#top""##));
+        assert!(prefixed.contains(r##"class="note""##));
+    }
+
+    #[test]
+    fn test_color_elements() {
+        let svg = concat!(
+            r##"<svg xmlns="http://www.w3.org/2000/svg">"##,
+            r##"<g id="note-1" class="note"><use href="#E0A4"/></g>"##,
+            r##"<g id="note-2" class="note" fill="black"/>"##,
+            r##"<g id="note-3" class="note"/></svg>"##,
+        );
+
+        let colored = color_elements(
+            svg,
+            &[("note-1", "red"), ("note-2", "#00f"), ("missing", "green")],
+        )
+        .unwrap();
+        let doc = parse(&colored).unwrap();
+        let attr = |id: &str, name: &str| {
+            doc.descendants()
+                .find(|node| node.attribute("id") == Some(id))
+                .and_then(|node| node.attribute(name))
+                .map(String::from)
+        };
+
+        assert_eq!(attr("note-1", "fill").as_deref(), Some("red"));
+        assert_eq!(attr("note-1", "stroke").as_deref(), Some("red"));
+        assert_eq!(attr("note-2", "fill").as_deref(), Some("#00f"));
+        assert_eq!(attr("note-2", "stroke").as_deref(), Some("#00f"));
+        assert_eq!(attr("note-3", "fill"), None);
+        assert!(colored.contains(r##"<use href="#E0A4"/>"##));
+    }
+
+    #[test]
+    fn test_color_elements_invalid_color() {
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg"><g id="a"/></svg>"#;
+        assert!(color_elements(svg, &[("a", "")]).is_err());
+        assert!(color_elements(svg, &[("a", "red\" onload=\"x")]).is_err());
+    }
+
+    #[test]
+    fn test_add_class() {
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg"><g id="m1" class="measure"/><g id="m2"/></svg>"#;
+        assert_eq!(
+            add_class(svg, "m1", "highlight").unwrap(),
+            r#"<svg xmlns="http://www.w3.org/2000/svg"><g id="m1" class="measure highlight"/><g id="m2"/></svg>"#
+        );
+        assert_eq!(
+            add_class(svg, "m2", "highlight").unwrap(),
+            r#"<svg xmlns="http://www.w3.org/2000/svg"><g id="m1" class="measure"/><g class="highlight" id="m2"/></svg>"#
+        );
+    }
+
+    #[test]
+    fn test_add_class_invalid() {
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg"><g id="m1"/></svg>"#;
+        assert!(add_class(svg, "m1", "").is_err());
+        assert!(add_class(svg, "m1", "a\" onload=\"x").is_err());
+        let err = add_class(svg, "m9", "highlight").unwrap_err();
+        assert!(err.to_string().contains("element not found: m9"));
+    }
+
+    #[test]
+    fn test_remove_groups() {
+        let svg = concat!(
+            r#"<svg><g id="n1" class="note"><g id="v1" class="verse"><g class="syl">Ky</g></g></g>"#,
+            r#"<g id="d1" class="dynam  highlighted">f</g><text class="verse">kept</text></svg>"#
+        );
+        assert_eq!(
+            remove_groups(svg, &["verse", "dynam"]).unwrap(),
+            r#"<svg><g id="n1" class="note"></g><text class="verse">kept</text></svg>"#
+        );
+        assert_eq!(
+            remove_groups(svg, &["note", "syl"]).unwrap(),
+            r#"<svg><g id="d1" class="dynam  highlighted">f</g><text class="verse">kept</text></svg>"#
+        );
+        assert_eq!(remove_groups(svg, &[]).unwrap(), svg);
+    }
+
+    #[test]
+    fn test_remove_groups_invalid_class() {
+        for class in ["", "verse dynam", "g[class]", "\"x"] {
+            let err = remove_groups("<svg/>", &[class]).unwrap_err();
+            assert!(err.to_string().contains("invalid element type"));
+        }
+    }
+
+    #[test]
+    fn test_add_accessibility() {
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" width="10px"><g/></svg>"#;
+        let accessible =
+            add_accessibility(svg, "page-1", "Fugue <No. 2> & Prelude", "Page 1 of 2").unwrap();
+        assert_eq!(
+            accessible,
+            concat!(
+                r#"<svg xmlns="http://www.w3.org/2000/svg" width="10px" role="img" aria-labelledby="page-1-title page-1-desc">"#,
+                r#"<title id="page-1-title">Fugue &lt;No. 2&gt; &amp; Prelude</title>"#,
+                r#"<desc id="page-1-desc">Page 1 of 2</desc><g/></svg>"#
+            )
+        );
+        parse(&accessible).unwrap();
+    }
+
+    #[test]
+    fn test_add_accessibility_self_closing_root() {
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg"/>"#;
+        let accessible = add_accessibility(svg, "page-1", "Title", "Description").unwrap();
+        let doc = parse(&accessible).unwrap();
+        let root = doc.root_element();
+        assert_eq!(root.attribute("role"), Some("img"));
+        assert_eq!(
+            root.first_element_child().and_then(|node| node.text()),
+            Some("Title")
+        );
+    }
+
+    #[test]
+    fn test_prefix_ids_mapped() {
+        let svg = concat!(
+            r##"<svg xmlns="http://www.w3.org/2000/svg">"##,
+            r##"<g id="note-1"><use href="#E0A4"/></g><symbol id="E0A4"/></svg>"##,
+        );
+        let (prefixed, ids) = prefix_ids_mapped(svg, "a-").unwrap();
+        assert_eq!(prefixed, prefix_ids(svg, "a-").unwrap());
+        assert_eq!(ids.len(), 2);
+        assert_eq!(ids["note-1"], "a-note-1");
+        assert_eq!(ids["E0A4"], "a-E0A4");
+    }
+
+    const INDENTED_SVG: &str = r##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink">
+   <!-- comment -->
+   <desc>Engraved by Verovio</desc>
+   <g class="" transform="translate(0, 0)">
+      <use xlink:href="#E0A4" x="10" y="20" />
+      <use xlink:href="#E0A4" x="10" y="20" />
+      <use xlink:href="#E0A4" x="30" y="20" />
+      <text xml:space="preserve"> <tspan>A </tspan></text>
+   </g>
+</svg>"##;
+
+    #[test]
+    fn test_check_lang() {
+        assert!(check_lang("he").is_ok());
+        assert!(check_lang("ar-EG").is_ok());
+        assert!(check_lang("zh-Hant-TW").is_ok());
+        assert!(check_lang("").is_err());
+        assert!(check_lang("he\"").is_err());
+        assert!(check_lang("en--US").is_err());
+    }
+
+    #[test]
+    fn test_set_text_lang() {
+        let svg = concat!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg"><g class="verse">"#,
+            r#"<text x="10" xml:space="preserve"><tspan>שלום</tspan></text>"#,
+            r#"<text xml:lang="en" direction="ltr">Hi</text></g></svg>"#
+        );
+
+        let hebrew = set_text_lang(svg, "he-IL").unwrap();
+        assert!(
+            hebrew
+                .contains(r#"<text xml:lang="he-IL" direction="rtl" x="10" xml:space="preserve">"#)
+        );
+        assert!(hebrew.contains(r#"<text xml:lang="he-IL" direction="rtl">Hi</text>"#));
+        parse(&hebrew).unwrap();
+
+        let german = set_text_lang(svg, "de").unwrap();
+        assert!(german.contains(r#"<text xml:lang="de" x="10""#));
+        assert!(!german.contains("rtl"));
+    }
+
+    #[test]
+    fn test_round_value() {
+        assert_eq!(
+            round_value("translate(1692, 1765) scale(0.72, 0.72)", 1),
+            "translate(1692, 1765) scale(0.7, 0.7)"
+        );
+        assert_eq!(
+            round_value("M0.126.5L-3.14159 2e-1 2.5e-3", 2),
+            "M0.13.5L-3.14 0.2 0"
+        );
+        assert_eq!(round_value("M1.001.5", 2), "M1 .5");
+        assert_eq!(round_value("9.000000", 3), "9");
+        assert_eq!(round_value("-0.0004", 2), "0");
+        assert_eq!(round_value("2.6px", 0), "3px");
+        assert_eq!(round_value("12 -4 .", 2), "12 -4 .");
+    }
+
+    #[test]
+    fn test_round_numbers() {
+        let svg = concat!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="210.123456mm" viewBox="0 0 21000.5 2970.25">"#,
+            r##"<g id="note-0.123456" class="note" transform="translate(10.55, 20) scale(0.666667, 0.666667)">"##,
+            r#"<path d="M1.23456 2.5 L3.999 4" stroke-width="9.000000"/>"#,
+            r#"<text x="1.23456">1.23456</text></g></svg>"#
+        );
+        let rounded = round_numbers(svg, 2).unwrap();
+        assert!(rounded.contains(r#"width="210.12mm""#));
+        assert!(rounded.contains(r#"viewBox="0 0 21000.5 2970.25""#));
+        assert!(rounded.contains(r#"transform="translate(10.55, 20) scale(0.67, 0.67)""#));
+        assert!(rounded.contains(r#"d="M1.23 2.5 L4 4""#));
+        assert!(rounded.contains(r#"stroke-width="9""#));
+        assert!(rounded.contains(r#"<text x="1.23">1.23456</text>"#));
+        assert!(rounded.contains(r#"id="note-0.123456""#));
+        parse(&rounded).unwrap();
+    }
+
+    #[test]
+    fn test_optimize_none() {
+        assert_eq!(
+            optimize(INDENTED_SVG, OptimizeLevel::None).unwrap(),
+            INDENTED_SVG
+        );
+    }
+
+    #[test]
+    fn test_optimize_basic() {
+        let optimized = optimize(INDENTED_SVG, OptimizeLevel::Basic).unwrap();
+        assert_eq!(
+            optimized,
+            concat!(
+                "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+                r##"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink">"##,
+                r##"<desc>Engraved by Verovio</desc><g class="" transform="translate(0, 0)">"##,
+                r##"<use xlink:href="#E0A4" x="10" y="20" /><use xlink:href="#E0A4" x="10" y="20" />"##,
+                r##"<use xlink:href="#E0A4" x="30" y="20" />"##,
+                r##"<text xml:space="preserve"> <tspan>A </tspan></text></g></svg>"##
+            )
+        );
+    }
+
+    #[test]
+    fn test_optimize_aggressive() {
+        let optimized = optimize(INDENTED_SVG, OptimizeLevel::Aggressive).unwrap();
+        assert_eq!(
+            optimized,
+            concat!(
+                "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+                r##"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink">"##,
+                r##"<desc>Engraved by Verovio</desc><g>"##,
+                r##"<use xlink:href="#E0A4" x="10" y="20" /><use xlink:href="#E0A4" x="30" y="20" />"##,
+                r##"<text xml:space="preserve"> <tspan>A </tspan></text></g></svg>"##
+            )
+        );
+        assert!(optimized.len() < optimize(INDENTED_SVG, OptimizeLevel::Basic).unwrap().len());
+        parse(&optimized).unwrap();
+    }
+
+    #[test]
+    fn test_optimize_keeps_translations() {
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg"><g transform="translate(0, 10)"/><g transform="translate(0) scale(2)"/></svg>"#;
+        assert_eq!(optimize(svg, OptimizeLevel::Aggressive).unwrap(), svg);
+    }
+
+    #[test]
+    fn test_prefix_ids_invalid_prefix() {
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg"/>"#;
+        assert!(prefix_ids(svg, "").is_err());
+        assert!(prefix_ids(svg, "1score").is_err());
+        assert!(prefix_ids(svg, "a\"b").is_err());
+        assert!(prefix_ids(svg, "a b").is_err());
+    }
+
+    const VEROVIO_LIKE_SVG: &str = concat!(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" width="2100px" height="2970px">"##,
+        r##"<svg class="definition-scale" viewBox="0 0 21000 29700">"##,
+        r##"<g class="page-margin" transform="translate(500, 500)">"##,
+        r##"<g id="note-1" class="note"><g class="notehead"><use xlink:href="#E0A4" x="1200" y="3000"/></g></g>"##,
+        r##"<g id="note-2" class="note"><g class="notehead"><use xlink:href="#E0A4" x="900" y="3100"/></g></g>"##,
+        r##"<g id="note-3" class="note" transform="translate(100)"><use xlink:href="#E0A4" x="4000" y="3000"/></g>"##,
+        r##"</g></svg></svg>"##,
+    );
+
+    #[test]
+    fn test_view_box_display_round_trip() {
+        let view_box: ViewBox = "0 -5 21000 29700.5".parse().unwrap();
+        assert_eq!(view_box.to_string(), "0 -5 21000 29700.5");
+        assert_eq!(view_box.to_string().parse::<ViewBox>().unwrap(), view_box);
+    }
+
+    #[test]
+    fn test_parse_translate() {
+        assert_eq!(parse_translate("translate(500, 500)"), Some((500.0, 500.0)));
+        assert_eq!(parse_translate("translate(100)"), Some((100.0, 0.0)));
+        assert_eq!(parse_translate("scale(2) translate(1 2)"), Some((1.0, 2.0)));
+        assert_eq!(parse_translate("scale(2)"), None);
+    }
+
+    #[test]
+    fn test_elements_min_x() {
+        let ids = vec!["note-1".to_string(), "note-2".to_string()];
+        assert_eq!(
+            elements_min_x(VEROVIO_LIKE_SVG, &ids).unwrap(),
+            Some(1400.0)
+        );
+
+        let ids = vec!["note-3".to_string()];
+        assert_eq!(
+            elements_min_x(VEROVIO_LIKE_SVG, &ids).unwrap(),
+            Some(4600.0)
+        );
+
+        let ids = vec!["not-on-page".to_string()];
+        assert_eq!(elements_min_x(VEROVIO_LIKE_SVG, &ids).unwrap(), None);
+    }
+
+    #[test]
+    fn test_glyph_codes() {
+        let svg = concat!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg"><defs>"#,
+            r#"<symbol id="E0A4-1a2b3c"><path d="M0 0"/></symbol>"#,
+            r#"<symbol id="E050-4d5e6f"><path d="M0 0"/></symbol>"#,
+            r#"<symbol id="E0A4-7a8b9c"><path d="M0 0"/></symbol>"#,
+            r#"<symbol id="clip-path"/></defs><g id="note-1"/></svg>"#
+        );
+        let codes = glyph_codes(svg).unwrap();
+        assert_eq!(codes.into_iter().collect::<Vec<_>>(), vec!["E050", "E0A4"]);
+    }
+
+    #[test]
+    fn test_annotate_glyphs() {
+        let svg = concat!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink">"#,
+            r##"<g id="note-1" class="note"><use xlink:href="#E0A4-1a2b3c" x="10"/></g>"##,
+            r##"<g id="clef-1" class="clef"><use href="#E050-4d5e6f"></use></g>"##,
+            r##"<g id="rest-1" class="rest"><use xlink:href="#E4E5-7a8b9c"/></g></svg>"##
+        );
+        let names = BTreeMap::from([
+            ("E0A4".to_string(), "noteheadBlack".to_string()),
+            ("E050".to_string(), "gClef".to_string()),
+        ]);
+
+        let annotated = annotate_glyphs(svg, &names).unwrap();
+        assert!(annotated.contains(
+            r##"<use xlink:href="#E0A4-1a2b3c" x="10"><title>noteheadBlack</title></use>"##
+        ));
+        assert!(annotated.contains(r##"<use href="#E050-4d5e6f"><title>gClef</title></use>"##));
+        assert!(annotated.contains(r##"<use xlink:href="#E4E5-7a8b9c"/>"##));
+        parse(&annotated).unwrap();
+    }
+
+    #[test]
+    fn test_element_at_point() {
+        let svg = concat!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg"><svg class="definition-scale" viewBox="0 0 5000 2000">"#,
+            r#"<g class="page-margin" transform="translate(500, 500)">"#,
+            r#"<g id="measure-1" class="measure">"#,
+            r#"<g id="bbox-measure-1" class="bounding-box"><rect x="0" y="0" width="2000" height="1000"/></g>"#,
+            r#"<g id="note-1" class="note">"#,
+            r#"<g id="bbox-note-1" class="bounding-box"><rect x="100" y="200" width="300" height="250"/></g>"#,
+            r#"<g id="stem-1" class="stem"><g class="bounding-box"><rect x="380" y="-400" width="20" height="600"/></g></g>"#,
+            r#"</g></g></g></svg></svg>"#
+        );
+
+        assert_eq!(
+            element_at_point(svg, 750.0, 825.0).unwrap().as_deref(),
+            Some("note-1")
+        );
+        assert_eq!(
+            element_at_point(svg, 890.0, 300.0).unwrap().as_deref(),
+            Some("stem-1")
+        );
+        assert_eq!(
+            element_at_point(svg, 2000.0, 1200.0).unwrap().as_deref(),
+            Some("measure-1")
+        );
+        assert_eq!(element_at_point(svg, 100.0, 100.0).unwrap(), None);
+    }
+
+    #[test]
+    fn test_element_anchors() {
+        let svg = concat!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg"><svg class="definition-scale" viewBox="0 0 5000 2000">"#,
+            r#"<g class="page-margin" transform="translate(500, 500)">"#,
+            r#"<g id="measure-1" class="measure">"#,
+            r#"<g id="bbox-measure-1" class="bounding-box"><rect x="0" y="0" width="2000" height="1000"/></g>"#,
+            r#"<g id="note-1" class="note">"#,
+            r#"<g id="bbox-note-1" class="bounding-box"><rect x="100" y="200" width="300" height="250"/></g>"#,
+            r#"<g class="bounding-box"><rect x="0" y="0" width="10" height="10"/></g>"#,
+            r#"<g id="stem-1" class="stem"><g class="bounding-box"><rect x="380" y="-400" width="20" height="600"/></g></g>"#,
+            r#"</g></g></g></svg></svg>"#
+        );
+
+        let anchors = element_anchors(svg).unwrap();
+        let summary: Vec<_> = anchors
+            .iter()
+            .map(|anchor| (anchor.id.as_str(), anchor.kind.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("measure-1", "measure"),
+                ("note-1", "note"),
+                ("stem-1", "stem")
+            ]
+        );
+        assert_eq!(
+            anchors[1].bounds,
+            ViewBox {
+                min_x: 600.0,
+                min_y: 700.0,
+                width: 300.0,
+                height: 250.0,
+            }
+        );
+        assert!(
+            element_anchors(BOXED_PAGE.replace("bounding-box", "x").as_str())
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_set_data_attributes_root() {
+        let svg =
+            r#"<svg xmlns="http://www.w3.org/2000/svg" data-foo="old"><g class="note"/></svg>"#;
+        let result = set_data_attributes(
+            svg,
+            None,
+            &[("data-foo", "bar"), ("data-label", "a \"b\" & <c>")],
+        )
+        .unwrap();
+        assert_eq!(
+            result,
+            r#"<svg data-label="a &quot;b&quot; &amp; &lt;c&gt;" xmlns="http://www.w3.org/2000/svg" data-foo="bar"><g class="note"/></svg>"#
+        );
+        let doc = parse(&result).unwrap();
+        assert_eq!(
+            doc.root_element().attribute("data-label"),
+            Some(r#"a "b" & <c>"#)
+        );
+    }
+
+    #[test]
+    fn test_set_data_attributes_groups() {
+        let svg = r#"<svg><g id="n1" class="note"><g class="stem"/></g><g class="note beamed"></g><g class="rest"/></svg>"#;
+        let result = set_data_attributes(svg, Some("note"), &[("data-role", "hook")]).unwrap();
+        assert_eq!(
+            result,
+            r#"<svg><g data-role="hook" id="n1" class="note"><g class="stem"/></g><g data-role="hook" class="note beamed"></g><g class="rest"/></svg>"#
+        );
+    }
+
+    #[test]
+    fn test_set_data_attributes_invalid_name() {
+        for name in ["foo", "data-", "data-a b", "data-x\"", "onclick"] {
+            let err = set_data_attributes("<svg/>", None, &[(name, "v")]).unwrap_err();
+            assert!(err.to_string().contains("invalid data attribute name"));
+        }
+    }
+
+    #[test]
+    fn test_coordinate_mapper() {
+        let view_box = ViewBox {
+            min_x: 0.0,
+            min_y: 0.0,
+            width: 21000.0,
+            height: 29700.0,
+        };
+        let mapper = CoordinateMapper::new(view_box, 1050.0, 1485.0).unwrap();
+        assert_eq!(mapper.scale(), 0.05);
+        assert_eq!(mapper.user_to_pixel(0.0, 0.0), (0.0, 0.0));
+        assert_eq!(mapper.user_to_pixel(2000.0, 3000.0), (100.0, 150.0));
+        assert_eq!(mapper.pixel_to_user(100.0, 150.0), (2000.0, 3000.0));
+        assert_eq!(mapper.user_to_pixel(21000.0, 29700.0), (1050.0, 1485.0));
+    }
+
+    #[test]
+    fn test_coordinate_mapper_offset_view_box_and_letterbox() {
+        let view_box = ViewBox {
+            min_x: 100.0,
+            min_y: 200.0,
+            width: 1000.0,
+            height: 500.0,
+        };
+        // Twice as tall as needed: the drawing is centered vertically
+        let mapper = CoordinateMapper::new(view_box, 500.0, 500.0).unwrap();
+        assert_eq!(mapper.scale(), 0.5);
+        assert_eq!(mapper.user_to_pixel(100.0, 200.0), (0.0, 125.0));
+        assert_eq!(mapper.user_to_pixel(1100.0, 700.0), (500.0, 375.0));
+        assert_eq!(mapper.pixel_to_user(250.0, 250.0), (600.0, 450.0));
+    }
+
+    #[test]
+    fn test_coordinate_mapper_invalid() {
+        let view_box: ViewBox = "0 0 100 100".parse().unwrap();
+        assert!(CoordinateMapper::new(view_box, 0.0, 100.0).is_err());
+        assert!(CoordinateMapper::new(view_box, 100.0, f64::NAN).is_err());
+        let empty: ViewBox = "0 0 0 100".parse().unwrap();
+        let err = CoordinateMapper::new(empty, 100.0, 100.0).unwrap_err();
+        assert!(err.to_string().contains("invalid viewBox"));
+    }
+
+    #[test]
+    fn test_staff_groups() {
+        let svg = concat!(
+            r#"<svg><g id="m1" class="measure">"#,
+            r#"<g id="s1" class="staff" data-n="1"/><g id="s2" class="staff" data-n="2"/></g>"#,
+            r#"<g id="m2" class="measure"><g id="s3" class="staff" data-n="1"/>"#,
+            r#"<g class="staff" data-n="2"/><g id="s5" class="staff"/><g id="l1" class="layer" data-n="1"/></g></svg>"#
+        );
+        assert_eq!(
+            staff_groups(svg).unwrap(),
+            vec![
+                (1, "s1".to_string()),
+                (2, "s2".to_string()),
+                (1, "s3".to_string())
+            ]
+        );
+    }
+
+    const BOXED_PAGE: &str = concat!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="500px" height="200px">"#,
+        r#"<svg class="definition-scale" viewBox="0 0 5000 2000">"#,
+        r#"<g class="page-margin" transform="translate(500, 500)">"#,
+        r#"<g id="note-1"><g class="bounding-box"><rect x="100" y="200" width="300" height="250"/></g></g>"#,
+        r#"<g id="stem-1"><g class="bounding-box"><rect x="380" y="-400" width="20" height="600"/></g></g>"#,
+        r#"</g></svg></svg>"#
+    );
+
+    #[test]
+    fn test_content_bounds() {
+        assert_eq!(
+            content_bounds(BOXED_PAGE).unwrap(),
+            Some(ViewBox {
+                min_x: 600.0,
+                min_y: 100.0,
+                width: 300.0,
+                height: 850.0,
+            })
+        );
+        assert_eq!(content_bounds("<svg/>").unwrap(), None);
+    }
+
+    #[test]
+    fn test_crop() {
+        let bounds = content_bounds(BOXED_PAGE).unwrap().unwrap();
+        let cropped = crop(BOXED_PAGE, bounds, 50.0).unwrap();
+
+        assert_eq!(root_view_box(&cropped).unwrap().to_string(), "0 0 40 95");
+        let doc = parse(&cropped).unwrap();
+        assert_eq!(doc.root_element().attribute("width"), Some("40px"));
+        assert_eq!(doc.root_element().attribute("height"), Some("95px"));
+        assert_eq!(
+            drawing_view_box(&doc).unwrap().to_string(),
+            "550 50 400 950"
+        );
+        assert!(cropped.contains(r#"<g class="page-margin" transform="translate(500, 500)">"#));
+    }
+
+    #[test]
+    fn test_crop_root_view_box_only() {
+        let svg = r#"<svg viewBox="0 0 1000 500"><rect x="0" y="0" width="10" height="10"/></svg>"#;
+        let bounds = ViewBox {
+            min_x: 100.0,
+            min_y: 100.0,
+            width: 200.0,
+            height: 100.0,
+        };
+        let cropped = crop(svg, bounds, 0.0).unwrap();
+        assert_eq!(root_view_box(&cropped).unwrap(), bounds);
+        assert!(!cropped.contains("width=\"200"));
+    }
+
+    #[test]
+    fn test_diff_identical_after_normalization() {
+        let baseline = concat!(
+            r#"<svg><defs><symbol id="E0A4-1"/></defs>"#,
+            r##"<g id="n1" class="note"><use x="10" y="20" href="#E0A4-1"/></g></svg>"##
+        );
+        let current = r##"<svg>
+              <defs><symbol id="E0A4-2"/></defs>
+              <g class="note"  id="n1">
+                <use href="#E0A4-2" y="20" x="10"/>
+              </g>
+            </svg>"##;
+        assert!(diff(baseline, current).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_diff_reports_element_changes() {
+        let baseline = concat!(
+            r#"<svg><g transform="translate(100, 100)">"#,
+            r#"<g id="n1" class="note"><use x="10" y="20"/></g>"#,
+            r#"<g id="n2" class="note"><use x="30" y="20"/></g>"#,
+            r#"<g id="d1" class="dynam"><text x="0" y="50">p</text></g>"#,
+            r#"</g></svg>"#
+        );
+        let current = concat!(
+            r#"<svg><g transform="translate(100, 100)">"#,
+            r#"<g id="n1" class="note"><use x="10" y="5"/></g>"#,
+            r#"<g id="d1" class="dynam"><text x="0" y="50">f</text></g>"#,
+            r#"<g id="r1" class="rest"><use x="30" y="20"/></g>"#,
+            r#"</g></svg>"#
+        );
+        assert_eq!(
+            diff(baseline, current).unwrap(),
+            vec![
+                "removed note #n2",
+                "moved note #n1 from (110, 120) to (110, 105)",
+                "changed dynam #d1",
+                "added rest #r1",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_normalize_golden() {
+        let first = r##"<svg xmlns:xlink="http://www.w3.org/1999/xlink">
+   <!-- run 1 -->
+   <desc>Engraved by Verovio 5.6.0-1a2b3c4</desc>
+   <defs><symbol id="E0A4-x1y2z3"/></defs>
+   <g id="note-1" class="note"><use xlink:href="#E0A4-x1y2z3" x="10"/></g>
+   <g id="note-2" class="note"><use xlink:href="#note-1" x="20"/></g>
+</svg>"##;
+        let second = concat!(
+            r#"<svg xmlns:xlink="http://www.w3.org/1999/xlink">"#,
+            r#"<desc>Engraved by Verovio 5.7.0</desc>"#,
+            r#"<defs><symbol id="E0A4-q9r8s7"/></defs>"#,
+            r##"<g id="note-1" class="note"><use xlink:href="#E0A4-q9r8s7" x="10"/></g>"##,
+            r##"<g id="note-2" class="note"><use xlink:href="#note-1" x="20"/></g></svg>"##
+        );
+
+        let normalized = normalize_golden(first).unwrap();
+        assert_eq!(normalized, normalize_golden(second).unwrap());
+        assert!(normalized.contains(r#"<desc>Engraved by Verovio</desc>"#));
+        assert!(normalized.contains(r#"<symbol id="E0A4"/>"#));
+        assert!(normalized.contains(r##"xlink:href="#E0A4""##));
+        assert!(normalized.contains(r##"xlink:href="#note-1""##));
+        assert!(!normalized.contains("run 1"));
+    }
+
+    #[test]
+    fn test_diff_matches_layout_elements_by_position() {
+        let baseline = concat!(
+            r#"<svg><g id="system-0001" class="system">"#,
+            r#"<g id="n1" class="note"><use x="10" y="20"/></g></g>"#,
+            r#"<g id="system-0002" class="system"><use x="0" y="100"/></g></svg>"#
+        );
+        let current = concat!(
+            r#"<svg><g id="system-0815" class="system">"#,
+            r#"<g id="n1" class="note"><use x="10" y="20"/></g></g>"#,
+            r#"<g id="system-0816" class="system"><use x="0" y="140"/></g></svg>"#
+        );
+        assert_eq!(
+            diff(baseline, current).unwrap(),
+            vec!["moved system #system[2] from (0, 100) to (0, 140)"]
+        );
+    }
+
+    #[test]
+    fn test_diff_invalid_baseline() {
+        assert!(diff("<svg", "<svg/>").is_err());
+    }
+
+    #[test]
+    fn test_cursor_overlay() {
+        let overlay = cursor_overlay(VEROVIO_LIKE_SVG, 1400.0).unwrap();
+        let doc = parse(&overlay).unwrap();
+        let root = doc.root_element();
+        assert_eq!(root.attribute("width"), Some("2100px"));
+        assert_eq!(root.attribute("height"), Some("2970px"));
+        assert_eq!(root.attribute("viewBox"), Some("0 0 21000 29700"));
+
+        let line = root.first_element_child().unwrap();
+        assert!(line.has_tag_name("line"));
+        assert_eq!(line.attribute("x1"), Some("1400"));
+        assert_eq!(line.attribute("x2"), Some("1400"));
+        assert_eq!(line.attribute("y2"), Some("29700"));
+    }
+
+    fn page(symbol: &str) -> String {
+        format!(
+            concat!(
+                r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" width="100px" height="50px">"#,
+                r#"<defs><symbol id="E0A4"><path d="M0 0"/></symbol>{}</defs>"#,
+                r##"<svg class="definition-scale" viewBox="0 0 1000 500"><use xlink:href="#E0A4"/><use xlink:href="#E050"/></svg></svg>"##
+            ),
+            symbol
+        )
+    }
+
+    #[test]
+    fn test_combine_pages_deduplicates_definitions() {
+        let pages = vec![
+            page(r#"<symbol id="E050"><path d="M1 1"/></symbol>"#),
+            page(r#"<symbol id="E050"><path d="M1 1"/></symbol>"#),
+        ];
+        let combined = combine_pages(&pages).unwrap();
+
+        let per_page: usize = pages.iter().map(|p| p.matches("<symbol").count()).sum();
+        assert_eq!(per_page, 4);
+        assert_eq!(combined.matches("<symbol").count(), 2);
+
+        let doc = parse(&combined).unwrap();
+        let root = doc.root_element();
+        assert_eq!(root.attribute("width"), Some("100"));
+        assert_eq!(root.attribute("height"), Some("100"));
+        let offsets: Vec<&str> = root
+            .children()
+            .filter(|node| node.has_tag_name("svg"))
+            .filter_map(|node| node.attribute("y"))
+            .collect();
+        assert_eq!(offsets, vec!["0", "50"]);
+    }
+
+    #[test]
+    fn test_combine_pages_renames_conflicting_definitions() {
+        let pages = vec![
+            page(r#"<symbol id="E050"><path d="M1 1"/></symbol>"#),
+            page(r#"<symbol id="E050"><path d="M2 2"/></symbol>"#),
+        ];
+        let combined = combine_pages(&pages).unwrap();
+
+        assert_eq!(combined.matches("<symbol").count(), 3);
+        assert!(combined.contains(r#"<symbol id="E050-p2"><path d="M2 2"/>"#));
+        assert_eq!(combined.matches(r##"xlink:href="#E050-p2""##).count(), 1);
+        assert_eq!(combined.matches(r##"xlink:href="#E050""##).count(), 1);
+        assert_eq!(combined.matches(r##"xlink:href="#E0A4""##).count(), 2);
+    }
+
+    #[test]
+    fn test_combine_pages_renames_only_references() {
+        let second = concat!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" width="100px" height="50px">"#,
+            r#"<defs><symbol id="E0A4"><path d="M1 1"/></symbol></defs>"#,
+            r##"<g filter="url(#E0A4)"><use xlink:href="#E0A4"/><use xlink:href="#E0A4-x"/>"##,
+            r##"<text>"#E0A4" url(#E0A4)</text></g></svg>"##
+        );
+        let combined = combine_pages(&[page(""), second.to_string()]).unwrap();
+
+        assert!(combined.contains(r#"<symbol id="E0A4-p2"><path d="M1 1"/>"#));
+        assert!(combined.contains(r##"<g filter="url(#E0A4-p2)">"##));
+        assert!(combined.contains(r##"<use xlink:href="#E0A4-p2"/>"##));
+        assert!(combined.contains(r##"<use xlink:href="#E0A4-x"/>"##));
+        assert!(combined.contains(r##"<text>"#E0A4" url(#E0A4)</text>"##));
+        parse(&combined).unwrap();
+    }
+
+    #[test]
+    fn test_combine_pages_empty() {
+        let combined = combine_pages(&[]).unwrap();
+        assert!(parse(&combined).is_ok());
+    }
+}
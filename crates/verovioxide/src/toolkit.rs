@@ -39,14 +39,17 @@
 //! println!("{}", svg);
 //! ```
 
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::ffi::{CStr, CString, c_void};
 use std::path::Path;
+use std::time::{Duration, Instant};
 
 #[cfg(feature = "bundled-data")]
 use tempfile::TempDir;
 
 use crate::error::{Error, Result};
-use crate::options::Options;
+use crate::options::{BreakMode, CondenseMode, Options, ScaleMode};
 
 /// Marker type for loading base64-encoded ZIP data (compressed MusicXML).
 ///
@@ -83,6 +86,87 @@ pub struct ZipBase64<'a>(pub &'a str);
 #[derive(Debug, Clone, Copy)]
 pub struct ZipBuffer<'a>(pub &'a [u8]);
 
+/// A rendered page together with its playback data.
+///
+/// Returned by [`Toolkit::render_page_bundle`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageBundle {
+    /// The page rendered as SVG.
+    pub svg: String,
+    /// The timemap entries for the elements on the page, as a JSON array.
+    ///
+    /// Entries have the same format as those of
+    /// [`Toolkit::render_to_timemap`].
+    pub timemap: String,
+    /// The time span covered by the page's timemap, in milliseconds.
+    pub duration_ms: f64,
+}
+
+/// A position in the pages of a [`Toolkit`], for rendering pages on demand.
+///
+/// Created by [`Toolkit::page_cursor`]. The cursor does not borrow the
+/// toolkit, so the document can be edited or relaid out while scrolling; when
+/// the toolkit's [`generation`](Toolkit::generation) has changed since the
+/// last page, the cursor starts over from page 1.
+///
+/// # Example
+///
+/// ```no_run
+/// use verovioxide::Toolkit;
+///
+/// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+/// // ... load data ...
+///
+/// let mut cursor = toolkit.page_cursor();
+/// while let Some(svg) = cursor.next_page(&toolkit) {
+///     let svg = svg.expect("Failed to render page");
+///     println!("page {}: {} bytes", cursor.page(), svg.len());
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageCursor {
+    /// The page last returned, or 0 before the first page.
+    page: u32,
+    /// The page count when the cursor was created or last reset.
+    page_count: u32,
+    /// The toolkit generation the position refers to.
+    generation: u64,
+}
+
+impl PageCursor {
+    /// Renders the next page to SVG, or returns `None` after the last page.
+    ///
+    /// If the toolkit has changed since the previous page (see
+    /// [`Toolkit::generation`]), the cursor is reset and page 1 of the new
+    /// layout is returned; [`page`](Self::page) tells which page was rendered.
+    pub fn next_page(&mut self, toolkit: &Toolkit) -> Option<Result<String>> {
+        if toolkit.generation() != self.generation {
+            *self = toolkit.page_cursor();
+        }
+        if !self.has_more() {
+            return None;
+        }
+
+        self.page += 1;
+        Some(toolkit.render_to_svg(self.page))
+    }
+
+    /// Returns `true` if there are pages left to render.
+    ///
+    /// This reflects the layout the cursor last saw; after the toolkit
+    /// changes, the next call to [`next_page`](Self::next_page) starts over.
+    #[must_use]
+    pub fn has_more(&self) -> bool {
+        self.page < self.page_count
+    }
+
+    /// Returns the number of the page last rendered, or 0 if none has been.
+    #[must_use]
+    pub fn page(&self) -> u32 {
+        self.page
+    }
+}
+
 /// Trait for types that can be loaded into a [`Toolkit`].
 ///
 /// This trait enables the unified [`Toolkit::load`] method to accept multiple
@@ -152,6 +236,268 @@ impl<'a> LoadSource for ZipBuffer<'a> {
     }
 }
 
+/// Guesses the format of music data from its first characters.
+///
+/// Returns the Verovio input format name (`mei`, `musicxml`, `humdrum`, `pae`
+/// or `abc`), or `None` if the data does not clearly look like any of them.
+fn sniff_format(data: &str) -> Option<&'static str> {
+    let data = data.trim_start_matches('\u{feff}').trim_start();
+
+    if data.starts_with('<') {
+        let root = data
+            .match_indices('<')
+            .map(|(start, _)| &data[start + 1..])
+            .find(|tag| !tag.starts_with(['?', '!']))?;
+        let name = root
+            .split(|c: char| c.is_whitespace() || c == '>' || c == '/')
+            .next()
+            .unwrap_or_default();
+        return match name {
+            "mei" | "meiHead" | "meiCorpus" | "music" => Some("mei"),
+            "score-partwise" | "score-timewise" | "opus" => Some("musicxml"),
+            _ => None,
+        };
+    }
+    if data.starts_with("**") || data.starts_with("!!") {
+        return Some("humdrum");
+    }
+    if data.starts_with('@') {
+        return Some("pae");
+    }
+    if data.lines().any(|line| line.trim_start().starts_with("X:")) {
+        return Some("abc");
+    }
+    None
+}
+
+/// Returns the byte range of the `encoding` value in the XML declaration at the
+/// start of `data`, or `None` if there is no declaration or it names no
+/// encoding.
+fn declared_encoding(data: &[u8]) -> Option<std::ops::Range<usize>> {
+    let start = if data.starts_with(b"\xEF\xBB\xBF") {
+        3
+    } else {
+        0
+    };
+    let rest = data.get(start..)?;
+    if !rest.starts_with(b"<?xml") {
+        return None;
+    }
+    let declaration = &rest[..rest.windows(2).position(|w| w == b"?>")?];
+
+    let skip_whitespace = |mut i: usize| {
+        while declaration.get(i).is_some_and(u8::is_ascii_whitespace) {
+            i += 1;
+        }
+        i
+    };
+    let key = declaration.windows(8).position(|w| w == b"encoding")? + 8;
+    let equals = skip_whitespace(key);
+    if declaration.get(equals) != Some(&b'=') {
+        return None;
+    }
+    let value = skip_whitespace(equals + 1);
+    let quote = *declaration
+        .get(value)
+        .filter(|&&c| c == b'"' || c == b'\'')?;
+    let length = declaration[value + 1..].iter().position(|&c| c == quote)?;
+    Some(start + value + 1..start + value + 1 + length)
+}
+
+/// Decodes music data to UTF-8 according to its XML declaration.
+///
+/// Data without a declared encoding, or declared as UTF-8, must be valid UTF-8.
+/// Other encodings are transcoded with the `encoding` feature, and the
+/// declaration is rewritten to say `UTF-8`.
+fn decode_data(data: &[u8]) -> Result<std::borrow::Cow<'_, str>> {
+    let label = declared_encoding(data)
+        .map(|range| {
+            String::from_utf8_lossy(&data[range])
+                .trim()
+                .to_ascii_lowercase()
+        })
+        .filter(|label| label != "utf-8" && label != "utf8");
+
+    let Some(label) = label else {
+        return std::str::from_utf8(data)
+            .map(std::borrow::Cow::Borrowed)
+            .map_err(|e| Error::LoadError(format!("data is not valid UTF-8: {}", e)));
+    };
+
+    #[cfg(feature = "encoding")]
+    {
+        let encoding = encoding_rs::Encoding::for_label(label.as_bytes())
+            .ok_or_else(|| Error::LoadError(format!("unsupported encoding: {}", label)))?;
+        let mut text = encoding.decode(data).0.into_owned();
+        if let Some(range) = declared_encoding(text.as_bytes()) {
+            text.replace_range(range, "UTF-8");
+        }
+        Ok(std::borrow::Cow::Owned(text))
+    }
+    #[cfg(not(feature = "encoding"))]
+    Err(Error::LoadError(format!(
+        "data is encoded as {}; enable the `encoding` feature to load it",
+        label
+    )))
+}
+
+/// Maps an `inputFrom` value to the format [`sniff_format`] reports for such
+/// data, or `None` for formats that are not sniffed.
+fn input_format_family(input_from: &str) -> Option<&'static str> {
+    match input_from.to_ascii_lowercase().as_str() {
+        "mei" | "mei-hum" | "mei-pb-serialized" => Some("mei"),
+        "musicxml" | "musicxml-hum" => Some("musicxml"),
+        "humdrum" => Some("humdrum"),
+        "pae" => Some("pae"),
+        "abc" => Some("abc"),
+        _ => None,
+    }
+}
+
+/// Options that only affect rendering or export, or only take effect on the
+/// next load, in addition to the `svg*`, `midi*` and `output*` options.
+const NON_LAYOUT_OPTIONS: &[&str] = &[
+    "appXPathQuery",
+    "choiceXPathQuery",
+    "expand",
+    "inputFrom",
+    "loadSelectedMdivOnly",
+    "mdivAll",
+    "mdivXPathQuery",
+    "removeIds",
+    "substXPathQuery",
+    "transpose",
+    "transposeMdiv",
+    "transposeSelectedOnly",
+    "transposeToSoundingPitch",
+    "xmlIdChecksum",
+    "xmlIdSeed",
+];
+
+/// Returns whether a new value for the Verovio option `key` can change the
+/// layout of a loaded document.
+fn affects_layout(key: &str) -> bool {
+    !(key.starts_with("svg")
+        || key.starts_with("midi")
+        || key.starts_with("output")
+        || NON_LAYOUT_OPTIONS.contains(&key))
+}
+
+/// Computes the 64-bit FNV-1a hash of the concatenation of `parts`.
+fn fnv1a(parts: &[&[u8]]) -> u64 {
+    parts
+        .iter()
+        .flat_map(|part| part.iter())
+        .fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+        })
+}
+
+/// Returns the error for a path that is not valid UTF-8.
+fn invalid_path(context: &str) -> Error {
+    Error::InvalidUtf8 {
+        context: context.to_string(),
+    }
+}
+
+/// Returns the error for a render request on a toolkit without resources.
+fn resources_required() -> Error {
+    Error::InitializationError("rendering requires resources; use Toolkit::new()".into())
+}
+
+/// Keeps the timemap entries that start or stop an element drawn in `svg`.
+///
+/// Returns the entries as a JSON array together with the time between the
+/// first and last of them.
+fn page_timemap(timemap: &str, svg: &str) -> Result<(String, f64)> {
+    let doc = crate::svg::parse(svg)?;
+    let ids: std::collections::HashSet<&str> = doc
+        .descendants()
+        .filter_map(|node| node.attribute("id"))
+        .collect();
+
+    let entries: Vec<serde_json::Value> =
+        serde_json::from_str(timemap).map_err(|e| Error::RenderError(e.to_string()))?;
+    let on_page: Vec<serde_json::Value> = entries
+        .into_iter()
+        .filter(|entry| {
+            ["on", "off", "restsOn", "restsOff"].iter().any(|key| {
+                entry[*key].as_array().is_some_and(|list| {
+                    list.iter()
+                        .filter_map(serde_json::Value::as_str)
+                        .any(|id| ids.contains(id))
+                })
+            })
+        })
+        .collect();
+
+    let stamps = on_page.iter().filter_map(|entry| entry["tstamp"].as_f64());
+    let duration_ms = match stamps.clone().reduce(f64::min) {
+        Some(first) => stamps.fold(first, f64::max) - first,
+        None => 0.0,
+    };
+
+    let json = serde_json::to_string(&on_page).map_err(|e| Error::RenderError(e.to_string()))?;
+    Ok((json, duration_ms))
+}
+
+/// Returns the measures of a timemap exported with `includeMeasures`, as
+/// `(start, end, id)` in time order.
+///
+/// A measure lasts from its `measureOn` entry to the next one; the last
+/// measure lasts until the last entry of the timemap.
+fn measure_spans(timemap: &str) -> Result<Vec<(f64, f64, String)>> {
+    let entries: Vec<serde_json::Value> =
+        serde_json::from_str(timemap).map_err(|e| Error::RenderError(e.to_string()))?;
+    let score_end = entries
+        .iter()
+        .filter_map(|entry| entry["tstamp"].as_f64())
+        .fold(0.0, f64::max);
+    let mut measures: Vec<(f64, &str)> = entries
+        .iter()
+        .filter_map(|entry| Some((entry["tstamp"].as_f64()?, entry["measureOn"].as_str()?)))
+        .collect();
+    measures.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let ends = measures
+        .iter()
+        .skip(1)
+        .map(|(start, _)| *start)
+        .chain(std::iter::once(score_end));
+    Ok(measures
+        .iter()
+        .zip(ends)
+        .map(|((start, id), end)| (*start, end, id.to_string()))
+        .collect())
+}
+
+/// Returns the ids of the first and last measures sounding in the time window
+/// `start_ms..end_ms`, from a timemap exported with `includeMeasures`.
+///
+/// Returns `None` if no measure overlaps the window.
+fn window_measures(timemap: &str, start_ms: f64, end_ms: f64) -> Result<Option<(String, String)>> {
+    let mut overlapping = measure_spans(timemap)?
+        .into_iter()
+        .filter(|(start, end, _)| *start < end_ms && *end > start_ms)
+        .map(|(_, _, id)| id);
+
+    Ok(overlapping.next().map(|first| {
+        let last = overlapping.next_back().unwrap_or_else(|| first.clone());
+        (first, last)
+    }))
+}
+
+/// Returns the id of the measure sounding at `millis`, from a timemap
+/// exported with `includeMeasures`.
+///
+/// Returns `None` if `millis` is at or after the end of the score.
+fn measure_at(timemap: &str, millis: f64) -> Result<Option<String>> {
+    Ok(measure_spans(timemap)?
+        .into_iter()
+        .find(|(start, end, _)| *start <= millis && millis < *end)
+        .map(|(_, _, id)| id))
+}
+
 /// A safe wrapper around the Verovio toolkit.
 ///
 /// This struct provides a safe, idiomatic interface to the Verovio music engraving library.
@@ -176,6 +522,83 @@ pub struct Toolkit {
     /// Kept alive for the lifetime of the toolkit.
     #[cfg(feature = "bundled-data")]
     _temp_dir: Option<TempDir>,
+
+    /// Incremented by every method that may change the loaded document,
+    /// its layout or the options.
+    generation: u64,
+
+    /// Cached exports, valid for a single generation.
+    export_cache: RefCell<ExportCache>,
+
+    /// Called with the page count after every load and relayout.
+    relayout_hook: Option<Box<dyn FnMut(u32) + Send>>,
+
+    /// Whether [`Toolkit::load_data`] rejects data contradicting an explicit
+    /// input format.
+    check_input_format: bool,
+
+    /// Whether loads keep a copy of their data in `source`.
+    retain_source: bool,
+
+    /// The data last loaded, when `retain_source` was enabled at the time.
+    source: Option<Vec<u8>>,
+
+    /// Whether the last load succeeded, so that a document without pages
+    /// can be told apart from no document.
+    loaded: bool,
+
+    /// The language applied to the text of rendered SVG, from
+    /// [`Options::text_lang`].
+    text_lang: Option<String>,
+
+    /// The seed last passed to [`Toolkit::reset_xml_id_seed`], replayed by
+    /// parallel render workers.
+    xml_id_seed: i32,
+
+    /// The last music font Verovio could load from the resources, or `None`
+    /// while it uses its default font.
+    applied_font: RefCell<Option<String>>,
+}
+
+/// Writes options back to a [`Toolkit`] when dropped, for
+/// [`Toolkit::with_temporary_options`].
+struct RestoreOptions<'a> {
+    toolkit: &'a Toolkit,
+    previous: String,
+}
+
+impl Drop for RestoreOptions<'_> {
+    fn drop(&mut self) {
+        let _ = self.toolkit.set_options_json(&self.previous);
+    }
+}
+
+/// The settings a scratch [`Toolkit`] needs to render like the toolkit they
+/// were taken from: the resources, the Verovio options and the Rust-side
+/// render state. Unlike a `Toolkit` reference, they can be shared with worker
+/// threads.
+struct ScratchSettings {
+    resource_path: String,
+    options: String,
+    text_lang: Option<String>,
+}
+
+impl ScratchSettings {
+    /// Creates a toolkit with these settings and no document loaded.
+    fn toolkit(&self) -> Result<Toolkit> {
+        let mut toolkit = Toolkit::with_resource_path(Path::new(&self.resource_path))?;
+        toolkit.set_options_json(&self.options)?;
+        toolkit.text_lang.clone_from(&self.text_lang);
+        Ok(toolkit)
+    }
+}
+
+/// Exports cached by a [`Toolkit`] for the generation they were produced in.
+#[derive(Debug, Default)]
+struct ExportCache {
+    generation: u64,
+    humdrum: Option<String>,
+    mei: Option<String>,
 }
 
 // SAFETY: Toolkit can be sent between threads because:
@@ -235,9 +658,9 @@ impl Toolkit {
         let temp_dir = verovioxide_data::extract_resources()?;
         let resource_path = temp_dir.path();
 
-        let path_str = resource_path.to_str().ok_or_else(|| {
-            Error::InitializationError("resource path contains invalid UTF-8".into())
-        })?;
+        let path_str = resource_path
+            .to_str()
+            .ok_or_else(|| invalid_path("resource path"))?;
 
         let c_path = CString::new(path_str)?;
 
@@ -253,6 +676,16 @@ impl Toolkit {
         Ok(Self {
             ptr,
             _temp_dir: Some(temp_dir),
+            generation: 0,
+            export_cache: RefCell::default(),
+            relayout_hook: None,
+            check_input_format: true,
+            retain_source: false,
+            source: None,
+            loaded: false,
+            text_lang: None,
+            xml_id_seed: 0,
+            applied_font: RefCell::default(),
         })
     }
 
@@ -281,9 +714,7 @@ impl Toolkit {
     ///     .expect("Failed to create toolkit");
     /// ```
     pub fn with_resource_path(path: &Path) -> Result<Self> {
-        let path_str = path.to_str().ok_or_else(|| {
-            Error::InitializationError("resource path contains invalid UTF-8".into())
-        })?;
+        let path_str = path.to_str().ok_or_else(|| invalid_path("resource path"))?;
 
         let c_path = CString::new(path_str)?;
 
@@ -300,13 +731,25 @@ impl Toolkit {
             ptr,
             #[cfg(feature = "bundled-data")]
             _temp_dir: None,
+            generation: 0,
+            export_cache: RefCell::default(),
+            relayout_hook: None,
+            check_input_format: true,
+            retain_source: false,
+            source: None,
+            loaded: false,
+            text_lang: None,
+            xml_id_seed: 0,
+            applied_font: RefCell::default(),
         })
     }
 
     /// Creates a new toolkit without loading any resources.
     ///
     /// This is useful for operations that don't require font resources, such as
-    /// converting between formats or extracting metadata.
+    /// converting between formats or extracting metadata. Rendering to SVG
+    /// needs fonts, so render calls on such a toolkit return
+    /// [`Error::InitializationError`].
     ///
     /// # Errors
     ///
@@ -334,6 +777,16 @@ impl Toolkit {
             ptr,
             #[cfg(feature = "bundled-data")]
             _temp_dir: None,
+            generation: 0,
+            export_cache: RefCell::default(),
+            relayout_hook: None,
+            check_input_format: true,
+            retain_source: false,
+            source: None,
+            loaded: false,
+            text_lang: None,
+            xml_id_seed: 0,
+            applied_font: RefCell::default(),
         })
     }
 
@@ -404,9 +857,20 @@ impl Toolkit {
     ///
     /// * `data` - The music data as a string
     ///
+    /// A leading UTF-8 byte order mark is stripped before the data is passed to
+    /// Verovio, whose format detection would otherwise fail on it. No other
+    /// leading content is removed.
+    ///
+    /// When an input format was set explicitly with
+    /// [`set_input_from`](Self::set_input_from) and the data clearly looks
+    /// like another format (see [`detect_format`](Self::detect_format)), the
+    /// data is rejected before Verovio parses it. Disable this check with
+    /// [`set_input_format_check`](Self::set_input_format_check).
+    ///
     /// # Errors
     ///
     /// Returns an error if:
+    /// - The data contradicts the explicit input format
     /// - The data is malformed
     /// - The format is not recognized
     ///
@@ -425,12 +889,23 @@ impl Toolkit {
     ///
     /// - [`load_file`](Self::load_file) - Load music data from a file
     pub fn load_data(&mut self, data: &str) -> Result<()> {
+        let data = data.strip_prefix('\u{feff}').unwrap_or(data);
+        if self.check_input_format {
+            self.check_input_format(data)?;
+        }
         let c_data = CString::new(data)?;
 
+        self.bump_generation();
+        self.source = None;
+        self.loaded = false;
+
         // SAFETY: ptr is valid, c_data is a valid null-terminated string
         let success = unsafe { verovioxide_sys::vrvToolkit_loadData(self.ptr, c_data.as_ptr()) };
 
         if success {
+            self.retain(data.as_bytes());
+            self.loaded = true;
+            self.notify_relayout();
             Ok(())
         } else {
             Err(Error::LoadError(
@@ -439,6 +914,82 @@ impl Toolkit {
         }
     }
 
+    /// Loads music data from bytes, honoring the encoding named in an XML
+    /// declaration.
+    ///
+    /// Legacy MusicXML is often encoded as ISO-8859-1 or Windows-1252, which
+    /// [`load_data`](Self::load_data) cannot accept. This method reads the
+    /// `encoding` of the `<?xml ...?>` declaration and, with the `encoding`
+    /// feature, transcodes the data to UTF-8 before loading it. Any encoding
+    /// label known to `encoding_rs` is accepted; as in browsers, `ISO-8859-1`
+    /// is decoded as its superset Windows-1252. Data without a declaration
+    /// (ABC, Humdrum, ...) or declared as UTF-8 is loaded as UTF-8.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The music data
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The data is declared as UTF-8 (or not declared) but is not valid UTF-8
+    /// - The declared encoding is unknown, or the `encoding` feature is disabled
+    ///   and the declared encoding is not UTF-8
+    /// - Loading fails, as for [`load_data`](Self::load_data)
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    ///
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// let bytes = std::fs::read("legacy.musicxml").expect("Failed to read file");
+    /// toolkit.load_data_bytes(&bytes).expect("Failed to load data");
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`load_data`](Self::load_data) - Load music data from a string
+    pub fn load_data_bytes(&mut self, data: &[u8]) -> Result<()> {
+        let data = decode_data(data)?;
+        self.load_data(&data)
+    }
+
+    /// Loads MusicXML data in either the partwise or the timewise layout.
+    ///
+    /// Verovio only reads `score-partwise` MusicXML. A `score-timewise`
+    /// document, where parts are nested in measures instead of the other way
+    /// round, is reordered into a partwise document before loading; other data
+    /// is passed to [`load_data`](Self::load_data) unchanged.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The MusicXML document
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - A timewise document is not well-formed XML
+    /// - Loading fails, as for [`load_data`](Self::load_data)
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    ///
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// let xml = std::fs::read_to_string("timewise.musicxml").expect("Failed to read file");
+    /// toolkit.load_musicxml(&xml).expect("Failed to load MusicXML");
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`load_data`](Self::load_data) - Load music data in any supported format
+    pub fn load_musicxml(&mut self, data: &str) -> Result<()> {
+        let data = crate::musicxml::to_partwise(data)?;
+        self.load_data(&data)
+    }
+
     /// Loads music data from a file.
     ///
     /// The file format is auto-detected based on content.
@@ -480,16 +1031,25 @@ impl Toolkit {
             return Err(Error::FileNotFound(path.to_path_buf()));
         }
 
-        let path_str = path
-            .to_str()
-            .ok_or_else(|| Error::LoadError("file path contains invalid UTF-8".into()))?;
+        let path_str = path.to_str().ok_or_else(|| invalid_path("file path"))?;
 
         let c_path = CString::new(path_str)?;
 
+        self.bump_generation();
+        self.source = None;
+        self.loaded = false;
+
         // SAFETY: ptr is valid, c_path is a valid null-terminated string
         let success = unsafe { verovioxide_sys::vrvToolkit_loadFile(self.ptr, c_path.as_ptr()) };
 
         if success {
+            self.loaded = true;
+            if self.retain_source {
+                // The document is loaded either way; failing to re-read the
+                // file only leaves no source retained.
+                self.source = std::fs::read(path).ok();
+            }
+            self.notify_relayout();
             Ok(())
         } else {
             Err(Error::LoadError(format!(
@@ -499,6 +1059,45 @@ impl Toolkit {
         }
     }
 
+    /// Loads music data from a reader.
+    ///
+    /// The reader is read to the end and its contents are loaded with
+    /// [`load_data`](Self::load_data), so the format is auto-detected and a
+    /// leading UTF-8 byte order mark is ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - A source of UTF-8 encoded music data
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Reading fails
+    /// - The data is not valid UTF-8
+    /// - The data is malformed
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    ///
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// let file = std::fs::File::open("score.musicxml").expect("Failed to open file");
+    /// toolkit.load_reader(file).expect("Failed to load data");
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`load_data`](Self::load_data) - Load music data from a string
+    pub fn load_reader(&mut self, mut reader: impl std::io::Read) -> Result<()> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        let data = String::from_utf8(bytes).map_err(|_| Error::InvalidUtf8 {
+            context: "reader input".into(),
+        })?;
+        self.load_data(&data)
+    }
+
     // =========================================================================
     // Format Control Functions
     // =========================================================================
@@ -531,6 +1130,7 @@ impl Toolkit {
     /// - [`set_output_to`](Self::set_output_to) - Set output format
     /// - [`load_data`](Self::load_data) - Load music data
     pub fn set_input_from(&mut self, format: &str) -> Result<()> {
+        self.bump_generation();
         let c_format = CString::new(format)?;
 
         // SAFETY: ptr is valid, c_format is a valid null-terminated string
@@ -547,6 +1147,79 @@ impl Toolkit {
         }
     }
 
+    /// Guesses the format of music data from its first characters.
+    ///
+    /// This is a cheap check of the data's opening, not a parse: XML is
+    /// classified by its root element, Humdrum by a leading `**` or `!!`,
+    /// Plaine & Easie by a leading `@`, and ABC by an `X:` header line.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The music data
+    ///
+    /// # Returns
+    ///
+    /// The Verovio input format name (`"mei"`, `"musicxml"`, `"humdrum"`,
+    /// `"pae"` or `"abc"`), or `None` if the data does not clearly look like
+    /// any of them.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use verovioxide::Toolkit;
+    ///
+    /// assert_eq!(Toolkit::detect_format("X:1\nK:C\nCDEF|"), Some("abc"));
+    /// assert_eq!(Toolkit::detect_format("<score-partwise/>"), Some("musicxml"));
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`set_input_from`](Self::set_input_from) - Set the input format
+    #[must_use]
+    pub fn detect_format(data: &str) -> Option<&'static str> {
+        sniff_format(data)
+    }
+
+    /// Enables or disables the input format check of
+    /// [`load_data`](Self::load_data).
+    ///
+    /// The check is enabled by default. Disable it to hand data to Verovio
+    /// as-is when an explicit input format is known to be right even though
+    /// the data looks otherwise, e.g. MEI wrapped in an unusual envelope.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - `false` to load data without checking it against the
+    ///   input format
+    ///
+    /// # See also
+    ///
+    /// - [`set_input_from`](Self::set_input_from) - Set the input format
+    pub fn set_input_format_check(&mut self, enabled: bool) {
+        self.check_input_format = enabled;
+    }
+
+    /// Rejects `data` if it clearly contradicts the explicit input format.
+    fn check_input_format(&self, data: &str) -> Result<()> {
+        let Some(detected) = sniff_format(data) else {
+            return Ok(());
+        };
+        let options: serde_json::Value = serde_json::from_str(&self.get_options())
+            .map_err(|e| Error::OptionsError(e.to_string()))?;
+        let Some(input_from) = options.get("inputFrom").and_then(serde_json::Value::as_str) else {
+            return Ok(());
+        };
+
+        match input_format_family(input_from) {
+            Some(expected) if expected != detected => Err(Error::LoadError(format!(
+                "input format is set to {:?} but the data looks like {}; \
+                 call set_input_from(\"auto\") or disable the check with set_input_format_check(false)",
+                input_from, detected
+            ))),
+            _ => Ok(()),
+        }
+    }
+
     /// Sets the output format.
     ///
     /// This affects the format used by [`render_data`](Self::render_data) and
@@ -575,6 +1248,7 @@ impl Toolkit {
     /// - [`set_input_from`](Self::set_input_from) - Set input format
     /// - [`render_data`](Self::render_data) - Render data with current output format
     pub fn set_output_to(&mut self, format: &str) -> Result<()> {
+        self.bump_generation();
         let c_format = CString::new(format)?;
 
         // SAFETY: ptr is valid, c_format is a valid null-terminated string
@@ -628,6 +1302,9 @@ impl Toolkit {
     /// - [`load_zip_data_buffer`](Self::load_zip_data_buffer) - Load from binary buffer
     /// - [`load_data`](Self::load_data) - Load uncompressed data
     pub fn load_zip_data_base64(&mut self, data: &str) -> Result<()> {
+        self.bump_generation();
+        self.source = None;
+        self.loaded = false;
         let c_data = CString::new(data)?;
 
         // SAFETY: ptr is valid, c_data is a valid null-terminated string
@@ -635,6 +1312,9 @@ impl Toolkit {
             unsafe { verovioxide_sys::vrvToolkit_loadZipDataBase64(self.ptr, c_data.as_ptr()) };
 
         if success {
+            self.retain(data.as_bytes());
+            self.loaded = true;
+            self.notify_relayout();
             Ok(())
         } else {
             Err(Error::LoadError("failed to load ZIP data (base64)".into()))
@@ -673,6 +1353,9 @@ impl Toolkit {
     /// - [`load_zip_data_base64`](Self::load_zip_data_base64) - Load from base64 string
     /// - [`load_file`](Self::load_file) - Load from file path
     pub fn load_zip_data_buffer(&mut self, data: &[u8]) -> Result<()> {
+        self.bump_generation();
+        self.source = None;
+        self.loaded = false;
         // SAFETY: ptr is valid, data.as_ptr() is valid for data.len() bytes
         let success = unsafe {
             verovioxide_sys::vrvToolkit_loadZipDataBuffer(
@@ -683,6 +1366,9 @@ impl Toolkit {
         };
 
         if success {
+            self.retain(data);
+            self.loaded = true;
+            self.notify_relayout();
             Ok(())
         } else {
             Err(Error::LoadError("failed to load ZIP data buffer".into()))
@@ -731,7 +1417,7 @@ impl Toolkit {
         let result_ptr =
             unsafe { verovioxide_sys::vrvToolkit_validatePAE(self.ptr, c_data.as_ptr()) };
 
-        self.ptr_to_string(result_ptr)
+        self.ptr_to_utf8(result_ptr, "PAE validation result")?
             .ok_or_else(|| Error::RenderError("failed to validate PAE".into()))
     }
 
@@ -769,9 +1455,7 @@ impl Toolkit {
             return Err(Error::FileNotFound(path.to_path_buf()));
         }
 
-        let path_str = path
-            .to_str()
-            .ok_or_else(|| Error::RenderError("file path contains invalid UTF-8".into()))?;
+        let path_str = path.to_str().ok_or_else(|| invalid_path("file path"))?;
 
         let c_path = CString::new(path_str)?;
 
@@ -779,9 +1463,10 @@ impl Toolkit {
         let result_ptr =
             unsafe { verovioxide_sys::vrvToolkit_validatePAEFile(self.ptr, c_path.as_ptr()) };
 
-        self.ptr_to_string(result_ptr).ok_or_else(|| {
-            Error::RenderError(format!("failed to validate PAE file: {}", path.display()))
-        })
+        self.ptr_to_utf8(result_ptr, "PAE validation result")?
+            .ok_or_else(|| {
+                Error::RenderError(format!("failed to validate PAE file: {}", path.display()))
+            })
     }
 
     // =========================================================================
@@ -819,6 +1504,7 @@ impl Toolkit {
     /// - [`render_to_svg`](Self::render_to_svg) - Render with selection applied
     /// - [`edit`](Self::edit) - Perform editor actions
     pub fn select(&mut self, selection: &str) -> Result<()> {
+        self.bump_generation();
         let c_selection = CString::new(selection)?;
 
         // SAFETY: ptr is valid, c_selection is a valid null-terminated string
@@ -850,6 +1536,7 @@ impl Toolkit {
     ///
     /// - [`redo_layout`](Self::redo_layout) - Full layout recalculation
     pub fn redo_page_pitch_pos_layout(&mut self) {
+        self.bump_generation();
         // SAFETY: ptr is valid
         unsafe { verovioxide_sys::vrvToolkit_redoPagePitchPosLayout(self.ptr) };
     }
@@ -874,6 +1561,8 @@ impl Toolkit {
     /// // Now newly generated IDs will be deterministic based on this seed
     /// ```
     pub fn reset_xml_id_seed(&mut self, seed: i32) {
+        self.bump_generation();
+        self.xml_id_seed = seed;
         // SAFETY: ptr is valid
         unsafe { verovioxide_sys::vrvToolkit_resetXmlIdSeed(self.ptr, seed) };
     }
@@ -1078,6 +1767,81 @@ impl Toolkit {
         format.render_to_file(self, path.as_ref())
     }
 
+    /// Writes the loaded document in several formats at once.
+    ///
+    /// Each format is written to `dir/<basename>.<extension>`, using the
+    /// extension given by [`OutputFormat::extension`](crate::OutputFormat::extension).
+    /// Paged formats (SVG and PNG) are written like
+    /// [`Svg::all_pages`](crate::Svg::all_pages), to a `dir/<basename>/`
+    /// directory with one `page-001.svg` file per page. `dir` is created if it
+    /// does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - The output directory
+    /// * `basename` - The file name, without extension, shared by all outputs
+    /// * `formats` - The formats to write, in order
+    ///
+    /// # Returns
+    ///
+    /// The paths of all files written, in the order of `formats`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - No data has been loaded
+    /// - `basename` is empty or contains a path separator
+    /// - A file or directory cannot be created
+    /// - Rendering fails
+    ///
+    /// Formats before the failing one have already been written.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::path::Path;
+    /// use verovioxide::{OutputFormat, Toolkit};
+    ///
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// // ... load data ...
+    ///
+    /// let paths = toolkit
+    ///     .export_all(
+    ///         Path::new("out"),
+    ///         "score",
+    ///         &[OutputFormat::Svg, OutputFormat::Mei, OutputFormat::Midi],
+    ///     )
+    ///     .expect("Failed to export");
+    /// for path in paths {
+    ///     println!("wrote {}", path.display());
+    /// }
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`render_to_as`](Self::render_to_as) - Write a single format
+    pub fn export_all(
+        &self,
+        dir: &Path,
+        basename: &str,
+        formats: &[crate::OutputFormat],
+    ) -> Result<Vec<std::path::PathBuf>> {
+        self.check_loaded()?;
+        if basename.is_empty() || basename.contains(['/', '\\']) {
+            return Err(Error::RenderError(format!(
+                "invalid export basename: {:?}",
+                basename
+            )));
+        }
+
+        std::fs::create_dir_all(dir)?;
+        let mut paths = Vec::new();
+        for format in formats {
+            paths.extend(format.export(self, dir, basename)?);
+        }
+        Ok(paths)
+    }
+
     // =========================================================================
     // Unified Query API
     // =========================================================================
@@ -1182,8 +1946,10 @@ impl Toolkit {
     ///
     /// Returns an error if:
     /// - No data has been loaded
+    /// - The loaded document produced no pages, e.g. because it has no content
     /// - The page number is out of range
     /// - Rendering fails
+    /// - The toolkit has no resources ([`Error::InitializationError`])
     ///
     /// # Example
     ///
@@ -1203,25 +1969,69 @@ impl Toolkit {
     /// - [`render_all_pages`](Self::render_all_pages) - Render all pages at once
     /// - [`page_count`](Self::page_count) - Get the total number of pages
     pub fn render_to_svg(&self, page: u32) -> Result<String> {
-        let page_count = self.page_count();
-        if page == 0 || page > page_count {
-            return Err(Error::RenderError(format!(
-                "page {} out of range (document has {} pages)",
-                page, page_count
-            )));
-        }
+        self.check_svg_page(page)?;
 
         // SAFETY: ptr is valid, page number is in range
         let svg_ptr =
             unsafe { verovioxide_sys::vrvToolkit_renderToSVG(self.ptr, page as i32, false) };
 
-        self.ptr_to_string(svg_ptr)
-            .ok_or_else(|| Error::RenderError("failed to render SVG".into()))
+        let svg = self
+            .ptr_to_utf8(svg_ptr, "SVG output")?
+            .ok_or_else(|| Error::RenderError("failed to render SVG".into()))?;
+        self.apply_text_lang(svg)
     }
 
-    /// Renders a page to SVG with XML declaration.
+    /// Renders a page to SVG and reports how long rendering took.
     ///
-    /// Same as [`render_to_svg`](Self::render_to_svg) but includes the XML declaration
+    /// The duration covers only the Verovio render call, not the page range
+    /// check or the conversion of the result into a Rust string. This is a
+    /// lightweight way to surface per-page render cost or spot pathological
+    /// pages.
+    ///
+    /// # Arguments
+    ///
+    /// * `page` - The page number to render (1-based)
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - No data has been loaded
+    /// - The page number is out of range
+    /// - Rendering fails or the output is not valid UTF-8
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    ///
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// // ... load data ...
+    ///
+    /// let (svg, elapsed) = toolkit.render_to_svg_timed(1).expect("Failed to render");
+    /// println!("Rendered {} bytes in {:?}", svg.len(), elapsed);
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`render_to_svg`](Self::render_to_svg) - Render without timing
+    pub fn render_to_svg_timed(&self, page: u32) -> Result<(String, Duration)> {
+        self.check_svg_page(page)?;
+
+        let start = Instant::now();
+        // SAFETY: ptr is valid, page number is in range
+        let svg_ptr =
+            unsafe { verovioxide_sys::vrvToolkit_renderToSVG(self.ptr, page as i32, false) };
+        let elapsed = start.elapsed();
+
+        let svg = self
+            .ptr_to_utf8(svg_ptr, "SVG output")?
+            .ok_or_else(|| Error::RenderError("failed to render SVG".into()))?;
+        Ok((self.apply_text_lang(svg)?, elapsed))
+    }
+
+    /// Renders a page to SVG with XML declaration.
+    ///
+    /// Same as [`render_to_svg`](Self::render_to_svg) but includes the XML declaration
     /// at the start of the SVG output.
     ///
     /// # Arguments
@@ -1237,40 +2047,47 @@ impl Toolkit {
     /// - [`render_to_svg`](Self::render_to_svg) - Render without XML declaration
     /// - [`render_all_pages`](Self::render_all_pages) - Render all pages at once
     pub fn render_to_svg_with_declaration(&self, page: u32) -> Result<String> {
-        let page_count = self.page_count();
-        if page == 0 || page > page_count {
-            return Err(Error::RenderError(format!(
-                "page {} out of range (document has {} pages)",
-                page, page_count
-            )));
-        }
+        self.check_svg_page(page)?;
 
         // SAFETY: ptr is valid, page number is in range
         let svg_ptr =
             unsafe { verovioxide_sys::vrvToolkit_renderToSVG(self.ptr, page as i32, true) };
 
-        self.ptr_to_string(svg_ptr)
-            .ok_or_else(|| Error::RenderError("failed to render SVG".into()))
+        let svg = self
+            .ptr_to_utf8(svg_ptr, "SVG output")?
+            .ok_or_else(|| Error::RenderError("failed to render SVG".into()))?;
+        self.apply_text_lang(svg)
     }
 
-    /// Renders all pages to SVG.
+    /// Marks the text of a rendered SVG with the language set with
+    /// [`Options::text_lang`], if any.
+    fn apply_text_lang(&self, svg: String) -> Result<String> {
+        match &self.text_lang {
+            Some(lang) => crate::svg::set_text_lang(&svg, lang),
+            None => Ok(svg),
+        }
+    }
+
+    /// Renders a page to SVG with all element ids prefixed.
     ///
-    /// # Performance
+    /// Every SVG rendered by Verovio uses the same scheme for element and glyph
+    /// ids, so several scores embedded in one HTML page will collide in the DOM.
+    /// This method prefixes every `id` attribute with `prefix` and rewrites
+    /// internal references (`xlink:href="#..."`, `href="#..."`, `url(#...)`)
+    /// to match, so each SVG stays self-consistent.
     ///
-    /// This method renders pages sequentially. For a document with N pages,
-    /// the total time is approximately N times the single-page render time.
-    /// The method pre-allocates the result vector to avoid reallocations.
+    /// # Arguments
     ///
-    /// For parallel rendering of the same document, you would need to create
-    /// multiple [`Toolkit`] instances, each with its own copy of the loaded
-    /// data. However, for most use cases, sequential rendering is sufficient
-    /// and avoids the overhead of multiple toolkit instances.
+    /// * `page` - The page number (1-based)
+    /// * `prefix` - The prefix to add; it must start with an ASCII letter or `_`
+    ///   and contain only ASCII letters, digits, `-`, `_` and `.`
     ///
     /// # Errors
     ///
     /// Returns an error if:
     /// - No data has been loaded
-    /// - Rendering any page fails
+    /// - The page number is out of range
+    /// - The prefix is not a valid id prefix
     ///
     /// # Example
     ///
@@ -1280,30 +2097,40 @@ impl Toolkit {
     /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
     /// // ... load data ...
     ///
-    /// let pages = toolkit.render_all_pages().expect("Failed to render");
-    /// for (i, svg) in pages.iter().enumerate() {
-    ///     println!("Page {}: {} bytes", i + 1, svg.len());
-    /// }
+    /// let svg = toolkit
+    ///     .render_to_svg_prefixed(1, "score-a-")
+    ///     .expect("Failed to render");
     /// ```
     ///
     /// # See also
     ///
-    /// - [`render_to_svg`](Self::render_to_svg) - Render a single page
-    /// - [`page_count`](Self::page_count) - Get the total number of pages
-    pub fn render_all_pages(&self) -> Result<Vec<String>> {
-        let count = self.page_count();
-        let mut pages = Vec::with_capacity(count as usize);
-
-        for page in 1..=count {
-            pages.push(self.render_to_svg(page)?);
-        }
-
-        Ok(pages)
+    /// - [`render_to_svg`](Self::render_to_svg) - Render without prefixing
+    pub fn render_to_svg_prefixed(&self, page: u32, prefix: &str) -> Result<String> {
+        let svg = self.render_to_svg(page)?;
+        crate::svg::prefix_ids(&svg, prefix)
     }
 
-    /// Returns the number of pages in the loaded document.
+    /// Renders a page to SVG without the elements of the given types.
     ///
-    /// Returns 0 if no document is loaded.
+    /// Verovio draws each element as a `<g>` group whose `class` is the MEI
+    /// element name, such as `dynam` for dynamics or `verse` for a line of
+    /// lyrics. Every group with one of `element_types` among its classes is
+    /// removed from the rendered page, together with its content. The layout
+    /// is unchanged, so the space the hidden elements took is left blank.
+    ///
+    /// # Arguments
+    ///
+    /// * `page` - The page number (1-based)
+    /// * `element_types` - The classes of the groups to remove, e.g.
+    ///   `["dynam", "verse"]`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - An element type is empty or contains characters other than ASCII
+    ///   letters, digits, `-` and `_`
+    /// - No data has been loaded
+    /// - The page number is out of range
     ///
     /// # Example
     ///
@@ -1313,157 +2140,139 @@ impl Toolkit {
     /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
     /// // ... load data ...
     ///
-    /// println!("Document has {} pages", toolkit.page_count());
+    /// let svg = toolkit
+    ///     .render_to_svg_hiding(1, &["dynam", "verse"])
+    ///     .expect("Failed to render");
     /// ```
     ///
     /// # See also
     ///
-    /// - [`render_to_svg`](Self::render_to_svg) - Render a specific page
-    /// - [`render_all_pages`](Self::render_all_pages) - Render all pages at once
-    #[must_use]
-    pub fn page_count(&self) -> u32 {
-        // SAFETY: ptr is valid
-        let count = unsafe { verovioxide_sys::vrvToolkit_getPageCount(self.ptr) };
-        count.max(0) as u32
+    /// - [`render_to_svg`](Self::render_to_svg) - Render every element
+    pub fn render_to_svg_hiding(&self, page: u32, element_types: &[&str]) -> Result<String> {
+        crate::svg::check_classes(element_types)?;
+        let svg = self.render_to_svg(page)?;
+        crate::svg::remove_groups(&svg, element_types)
     }
 
-    /// Sets rendering options.
+    /// Renders a page to SVG with `data-*` attributes on the root `<svg>`.
     ///
-    /// Options are merged with existing options. To reset to defaults, use
-    /// [`reset_options()`](Self::reset_options) first.
-    ///
-    /// # Performance
-    ///
-    /// Setting options is a lightweight operation that only stores configuration
-    /// values. However, if a document is already loaded, certain option changes
-    /// (such as page dimensions, margins, or break modes) will require a layout
-    /// recalculation on the next render. For best performance when experimenting
-    /// with different options, set all desired options before loading data, or
-    /// call [`redo_layout`](Self::redo_layout) explicitly after changing layout-
-    /// affecting options.
+    /// Frontend frameworks often look for their own `data-` hooks on the
+    /// elements they manage. The attributes are added to the parsed root
+    /// element, replacing existing attributes of the same name, with values
+    /// escaped as needed. Unlike Verovio's `svgAdditionalAttribute` option,
+    /// which copies MEI attributes, this sets arbitrary values.
     ///
     /// # Arguments
     ///
-    /// * `options` - The rendering options to set
+    /// * `page` - The page number (1-based)
+    /// * `attrs` - `(name, value)` pairs; names must start with `data-`
     ///
     /// # Errors
     ///
     /// Returns an error if:
-    /// - JSON serialization fails
-    /// - Option values are invalid
+    /// - A name does not start with `data-` followed by ASCII letters,
+    ///   digits, `-`, `_` or `.`
+    /// - No data has been loaded
+    /// - The page number is out of range
     ///
     /// # Example
     ///
     /// ```no_run
-    /// use verovioxide::{Toolkit, Options};
+    /// use verovioxide::Toolkit;
     ///
     /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// // ... load data ...
     ///
-    /// let options = Options::builder()
-    ///     .scale(80)
-    ///     .adjust_page_height(true)
-    ///     .build();
-    ///
-    /// toolkit.set_options(&options).expect("Failed to set options");
+    /// let svg = toolkit
+    ///     .render_to_svg_with_data_attrs(1, &[("data-score", "bach-bwv-846")])
+    ///     .expect("Failed to render");
     /// ```
     ///
     /// # See also
     ///
-    /// - [`get_options`](Self::get_options) - Get current options as JSON
-    /// - [`reset_options`](Self::reset_options) - Reset to default options
-    /// - [`get_default_options`](Self::get_default_options) - Get default options as JSON
-    /// - [`Options`] - The options type
-    pub fn set_options(&mut self, options: &Options) -> Result<()> {
-        let json = options
-            .to_json()
-            .map_err(|e| Error::OptionsError(e.to_string()))?;
-
-        let c_json = CString::new(json)?;
-
-        // SAFETY: ptr is valid, c_json is a valid null-terminated string
-        let success = unsafe { verovioxide_sys::vrvToolkit_setOptions(self.ptr, c_json.as_ptr()) };
-
-        if success {
-            Ok(())
-        } else {
-            Err(Error::OptionsError("failed to set options".into()))
-        }
+    /// - [`render_to_svg_with_element_data_attrs`](Self::render_to_svg_with_element_data_attrs) - Set attributes on elements
+    pub fn render_to_svg_with_data_attrs(
+        &self,
+        page: u32,
+        attrs: &[(&str, &str)],
+    ) -> Result<String> {
+        crate::svg::check_data_attribute_names(attrs)?;
+        let svg = self.render_to_svg(page)?;
+        crate::svg::set_data_attributes(&svg, None, attrs)
     }
 
-    /// Gets the current options as a JSON string.
+    /// Renders a page to SVG with `data-*` attributes on every element of a
+    /// type.
     ///
-    /// # Example
+    /// Like [`render_to_svg_with_data_attrs`](Self::render_to_svg_with_data_attrs),
+    /// but the attributes are set on every `<g>` group with `element_type`
+    /// among its classes, such as `note` or `measure`.
     ///
-    /// ```no_run
-    /// use verovioxide::Toolkit;
+    /// # Arguments
     ///
-    /// let toolkit = Toolkit::new().expect("Failed to create toolkit");
-    /// let options_json = toolkit.get_options();
-    /// println!("Current options: {}", options_json);
-    /// ```
+    /// * `page` - The page number (1-based)
+    /// * `element_type` - The class of the groups to annotate, e.g. `note`
+    /// * `attrs` - `(name, value)` pairs; names must start with `data-`
     ///
-    /// # See also
+    /// # Errors
     ///
-    /// - [`set_options`](Self::set_options) - Set rendering options
-    /// - [`reset_options`](Self::reset_options) - Reset to default options
-    /// - [`get_default_options`](Self::get_default_options) - Get default options as JSON
-    /// - [`get_available_options`](Self::get_available_options) - Get all available options
-    #[must_use]
-    pub fn get_options(&self) -> String {
-        // SAFETY: ptr is valid
-        let options_ptr = unsafe { verovioxide_sys::vrvToolkit_getOptions(self.ptr) };
-        self.ptr_to_string(options_ptr).unwrap_or_default()
-    }
-
-    /// Gets the default options as a JSON string.
+    /// Returns an error if:
+    /// - The element type is empty or contains characters other than ASCII
+    ///   letters, digits, `-` and `_`
+    /// - A name does not start with `data-` followed by ASCII letters,
+    ///   digits, `-`, `_` or `.`
+    /// - No data has been loaded
+    /// - The page number is out of range
     ///
     /// # Example
     ///
     /// ```no_run
     /// use verovioxide::Toolkit;
     ///
-    /// let toolkit = Toolkit::new().expect("Failed to create toolkit");
-    /// let defaults = toolkit.get_default_options();
-    /// println!("Default options: {}", defaults);
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// // ... load data ...
+    ///
+    /// let svg = toolkit
+    ///     .render_to_svg_with_element_data_attrs(1, "note", &[("data-action", "play")])
+    ///     .expect("Failed to render");
     /// ```
     ///
     /// # See also
     ///
-    /// - [`set_options`](Self::set_options) - Set rendering options
-    /// - [`get_options`](Self::get_options) - Get current options as JSON
-    /// - [`reset_options`](Self::reset_options) - Reset to default options
-    #[must_use]
-    pub fn get_default_options(&self) -> String {
-        // SAFETY: ptr is valid
-        let options_ptr = unsafe { verovioxide_sys::vrvToolkit_getDefaultOptions(self.ptr) };
-        self.ptr_to_string(options_ptr).unwrap_or_default()
+    /// - [`render_to_svg_with_data_attrs`](Self::render_to_svg_with_data_attrs) - Set attributes on the root
+    pub fn render_to_svg_with_element_data_attrs(
+        &self,
+        page: u32,
+        element_type: &str,
+        attrs: &[(&str, &str)],
+    ) -> Result<String> {
+        crate::svg::check_classes(&[element_type])?;
+        crate::svg::check_data_attribute_names(attrs)?;
+        let svg = self.render_to_svg(page)?;
+        crate::svg::set_data_attributes(&svg, Some(element_type), attrs)
     }
 
-    /// Gets all available options and their descriptions as a JSON string.
+    /// Renders a page to SVG with all element ids prefixed, and returns the
+    /// mapping from original to prefixed ids.
     ///
-    /// # Example
+    /// The SVG is the same as produced by
+    /// [`render_to_svg_prefixed`](Self::render_to_svg_prefixed). The map has an
+    /// entry for every `id` in the page, including glyph definitions, so event
+    /// handlers registered for MEI ids can be attached to the prefixed
+    /// elements.
     ///
-    /// ```no_run
-    /// use verovioxide::Toolkit;
+    /// # Arguments
     ///
-    /// let toolkit = Toolkit::new().expect("Failed to create toolkit");
-    /// let available = toolkit.get_available_options();
-    /// println!("Available options: {}", available);
-    /// ```
+    /// * `page` - The page number (1-based)
+    /// * `prefix` - The prefix to add; see
+    ///   [`render_to_svg_prefixed`](Self::render_to_svg_prefixed) for the rules
     ///
-    /// # See also
+    /// # Errors
     ///
-    /// - [`set_options`](Self::set_options) - Set rendering options
-    /// - [`get_options`](Self::get_options) - Get current options as JSON
-    /// - [`get_default_options`](Self::get_default_options) - Get default options as JSON
-    #[must_use]
-    pub fn get_available_options(&self) -> String {
-        // SAFETY: ptr is valid
-        let options_ptr = unsafe { verovioxide_sys::vrvToolkit_getAvailableOptions(self.ptr) };
-        self.ptr_to_string(options_ptr).unwrap_or_default()
-    }
-
-    /// Resets all options to their default values.
+    /// Returns an error if:
+    /// - No data has been loaded
+    /// - The page number is out of range
+    /// - The prefix is not a valid id prefix
     ///
     /// # Example
     ///
@@ -1471,42 +2280,48 @@ impl Toolkit {
     /// use verovioxide::Toolkit;
     ///
     /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
-    /// toolkit.reset_options();
+    /// // ... load data ...
+    ///
+    /// let (svg, ids) = toolkit
+    ///     .render_to_svg_prefixed_with_map(1, "score-a-")
+    ///     .expect("Failed to render");
+    /// if let Some(prefixed) = ids.get("note-1") {
+    ///     println!("note-1 is now {}", prefixed);
+    /// }
     /// ```
     ///
     /// # See also
     ///
-    /// - [`set_options`](Self::set_options) - Set rendering options
-    /// - [`get_options`](Self::get_options) - Get current options as JSON
-    /// - [`get_default_options`](Self::get_default_options) - Get default options as JSON
-    pub fn reset_options(&mut self) {
-        // SAFETY: ptr is valid
-        unsafe { verovioxide_sys::vrvToolkit_resetOptions(self.ptr) };
+    /// - [`render_to_svg_prefixed`](Self::render_to_svg_prefixed) - Render without the map
+    pub fn render_to_svg_prefixed_with_map(
+        &self,
+        page: u32,
+        prefix: &str,
+    ) -> Result<(String, HashMap<String, String>)> {
+        let svg = self.render_to_svg(page)?;
+        crate::svg::prefix_ids_mapped(&svg, prefix)
     }
 
-    /// Returns the Verovio version string.
+    /// Renders a page to SVG with some elements colored.
     ///
-    /// # Example
+    /// Each pair in `colors` is `(element_id, css_color)`. After rendering, the
+    /// `fill` and `stroke` attributes of the matching SVG groups are set to the
+    /// color, which tints everything drawn inside them unless a child sets its
+    /// own color. The SVG is edited structurally, so it stays well-formed. Ids
+    /// that are not on the page are ignored; when an id is listed twice, the
+    /// last color wins.
     ///
-    /// ```no_run
-    /// use verovioxide::Toolkit;
+    /// # Arguments
     ///
-    /// let toolkit = Toolkit::new().expect("Failed to create toolkit");
-    /// println!("Verovio version: {}", toolkit.version());
-    /// ```
-    #[must_use]
-    pub fn version(&self) -> String {
-        // SAFETY: ptr is valid
-        let version_ptr = unsafe { verovioxide_sys::vrvToolkit_getVersion(self.ptr) };
-        self.ptr_to_string(version_ptr)
-            .unwrap_or_else(|| "unknown".to_string())
-    }
-
-    /// Returns the log output from Verovio.
+    /// * `page` - The page number (1-based)
+    /// * `colors` - Pairs of element id and CSS color (e.g. `"red"`, `"#c00"`)
     ///
-    /// Log output is only available if logging to buffer was enabled before
-    /// loading data. Use [`enable_log_to_buffer()`](Self::enable_log_to_buffer)
-    /// to enable it.
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - No data has been loaded
+    /// - The page number is out of range
+    /// - A color is empty or contains `"`, `<`, `>` or `&`
     ///
     /// # Example
     ///
@@ -1514,23 +2329,44 @@ impl Toolkit {
     /// use verovioxide::Toolkit;
     ///
     /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
-    /// Toolkit::enable_log_to_buffer(true);
     /// // ... load data ...
-    /// let log = toolkit.get_log();
-    /// println!("Verovio log: {}", log);
+    ///
+    /// let svg = toolkit
+    ///     .render_page_colored(1, &[("note-1", "red"), ("note-2", "#1e90ff")])
+    ///     .expect("Failed to render");
     /// ```
-    #[must_use]
-    pub fn get_log(&self) -> String {
-        // SAFETY: ptr is valid
-        let log_ptr = unsafe { verovioxide_sys::vrvToolkit_getLog(self.ptr) };
-        self.ptr_to_string(log_ptr).unwrap_or_default()
+    ///
+    /// # See also
+    ///
+    /// - [`render_to_svg`](Self::render_to_svg) - Render without coloring
+    pub fn render_page_colored(&self, page: u32, colors: &[(&str, &str)]) -> Result<String> {
+        let svg = self.render_to_svg(page)?;
+        crate::svg::color_elements(&svg, colors)
     }
 
-    /// Exports the loaded document as MEI.
+    /// Renders a page to SVG with each staff in its own color, as used to
+    /// color-code instruments in a conductor's score.
+    ///
+    /// Each pair in `staff_colors` is `(staff_number, css_color)`, where the
+    /// staff number is the `@n` of the staff in the document. The page is
+    /// rendered with `staff@n` added to the `svgAdditionalAttribute` option
+    /// for this render only, so every staff group carries a `data-n`
+    /// attribute; the groups of the listed staves then get the color as
+    /// `fill` and `stroke`, as in [`render_page_colored`](Self::render_page_colored).
+    /// When a staff is listed twice, the last color wins.
+    ///
+    /// # Arguments
+    ///
+    /// * `page` - The page number (1-based)
+    /// * `staff_colors` - Pairs of staff number and CSS color
     ///
     /// # Errors
     ///
-    /// Returns an error if no document is loaded or export fails.
+    /// Returns an error if:
+    /// - No data has been loaded
+    /// - The page number is out of range
+    /// - A staff number does not occur on the page
+    /// - A color is empty or contains `"`, `<`, `>` or `&`
     ///
     /// # Example
     ///
@@ -1538,211 +2374,284 @@ impl Toolkit {
     /// use verovioxide::Toolkit;
     ///
     /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
-    /// // ... load MusicXML or other format ...
+    /// // ... load a score ...
     ///
-    /// let mei = toolkit.get_mei().expect("Failed to export MEI");
-    /// println!("{}", mei);
+    /// let svg = toolkit
+    ///     .render_page_staff_colors(1, &[(1, "#b22222"), (2, "#1e90ff")])
+    ///     .expect("Failed to render");
     /// ```
     ///
     /// # See also
     ///
-    /// - [`get_mei_with_options`](Self::get_mei_with_options) - Export with custom options
-    /// - [`get_humdrum`](Self::get_humdrum) - Export as Humdrum
-    /// - [`render_to_pae`](Self::render_to_pae) - Export as Plaine & Easie
-    /// - [`render_to_midi`](Self::render_to_midi) - Export as MIDI
-    pub fn get_mei(&self) -> Result<String> {
-        self.get_mei_with_options("{}")
+    /// - [`render_page_colored`](Self::render_page_colored) - Color elements by id
+    pub fn render_page_staff_colors(
+        &self,
+        page: u32,
+        staff_colors: &[(u32, &str)],
+    ) -> Result<String> {
+        let current: serde_json::Value = serde_json::from_str(&self.get_options())
+            .map_err(|e| Error::OptionsError(e.to_string()))?;
+        let mut attributes: Vec<serde_json::Value> = current["svgAdditionalAttribute"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default();
+        if !attributes.iter().any(|value| value == "staff@n") {
+            attributes.push("staff@n".into());
+        }
+        let options = serde_json::json!({ "svgAdditionalAttribute": attributes }).to_string();
+        let svg =
+            self.with_temporary_options(&options, |toolkit| toolkit.render_to_svg(page))??;
+
+        let staves = crate::svg::staff_groups(&svg)?;
+        let mut colors = Vec::new();
+        for &(n, color) in staff_colors {
+            let before = colors.len();
+            colors.extend(
+                staves
+                    .iter()
+                    .filter(|(staff, _)| *staff == n)
+                    .map(|(_, id)| (id.as_str(), color)),
+            );
+            if colors.len() == before {
+                return Err(Error::RenderError(format!(
+                    "no staff {} on page {}",
+                    n, page
+                )));
+            }
+        }
+        crate::svg::color_elements(&svg, &colors)
     }
 
-    /// Exports the loaded document as MEI with options.
+    /// Renders a page with a CSS class added to a measure, given its number.
+    ///
+    /// The measure number is the measure's `@n` in the exported MEI; measures
+    /// without `@n` are numbered by their position in the document, starting
+    /// at 1. The class is appended to the `class` attribute of the measure's
+    /// group, so it can be styled with CSS such as
+    /// `g.measure.highlight { fill: crimson; }`.
     ///
     /// # Arguments
     ///
-    /// * `options` - JSON string with MEI export options
+    /// * `page` - The page number (1-based)
+    /// * `measure_no` - The measure number
+    /// * `css_class` - The class to add
     ///
     /// # Errors
     ///
-    /// Returns an error if no document is loaded or export fails.
+    /// Returns an error if:
+    /// - No data has been loaded
+    /// - No measure has the number, or it is not on `page`
+    /// - The class is empty or contains `"`, `<`, `>` or `&`
+    /// - Rendering fails
     ///
-    /// # See also
+    /// # Example
     ///
-    /// - [`get_mei`](Self::get_mei) - Export with default options
-    pub fn get_mei_with_options(&self, options: &str) -> Result<String> {
-        let c_options = CString::new(options)?;
-
-        // SAFETY: ptr is valid, c_options is a valid null-terminated string
-        let mei_ptr = unsafe { verovioxide_sys::vrvToolkit_getMEI(self.ptr, c_options.as_ptr()) };
-
-        self.ptr_to_string(mei_ptr)
-            .ok_or_else(|| Error::RenderError("failed to export MEI".into()))
-    }
-
-    /// Exports the loaded document as Humdrum.
+    /// ```no_run
+    /// use verovioxide::Toolkit;
     ///
-    /// # Errors
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// // ... load data ...
     ///
-    /// Returns an error if no document is loaded or export fails.
+    /// let svg = toolkit
+    ///     .highlight_measure_number(1, 4, "highlight")
+    ///     .expect("Failed to render");
+    /// ```
     ///
     /// # See also
     ///
-    /// - [`get_mei`](Self::get_mei) - Export as MEI
-    /// - [`render_to_pae`](Self::render_to_pae) - Export as Plaine & Easie
-    /// - [`render_to_midi`](Self::render_to_midi) - Export as MIDI
-    pub fn get_humdrum(&self) -> Result<String> {
-        // SAFETY: ptr is valid
-        let humdrum_ptr = unsafe { verovioxide_sys::vrvToolkit_getHumdrum(self.ptr) };
+    /// - [`measure_page_index`](Self::measure_page_index) - Pages of all measures
+    /// - [`render_page_colored`](Self::render_page_colored) - Color elements by id
+    pub fn highlight_measure_number(
+        &self,
+        page: u32,
+        measure_no: u32,
+        css_class: &str,
+    ) -> Result<String> {
+        self.check_loaded()?;
+
+        let id = crate::mei::measure_id_for_number(&self.get_mei()?, measure_no)?
+            .ok_or_else(|| Error::RenderError(format!("measure not found: {}", measure_no)))?;
+        let measure_page = self.get_page_with_element(&id)?;
+        if measure_page != page {
+            return Err(Error::RenderError(format!(
+                "measure {} is on page {}, not page {}",
+                measure_no, measure_page, page
+            )));
+        }
 
-        self.ptr_to_string(humdrum_ptr)
-            .ok_or_else(|| Error::RenderError("failed to export Humdrum".into()))
+        crate::svg::add_class(&self.render_to_svg(page)?, &id, css_class)
     }
 
-    // =========================================================================
-    // Conversion Functions
-    // =========================================================================
-
-    /// Converts Humdrum data to processed Humdrum.
+    /// Renders a page to SVG with metadata for screen readers.
     ///
-    /// This method processes Humdrum data through Verovio's internal pipeline,
-    /// which can normalize and enhance the data.
+    /// The root `<svg>` gets `role="img"` and an `aria-labelledby` pointing at
+    /// a `<title>` and a `<desc>` inserted as its first children. The title is
+    /// the first title in the MEI header (`<fileDesc>/<titleStmt>`), or
+    /// "Music notation" if the document has none; the description gives the
+    /// page number. Verovio has no option producing this markup itself, so it is
+    /// added to the rendered SVG. The inserted ids include the page number
+    /// (`page-1-title` and `page-1-desc` on page 1), so several pages of one
+    /// document can share an HTML document; prefix them (see
+    /// [`render_to_svg_prefixed`](Self::render_to_svg_prefixed)) when pages of
+    /// several documents do.
     ///
     /// # Arguments
     ///
-    /// * `data` - Humdrum data as a string
+    /// * `page` - The page number (1-based)
     ///
     /// # Errors
     ///
     /// Returns an error if:
-    /// - The data contains a null byte
-    /// - Conversion fails
+    /// - No data has been loaded
+    /// - The page number is out of range
+    /// - The MEI export fails
     ///
     /// # Example
     ///
     /// ```no_run
     /// use verovioxide::Toolkit;
     ///
-    /// let toolkit = Toolkit::new().expect("Failed to create toolkit");
-    /// let humdrum_data = "**kern\n4c\n*-\n";
-    /// let processed = toolkit.convert_humdrum_to_humdrum(humdrum_data)
-    ///     .expect("Failed to convert");
-    /// println!("{}", processed);
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// // ... load data ...
+    ///
+    /// let svg = toolkit.render_accessible_svg(1).expect("Failed to render");
     /// ```
     ///
     /// # See also
     ///
-    /// - [`convert_humdrum_to_midi`](Self::convert_humdrum_to_midi) - Convert to MIDI
-    /// - [`convert_mei_to_humdrum`](Self::convert_mei_to_humdrum) - Convert MEI to Humdrum
-    /// - [`get_humdrum`](Self::get_humdrum) - Get Humdrum from loaded document
-    pub fn convert_humdrum_to_humdrum(&self, data: &str) -> Result<String> {
-        let c_data = CString::new(data)?;
-
-        // SAFETY: ptr is valid, c_data is a valid null-terminated string
-        let result_ptr = unsafe {
-            verovioxide_sys::vrvToolkit_convertHumdrumToHumdrum(self.ptr, c_data.as_ptr())
-        };
+    /// - [`render_to_svg`](Self::render_to_svg) - Render without accessibility metadata
+    pub fn render_accessible_svg(&self, page: u32) -> Result<String> {
+        let svg = self.render_to_svg(page)?;
+        let title = self.metadata()?.title;
+        let description = format!("Page {} of {}", page, self.page_count());
 
-        self.ptr_to_string(result_ptr)
-            .ok_or_else(|| Error::RenderError("failed to convert Humdrum to Humdrum".into()))
+        crate::svg::add_accessibility(
+            &svg,
+            &format!("page-{}", page),
+            title.as_deref().unwrap_or("Music notation"),
+            &description,
+        )
     }
 
-    /// Converts Humdrum data to MIDI (base64-encoded).
+    /// Renders a page to SVG and reduces its size.
     ///
-    /// This method converts Humdrum data directly to MIDI without loading
-    /// the data into the toolkit first.
+    /// [`OptimizeLevel::Basic`](crate::OptimizeLevel::Basic) strips comments
+    /// and the indentation between elements, which accounts for most of the
+    /// reduction on typical pages.
+    /// [`OptimizeLevel::Aggressive`](crate::OptimizeLevel::Aggressive) also
+    /// drops duplicate `<use>` references, empty attributes and zero
+    /// translations. Text content and styles are left untouched, so the
+    /// output renders the same as [`render_to_svg`](Self::render_to_svg).
     ///
     /// # Arguments
     ///
-    /// * `data` - Humdrum data as a string
+    /// * `page` - The page number (1-based)
+    /// * `level` - How much to optimize
     ///
     /// # Errors
     ///
     /// Returns an error if:
-    /// - The data contains a null byte
-    /// - Conversion fails
+    /// - No data has been loaded
+    /// - The page number is out of range
+    /// - The rendered SVG cannot be parsed
     ///
     /// # Example
     ///
     /// ```no_run
-    /// use verovioxide::Toolkit;
+    /// use verovioxide::{OptimizeLevel, Toolkit};
     ///
-    /// let toolkit = Toolkit::new().expect("Failed to create toolkit");
-    /// let humdrum_data = "**kern\n4c\n*-\n";
-    /// let midi_base64 = toolkit.convert_humdrum_to_midi(humdrum_data)
-    ///     .expect("Failed to convert");
-    /// println!("MIDI (base64): {}", midi_base64);
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// // ... load data ...
+    ///
+    /// let svg = toolkit
+    ///     .render_to_svg_optimized(1, OptimizeLevel::Aggressive)
+    ///     .expect("Failed to render");
     /// ```
     ///
     /// # See also
     ///
-    /// - [`convert_humdrum_to_humdrum`](Self::convert_humdrum_to_humdrum) - Process Humdrum
-    /// - [`render_to_midi`](Self::render_to_midi) - Render loaded document to MIDI
-    pub fn convert_humdrum_to_midi(&self, data: &str) -> Result<String> {
-        let c_data = CString::new(data)?;
-
-        // SAFETY: ptr is valid, c_data is a valid null-terminated string
-        let result_ptr =
-            unsafe { verovioxide_sys::vrvToolkit_convertHumdrumToMIDI(self.ptr, c_data.as_ptr()) };
-
-        self.ptr_to_string(result_ptr)
-            .ok_or_else(|| Error::RenderError("failed to convert Humdrum to MIDI".into()))
+    /// - [`render_to_svg`](Self::render_to_svg) - Render without optimization
+    pub fn render_to_svg_optimized(
+        &self,
+        page: u32,
+        level: crate::OptimizeLevel,
+    ) -> Result<String> {
+        crate::svg::optimize(&self.render_to_svg(page)?, level)
     }
 
-    /// Converts MEI data to Humdrum.
+    /// Renders a page to SVG with numbers rounded to a fixed precision, and
+    /// optimizes it as [`render_to_svg_optimized`](Self::render_to_svg_optimized)
+    /// does.
     ///
-    /// This method converts MEI data directly to Humdrum without loading
-    /// the data into the toolkit first.
+    /// Numbers in path data, transforms, coordinates and lengths are rounded
+    /// to at most `precision` decimal places, and trailing zeros are dropped.
+    /// Most coordinates Verovio writes are whole numbers already; the savings
+    /// come from glyph scale factors and other derived values. Rounding those
+    /// to 0 decimals can change small glyph sizes visibly. Text content and
+    /// ids are left unchanged.
     ///
     /// # Arguments
     ///
-    /// * `data` - MEI data as a string
+    /// * `page` - The page number (1-based)
+    /// * `level` - How much to optimize
+    /// * `precision` - The maximum number of decimal places
     ///
     /// # Errors
     ///
     /// Returns an error if:
-    /// - The data contains a null byte
-    /// - Conversion fails
+    /// - No data has been loaded
+    /// - The page number is out of range
+    /// - The rendered SVG cannot be parsed
     ///
     /// # Example
     ///
     /// ```no_run
-    /// use verovioxide::Toolkit;
+    /// use verovioxide::{OptimizeLevel, Toolkit};
     ///
-    /// let toolkit = Toolkit::new().expect("Failed to create toolkit");
-    /// let mei_data = r#"<mei xmlns="http://www.music-encoding.org/ns/mei">...</mei>"#;
-    /// let humdrum = toolkit.convert_mei_to_humdrum(mei_data)
-    ///     .expect("Failed to convert");
-    /// println!("{}", humdrum);
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// // ... load data ...
+    ///
+    /// let svg = toolkit
+    ///     .render_to_svg_with_precision(1, OptimizeLevel::Basic, 2)
+    ///     .expect("Failed to render");
     /// ```
     ///
     /// # See also
     ///
-    /// - [`get_humdrum`](Self::get_humdrum) - Get Humdrum from loaded document
-    /// - [`convert_humdrum_to_humdrum`](Self::convert_humdrum_to_humdrum) - Process Humdrum
-    pub fn convert_mei_to_humdrum(&self, data: &str) -> Result<String> {
-        let c_data = CString::new(data)?;
-
-        // SAFETY: ptr is valid, c_data is a valid null-terminated string
-        let result_ptr =
-            unsafe { verovioxide_sys::vrvToolkit_convertMEIToHumdrum(self.ptr, c_data.as_ptr()) };
-
-        self.ptr_to_string(result_ptr)
-            .ok_or_else(|| Error::RenderError("failed to convert MEI to Humdrum".into()))
+    /// - [`render_to_svg_optimized`](Self::render_to_svg_optimized) - Optimize at full precision
+    pub fn render_to_svg_with_precision(
+        &self,
+        page: u32,
+        level: crate::OptimizeLevel,
+        precision: u8,
+    ) -> Result<String> {
+        let svg = self.render_to_svg_optimized(page, level)?;
+        crate::svg::round_numbers(&svg, precision)
     }
 
-    /// Renders data with options in one step.
+    /// Renders a page to SVG cropped to its content.
     ///
-    /// This is a convenience method that loads data and renders it in a single
-    /// operation. It combines `load_data`, `set_options`, and rendering.
+    /// The page is first rendered with Verovio's `svgBoundingBoxes` option to
+    /// find the union of the bounding boxes of everything drawn on it. The
+    /// page is then rendered normally and its drawing `viewBox` is narrowed to
+    /// that box plus `padding` on every side. The root `width` and `height`
+    /// shrink in proportion and the root gets a matching `viewBox`, so the
+    /// page margins and the empty space below the last system are trimmed,
+    /// e.g. for label printing.
     ///
     /// # Arguments
     ///
-    /// * `data` - Music data to render (format auto-detected)
-    /// * `options` - Optional JSON string with rendering options
+    /// * `page` - The page number (1-based)
+    /// * `padding` - The margin kept around the content, in the drawing units
+    ///   of the page's inner `viewBox`
     ///
     /// # Errors
     ///
     /// Returns an error if:
-    /// - The data contains a null byte
-    /// - Loading or rendering fails
+    /// - `padding` is negative or not finite
+    /// - No data has been loaded
+    /// - The page number is out of range
+    /// - The page has no content to crop to
     ///
     /// # Example
     ///
@@ -1750,633 +2659,709 @@ impl Toolkit {
     /// use verovioxide::Toolkit;
     ///
     /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
-    /// let mei = r#"<mei xmlns="http://www.music-encoding.org/ns/mei">...</mei>"#;
-    /// let options = r#"{"scale": 50}"#;
-    /// let svg = toolkit.render_data(mei, Some(options))
-    ///     .expect("Failed to render");
-    /// println!("{}", svg);
+    /// // ... load data ...
+    ///
+    /// let svg = toolkit.render_to_svg_cropped(1, 50.0).expect("Failed to render");
     /// ```
     ///
     /// # See also
     ///
-    /// - [`load_data`](Self::load_data) - Load data separately
-    /// - [`set_options`](Self::set_options) - Set options separately
-    /// - [`render_to_svg`](Self::render_to_svg) - Render to SVG
-    pub fn render_data(&mut self, data: &str, options: Option<&str>) -> Result<String> {
-        let c_data = CString::new(data)?;
-        let c_options = CString::new(options.unwrap_or("{}"))?;
+    /// - [`render_to_svg`](Self::render_to_svg) - Render the full page
+    /// - [`page_view_box`](Self::page_view_box) - Get the viewBox of the full page
+    pub fn render_to_svg_cropped(&self, page: u32, padding: f64) -> Result<String> {
+        if !padding.is_finite() || padding < 0.0 {
+            return Err(Error::RenderError(format!(
+                "invalid crop padding: {}",
+                padding
+            )));
+        }
 
-        // SAFETY: ptr is valid, c_data and c_options are valid null-terminated strings
-        let result_ptr = unsafe {
-            verovioxide_sys::vrvToolkit_renderData(self.ptr, c_data.as_ptr(), c_options.as_ptr())
-        };
+        let boxed = self.with_temporary_options(r#"{"svgBoundingBoxes": true}"#, |toolkit| {
+            toolkit.render_to_svg(page)
+        })??;
+        let bounds = crate::svg::content_bounds(&boxed)?
+            .ok_or_else(|| Error::RenderError(format!("page {} has no content to crop", page)))?;
 
-        self.ptr_to_string(result_ptr)
-            .ok_or_else(|| Error::RenderError("failed to render data".into()))
+        crate::svg::crop(&self.render_to_svg(page)?, bounds, padding)
     }
 
-    /// Renders the loaded document to MIDI as base64-encoded data.
+    /// Renders a page to SVG wrapped in a JSON object.
     ///
-    /// # Performance
+    /// The result is `{"page": n, "svg": "...", "view_box": {...}}`, with the
+    /// SVG escaped by serde so it can be handed to web clients without
+    /// hand-written escaping. The page is rendered with the `svgViewBox`
+    /// option temporarily enabled, so the SVG scales to its container and
+    /// `view_box` is the same as [`page_view_box`](Self::page_view_box).
     ///
-    /// MIDI generation traverses the entire score to extract timing and pitch
-    /// information, then base64-encodes the binary MIDI data. For large scores,
-    /// the base64 encoding adds a small overhead. The returned string is
-    /// approximately 33% larger than the raw MIDI binary data.
+    /// # Arguments
+    ///
+    /// * `page` - The page number (1-based)
     ///
     /// # Errors
     ///
-    /// Returns an error if no document is loaded or rendering fails.
+    /// Returns an error if:
+    /// - No data has been loaded
+    /// - The page number is out of range
+    /// - The rendered SVG has no parsable `viewBox`
     ///
-    /// # See also
+    /// # Example
     ///
-    /// - [`get_mei`](Self::get_mei) - Export as MEI
-    /// - [`get_humdrum`](Self::get_humdrum) - Export as Humdrum
-    /// - [`render_to_pae`](Self::render_to_pae) - Export as Plaine & Easie
-    /// - [`render_to_timemap`](Self::render_to_timemap) - Get timing information
-    pub fn render_to_midi(&self) -> Result<String> {
-        if self.page_count() == 0 {
-            return Err(Error::RenderError("no data loaded".into()));
-        }
-
-        // SAFETY: ptr is valid, data is loaded
-        let midi_ptr = unsafe { verovioxide_sys::vrvToolkit_renderToMIDI(self.ptr) };
-
-        self.ptr_to_string(midi_ptr)
-            .ok_or_else(|| Error::RenderError("failed to render MIDI".into()))
-    }
-
-    /// Renders the loaded document to Plaine & Easie code.
+    /// ```no_run
+    /// use verovioxide::Toolkit;
     ///
-    /// # Errors
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// // ... load data ...
     ///
-    /// Returns an error if no document is loaded or rendering fails.
+    /// let json = toolkit.render_to_svg_json(1).expect("Failed to render");
+    /// ```
     ///
     /// # See also
     ///
-    /// - [`get_mei`](Self::get_mei) - Export as MEI
-    /// - [`get_humdrum`](Self::get_humdrum) - Export as Humdrum
-    /// - [`render_to_midi`](Self::render_to_midi) - Export as MIDI
-    pub fn render_to_pae(&self) -> Result<String> {
-        if self.page_count() == 0 {
-            return Err(Error::RenderError("no data loaded".into()));
+    /// - [`render_to_svg`](Self::render_to_svg) - Render to a plain SVG string
+    /// - [`page_view_box`](Self::page_view_box) - Get the viewBox of a page
+    pub fn render_to_svg_json(&self, page: u32) -> Result<String> {
+        #[derive(serde::Serialize)]
+        struct SvgPayload<'a> {
+            page: u32,
+            svg: &'a str,
+            view_box: crate::svg::ViewBox,
         }
 
-        // SAFETY: ptr is valid, data is loaded
-        let pae_ptr = unsafe { verovioxide_sys::vrvToolkit_renderToPAE(self.ptr) };
+        let svg = self.with_temporary_options(r#"{"svgViewBox": true}"#, |toolkit| {
+            toolkit.render_to_svg(page)
+        })??;
+        let payload = SvgPayload {
+            page,
+            view_box: crate::svg::root_view_box(&svg)?,
+            svg: &svg,
+        };
 
-        self.ptr_to_string(pae_ptr)
-            .ok_or_else(|| Error::RenderError("failed to render PAE".into()))
+        serde_json::to_string(&payload).map_err(|e| Error::RenderError(e.to_string()))
     }
 
-    /// Gets the timemap as JSON.
+    /// Renders a page and lists how it differs from a previous rendering.
     ///
-    /// The timemap provides timing information for elements in the score,
-    /// mapping musical time to milliseconds.
+    /// Meant for regression tests across Verovio versions or option changes,
+    /// where a text diff of two SVGs is mostly noise. Elements are matched by
+    /// their `id`, which is the MEI `xml:id`; attribute order and whitespace
+    /// are ignored, as are the glyph definitions in `<defs>` and the generated
+    /// suffixes of glyph ids. Systems, pages, and page headers and footers get
+    /// new ids with every layout, so they are matched by position instead and
+    /// named by class and index, as in `system[2]`. Each difference is one
+    /// line naming the element's class and id:
     ///
-    /// # Errors
+    /// - `removed note #n1` - only in `baseline_svg`
+    /// - `added note #n1` - only in the new rendering
+    /// - `moved note #n1 from (x, y) to (x, y)` - drawn at another position
+    /// - `changed note #n1` - other attributes or text differ
     ///
-    /// Returns an error if no document is loaded or export fails.
+    /// Documents without `xml:id`s get new generated ids each time they are
+    /// loaded, so compare renderings of the same load or of documents with
+    /// explicit ids.
     ///
-    /// # See also
+    /// # Arguments
     ///
-    /// - [`render_to_timemap_with_options`](Self::render_to_timemap_with_options) - Get timemap with custom options
-    /// - [`get_elements_at_time`](Self::get_elements_at_time) - Get elements at a specific time
-    /// - [`get_time_for_element`](Self::get_time_for_element) - Get time for a specific element
-    /// - [`render_to_midi`](Self::render_to_midi) - Export as MIDI (includes timing)
-    pub fn render_to_timemap(&self) -> Result<String> {
-        self.render_to_timemap_with_options("{}")
-    }
-
-    /// Gets the timemap as JSON with options.
+    /// * `page` - The page number (1-based)
+    /// * `baseline_svg` - The SVG to compare against, e.g. a stored rendering
     ///
-    /// # Arguments
+    /// # Returns
     ///
-    /// * `options` - JSON string with timemap options
+    /// The differences in document order, empty if the renderings match.
     ///
     /// # Errors
     ///
-    /// Returns an error if no document is loaded or export fails.
+    /// Returns an error if:
+    /// - No data has been loaded
+    /// - The page number is out of range
+    /// - `baseline_svg` cannot be parsed
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    ///
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// // ... load data ...
+    ///
+    /// let baseline = std::fs::read_to_string("page-1.svg").expect("Failed to read baseline");
+    /// for difference in toolkit.diff_render(1, &baseline).expect("Failed to diff") {
+    ///     println!("{}", difference);
+    /// }
+    /// ```
     ///
     /// # See also
     ///
-    /// - [`render_to_timemap`](Self::render_to_timemap) - Get timemap with default options
-    /// - [`get_elements_at_time`](Self::get_elements_at_time) - Get elements at a specific time
-    /// - [`get_time_for_element`](Self::get_time_for_element) - Get time for a specific element
-    pub fn render_to_timemap_with_options(&self, options: &str) -> Result<String> {
-        let c_options = CString::new(options)?;
-
-        // SAFETY: ptr is valid, c_options is a valid null-terminated string
-        let timemap_ptr =
-            unsafe { verovioxide_sys::vrvToolkit_renderToTimemap(self.ptr, c_options.as_ptr()) };
-
-        self.ptr_to_string(timemap_ptr)
-            .ok_or_else(|| Error::RenderError("failed to render timemap".into()))
+    /// - [`render_to_svg`](Self::render_to_svg) - Render a page to SVG
+    pub fn diff_render(&self, page: u32, baseline_svg: &str) -> Result<Vec<String>> {
+        let svg = self.render_to_svg(page)?;
+        crate::svg::diff(baseline_svg, &svg)
     }
 
-    /// Gets the expansion map as JSON.
+    /// Compares a rendered page with a golden SVG file.
     ///
-    /// # Errors
+    /// Both the rendering and the golden file are normalized first: comments
+    /// and indentation are removed, and so are the generated suffixes of glyph
+    /// ids and the Verovio version in the SVG description, so a golden file
+    /// keeps matching across runs. When they still differ, the differences
+    /// are listed as by [`diff_render`](Self::diff_render). Element ids are
+    /// compared as rendered; documents without `xml:id`s get new generated
+    /// ids each time they are loaded, so golden tests should use documents
+    /// with explicit ids.
     ///
-    /// Returns an error if no document is loaded or export fails.
-    pub fn render_to_expansion_map(&self) -> Result<String> {
-        // SAFETY: ptr is valid
-        let map_ptr = unsafe { verovioxide_sys::vrvToolkit_renderToExpansionMap(self.ptr) };
-
-        self.ptr_to_string(map_ptr)
-            .ok_or_else(|| Error::RenderError("failed to render expansion map".into()))
-    }
-
-    // =========================================================================
-    // File Output Functions
-    // =========================================================================
-
-    /// Renders a page to SVG and saves to a file.
+    /// If the `VEROVIOXIDE_UPDATE_GOLDEN` environment variable is set to a
+    /// value other than `0`, a missing or differing golden file is written
+    /// with the rendering instead, creating its directory if needed, and the
+    /// comparison passes.
     ///
-    /// This is a convenience method that combines rendering and file writing
-    /// in a single operation.
+    /// Available in this crate's tests and with the `test-utils` feature.
     ///
     /// # Arguments
     ///
-    /// * `path` - Path to the output file
-    /// * `page` - The page number to render (1-based)
+    /// * `page` - The page number (1-based)
+    /// * `golden` - The path of the golden SVG file
+    ///
+    /// # Returns
+    ///
+    /// Whether the comparison passed, the differences, and whether the
+    /// golden file was updated.
     ///
     /// # Errors
     ///
     /// Returns an error if:
     /// - No data has been loaded
     /// - The page number is out of range
-    /// - The path contains invalid UTF-8
-    /// - Writing the file fails
+    /// - The golden file does not exist and is not being updated
+    /// - The golden file cannot be read, parsed or written
     ///
     /// # Example
     ///
     /// ```no_run
-    /// use verovioxide::Toolkit;
+    /// # #[cfg(feature = "test-utils")]
+    /// # {
     /// use std::path::Path;
+    /// use verovioxide::Toolkit;
     ///
     /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
     /// // ... load data ...
-    /// toolkit.render_to_svg_file(Path::new("output.svg"), 1)
-    ///     .expect("Failed to save SVG");
+    ///
+    /// let result = toolkit
+    ///     .compare_golden(1, Path::new("tests/golden/page-1.svg"))
+    ///     .expect("Failed to compare");
+    /// assert!(result.passed, "{:#?}", result.differences);
+    /// # }
     /// ```
     ///
     /// # See also
     ///
-    /// - [`render_to_svg`](Self::render_to_svg) - Render to string
-    /// - [`render_to_midi_file`](Self::render_to_midi_file) - Save MIDI to file
-    pub fn render_to_svg_file(&self, path: &Path, page: u32) -> Result<()> {
-        let path_str = path
-            .to_str()
-            .ok_or_else(|| Error::RenderError("file path contains invalid UTF-8".into()))?;
-
-        let c_path = CString::new(path_str)?;
-
-        // SAFETY: ptr is valid, c_path is a valid null-terminated string
-        let success = unsafe {
-            verovioxide_sys::vrvToolkit_renderToSVGFile(self.ptr, c_path.as_ptr(), page as i32)
+    /// - [`diff_render`](Self::diff_render) - Compare a page with an SVG string
+    #[cfg(any(test, feature = "test-utils"))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "test-utils")))]
+    pub fn compare_golden(&self, page: u32, golden: &Path) -> Result<crate::GoldenResult> {
+        let svg = self.render_to_svg(page)?;
+        let current = crate::svg::normalize_golden(&svg)?;
+        let update = std::env::var_os("VEROVIOXIDE_UPDATE_GOLDEN")
+            .is_some_and(|value| !value.is_empty() && value != "0");
+
+        let differences = if golden.exists() {
+            let expected = crate::svg::normalize_golden(&std::fs::read_to_string(golden)?)?;
+            if expected == current {
+                Vec::new()
+            } else {
+                let mut differences = crate::svg::diff(&expected, &current)?;
+                if differences.is_empty() {
+                    differences.push("changed markup outside elements with an id".to_string());
+                }
+                differences
+            }
+        } else if update {
+            vec![format!("missing golden file {}", golden.display())]
+        } else {
+            return Err(Error::FileNotFound(golden.to_path_buf()));
         };
 
-        if success {
-            Ok(())
-        } else {
-            Err(Error::RenderError(format!(
-                "failed to save SVG to file: {}",
-                path.display()
-            )))
+        if differences.is_empty() || !update {
+            return Ok(crate::GoldenResult {
+                passed: differences.is_empty(),
+                differences,
+                updated: false,
+            });
+        }
+
+        if let Some(parent) = golden.parent() {
+            std::fs::create_dir_all(parent)?;
         }
+        std::fs::write(golden, svg)?;
+        Ok(crate::GoldenResult {
+            passed: true,
+            differences,
+            updated: true,
+        })
     }
 
-    /// Renders the document to MIDI and saves to a file.
+    /// Renders a page to SVG styled for dark backgrounds.
+    ///
+    /// [`DARK_THEME_CSS`](crate::DARK_THEME_CSS) is appended to the `svgCss`
+    /// option for this render only, so staff lines, notes and text are drawn
+    /// light on a transparent background. CSS already set with
+    /// [`svg_css`](crate::OptionsBuilder::svg_css) is kept and comes first.
+    /// Text uses the fonts of the viewing page; enable
+    /// [`svg_font_face_include`](crate::OptionsBuilder::svg_font_face_include)
+    /// for standalone files.
     ///
     /// # Arguments
     ///
-    /// * `path` - Path to the output MIDI file
+    /// * `page` - The page number (1-based)
     ///
     /// # Errors
     ///
     /// Returns an error if:
     /// - No data has been loaded
-    /// - The path contains invalid UTF-8
-    /// - Writing the file fails
+    /// - The page number is out of range
+    /// - The options cannot be read or applied
     ///
     /// # Example
     ///
     /// ```no_run
     /// use verovioxide::Toolkit;
-    /// use std::path::Path;
     ///
     /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
     /// // ... load data ...
-    /// toolkit.render_to_midi_file(Path::new("output.mid"))
-    ///     .expect("Failed to save MIDI");
+    ///
+    /// let svg = toolkit.render_to_svg_dark(1).expect("Failed to render");
     /// ```
     ///
     /// # See also
     ///
-    /// - [`render_to_midi`](Self::render_to_midi) - Render to base64 string
-    /// - [`render_to_svg_file`](Self::render_to_svg_file) - Save SVG to file
-    pub fn render_to_midi_file(&self, path: &Path) -> Result<()> {
-        let path_str = path
-            .to_str()
-            .ok_or_else(|| Error::RenderError("file path contains invalid UTF-8".into()))?;
-
-        let c_path = CString::new(path_str)?;
-
-        // SAFETY: ptr is valid, c_path is a valid null-terminated string
-        let success =
-            unsafe { verovioxide_sys::vrvToolkit_renderToMIDIFile(self.ptr, c_path.as_ptr()) };
+    /// - [`render_to_svg`](Self::render_to_svg) - Render with the default colors
+    pub fn render_to_svg_dark(&self, page: u32) -> Result<String> {
+        let css = match self.current_options()?.svg_css {
+            Some(css) if !css.trim().is_empty() => format!("{}\n{}", css, crate::DARK_THEME_CSS),
+            _ => crate::DARK_THEME_CSS.to_string(),
+        };
+        let options = serde_json::json!({ "svgCss": css }).to_string();
 
-        if success {
-            Ok(())
-        } else {
-            Err(Error::RenderError(format!(
-                "failed to save MIDI to file: {}",
-                path.display()
-            )))
-        }
+        self.with_temporary_options(&options, |toolkit| toolkit.render_to_svg(page))?
     }
 
-    /// Renders the document to PAE and saves to a file.
+    /// Renders a page to SVG annotated for debugging engraving and font issues.
+    ///
+    /// The page is rendered with bounding boxes (as with the `svgBoundingBoxes`
+    /// option), and every glyph reference gets a `<title>` with the glyph's
+    /// SMuFL name (e.g. `noteheadBlack`), which SVG viewers and browsers show
+    /// on hover. Names are read from the font definitions in the resource
+    /// directory.
     ///
     /// # Arguments
     ///
-    /// * `path` - Path to the output PAE file
+    /// * `page` - The page number (1-based)
     ///
     /// # Errors
     ///
     /// Returns an error if:
     /// - No data has been loaded
-    /// - The path contains invalid UTF-8
-    /// - Writing the file fails
+    /// - The page number is out of range
+    /// - The toolkit has no resource path or the font definition cannot be read
     ///
     /// # Example
     ///
     /// ```no_run
     /// use verovioxide::Toolkit;
-    /// use std::path::Path;
     ///
-    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// let toolkit = Toolkit::new().expect("Failed to create toolkit");
     /// // ... load data ...
-    /// toolkit.render_to_pae_file(Path::new("output.pae"))
-    ///     .expect("Failed to save PAE");
+    ///
+    /// let svg = toolkit.render_to_svg_annotated(1).expect("Failed to render");
+    /// std::fs::write("debug.svg", svg).expect("Failed to write SVG");
     /// ```
     ///
     /// # See also
     ///
-    /// - [`render_to_pae`](Self::render_to_pae) - Render to string
-    /// - [`validate_pae`](Self::validate_pae) - Validate PAE code
-    pub fn render_to_pae_file(&self, path: &Path) -> Result<()> {
-        let path_str = path
-            .to_str()
-            .ok_or_else(|| Error::RenderError("file path contains invalid UTF-8".into()))?;
-
-        let c_path = CString::new(path_str)?;
-
-        // SAFETY: ptr is valid, c_path is a valid null-terminated string
-        let success =
-            unsafe { verovioxide_sys::vrvToolkit_renderToPAEFile(self.ptr, c_path.as_ptr()) };
+    /// - [`render_to_svg`](Self::render_to_svg) - Render without annotations
+    /// - [`missing_glyphs`](Self::missing_glyphs) - Glyphs the font lacks
+    pub fn render_to_svg_annotated(&self, page: u32) -> Result<String> {
+        self.check_loaded()?;
+        let resource_path = self.get_resource_path();
+        if resource_path.is_empty() {
+            return Err(Error::RenderError("no resource path".into()));
+        }
 
-        if success {
-            Ok(())
-        } else {
-            Err(Error::RenderError(format!(
-                "failed to save PAE to file: {}",
-                path.display()
-            )))
+        let resource_path = Path::new(&resource_path);
+        let mut names =
+            crate::diagnostics::font_glyphs(resource_path, crate::diagnostics::BASELINE_FONT)?;
+        if let Ok(font) = self.active_font() {
+            names.extend(crate::diagnostics::font_glyphs(resource_path, &font).unwrap_or_default());
         }
+
+        let svg = self.with_temporary_options(r#"{"svgBoundingBoxes": true}"#, |toolkit| {
+            toolkit.render_to_svg(page)
+        })??;
+        crate::svg::annotate_glyphs(&svg, &names)
     }
 
-    /// Renders the expansion map and saves to a file.
+    /// Renders a page to SVG with ids generated from a fixed seed.
+    ///
+    /// Verovio generates ids for elements it creates during layout, such as
+    /// systems, so two renders of the same input normally differ in those ids.
+    /// This method resets the id seed with [`reset_xml_id_seed`](Self::reset_xml_id_seed)
+    /// and lays the document out again before rendering, so the same seed
+    /// always yields identical SVG. This makes the output suitable for
+    /// snapshot tests.
+    ///
+    /// Ids of encoded elements are assigned when the document is loaded and
+    /// are not affected. For output that is also stable across processes, the
+    /// input must carry its own xml:ids, or the seed must be reset before
+    /// [`load_data`](Self::load_data) as well.
+    ///
+    /// # Performance
+    ///
+    /// Every call performs a full layout of the document, which usually costs
+    /// more than rendering the page itself. Use [`render_to_svg`](Self::render_to_svg)
+    /// when stable ids are not needed.
     ///
     /// # Arguments
     ///
-    /// * `path` - Path to the output file
+    /// * `page` - The page number (1-based)
+    /// * `seed` - The id seed
     ///
     /// # Errors
     ///
     /// Returns an error if:
     /// - No data has been loaded
-    /// - The path contains invalid UTF-8
-    /// - Writing the file fails
+    /// - The page number is out of range
+    /// - Rendering fails
     ///
     /// # Example
     ///
     /// ```no_run
     /// use verovioxide::Toolkit;
-    /// use std::path::Path;
     ///
     /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
     /// // ... load data ...
-    /// toolkit.render_to_expansion_map_file(Path::new("expansion_map.json"))
-    ///     .expect("Failed to save expansion map");
+    ///
+    /// let svg = toolkit.render_to_svg_stable(1, 42).expect("Failed to render");
     /// ```
     ///
     /// # See also
     ///
-    /// - [`render_to_expansion_map`](Self::render_to_expansion_map) - Render to string
-    pub fn render_to_expansion_map_file(&self, path: &Path) -> Result<()> {
-        let path_str = path
-            .to_str()
-            .ok_or_else(|| Error::RenderError("file path contains invalid UTF-8".into()))?;
-
-        let c_path = CString::new(path_str)?;
-
-        // SAFETY: ptr is valid, c_path is a valid null-terminated string
-        let success = unsafe {
-            verovioxide_sys::vrvToolkit_renderToExpansionMapFile(self.ptr, c_path.as_ptr())
-        };
+    /// - [`reset_xml_id_seed`](Self::reset_xml_id_seed) - Reset the id seed only
+    pub fn render_to_svg_stable(&mut self, page: u32, seed: i32) -> Result<String> {
+        self.check_loaded()?;
 
-        if success {
-            Ok(())
-        } else {
-            Err(Error::RenderError(format!(
-                "failed to save expansion map to file: {}",
-                path.display()
-            )))
-        }
+        self.reset_xml_id_seed(seed);
+        self.redo_layout(None)?;
+        self.render_to_svg(page)
     }
 
-    /// Renders the timemap and saves to a file.
+    /// Renders a page together with the playback data for that page.
+    ///
+    /// The bundle contains the page's SVG, the timemap entries that start or
+    /// stop an element drawn on the page, and the time span those entries
+    /// cover. This is everything a web client needs to highlight notes on
+    /// the page during playback.
     ///
     /// # Arguments
     ///
-    /// * `path` - Path to the output file
-    /// * `options` - Optional JSON string with timemap options
+    /// * `page` - The page number (1-based)
     ///
     /// # Errors
     ///
     /// Returns an error if:
     /// - No data has been loaded
-    /// - The path contains invalid UTF-8
-    /// - Writing the file fails
+    /// - The page number is out of range
+    /// - The timemap cannot be produced or parsed
     ///
     /// # Example
     ///
     /// ```no_run
     /// use verovioxide::Toolkit;
-    /// use std::path::Path;
     ///
     /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
     /// // ... load data ...
-    /// toolkit.render_to_timemap_file(Path::new("timemap.json"), None)
-    ///     .expect("Failed to save timemap");
+    ///
+    /// let bundle = toolkit.render_page_bundle(1).expect("Failed to render");
+    /// println!("page 1 lasts {} ms", bundle.duration_ms);
     /// ```
     ///
     /// # See also
     ///
-    /// - [`render_to_timemap`](Self::render_to_timemap) - Render to string
-    /// - [`render_to_timemap_with_options`](Self::render_to_timemap_with_options) - Render with options
-    pub fn render_to_timemap_file(&self, path: &Path, options: Option<&str>) -> Result<()> {
-        let path_str = path
-            .to_str()
-            .ok_or_else(|| Error::RenderError("file path contains invalid UTF-8".into()))?;
-
-        let c_path = CString::new(path_str)?;
-        let c_options = CString::new(options.unwrap_or("{}"))?;
+    /// - [`render_to_svg`](Self::render_to_svg) - Render the SVG only
+    /// - [`render_to_timemap`](Self::render_to_timemap) - Timemap for the whole document
+    pub fn render_page_bundle(&self, page: u32) -> Result<PageBundle> {
+        let svg = self.render_to_svg(page)?;
+        let (timemap, duration_ms) = page_timemap(&self.render_to_timemap()?, &svg)?;
 
-        // SAFETY: ptr is valid, c_path and c_options are valid null-terminated strings
-        let success = unsafe {
-            verovioxide_sys::vrvToolkit_renderToTimemapFile(
-                self.ptr,
-                c_path.as_ptr(),
-                c_options.as_ptr(),
-            )
-        };
-
-        if success {
-            Ok(())
-        } else {
-            Err(Error::RenderError(format!(
-                "failed to save timemap to file: {}",
-                path.display()
-            )))
-        }
+        Ok(PageBundle {
+            svg,
+            timemap,
+            duration_ms,
+        })
     }
 
-    /// Saves the document to a file with options.
+    /// Renders the measures sounding in a time window, for showing the
+    /// notation that goes with a segment of audio.
     ///
-    /// This method saves the currently loaded document to a file. The output
-    /// format depends on the options and the configured output format.
+    /// The window is mapped to measures with the timemap: every measure that
+    /// sounds at some point in `start_ms..end_ms` is included. The measures
+    /// are selected and laid out in a separate toolkit, loaded with this
+    /// toolkit's MEI and options, so this toolkit's selection and layout are
+    /// untouched. Times follow the tempo of the document, as in
+    /// [`render_to_timemap`](Self::render_to_timemap).
+    ///
+    /// # Performance
+    ///
+    /// The document is exported, loaded into a new toolkit and laid out
+    /// twice, so this costs about as much as loading the document again.
     ///
     /// # Arguments
     ///
-    /// * `path` - Path to the output file
-    /// * `options` - Optional JSON string with save options
+    /// * `start_ms` - The start of the window, in milliseconds
+    /// * `end_ms` - The end of the window, in milliseconds, after `start_ms`
+    ///
+    /// # Returns
+    ///
+    /// The first page of the excerpt; long windows may not fit on one page.
     ///
     /// # Errors
     ///
     /// Returns an error if:
+    /// - `end_ms` is not after `start_ms`
     /// - No data has been loaded
-    /// - The path contains invalid UTF-8
-    /// - Writing the file fails
+    /// - No measure sounds in the window
+    /// - The excerpt cannot be exported, selected or rendered
     ///
     /// # Example
     ///
     /// ```no_run
     /// use verovioxide::Toolkit;
-    /// use std::path::Path;
     ///
     /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
     /// // ... load data ...
-    /// toolkit.save_file(Path::new("output.mei"), None)
-    ///     .expect("Failed to save file");
+    ///
+    /// // The notation for the audio from 0:12 to 0:20
+    /// let svg = toolkit
+    ///     .render_time_window(12_000, 20_000)
+    ///     .expect("Failed to render excerpt");
     /// ```
     ///
     /// # See also
     ///
-    /// - [`get_mei`](Self::get_mei) - Get MEI as string
-    /// - [`set_output_to`](Self::set_output_to) - Set output format
-    pub fn save_file(&self, path: &Path, options: Option<&str>) -> Result<()> {
-        let path_str = path
-            .to_str()
-            .ok_or_else(|| Error::RenderError("file path contains invalid UTF-8".into()))?;
+    /// - [`select`](Self::select) - Select a range of this toolkit
+    /// - [`get_elements_at_time`](Self::get_elements_at_time) - Elements sounding at one time
+    pub fn render_time_window(&self, start_ms: u32, end_ms: u32) -> Result<String> {
+        if end_ms <= start_ms {
+            return Err(Error::RenderError(format!(
+                "invalid time window: {}-{} ms",
+                start_ms, end_ms
+            )));
+        }
+        self.check_loaded()?;
 
-        let c_path = CString::new(path_str)?;
-        let c_options = CString::new(options.unwrap_or("{}"))?;
+        let mut excerpt = self.scratch_toolkit()?;
+        excerpt.load_mei_internal(&self.get_mei()?)?;
 
-        // SAFETY: ptr is valid, c_path and c_options are valid null-terminated strings
-        let success = unsafe {
-            verovioxide_sys::vrvToolkit_saveFile(self.ptr, c_path.as_ptr(), c_options.as_ptr())
-        };
+        let timemap = excerpt.render_to_timemap_with_options(r#"{"includeMeasures": true}"#)?;
+        let (first, last) = window_measures(&timemap, f64::from(start_ms), f64::from(end_ms))?
+            .ok_or_else(|| {
+                Error::RenderError(format!(
+                    "no measures in time window {}-{} ms",
+                    start_ms, end_ms
+                ))
+            })?;
 
-        if success {
-            Ok(())
-        } else {
-            Err(Error::RenderError(format!(
-                "failed to save to file: {}",
-                path.display()
-            )))
-        }
+        excerpt.select(&serde_json::json!({ "start": first, "end": last }).to_string())?;
+        excerpt.redo_layout(None)?;
+        excerpt.render_to_svg(1)
     }
 
-    /// Saves the Humdrum representation to a file.
+    /// Renders all pages to SVG.
     ///
-    /// # Arguments
+    /// # Performance
     ///
-    /// * `path` - Path to the output file
+    /// This method renders pages sequentially. For a document with N pages,
+    /// the total time is approximately N times the single-page render time.
+    /// The method pre-allocates the result vector to avoid reallocations.
+    ///
+    /// For long documents, [`render_all_pages_parallel`](Self::render_all_pages_parallel)
+    /// renders pages on several threads. For most use cases, sequential
+    /// rendering is sufficient and avoids the overhead of loading the document
+    /// into additional toolkit instances.
     ///
     /// # Errors
     ///
     /// Returns an error if:
     /// - No data has been loaded
-    /// - The path contains invalid UTF-8
-    /// - Writing the file fails
+    /// - Rendering any page fails
     ///
     /// # Example
     ///
     /// ```no_run
     /// use verovioxide::Toolkit;
-    /// use std::path::Path;
     ///
     /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
     /// // ... load data ...
-    /// toolkit.save_humdrum_to_file(Path::new("output.krn"))
-    ///     .expect("Failed to save Humdrum");
+    ///
+    /// let pages = toolkit.render_all_pages().expect("Failed to render");
+    /// for (i, svg) in pages.iter().enumerate() {
+    ///     println!("Page {}: {} bytes", i + 1, svg.len());
+    /// }
     /// ```
     ///
     /// # See also
     ///
-    /// - [`get_humdrum`](Self::get_humdrum) - Get Humdrum as string
-    pub fn save_humdrum_to_file(&self, path: &Path) -> Result<()> {
-        let path_str = path
-            .to_str()
-            .ok_or_else(|| Error::RenderError("file path contains invalid UTF-8".into()))?;
+    /// - [`render_to_svg`](Self::render_to_svg) - Render a single page
+    /// - [`page_count`](Self::page_count) - Get the total number of pages
+    pub fn render_all_pages(&self) -> Result<Vec<String>> {
+        let count = self.page_count();
+        let mut pages = Vec::with_capacity(count as usize);
 
-        let c_path = CString::new(path_str)?;
+        for page in 1..=count {
+            pages.push(self.render_to_svg(page)?);
+        }
 
-        // SAFETY: ptr is valid, c_path is a valid null-terminated string
-        let success =
-            unsafe { verovioxide_sys::vrvToolkit_getHumdrumFile(self.ptr, c_path.as_ptr()) };
+        Ok(pages)
+    }
 
-        if success {
-            Ok(())
-        } else {
-            Err(Error::RenderError(format!(
-                "failed to save Humdrum to file: {}",
-                path.display()
-            )))
-        }
-    }
-
-    /// Gets the current rendering scale as a percentage.
+    /// Returns a cursor rendering the pages one at a time, from page 1.
+    ///
+    /// Useful for infinite scrolling, where pages are rendered as the user
+    /// reaches them rather than all at once.
     ///
     /// # Example
     ///
     /// ```no_run
     /// use verovioxide::Toolkit;
     ///
-    /// let toolkit = Toolkit::new().expect("Failed to create toolkit");
-    /// let scale = toolkit.get_scale();
-    /// println!("Current scale: {}%", scale);
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// // ... load data ...
     ///
-    /// // The scale affects the rendered output size
-    /// if scale < 100 {
-    ///     println!("Rendering at reduced size");
-    /// }
+    /// let mut cursor = toolkit.page_cursor();
+    /// let first = cursor.next_page(&toolkit);
+    /// println!("more pages: {}", cursor.has_more());
     /// ```
-    #[must_use]
-    pub fn get_scale(&self) -> i32 {
-        // SAFETY: ptr is valid
-        unsafe { verovioxide_sys::vrvToolkit_getScale(self.ptr) }
-    }
-
-    /// Sets the rendering scale as a percentage.
     ///
-    /// # Arguments
-    ///
-    /// * `scale` - Scale percentage (e.g., 100 for 100%)
-    ///
-    /// # Errors
+    /// # See also
     ///
-    /// Returns an error if the scale value is invalid.
-    pub fn set_scale(&mut self, scale: i32) -> Result<()> {
-        // SAFETY: ptr is valid
-        let success = unsafe { verovioxide_sys::vrvToolkit_setScale(self.ptr, scale) };
-
-        if success {
-            Ok(())
-        } else {
-            Err(Error::OptionsError(format!("invalid scale: {}", scale)))
+    /// - [`render_all_pages`](Self::render_all_pages) - Render all pages at once
+    #[must_use]
+    pub fn page_cursor(&self) -> PageCursor {
+        PageCursor {
+            page: 0,
+            page_count: self.page_count(),
+            generation: self.generation,
         }
     }
 
-    /// Gets the toolkit instance ID.
+    /// Renders all pages to SVG on multiple threads.
     ///
-    /// Each toolkit instance has a unique identifier assigned by Verovio.
+    /// Verovio keeps drawing state in the toolkit while rendering, so one
+    /// toolkit cannot render pages concurrently, even read-only. Instead, each
+    /// worker thread creates its own toolkit, resets its id seed to the one
+    /// last passed to [`reset_xml_id_seed`](Self::reset_xml_id_seed), loads
+    /// the MEI exported from this toolkit with the same options, and renders
+    /// every n-th page.
     ///
-    /// # Example
+    /// Ids of encoded elements (notes, measures, ...) are carried over by the
+    /// export. Ids of elements Verovio creates during layout, such as systems,
+    /// come from the seed, so the pages are byte-identical to
+    /// [`render_all_pages`](Self::render_all_pages) when this toolkit loaded
+    /// MEI with xml:ids right after resetting the seed and has not redone the
+    /// layout since. Otherwise only those generated ids differ.
     ///
-    /// ```no_run
-    /// use verovioxide::Toolkit;
+    /// # Performance
     ///
-    /// let toolkit = Toolkit::new().expect("Failed to create toolkit");
-    /// let id = toolkit.get_id();
-    /// println!("Toolkit ID: {}", id);
-    /// ```
-    #[must_use]
-    pub fn get_id(&self) -> String {
-        // SAFETY: ptr is valid
-        let id_ptr = unsafe { verovioxide_sys::vrvToolkit_getID(self.ptr) };
-        self.ptr_to_string(id_ptr).unwrap_or_default()
-    }
-
-    /// Gets the current resource path.
+    /// Every worker loads and lays out the whole document before rendering,
+    /// so this only pays off when rendering takes longer than loading, i.e.
+    /// for documents with many pages. The number of workers is limited by
+    /// [`std::thread::available_parallelism`] and the page count; with a
+    /// single worker, pages are rendered sequentially by this toolkit.
     ///
-    /// Returns the path to the directory containing Verovio resources (fonts, etc.).
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The MEI export fails
+    /// - A worker toolkit cannot be created or fails to load the document
+    /// - A worker lays the document out on a different number of pages
+    /// - Rendering any page fails
     ///
     /// # Example
     ///
     /// ```no_run
     /// use verovioxide::Toolkit;
     ///
-    /// let toolkit = Toolkit::new().expect("Failed to create toolkit");
-    /// let path = toolkit.get_resource_path();
-    /// println!("Resources located at: {}", path);
-    /// ```
-    #[must_use]
-    pub fn get_resource_path(&self) -> String {
-        // SAFETY: ptr is valid
-        let path_ptr = unsafe { verovioxide_sys::vrvToolkit_getResourcePath(self.ptr) };
-        self.ptr_to_string(path_ptr).unwrap_or_default()
-    }
-
-    /// Sets the resource path.
-    ///
-    /// # Arguments
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// // ... load data ...
     ///
-    /// * `path` - Path to the Verovio resources directory
+    /// let pages = toolkit
+    ///     .render_all_pages_parallel()
+    ///     .expect("Failed to render");
+    /// println!("Rendered {} pages", pages.len());
+    /// ```
     ///
-    /// # Errors
+    /// # See also
     ///
-    /// Returns an error if the path is invalid.
-    pub fn set_resource_path(&mut self, path: &Path) -> Result<()> {
-        let path_str = path
-            .to_str()
-            .ok_or_else(|| Error::OptionsError("resource path contains invalid UTF-8".into()))?;
-
-        let c_path = CString::new(path_str)?;
+    /// - [`render_all_pages`](Self::render_all_pages) - Render pages sequentially
+    pub fn render_all_pages_parallel(&self) -> Result<Vec<String>> {
+        let count = self.page_count();
+        let workers = std::thread::available_parallelism()
+            .map_or(1, std::num::NonZeroUsize::get)
+            .min(count as usize);
+        if workers <= 1 {
+            return self.render_all_pages();
+        }
 
-        // SAFETY: ptr is valid, c_path is a valid null-terminated string
-        let success =
-            unsafe { verovioxide_sys::vrvToolkit_setResourcePath(self.ptr, c_path.as_ptr()) };
+        let mei = self.get_mei()?;
+        let settings = self.scratch_settings();
+        let seed = self.xml_id_seed;
+
+        let rendered = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..workers)
+                .map(|worker| {
+                    let (mei, settings) = (&mei, &settings);
+                    scope.spawn(move || -> Result<Vec<(u32, String)>> {
+                        let mut toolkit = settings.toolkit()?;
+                        toolkit.reset_xml_id_seed(seed);
+                        toolkit.load_mei_internal(mei)?;
+                        if toolkit.page_count() != count {
+                            return Err(Error::RenderError(format!(
+                                "render worker laid out {} pages, expected {}",
+                                toolkit.page_count(),
+                                count
+                            )));
+                        }
+                        (1..=count)
+                            .skip(worker)
+                            .step_by(workers)
+                            .map(|page| Ok((page, toolkit.render_to_svg(page)?)))
+                            .collect()
+                    })
+                })
+                .collect();
+
+            handles
+                .into_iter()
+                .map(|handle| {
+                    handle
+                        .join()
+                        .map_err(|_| Error::RenderError("render worker panicked".into()))?
+                })
+                .collect::<Result<Vec<_>>>()
+        })?;
 
-        if success {
-            Ok(())
-        } else {
-            Err(Error::OptionsError("failed to set resource path".into()))
+        let mut pages = vec![String::new(); count as usize];
+        for (page, svg) in rendered.into_iter().flatten() {
+            pages[page as usize - 1] = svg;
         }
+        Ok(pages)
     }
 
-    /// Gets the page number containing a specific element.
-    ///
-    /// # Arguments
-    ///
-    /// * `xml_id` - The xml:id of the element
+    /// Renders all pages to SVG, returning a result for each page.
     ///
-    /// # Returns
+    /// Unlike [`render_all_pages`](Self::render_all_pages), a failing page does
+    /// not stop the remaining pages from being rendered. The returned vector has
+    /// one entry per page, in page order, which makes it easy to find the page
+    /// that triggers a problem in a large document.
     ///
-    /// The page number (1-based), or 0 if the element is not found.
+    /// Returns an empty vector if no data has been loaded.
     ///
     /// # Example
     ///
@@ -2384,34 +3369,37 @@ impl Toolkit {
     /// use verovioxide::Toolkit;
     ///
     /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
-    /// // ... load MEI data ...
+    /// // ... load data ...
     ///
-    /// let page = toolkit.get_page_with_element("note-0001").expect("Failed to get page");
-    /// if page > 0 {
-    ///     println!("Element is on page {}", page);
-    /// } else {
-    ///     println!("Element not found");
+    /// for (i, result) in toolkit.try_render_all_pages().into_iter().enumerate() {
+    ///     if let Err(e) = result {
+    ///         eprintln!("Page {} failed: {}", i + 1, e);
+    ///     }
     /// }
     /// ```
-    pub fn get_page_with_element(&self, xml_id: &str) -> Result<u32> {
-        let c_id = CString::new(xml_id)?;
-
-        // SAFETY: ptr is valid, c_id is a valid null-terminated string
-        let page =
-            unsafe { verovioxide_sys::vrvToolkit_getPageWithElement(self.ptr, c_id.as_ptr()) };
-
-        Ok(page.max(0) as u32)
+    ///
+    /// # See also
+    ///
+    /// - [`render_all_pages`](Self::render_all_pages) - Stop at the first failing page
+    /// - [`render_to_svg`](Self::render_to_svg) - Render a single page
+    #[must_use]
+    pub fn try_render_all_pages(&self) -> Vec<Result<String>> {
+        (1..=self.page_count())
+            .map(|page| self.render_to_svg(page))
+            .collect()
     }
 
-    /// Gets element attributes by xml:id.
-    ///
-    /// # Arguments
+    /// Renders all pages into a single SVG, stacked vertically.
     ///
-    /// * `xml_id` - The xml:id of the element
+    /// Each page becomes a nested `<svg>` placed below the previous one. The
+    /// glyph definitions Verovio repeats in every page are hoisted into one
+    /// shared `<defs>` and deduplicated by id, which keeps the combined output
+    /// much smaller than the concatenated pages. Definitions that share an id
+    /// but differ are kept apart by renaming, and their references rewritten.
     ///
-    /// # Returns
+    /// # Errors
     ///
-    /// A JSON string with the element's attributes.
+    /// Returns an error if no data has been loaded or any page fails to render.
     ///
     /// # Example
     ///
@@ -2419,85 +3407,98 @@ impl Toolkit {
     /// use verovioxide::Toolkit;
     ///
     /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
-    /// // ... load MEI data with elements having xml:id attributes ...
+    /// // ... load data ...
     ///
-    /// let attrs = toolkit.get_element_attr("note-0001").expect("Failed to get attributes");
-    /// println!("Note attributes: {}", attrs);
+    /// let svg = toolkit.render_to_single_svg().expect("Failed to render");
+    /// std::fs::write("score.svg", svg).expect("Failed to write SVG");
     /// ```
-    pub fn get_element_attr(&self, xml_id: &str) -> Result<String> {
-        let c_id = CString::new(xml_id)?;
-
-        // SAFETY: ptr is valid, c_id is a valid null-terminated string
-        let attr_ptr =
-            unsafe { verovioxide_sys::vrvToolkit_getElementAttr(self.ptr, c_id.as_ptr()) };
+    ///
+    /// # See also
+    ///
+    /// - [`render_all_pages`](Self::render_all_pages) - One SVG per page
+    pub fn render_to_single_svg(&self) -> Result<String> {
+        self.check_loaded()?;
 
-        self.ptr_to_string(attr_ptr).ok_or_else(|| {
-            Error::RenderError(format!("failed to get attributes for element: {}", xml_id))
-        })
+        crate::svg::combine_pages(&self.render_all_pages()?)
     }
 
-    /// Gets elements at a specific time in milliseconds.
+    /// Renders all pages of the document with an expansion applied.
     ///
-    /// # Arguments
-    ///
-    /// * `millisec` - Time in milliseconds
+    /// Verovio applies expansions when a document is loaded, so this method
+    /// exports the document as MEI and loads it in a separate toolkit with the
+    /// current options and the `expand` option set to `expansion_id`, leaving
+    /// this toolkit untouched. This is the one-call path for showing the fully
+    /// repeated version of a score.
     ///
-    /// # Returns
+    /// # Arguments
     ///
-    /// A JSON string with the element IDs at the specified time.
+    /// * `expansion_id` - The xml:id of an `<expansion>` element in the document
     ///
-    /// # See also
+    /// # Errors
     ///
-    /// - [`get_time_for_element`](Self::get_time_for_element) - Get time for a specific element
-    /// - [`render_to_timemap`](Self::render_to_timemap) - Get the full timemap
-    pub fn get_elements_at_time(&self, millisec: i32) -> Result<String> {
-        // SAFETY: ptr is valid
-        let elements_ptr =
-            unsafe { verovioxide_sys::vrvToolkit_getElementsAtTime(self.ptr, millisec) };
-
-        self.ptr_to_string(elements_ptr).ok_or_else(|| {
-            Error::RenderError(format!("failed to get elements at time: {}", millisec))
-        })
-    }
-
-    /// Gets the time (in milliseconds) for an element.
+    /// Returns an error if:
+    /// - No data has been loaded
+    /// - The document has no `<expansion>` with the given xml:id
+    /// - Reloading or rendering the document fails
     ///
-    /// # Arguments
+    /// # Example
     ///
-    /// * `xml_id` - The xml:id of the element
+    /// ```no_run
+    /// use verovioxide::Toolkit;
     ///
-    /// # Returns
+    /// let toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// // ... load MEI with an <expansion xml:id="expansion-1"> ...
     ///
-    /// The time in milliseconds.
+    /// let pages = toolkit
+    ///     .render_expansion("expansion-1")
+    ///     .expect("Failed to render expansion");
+    /// println!("Expanded score has {} pages", pages.len());
+    /// ```
     ///
     /// # See also
     ///
-    /// - [`get_elements_at_time`](Self::get_elements_at_time) - Get elements at a specific time
-    /// - [`render_to_timemap`](Self::render_to_timemap) - Get the full timemap
-    pub fn get_time_for_element(&self, xml_id: &str) -> Result<f64> {
-        let c_id = CString::new(xml_id)?;
+    /// - [`render_all_pages`](Self::render_all_pages) - Render without an expansion
+    /// - [`render_to_expansion_map`](Self::render_to_expansion_map) - Map notated to expanded ids
+    pub fn render_expansion(&self, expansion_id: &str) -> Result<Vec<String>> {
+        self.check_loaded()?;
 
-        // SAFETY: ptr is valid, c_id is a valid null-terminated string
-        let time =
-            unsafe { verovioxide_sys::vrvToolkit_getTimeForElement(self.ptr, c_id.as_ptr()) };
+        let mei = self.get_mei()?;
+        if !crate::mei::expansion_ids(&mei)?
+            .iter()
+            .any(|id| id == expansion_id)
+        {
+            return Err(Error::RenderError(format!(
+                "expansion not found: {}",
+                expansion_id
+            )));
+        }
 
-        Ok(time)
+        let mut scratch = self.scratch_toolkit()?;
+        scratch.set_options_json(&serde_json::json!({ "expand": expansion_id }).to_string())?;
+        scratch.load_mei_internal(&mei)?;
+        scratch.render_all_pages()
     }
 
-    /// Gets expansion IDs for an element.
+    /// Renders a page showing only the given staves.
     ///
-    /// When working with documents that contain expansion elements (e.g., repeats),
-    /// this method returns the expansion IDs associated with a given element.
+    /// Verovio's [`select`](Self::select) only restricts measure ranges, so
+    /// the staff subset is applied to an exported MEI copy of the document:
+    /// other staves, their staff definitions and control events attached only
+    /// to them are removed. The copy is laid out in a separate toolkit with the
+    /// current options, leaving this toolkit untouched. Page numbers refer to
+    /// the reduced layout, which usually has fewer pages.
     ///
     /// # Arguments
     ///
-    /// * `xml_id` - The xml:id of the element
+    /// * `staff_ns` - The `@n` values of the staves to keep
+    /// * `page` - Page number in the reduced layout (1-based)
     ///
     /// # Errors
     ///
     /// Returns an error if:
-    /// - The xml_id contains a null byte
-    /// - The query fails
+    /// - No data has been loaded
+    /// - `staff_ns` is empty or names a staff not defined in the document
+    /// - Loading or rendering the reduced document fails
     ///
     /// # Example
     ///
@@ -2505,46 +3506,45 @@ impl Toolkit {
     /// use verovioxide::Toolkit;
     ///
     /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
-    /// // ... load data with expansion elements ...
+    /// // ... load an orchestral score ...
     ///
-    /// let expansion_ids = toolkit.get_expansion_ids_for_element("note-0001")
-    ///     .expect("Failed to get expansion IDs");
-    /// println!("Expansion IDs: {}", expansion_ids);
+    /// let violins = toolkit.render_staves(&[5, 6], 1).expect("Failed to render");
     /// ```
     ///
     /// # See also
     ///
-    /// - [`render_to_expansion_map`](Self::render_to_expansion_map) - Get the full expansion map
-    /// - [`get_notated_id_for_element`](Self::get_notated_id_for_element) - Get notated ID
-    pub fn get_expansion_ids_for_element(&self, xml_id: &str) -> Result<String> {
-        let c_id = CString::new(xml_id)?;
+    /// - [`render_to_svg`](Self::render_to_svg) - Render a page with all staves
+    pub fn render_staves(&self, staff_ns: &[u32], page: u32) -> Result<String> {
+        self.check_loaded()?;
 
-        // SAFETY: ptr is valid, c_id is a valid null-terminated string
-        let result_ptr = unsafe {
-            verovioxide_sys::vrvToolkit_getExpansionIdsForElement(self.ptr, c_id.as_ptr())
-        };
+        let mei = crate::mei::retain_staves(&self.get_mei()?, staff_ns)?;
 
-        self.ptr_to_string(result_ptr).ok_or_else(|| {
-            Error::RenderError(format!(
-                "failed to get expansion IDs for element: {}",
-                xml_id
-            ))
-        })
+        let mut scratch = self.scratch_toolkit()?;
+        scratch.load_mei_internal(&mei)?;
+        scratch.render_to_svg(page)
     }
 
-    /// Gets MIDI values for an element.
+    /// Renders a page showing only one verse of the lyrics.
     ///
-    /// Returns MIDI-related information (pitch, velocity, etc.) for a specific element.
+    /// Hymnals and song books often show one verse at a time. Verovio has no
+    /// option for this, so the other verses are removed from an exported MEI
+    /// copy of the document, which is laid out in a separate toolkit with the
+    /// current options. This toolkit and its document are left untouched, so
+    /// every verse can be rendered in turn. Enable
+    /// [`lyric_verse_collapse`](crate::OptionsBuilder::lyric_verse_collapse)
+    /// to draw the verse directly below the staff.
     ///
     /// # Arguments
     ///
-    /// * `xml_id` - The xml:id of the element
+    /// * `verse` - The `@n` of the verse to keep (1-based)
+    /// * `page` - Page number in the single-verse layout (1-based)
     ///
     /// # Errors
     ///
     /// Returns an error if:
-    /// - The xml_id contains a null byte
-    /// - The query fails
+    /// - No data has been loaded
+    /// - The document has no verse `verse`
+    /// - Loading or rendering the single-verse document fails
     ///
     /// # Example
     ///
@@ -2552,44 +3552,36 @@ impl Toolkit {
     /// use verovioxide::Toolkit;
     ///
     /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
-    /// // ... load data ...
+    /// // ... load a hymn ...
     ///
-    /// let midi_values = toolkit.get_midi_values_for_element("note-0001")
-    ///     .expect("Failed to get MIDI values");
-    /// println!("MIDI values: {}", midi_values);
+    /// let second_verse = toolkit.render_verse(2, 1).expect("Failed to render");
     /// ```
     ///
     /// # See also
     ///
-    /// - [`render_to_midi`](Self::render_to_midi) - Render full MIDI
-    /// - [`get_time_for_element`](Self::get_time_for_element) - Get timing for element
-    pub fn get_midi_values_for_element(&self, xml_id: &str) -> Result<String> {
-        let c_id = CString::new(xml_id)?;
+    /// - [`render_staves`](Self::render_staves) - Render a subset of the staves
+    /// - [`extract_text`](Self::extract_text) - The lyrics and other text
+    pub fn render_verse(&self, verse: u32, page: u32) -> Result<String> {
+        self.check_loaded()?;
 
-        // SAFETY: ptr is valid, c_id is a valid null-terminated string
-        let result_ptr =
-            unsafe { verovioxide_sys::vrvToolkit_getMIDIValuesForElement(self.ptr, c_id.as_ptr()) };
+        let mei = crate::mei::retain_verse(&self.get_mei()?, verse)?;
 
-        self.ptr_to_string(result_ptr).ok_or_else(|| {
-            Error::RenderError(format!("failed to get MIDI values for element: {}", xml_id))
-        })
+        let mut scratch = self.scratch_toolkit()?;
+        scratch.load_mei_internal(&mei)?;
+        scratch.render_to_svg(page)
     }
 
-    /// Gets the notated ID for an element.
-    ///
-    /// When working with expansions, elements may have different rendered IDs
-    /// than their notated IDs. This method returns the original notated ID
-    /// for a given element.
-    ///
-    /// # Arguments
+    /// Renders all pages into a single multi-page PDF.
     ///
-    /// * `xml_id` - The xml:id of the element (possibly a rendered ID)
+    /// Each page is rendered to SVG and converted to vector PDF content with
+    /// `svg2pdf`, one PDF page per score page. Every PDF page takes its size from
+    /// the corresponding SVG, so documents with mixed page sizes are preserved.
     ///
     /// # Errors
     ///
     /// Returns an error if:
-    /// - The xml_id contains a null byte
-    /// - The query fails
+    /// - No data has been loaded
+    /// - Rendering or converting any page fails
     ///
     /// # Example
     ///
@@ -2599,41 +3591,86 @@ impl Toolkit {
     /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
     /// // ... load data ...
     ///
-    /// let notated_id = toolkit.get_notated_id_for_element("rendered-note-0001")
-    ///     .expect("Failed to get notated ID");
-    /// println!("Notated ID: {}", notated_id);
+    /// let pdf = toolkit.render_to_pdf().expect("Failed to render PDF");
+    /// std::fs::write("score.pdf", pdf).expect("Failed to write PDF");
     /// ```
     ///
     /// # See also
     ///
-    /// - [`get_expansion_ids_for_element`](Self::get_expansion_ids_for_element) - Get expansion IDs
-    /// - [`render_to_expansion_map`](Self::render_to_expansion_map) - Get the full expansion map
-    pub fn get_notated_id_for_element(&self, xml_id: &str) -> Result<String> {
-        let c_id = CString::new(xml_id)?;
-
-        // SAFETY: ptr is valid, c_id is a valid null-terminated string
-        let result_ptr =
-            unsafe { verovioxide_sys::vrvToolkit_getNotatedIdForElement(self.ptr, c_id.as_ptr()) };
+    /// - [`render_all_pages`](Self::render_all_pages) - Render all pages to SVG
+    /// - [`Pdf`](crate::Pdf) - PDF format for the unified render API
+    #[cfg(feature = "pdf")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "pdf")))]
+    pub fn render_to_pdf(&self) -> Result<Vec<u8>> {
+        self.check_loaded()?;
 
-        self.ptr_to_string(result_ptr).ok_or_else(|| {
-            Error::RenderError(format!("failed to get notated ID for element: {}", xml_id))
-        })
+        crate::render::svgs_to_pdf(&self.render_all_pages()?)
     }
 
-    /// Gets timing information for an element.
+    /// Returns the number of pages in the loaded document.
     ///
-    /// Returns detailed timing information including onset time, offset time,
-    /// and duration for a specific element.
+    /// Returns 0 if no document is loaded.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    ///
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// // ... load data ...
+    ///
+    /// println!("Document has {} pages", toolkit.page_count());
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`render_to_svg`](Self::render_to_svg) - Render a specific page
+    /// - [`render_all_pages`](Self::render_all_pages) - Render all pages at once
+    #[must_use]
+    pub fn page_count(&self) -> u32 {
+        // SAFETY: ptr is valid
+        let count = unsafe { verovioxide_sys::vrvToolkit_getPageCount(self.ptr) };
+        count.max(0) as u32
+    }
+
+    /// Returns the current generation of the toolkit.
+    ///
+    /// The generation changes whenever a method that may modify the loaded
+    /// document, its layout or the options is called (loading, setting options,
+    /// selections, edits, relayouts). Two equal values mean nothing has changed
+    /// in between, so results derived from the document can be reused.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    ///
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// let before = toolkit.generation();
+    /// toolkit.set_scale(50).expect("Failed to set scale");
+    /// assert_ne!(before, toolkit.generation());
+    /// ```
+    #[must_use]
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Returns the `viewBox` of a rendered page in SVG user units.
+    ///
+    /// The page is rendered with the `svgViewBox` option temporarily enabled so
+    /// that the root `<svg>` element carries a `viewBox`; the previous value of
+    /// the option is restored afterwards.
     ///
     /// # Arguments
     ///
-    /// * `xml_id` - The xml:id of the element
+    /// * `page` - The page number (1-based)
     ///
     /// # Errors
     ///
     /// Returns an error if:
-    /// - The xml_id contains a null byte
-    /// - The query fails
+    /// - No data has been loaded
+    /// - The page number is out of range
+    /// - The rendered SVG has no parsable `viewBox`
     ///
     /// # Example
     ///
@@ -2643,42 +3680,42 @@ impl Toolkit {
     /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
     /// // ... load data ...
     ///
-    /// let times = toolkit.get_times_for_element("note-0001")
-    ///     .expect("Failed to get times");
-    /// println!("Timing info: {}", times);
+    /// let view_box = toolkit.page_view_box(1).expect("Failed to get viewBox");
+    /// println!("Page is {} x {} units", view_box.width, view_box.height);
     /// ```
     ///
     /// # See also
     ///
-    /// - [`get_time_for_element`](Self::get_time_for_element) - Get simple time value
-    /// - [`render_to_timemap`](Self::render_to_timemap) - Get full timemap
-    pub fn get_times_for_element(&self, xml_id: &str) -> Result<String> {
-        let c_id = CString::new(xml_id)?;
-
-        // SAFETY: ptr is valid, c_id is a valid null-terminated string
-        let result_ptr =
-            unsafe { verovioxide_sys::vrvToolkit_getTimesForElement(self.ptr, c_id.as_ptr()) };
-
-        self.ptr_to_string(result_ptr).ok_or_else(|| {
-            Error::RenderError(format!("failed to get times for element: {}", xml_id))
-        })
+    /// - [`render_to_svg`](Self::render_to_svg) - Render a page to SVG
+    /// - [`ViewBox`](crate::ViewBox) - The returned type
+    pub fn page_view_box(&self, page: u32) -> Result<crate::svg::ViewBox> {
+        let svg = self.with_temporary_options(r#"{"svgViewBox": true}"#, |toolkit| {
+            toolkit.render_to_svg(page)
+        })??;
+        crate::svg::root_view_box(&svg)
     }
 
-    /// Gets descriptive features from the document.
+    /// Returns a [`CoordinateMapper`](crate::CoordinateMapper) for a page
+    /// displayed at `width` x `height` pixels.
     ///
-    /// Extracts descriptive features and metadata from the loaded document,
-    /// useful for analysis and categorization.
+    /// The mapper converts the page's drawing coordinates, in which
+    /// [`element_at_point`](Self::element_at_point) and
+    /// [`document_anchors`](Self::document_anchors) work, to pixels of the
+    /// displayed SVG and back. Use it to place HTML overlays over elements or
+    /// to hit-test mouse positions.
     ///
     /// # Arguments
     ///
-    /// * `options` - Optional JSON string with feature extraction options
+    /// * `page` - The page number (1-based)
+    /// * `width` - The displayed width of the page, in pixels
+    /// * `height` - The displayed height of the page, in pixels
     ///
     /// # Errors
     ///
     /// Returns an error if:
     /// - No data has been loaded
-    /// - The options contain a null byte
-    /// - Feature extraction fails
+    /// - The page number is out of range
+    /// - The display size is not positive
     ///
     /// # Example
     ///
@@ -2688,2173 +3725,9868 @@ impl Toolkit {
     /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
     /// // ... load data ...
     ///
-    /// let features = toolkit.get_descriptive_features(None)
-    ///     .expect("Failed to get features");
-    /// println!("Features: {}", features);
+    /// let mapper = toolkit
+    ///     .coordinate_mapper(1, 800.0, 1131.0)
+    ///     .expect("Failed to create mapper");
+    /// let (x, y) = mapper.pixel_to_user(120.0, 340.0);
+    /// let clicked = toolkit.element_at_point(1, x, y).expect("Failed to hit-test");
     /// ```
-    pub fn get_descriptive_features(&self, options: Option<&str>) -> Result<String> {
-        let c_options = CString::new(options.unwrap_or("{}"))?;
-
-        // SAFETY: ptr is valid, c_options is a valid null-terminated string
-        let result_ptr = unsafe {
-            verovioxide_sys::vrvToolkit_getDescriptiveFeatures(self.ptr, c_options.as_ptr())
-        };
-
-        self.ptr_to_string(result_ptr)
-            .ok_or_else(|| Error::RenderError("failed to get descriptive features".into()))
-    }
-
-    /// Redoes the layout with optional new options.
     ///
-    /// # Arguments
+    /// # See also
     ///
-    /// * `options` - Optional JSON string with layout options
-    pub fn redo_layout(&mut self, options: Option<&str>) -> Result<()> {
-        let c_options = CString::new(options.unwrap_or("{}"))?;
-
-        // SAFETY: ptr is valid, c_options is a valid null-terminated string
-        unsafe { verovioxide_sys::vrvToolkit_redoLayout(self.ptr, c_options.as_ptr()) };
-
-        Ok(())
+    /// - [`page_view_box`](Self::page_view_box) - The viewBox of the page
+    pub fn coordinate_mapper(
+        &self,
+        page: u32,
+        width: f64,
+        height: f64,
+    ) -> Result<crate::CoordinateMapper> {
+        let svg = self.render_to_svg(page)?;
+        crate::CoordinateMapper::new(crate::svg::page_drawing_view_box(&svg)?, width, height)
     }
 
-    /// Performs an editor action on the loaded document.
+    /// Sets rendering options.
+    ///
+    /// Options are merged with existing options. To reset to defaults, use
+    /// [`reset_options()`](Self::reset_options) first.
+    ///
+    /// [`text_lang`](Options::text_lang) is not a Verovio option: it
+    /// is kept by the toolkit and applied to the text of every SVG rendered
+    /// with [`render_to_svg`](Self::render_to_svg). An empty `text_lang`
+    /// clears it (see [`set_text_lang`](Self::set_text_lang)).
+    ///
+    /// # Performance
+    ///
+    /// Setting options is a lightweight operation that only stores configuration
+    /// values. However, if a document is already loaded and an option that
+    /// can change the layout (such as page dimensions, margins, or break
+    /// modes) gets a new value, the layout is redone right away, so the page
+    /// count always matches the pages [`render_to_svg`](Self::render_to_svg)
+    /// can render, and the [`on_relayout`](Self::on_relayout) hook is called.
+    /// Options that only affect rendering or export, such as `svgCss` or the
+    /// MIDI options, never cause a relayout. For best performance when
+    /// experimenting with different options, set all desired options before
+    /// loading data, or batch option changes into a single call.
     ///
     /// # Arguments
     ///
-    /// * `action` - JSON string describing the editor action
+    /// * `options` - The rendering options to set
     ///
     /// # Errors
     ///
-    /// Returns an error if the action fails.
-    pub fn edit(&mut self, action: &str) -> Result<()> {
-        let c_action = CString::new(action)?;
+    /// Returns an error if:
+    /// - JSON serialization fails
+    /// - Option values are invalid
+    /// - [`text_lang`](Options::text_lang) is not a valid language tag
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::{Toolkit, Options};
+    ///
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    ///
+    /// let options = Options::builder()
+    ///     .scale(80)
+    ///     .adjust_page_height(true)
+    ///     .build();
+    ///
+    /// toolkit.set_options(&options).expect("Failed to set options");
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`get_options`](Self::get_options) - Get current options as JSON
+    /// - [`reset_options`](Self::reset_options) - Reset to default options
+    /// - [`get_default_options`](Self::get_default_options) - Get default options as JSON
+    /// - [`Options`] - The options type
+    pub fn set_options(&mut self, options: &Options) -> Result<()> {
+        if self.apply_options(options)? {
+            self.redo_layout_if_loaded()?;
+        }
+        Ok(())
+    }
 
-        // SAFETY: ptr is valid, c_action is a valid null-terminated string
-        let success = unsafe { verovioxide_sys::vrvToolkit_edit(self.ptr, c_action.as_ptr()) };
+    /// Applies `options` without redoing the layout.
+    ///
+    /// Returns whether an option that can change the layout (see
+    /// [`affects_layout`]) got a new value.
+    fn apply_options(&mut self, options: &Options) -> Result<bool> {
+        self.bump_generation();
+        let mut options = options.clone();
+        let text_lang = options.text_lang.take();
+        if let Some(lang) = &text_lang {
+            if !lang.is_empty() {
+                crate::svg::check_lang(lang)?;
+            }
+        }
+        let json = options
+            .to_json()
+            .map_err(|e| Error::OptionsError(e.to_string()))?;
 
-        if success {
-            Ok(())
-        } else {
-            Err(Error::RenderError("editor action failed".into()))
+        let current: serde_json::Value = serde_json::from_str(&self.get_options())
+            .map_err(|e| Error::OptionsError(e.to_string()))?;
+        let new: serde_json::Map<String, serde_json::Value> =
+            serde_json::from_str(&json).map_err(|e| Error::OptionsError(e.to_string()))?;
+        let layout_changed = new
+            .iter()
+            .any(|(key, value)| affects_layout(key) && current.get(key) != Some(value));
+
+        let c_json = CString::new(json)?;
+
+        // SAFETY: ptr is valid, c_json is a valid null-terminated string
+        let success = unsafe { verovioxide_sys::vrvToolkit_setOptions(self.ptr, c_json.as_ptr()) };
+
+        if !success {
+            return Err(Error::OptionsError("failed to set options".into()));
+        }
+        if let Some(lang) = text_lang {
+            self.text_lang = Some(lang).filter(|lang| !lang.is_empty());
         }
+        if let Some(font) = new.get("font").and_then(serde_json::Value::as_str) {
+            self.track_font(font);
+        }
+        Ok(layout_changed)
     }
 
-    /// Gets information about the last edit operation.
+    /// Sets the `svgCss` option from the contents of a CSS file.
     ///
-    /// Returns a JSON string containing details about the most recent edit
-    /// performed via [`edit()`](Self::edit).
+    /// This is convenient for shipping a stylesheet alongside an application
+    /// instead of embedding it as a string. The CSS replaces any set before
+    /// and is included in every rendered SVG.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path to the CSS file
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The file does not exist
+    /// - The file cannot be read or is not valid UTF-8
+    /// - The option cannot be set
     ///
     /// # Example
     ///
     /// ```no_run
     /// use verovioxide::Toolkit;
+    /// use std::path::Path;
     ///
     /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
-    /// // ... load data and perform an edit ...
+    /// toolkit
+    ///     .set_svg_css_file(Path::new("theme.css"))
+    ///     .expect("Failed to set CSS");
+    /// ```
     ///
-    /// let info = toolkit.edit_info();
-    /// println!("Last edit info: {}", info);
+    /// # See also
+    ///
+    /// - [`svg_css`](crate::OptionsBuilder::svg_css) - Set the CSS from a string
+    pub fn set_svg_css_file(&mut self, path: &Path) -> Result<()> {
+        if !path.exists() {
+            return Err(Error::FileNotFound(path.to_path_buf()));
+        }
+        let css = std::fs::read_to_string(path)?;
+        self.set_options(&Options::builder().svg_css(css).build())
+    }
+
+    /// Gets the current options as a JSON string.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    ///
+    /// let toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// let options_json = toolkit.get_options();
+    /// println!("Current options: {}", options_json);
     /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`set_options`](Self::set_options) - Set rendering options
+    /// - [`reset_options`](Self::reset_options) - Reset to default options
+    /// - [`get_default_options`](Self::get_default_options) - Get default options as JSON
+    /// - [`get_available_options`](Self::get_available_options) - Get all available options
     #[must_use]
-    pub fn edit_info(&self) -> String {
+    pub fn get_options(&self) -> String {
         // SAFETY: ptr is valid
-        let info_ptr = unsafe { verovioxide_sys::vrvToolkit_editInfo(self.ptr) };
-        self.ptr_to_string(info_ptr).unwrap_or_default()
+        let options_ptr = unsafe { verovioxide_sys::vrvToolkit_getOptions(self.ptr) };
+        self.ptr_to_string(options_ptr).unwrap_or_default()
     }
 
-    /// Enables or disables logging to stderr.
+    /// Gets the current options as a typed [`Options`] value.
     ///
-    /// # Arguments
+    /// Every option Verovio reports is included: modeled options are set on
+    /// their fields and the rest are kept in [`Options::extra`], so the result
+    /// can be passed back to [`set_options`](Self::set_options) unchanged.
+    /// [`text_lang`](Options::text_lang) is set from the toolkit's text
+    /// language.
     ///
-    /// * `enable` - `true` to enable logging, `false` to disable
-    pub fn enable_log(enable: bool) {
-        // SAFETY: This function has no preconditions
-        unsafe { verovioxide_sys::enableLog(enable) };
+    /// # Errors
+    ///
+    /// Returns an error if the options reported by Verovio cannot be parsed.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    ///
+    /// let toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// let options = toolkit.current_options().expect("Failed to read options");
+    /// println!("Scale: {:?}", options.scale);
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`get_options`](Self::get_options) - Get current options as JSON
+    /// - [`set_options`](Self::set_options) - Set rendering options
+    pub fn current_options(&self) -> Result<Options> {
+        let mut options = Options::from_json(&self.get_options())
+            .map_err(|e| Error::OptionsError(e.to_string()))?;
+        options.text_lang = self.text_lang.clone();
+        Ok(options)
     }
 
-    /// Enables or disables logging to an internal buffer.
+    /// Sets the language applied to the text of rendered SVG, or clears it.
     ///
-    /// When enabled, log messages can be retrieved with [`get_log()`](Self::get_log).
+    /// This is the same setting as [`Options::text_lang`]: every `<text>`
+    /// element rendered by [`render_to_svg`](Self::render_to_svg) is marked
+    /// with `xml:lang`, and with `direction="rtl"` for right-to-left scripts.
+    /// Verovio has no such option, so the layout is left untouched.
+    ///
+    /// # Performance
+    ///
+    /// While a language is set, every rendered page is parsed once more to
+    /// add the attributes. Clear it when it is not needed.
     ///
     /// # Arguments
     ///
-    /// * `enable` - `true` to enable buffer logging, `false` to disable
-    pub fn enable_log_to_buffer(enable: bool) {
-        // SAFETY: This function has no preconditions
-        unsafe { verovioxide_sys::enableLogToBuffer(enable) };
-    }
-
-    /// Converts a C string pointer to an owned Rust string.
+    /// * `lang` - A BCP 47 language tag such as `he` or `ar-EG`, or `None`
+    ///   to clear the language
     ///
-    /// Returns `None` if the pointer is null or contains invalid UTF-8.
-    fn ptr_to_string(&self, ptr: *const i8) -> Option<String> {
-        if ptr.is_null() {
-            return None;
+    /// # Errors
+    ///
+    /// Returns an error if `lang` is not a valid language tag.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    ///
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// // ... load Hebrew lyrics ...
+    ///
+    /// toolkit.set_text_lang(Some("he")).expect("Failed to set language");
+    /// let svg = toolkit.render_to_svg(1).expect("Failed to render");
+    /// toolkit.set_text_lang(None).expect("Failed to clear language");
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`text_lang`](crate::OptionsBuilder::text_lang) - Set the language with other options
+    pub fn set_text_lang(&mut self, lang: Option<&str>) -> Result<()> {
+        if let Some(lang) = lang {
+            crate::svg::check_lang(lang)?;
         }
+        self.bump_generation();
+        self.text_lang = lang.map(String::from);
+        Ok(())
+    }
 
-        // SAFETY: ptr is non-null and points to a valid C string owned by the toolkit
-        let c_str = unsafe { CStr::from_ptr(ptr) };
+    /// Returns the name of the music font Verovio is actually using.
+    ///
+    /// Verovio keeps the requested `font` option even when no font of that name
+    /// exists in the resource directory, in which case it carries on with the
+    /// font it already had loaded. This method checks the requested font against
+    /// the resource directory and, when it is missing, reports the last font
+    /// that was applied successfully, or the default font if none was, so
+    /// applications can warn that a substitution happened.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the current or default options cannot be parsed.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::{Options, Toolkit};
+    ///
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// toolkit
+    ///     .set_options(&Options::builder().font("Petaluma").build())
+    ///     .expect("Failed to set options");
+    ///
+    /// let font = toolkit.active_font().expect("Failed to get font");
+    /// if font != "Petaluma" {
+    ///     eprintln!("requested Petaluma, using {}", font);
+    /// }
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`Options::font`](crate::Options::font) - The requested font
+    /// - [`get_resource_path`](Self::get_resource_path) - Where fonts are looked up
+    pub fn active_font(&self) -> Result<String> {
+        let requested = Self::font_option(&self.get_options())?;
+        if self.has_font(&requested) {
+            return Ok(requested);
+        }
+        match self.applied_font.borrow().clone() {
+            Some(font) => Ok(font),
+            None => Self::font_option(&self.get_default_options()),
+        }
+    }
+
+    /// Sets the music font and the font used for glyphs it lacks, and redoes
+    /// the layout if a document is loaded.
+    ///
+    /// Glyphs missing from `primary` are drawn from `fallback` instead of
+    /// being left out, which avoids gaps with fonts that cover less of SMuFL
+    /// (see [`missing_glyphs`](Self::missing_glyphs)). Verovio only supports
+    /// [`Font::Leipzig`](crate::Font::Leipzig) and
+    /// [`Font::Bravura`](crate::Font::Bravura) as fallback fonts. With
+    /// `fallback` set to `None`, the current fallback font is kept.
+    ///
+    /// # Arguments
+    ///
+    /// * `primary` - The music font
+    /// * `fallback` - The font for glyphs missing from `primary`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - A font is not in the resource directory
+    /// - `fallback` is not Leipzig or Bravura
+    /// - The options cannot be applied
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::{Font, Toolkit};
+    ///
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// toolkit
+    ///     .set_fonts(Font::Petaluma, Some(Font::Bravura))
+    ///     .expect("Failed to set fonts");
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`active_font`](Self::active_font) - The font actually in use
+    /// - [`Options::fallback_font`](crate::Options::fallback_font) - The fallback option
+    pub fn set_fonts(&mut self, primary: crate::Font, fallback: Option<crate::Font>) -> Result<()> {
+        if let Some(fallback) = fallback {
+            if !matches!(fallback, crate::Font::Leipzig | crate::Font::Bravura) {
+                return Err(Error::OptionsError(format!(
+                    "fallback font must be Leipzig or Bravura, got {}",
+                    fallback.as_str()
+                )));
+            }
+        }
+        for font in std::iter::once(primary).chain(fallback) {
+            if !self.has_font(font.as_str()) {
+                return Err(Error::OptionsError(format!(
+                    "font not available: {}",
+                    font.as_str()
+                )));
+            }
+        }
+
+        let mut options = Options::builder().font(primary.as_str());
+        if let Some(fallback) = fallback {
+            options = options.fallback_font(fallback.as_str());
+        }
+        self.apply_options(&options.build())?;
+        self.redo_layout_if_loaded()
+    }
+
+    /// Extracts the `font` value from an options JSON string.
+    fn font_option(json: &str) -> Result<String> {
+        let options: serde_json::Value =
+            serde_json::from_str(json).map_err(|e| Error::OptionsError(e.to_string()))?;
+        options
+            .get("font")
+            .and_then(serde_json::Value::as_str)
+            .map(String::from)
+            .ok_or_else(|| Error::OptionsError("options have no font value".into()))
+    }
+
+    /// Returns whether the resource directory holds the baseline font Verovio
+    /// needs to lay out and draw music.
+    ///
+    /// A toolkit created with [`without_resources`](Self::without_resources)
+    /// has no usable resource directory.
+    fn has_resources(&self) -> bool {
+        let resource_path = self.get_resource_path();
+        !resource_path.is_empty()
+            && Path::new(&resource_path)
+                .join(format!("{}.xml", crate::diagnostics::BASELINE_FONT))
+                .is_file()
+    }
+
+    /// Checks that `page` can be rendered to SVG.
+    ///
+    /// Page 0 is never valid. Missing resources are reported before the page
+    /// range, so that a toolkit without fonts gets guidance rather than a page
+    /// count error. Likewise, a document that loaded but has no pages is
+    /// reported as such rather than as page 1 being out of range.
+    fn check_svg_page(&self, page: u32) -> Result<()> {
+        let page_count = self.page_count();
+        if page != 0 && !self.has_resources() {
+            return Err(resources_required());
+        }
+        if page_count == 0 && self.loaded {
+            self.check_loaded()?;
+        }
+        if page == 0 || page > page_count {
+            return Err(Error::RenderError(format!(
+                "page {} out of range (document has {} pages)",
+                page, page_count
+            )));
+        }
+        Ok(())
+    }
+
+    /// Checks that a document with at least one page is loaded, failing with
+    /// [`Error::RenderError`].
+    fn check_loaded(&self) -> Result<()> {
+        self.check_loaded_as(Error::RenderError)
+    }
+
+    /// Checks that a document with at least one page is loaded, failing with
+    /// the given error variant.
+    ///
+    /// A document that loaded but has no pages, e.g. an MEI skeleton without
+    /// measures, is reported as such rather than as no data being loaded.
+    fn check_loaded_as(&self, error: fn(String) -> Error) -> Result<()> {
+        if self.page_count() > 0 {
+            Ok(())
+        } else if self.loaded {
+            Err(error(
+                "document loaded but produced no pages; check for empty content".into(),
+            ))
+        } else {
+            Err(error("no data loaded".into()))
+        }
+    }
+
+    /// Returns whether the resource directory has a font definition for `font`.
+    ///
+    /// Without a resource path there is nothing to check against, so every
+    /// font is reported as available.
+    fn has_font(&self, font: &str) -> bool {
+        let resource_path = self.get_resource_path();
+        resource_path.is_empty()
+            || Path::new(&resource_path)
+                .join(format!("{}.xml", font))
+                .is_file()
+    }
+
+    /// Gets the default options as a JSON string.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    ///
+    /// let toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// let defaults = toolkit.get_default_options();
+    /// println!("Default options: {}", defaults);
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`set_options`](Self::set_options) - Set rendering options
+    /// - [`get_options`](Self::get_options) - Get current options as JSON
+    /// - [`reset_options`](Self::reset_options) - Reset to default options
+    #[must_use]
+    pub fn get_default_options(&self) -> String {
+        // SAFETY: ptr is valid
+        let options_ptr = unsafe { verovioxide_sys::vrvToolkit_getDefaultOptions(self.ptr) };
+        self.ptr_to_string(options_ptr).unwrap_or_default()
+    }
+
+    /// Gets all available options and their descriptions as a JSON string.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    ///
+    /// let toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// let available = toolkit.get_available_options();
+    /// println!("Available options: {}", available);
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`set_options`](Self::set_options) - Set rendering options
+    /// - [`get_options`](Self::get_options) - Get current options as JSON
+    /// - [`get_default_options`](Self::get_default_options) - Get default options as JSON
+    #[must_use]
+    pub fn get_available_options(&self) -> String {
+        // SAFETY: ptr is valid
+        let options_ptr = unsafe { verovioxide_sys::vrvToolkit_getAvailableOptions(self.ptr) };
+        self.ptr_to_string(options_ptr).unwrap_or_default()
+    }
+
+    /// Returns a typed description of every option Verovio accepts.
+    ///
+    /// This parses [`get_available_options`](Self::get_available_options),
+    /// keeping each option's group, type, default, bounds and accepted values.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if Verovio's option description cannot be parsed.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    ///
+    /// let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+    /// for option in toolkit.available_options().expect("Failed to list options") {
+    ///     println!("{} ({:?}): {}", option.name, option.kind, option.title);
+    /// }
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`options_schema_json`](Self::options_schema_json) - Options as JSON Schema
+    pub fn available_options(&self) -> Result<Vec<crate::OptionDescriptor>> {
+        crate::schema::parse_available_options(&self.get_available_options())
+    }
+
+    /// Returns the options Verovio accepts, grouped by category.
+    ///
+    /// The keys are the labels of Verovio's option groups (such as "General",
+    /// "General layout" or "MIDI"), i.e. the [`group`](crate::OptionDescriptor::group)
+    /// of each descriptor. Within a category, options keep Verovio's order.
+    /// This maps directly onto a tabbed settings dialog.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if Verovio's option description cannot be parsed.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    ///
+    /// let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+    /// let categories = toolkit
+    ///     .available_options_by_category()
+    ///     .expect("Failed to list options");
+    /// for (category, options) in &categories {
+    ///     println!("{}: {} option(s)", category, options.len());
+    /// }
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`available_options`](Self::available_options) - All options in one list
+    pub fn available_options_by_category(
+        &self,
+    ) -> Result<BTreeMap<String, Vec<crate::OptionDescriptor>>> {
+        let mut categories: BTreeMap<String, Vec<crate::OptionDescriptor>> = BTreeMap::new();
+        for option in self.available_options()? {
+            categories
+                .entry(option.group.clone())
+                .or_default()
+                .push(option);
+        }
+        Ok(categories)
+    }
+
+    /// Returns a JSON Schema document describing every option Verovio accepts.
+    ///
+    /// The schema is an object schema with one property per option, built from
+    /// [`available_options`](Self::available_options): each property carries
+    /// its JSON type, title, description and default, plus `minimum`/`maximum`
+    /// for bounded numbers and `enum` for options with a fixed set of values.
+    /// This is directly consumable by JSON Schema form generators.
+    ///
+    /// If Verovio's option description cannot be parsed, the schema has no
+    /// properties.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    ///
+    /// let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+    /// std::fs::write("options.schema.json", toolkit.options_schema_json())
+    ///     .expect("Failed to write schema");
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`available_options`](Self::available_options) - Typed option descriptions
+    #[must_use]
+    pub fn options_schema_json(&self) -> String {
+        let descriptors = self.available_options().unwrap_or_default();
+        crate::schema::to_json_schema(&descriptors).to_string()
+    }
+
+    /// Resets all options to their default values.
+    ///
+    /// If a document is loaded, its layout is redone with the defaults.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    ///
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// toolkit.reset_options();
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`set_options`](Self::set_options) - Set rendering options
+    /// - [`get_options`](Self::get_options) - Get current options as JSON
+    /// - [`get_default_options`](Self::get_default_options) - Get default options as JSON
+    pub fn reset_options(&mut self) {
+        self.bump_generation();
+        // SAFETY: ptr is valid
+        unsafe { verovioxide_sys::vrvToolkit_resetOptions(self.ptr) };
+        self.text_lang = None;
+        if self.page_count() > 0 {
+            self.relayout(c"{}");
+        }
+    }
+
+    /// Returns the toolkit to the state it was created in.
+    ///
+    /// The loaded document is unloaded, options are reset to their defaults
+    /// and the xml:id seed is reset. Verovio cannot unload a document, so the
+    /// underlying Verovio instance is replaced by a new one using the current
+    /// resource path; bundled resources stay extracted. This is the "start
+    /// over" primitive for pooled toolkits.
+    ///
+    /// Any retained source is dropped, so [`content_hash`](Self::content_hash)
+    /// fails until the next load. Settings that are not Verovio options, such
+    /// as [`set_retain_source`](Self::set_retain_source) and the
+    /// [`on_relayout`](Self::on_relayout) hook, are kept.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the new Verovio instance cannot be created. The
+    /// toolkit is left unchanged in that case.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    ///
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// // ... load data, set options ...
+    ///
+    /// toolkit.reset().expect("Failed to reset toolkit");
+    /// assert_eq!(toolkit.page_count(), 0);
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`reset_options`](Self::reset_options) - Reset the options only
+    /// - [`reset_xml_id_seed`](Self::reset_xml_id_seed) - Reset the id seed only
+    pub fn reset(&mut self) -> Result<()> {
+        let resource_path = self.get_resource_path();
+        let ptr = if resource_path.is_empty() {
+            // SAFETY: This function has no preconditions
+            unsafe { verovioxide_sys::vrvToolkit_constructorNoResource() }
+        } else {
+            let c_path = CString::new(resource_path)?;
+            // SAFETY: c_path is a valid null-terminated string
+            unsafe { verovioxide_sys::vrvToolkit_constructorResourcePath(c_path.as_ptr()) }
+        };
+        if ptr.is_null() {
+            return Err(Error::InitializationError(
+                "failed to create toolkit for reset".into(),
+            ));
+        }
+
+        // SAFETY: self.ptr is valid and was created by a constructor function;
+        // it is replaced before it can be used again
+        unsafe { verovioxide_sys::vrvToolkit_destructor(self.ptr) };
+        self.ptr = ptr;
+        self.loaded = false;
+        self.source = None;
+        self.text_lang = None;
+        self.applied_font.replace(None);
+        self.reset_xml_id_seed(0);
+        Ok(())
+    }
+
+    /// Exports the options that differ from Verovio's defaults as a compact
+    /// JSON object.
+    ///
+    /// The result is a portable style file: applying it to another toolkit with
+    /// [`import_config`](Self::import_config) reproduces this toolkit's
+    /// rendering configuration. A toolkit with default options exports `{}`.
+    /// The [`text_lang`](Options::text_lang) is exported as `textLang`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the current or default options cannot be parsed.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::{Options, Toolkit};
+    ///
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// toolkit
+    ///     .set_options(&Options::builder().scale(60).build())
+    ///     .expect("Failed to set options");
+    ///
+    /// let config = toolkit.export_config().expect("Failed to export config");
+    /// std::fs::write("style.json", config).expect("Failed to write config");
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`import_config`](Self::import_config) - Apply an exported configuration
+    /// - [`get_options`](Self::get_options) - All current options
+    pub fn export_config(&self) -> Result<String> {
+        let mut diff =
+            crate::options::options_diff(&self.get_options(), &self.get_default_options())
+                .map_err(|e| Error::OptionsError(e.to_string()))?;
+        if let Some(lang) = &self.text_lang {
+            diff.insert("textLang".into(), lang.as_str().into());
+        }
+        Ok(serde_json::Value::Object(diff).to_string())
+    }
+
+    /// Enables or disables keeping a copy of the loaded data.
+    ///
+    /// While enabled, every load keeps the data it was given (the file
+    /// contents for [`load_file`](Self::load_file), the ZIP data for the
+    /// compressed loaders), which [`content_hash`](Self::content_hash) needs.
+    /// Retention is disabled by default to avoid holding a second copy of
+    /// large documents. Disabling it drops the retained copy; enabling it
+    /// takes effect from the next load.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether loads keep a copy of their data
+    ///
+    /// # See also
+    ///
+    /// - [`content_hash`](Self::content_hash) - Hash the source and options
+    pub fn set_retain_source(&mut self, enabled: bool) {
+        self.retain_source = enabled;
+        if !enabled {
+            self.source = None;
+        }
+    }
+
+    /// Returns a hash of the loaded source and the options that differ from
+    /// Verovio's defaults, for use as a cache key.
+    ///
+    /// The hash covers the retained source bytes and the JSON of
+    /// [`export_config`](Self::export_config), so two toolkits that loaded the
+    /// same data with the same options get the same hash, and changing any
+    /// option changes it. It is a 64-bit FNV-1a hash, which is stable across
+    /// releases and platforms and suitable for keying a disk cache, but not
+    /// collision-resistant against crafted input. The Verovio version is not
+    /// included; add [`version`](Self::version) to the key if cached
+    /// renderings must not outlive an upgrade.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - No source is retained, because
+    ///   [`set_retain_source`](Self::set_retain_source) was not enabled before
+    ///   loading, the last load failed, or the file passed to
+    ///   [`load_file`](Self::load_file) could not be read back
+    /// - The current or default options cannot be parsed
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    ///
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// toolkit.set_retain_source(true);
+    /// // ... load data and set options ...
+    ///
+    /// let key = toolkit.content_hash().expect("Failed to hash");
+    /// let cache_file = format!("cache/{:016x}.svg", key);
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`set_retain_source`](Self::set_retain_source) - Keep the loaded data
+    /// - [`export_config`](Self::export_config) - The hashed options
+    pub fn content_hash(&self) -> Result<u64> {
+        let source = self.source.as_deref().ok_or_else(|| {
+            Error::LoadError(
+                "no retained source; call set_retain_source(true) before loading".into(),
+            )
+        })?;
+        let config = self.export_config()?;
+
+        Ok(fnv1a(&[source, b"\0", config.as_bytes()]))
+    }
+
+    /// Applies a configuration exported with
+    /// [`export_config`](Self::export_config) and redoes the layout if a
+    /// document is loaded.
+    ///
+    /// Options not named in `json` keep their current values.
+    ///
+    /// # Arguments
+    ///
+    /// * `json` - A JSON object of Verovio options
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `json` is not a JSON object or Verovio rejects the
+    /// options.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    ///
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// let config = std::fs::read_to_string("style.json").expect("Failed to read config");
+    /// toolkit.import_config(&config).expect("Failed to import config");
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`export_config`](Self::export_config) - Export the current configuration
+    pub fn import_config(&mut self, json: &str) -> Result<()> {
+        let mut config = serde_json::from_str::<serde_json::Map<String, serde_json::Value>>(json)
+            .map_err(|e| Error::OptionsError(format!("invalid config: {}", e)))?;
+        let text_lang = match config.remove("textLang") {
+            Some(serde_json::Value::String(lang)) => Some(lang),
+            Some(_) => {
+                return Err(Error::OptionsError(
+                    "invalid config: textLang must be a string".into(),
+                ));
+            }
+            None => None,
+        };
+        if let Some(lang) = text_lang.as_deref().filter(|lang| !lang.is_empty()) {
+            crate::svg::check_lang(lang)?;
+        }
+
+        self.bump_generation();
+        self.set_options_json(&serde_json::Value::Object(config).to_string())?;
+        if let Some(lang) = text_lang {
+            self.text_lang = Some(lang).filter(|lang| !lang.is_empty());
+        }
+        self.redo_layout_if_loaded()
+    }
+
+    /// Returns the Verovio version string.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    ///
+    /// let toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// println!("Verovio version: {}", toolkit.version());
+    /// ```
+    #[must_use]
+    pub fn version(&self) -> String {
+        // SAFETY: ptr is valid
+        let version_ptr = unsafe { verovioxide_sys::vrvToolkit_getVersion(self.ptr) };
+        self.ptr_to_string(version_ptr)
+            .unwrap_or_else(|| "unknown".to_string())
+    }
+
+    /// Returns the log output from Verovio.
+    ///
+    /// Log output is only available if logging to buffer was enabled before
+    /// loading data. Use [`enable_log_to_buffer()`](Self::enable_log_to_buffer)
+    /// to enable it.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    ///
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// Toolkit::enable_log_to_buffer(true);
+    /// // ... load data ...
+    /// let log = toolkit.get_log();
+    /// println!("Verovio log: {}", log);
+    /// ```
+    #[must_use]
+    pub fn get_log(&self) -> String {
+        // SAFETY: ptr is valid
+        let log_ptr = unsafe { verovioxide_sys::vrvToolkit_getLog(self.ptr) };
+        self.ptr_to_string(log_ptr).unwrap_or_default()
+    }
+
+    /// Reads the title, composer, lyricist and copyright from the header of the
+    /// loaded document.
+    ///
+    /// The values come from the `<fileDesc>` of the exported MEI header, so
+    /// they are available for every input format Verovio converts into one,
+    /// such as MusicXML work titles and creators. Missing values are `None`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no data has been loaded or the MEI export fails.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    ///
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// // ... load data ...
+    ///
+    /// let metadata = toolkit.metadata().expect("Failed to read metadata");
+    /// if let Some(title) = metadata.title {
+    ///     println!("Title: {}", title);
+    /// }
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`get_mei`](Self::get_mei) - The full MEI export
+    pub fn metadata(&self) -> Result<crate::DocumentMetadata> {
+        self.check_loaded()?;
+
+        crate::mei::metadata(&self.get_mei()?)
+    }
+
+    /// Extracts the text of the loaded document: lyrics, dynamics, tempo
+    /// indications and other text on the score.
+    ///
+    /// The text is read from the MEI export, so it is available for every input
+    /// format. Each lyric syllable, dynamic, tempo, direction, harmony label,
+    /// rehearsal mark or fingering is one [`TextSpan`](crate::TextSpan), in
+    /// document order. SMuFL glyphs with a Unicode equivalent, such as the
+    /// note in a metronome marking, are converted; other music-font glyphs are
+    /// dropped. Header text such as the title is not included; see
+    /// [`metadata`](Self::metadata).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no data has been loaded or the MEI export fails.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::{TextKind, Toolkit};
+    ///
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// // ... load data ...
+    ///
+    /// let lyrics: Vec<String> = toolkit
+    ///     .extract_text()
+    ///     .expect("Failed to extract text")
+    ///     .into_iter()
+    ///     .filter(|span| span.kind == TextKind::Lyric)
+    ///     .map(|span| span.text)
+    ///     .collect();
+    /// println!("{}", lyrics.join(" "));
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`metadata`](Self::metadata) - Read the header text
+    pub fn extract_text(&self) -> Result<Vec<crate::TextSpan>> {
+        self.check_loaded()?;
+
+        crate::mei::text_spans(&self.get_mei()?)
+    }
+
+    /// Returns `true` if the loaded document contains any notes or rests.
+    ///
+    /// A document with a valid structure but empty layers loads and lays out
+    /// into pages like any other, so [`page_count`](Self::page_count) cannot
+    /// tell it apart. Use this to warn about empty uploads.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no data has been loaded or the MEI export fails.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    ///
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// // ... load data ...
+    ///
+    /// if !toolkit.has_musical_content().expect("Failed to inspect document") {
+    ///     eprintln!("The document contains no notes or rests");
+    /// }
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`page_count`](Self::page_count) - Number of pages after layout
+    pub fn has_musical_content(&self) -> Result<bool> {
+        self.check_loaded()?;
+
+        crate::mei::has_events(&self.get_mei()?)
+    }
+
+    /// Reports structural problems in the loaded document.
+    ///
+    /// Issues come from two sources:
+    ///
+    /// - Warnings and errors in Verovio's log, such as unsupported elements
+    ///   skipped on import. These are only available if logging to buffer was
+    ///   enabled with [`enable_log_to_buffer`](Self::enable_log_to_buffer)
+    ///   before the document was loaded.
+    /// - Checks on the exported MEI: slurs, ties and other spanning elements
+    ///   without an end, references to missing elements, and staves without a
+    ///   clef. These issues carry the xml:id of the offending element.
+    ///
+    /// A clean document yields an empty list.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no data has been loaded or the MEI export fails.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    ///
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// Toolkit::enable_log_to_buffer(true);
+    /// // ... load data ...
+    ///
+    /// for issue in toolkit.validate_loaded().expect("Failed to validate") {
+    ///     println!("{:?}: {}", issue.severity, issue.message);
+    /// }
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`validate_pae`](Self::validate_pae) - Validate Plaine & Easie input
+    /// - [`get_log`](Self::get_log) - Raw log output
+    pub fn validate_loaded(&self) -> Result<Vec<crate::ValidationIssue>> {
+        self.check_loaded()?;
+
+        let mut issues = crate::validation::log_issues(&self.get_log());
+        issues.extend(crate::validation::mei_issues(&self.get_mei()?)?);
+        Ok(issues)
+    }
+
+    /// Exports the loaded document as MEI.
+    ///
+    /// The result is cached until the next call that changes the document, its
+    /// layout or the options (see [`generation`](Self::generation)), so repeated
+    /// calls in between are cheap.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no document is loaded or export fails.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    ///
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// // ... load MusicXML or other format ...
+    ///
+    /// let mei = toolkit.get_mei().expect("Failed to export MEI");
+    /// println!("{}", mei);
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`get_mei_with_options`](Self::get_mei_with_options) - Export with custom options
+    /// - [`get_humdrum`](Self::get_humdrum) - Export as Humdrum
+    /// - [`render_to_pae`](Self::render_to_pae) - Export as Plaine & Easie
+    /// - [`render_to_midi`](Self::render_to_midi) - Export as MIDI
+    pub fn get_mei(&self) -> Result<String> {
+        self.cached_export(|cache| &mut cache.mei, || self.get_mei_with_options("{}"))
+    }
+
+    /// Exports the loaded document as MEI with options.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - JSON string with MEI export options
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no document is loaded or export fails.
+    ///
+    /// # See also
+    ///
+    /// - [`get_mei`](Self::get_mei) - Export with default options
+    pub fn get_mei_with_options(&self, options: &str) -> Result<String> {
+        let c_options = CString::new(options)?;
+
+        // SAFETY: ptr is valid, c_options is a valid null-terminated string
+        let mei_ptr = unsafe { verovioxide_sys::vrvToolkit_getMEI(self.ptr, c_options.as_ptr()) };
+
+        self.ptr_to_utf8(mei_ptr, "MEI output")?
+            .ok_or_else(|| Error::RenderError("failed to export MEI".into()))
+    }
+
+    /// Gives every element of the loaded document an encoded `xml:id`.
+    ///
+    /// Formats such as MusicXML have no element ids, so Verovio generates them
+    /// when loading, and again differently on every load. This method exports
+    /// the document as MEI, in which Verovio writes the id of every element,
+    /// and loads that MEI in its place, with the id seed reset to a fixed
+    /// value so that ids generated during the new layout are deterministic.
+    /// Afterwards, ids found in the SVG or the MEI can be used with
+    /// [`element_at_point`](Self::element_at_point),
+    /// [`get_page_with_element`](Self::get_page_with_element) and the other
+    /// element queries, and survive exporting and reloading the document.
+    ///
+    /// Ids already encoded in the document are kept. For ids that are also
+    /// identical across processes, reset the seed with
+    /// [`reset_xml_id_seed`](Self::reset_xml_id_seed) before loading the
+    /// original document as well.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - No data has been loaded
+    /// - The export or the reload fails
+    /// - A note or measure still has no `xml:id` after the reload
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    ///
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// let xml = std::fs::read_to_string("score.musicxml").expect("Failed to read file");
+    /// toolkit.load_data(&xml).expect("Failed to load MusicXML");
+    ///
+    /// toolkit.ensure_ids().expect("Failed to assign ids");
+    /// let mei = toolkit.get_mei().expect("Failed to export MEI");
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`get_mei`](Self::get_mei) - Export as MEI
+    /// - [`reset_xml_id_seed`](Self::reset_xml_id_seed) - Reset the id seed
+    pub fn ensure_ids(&mut self) -> Result<()> {
+        self.check_loaded()?;
+
+        let mei = self.get_mei_with_options("{}")?;
+        self.reset_xml_id_seed(1);
+        self.load_mei_internal(&mei)?;
+
+        let missing = crate::mei::elements_without_id(&self.get_mei()?, &["measure", "note"])?;
+        if missing > 0 {
+            return Err(Error::LoadError(format!(
+                "{} notes or measures have no xml:id",
+                missing
+            )));
+        }
+        Ok(())
+    }
+
+    /// Writes the loaded document as MEI to `writer`.
+    ///
+    /// The MEI is written straight from the buffer Verovio returns, without
+    /// first copying it into a `String`, which halves peak memory for very
+    /// large documents compared to [`get_mei_with_options`](Self::get_mei_with_options)
+    /// followed by a write. The output is not cached.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - Where to write the MEI, e.g. a file or a `Vec<u8>`
+    /// * `options` - The MEI export options
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The export fails or is not valid UTF-8
+    /// - Writing fails
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::fs::File;
+    /// use std::io::BufWriter;
+    /// use verovioxide::{Mei, Toolkit};
+    ///
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// // ... load data ...
+    ///
+    /// let mut file = BufWriter::new(File::create("score.mei").expect("Failed to create file"));
+    /// toolkit
+    ///     .write_mei(&mut file, &Mei::with_options().remove_ids(true))
+    ///     .expect("Failed to write MEI");
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`get_mei_with_options`](Self::get_mei_with_options) - Export MEI as a string
+    /// - [`save_file`](Self::save_file) - Save to a file path
+    pub fn write_mei(
+        &self,
+        writer: &mut impl std::io::Write,
+        options: &crate::MeiOptionsBuilder,
+    ) -> Result<()> {
+        let c_options = CString::new(options.to_json())?;
+
+        // SAFETY: ptr is valid, c_options is a valid null-terminated string
+        let mei_ptr = unsafe { verovioxide_sys::vrvToolkit_getMEI(self.ptr, c_options.as_ptr()) };
+        if mei_ptr.is_null() {
+            return Err(Error::RenderError("failed to export MEI".into()));
+        }
+
+        // SAFETY: mei_ptr is non-null and points to a valid C string owned by the toolkit
+        let mei = unsafe { CStr::from_ptr(mei_ptr) }.to_bytes();
+        std::str::from_utf8(mei).map_err(|_| Error::InvalidUtf8 {
+            context: "MEI output".into(),
+        })?;
+        writer.write_all(mei)?;
+        Ok(())
+    }
+
+    /// Exports the loaded document as Humdrum.
+    ///
+    /// Like [`get_mei`](Self::get_mei), the result is cached until the next
+    /// call that changes the document, its layout or the options.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no document is loaded or export fails.
+    ///
+    /// # See also
+    ///
+    /// - [`get_mei`](Self::get_mei) - Export as MEI
+    /// - [`render_to_pae`](Self::render_to_pae) - Export as Plaine & Easie
+    /// - [`render_to_midi`](Self::render_to_midi) - Export as MIDI
+    pub fn get_humdrum(&self) -> Result<String> {
+        self.cached_export(
+            |cache| &mut cache.humdrum,
+            || {
+                // SAFETY: ptr is valid
+                let humdrum_ptr = unsafe { verovioxide_sys::vrvToolkit_getHumdrum(self.ptr) };
+
+                self.ptr_to_utf8(humdrum_ptr, "Humdrum output")?
+                    .ok_or_else(|| Error::RenderError("failed to export Humdrum".into()))
+            },
+        )
+    }
+
+    // =========================================================================
+    // Conversion Functions
+    // =========================================================================
+
+    /// Converts Humdrum data to processed Humdrum.
+    ///
+    /// This method processes Humdrum data through Verovio's internal pipeline,
+    /// which can normalize and enhance the data.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Humdrum data as a string
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The data contains a null byte
+    /// - Conversion fails
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    ///
+    /// let toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// let humdrum_data = "**kern\n4c\n*-\n";
+    /// let processed = toolkit.convert_humdrum_to_humdrum(humdrum_data)
+    ///     .expect("Failed to convert");
+    /// println!("{}", processed);
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`convert_humdrum_to_midi`](Self::convert_humdrum_to_midi) - Convert to MIDI
+    /// - [`convert_mei_to_humdrum`](Self::convert_mei_to_humdrum) - Convert MEI to Humdrum
+    /// - [`get_humdrum`](Self::get_humdrum) - Get Humdrum from loaded document
+    pub fn convert_humdrum_to_humdrum(&self, data: &str) -> Result<String> {
+        let c_data = CString::new(data)?;
+
+        // SAFETY: ptr is valid, c_data is a valid null-terminated string
+        let result_ptr = unsafe {
+            verovioxide_sys::vrvToolkit_convertHumdrumToHumdrum(self.ptr, c_data.as_ptr())
+        };
+
+        self.ptr_to_utf8(result_ptr, "Humdrum output")?
+            .ok_or_else(|| Error::RenderError("failed to convert Humdrum to Humdrum".into()))
+    }
+
+    /// Converts Humdrum data to MIDI (base64-encoded).
+    ///
+    /// This method converts Humdrum data directly to MIDI without loading
+    /// the data into the toolkit first.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Humdrum data as a string
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The data contains a null byte
+    /// - Conversion fails
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    ///
+    /// let toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// let humdrum_data = "**kern\n4c\n*-\n";
+    /// let midi_base64 = toolkit.convert_humdrum_to_midi(humdrum_data)
+    ///     .expect("Failed to convert");
+    /// println!("MIDI (base64): {}", midi_base64);
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`convert_humdrum_to_humdrum`](Self::convert_humdrum_to_humdrum) - Process Humdrum
+    /// - [`render_to_midi`](Self::render_to_midi) - Render loaded document to MIDI
+    pub fn convert_humdrum_to_midi(&self, data: &str) -> Result<String> {
+        let c_data = CString::new(data)?;
+
+        // SAFETY: ptr is valid, c_data is a valid null-terminated string
+        let result_ptr =
+            unsafe { verovioxide_sys::vrvToolkit_convertHumdrumToMIDI(self.ptr, c_data.as_ptr()) };
+
+        self.ptr_to_utf8(result_ptr, "MIDI output")?
+            .ok_or_else(|| Error::RenderError("failed to convert Humdrum to MIDI".into()))
+    }
+
+    /// Converts MEI data to Humdrum.
+    ///
+    /// This method converts MEI data directly to Humdrum without loading
+    /// the data into the toolkit first.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - MEI data as a string
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The data contains a null byte
+    /// - Conversion fails
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    ///
+    /// let toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// let mei_data = r#"<mei xmlns="http://www.music-encoding.org/ns/mei">...</mei>"#;
+    /// let humdrum = toolkit.convert_mei_to_humdrum(mei_data)
+    ///     .expect("Failed to convert");
+    /// println!("{}", humdrum);
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`get_humdrum`](Self::get_humdrum) - Get Humdrum from loaded document
+    /// - [`convert_humdrum_to_humdrum`](Self::convert_humdrum_to_humdrum) - Process Humdrum
+    pub fn convert_mei_to_humdrum(&self, data: &str) -> Result<String> {
+        let c_data = CString::new(data)?;
+
+        // SAFETY: ptr is valid, c_data is a valid null-terminated string
+        let result_ptr =
+            unsafe { verovioxide_sys::vrvToolkit_convertMEIToHumdrum(self.ptr, c_data.as_ptr()) };
+
+        self.ptr_to_utf8(result_ptr, "Humdrum output")?
+            .ok_or_else(|| Error::RenderError("failed to convert MEI to Humdrum".into()))
+    }
+
+    /// Renders data with options in one step.
+    ///
+    /// This is a convenience method that loads data and renders it in a single
+    /// operation. It combines `load_data`, `set_options`, and rendering, and
+    /// the data stays loaded afterwards as with [`load_data`](Self::load_data).
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Music data to render (format auto-detected)
+    /// * `options` - Optional JSON string with rendering options
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The data contains a null byte
+    /// - The toolkit has no resources
+    /// - Loading or rendering fails
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    ///
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// let mei = r#"<mei xmlns="http://www.music-encoding.org/ns/mei">...</mei>"#;
+    /// let options = r#"{"scale": 50}"#;
+    /// let svg = toolkit.render_data(mei, Some(options))
+    ///     .expect("Failed to render");
+    /// println!("{}", svg);
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`load_data`](Self::load_data) - Load data separately
+    /// - [`set_options`](Self::set_options) - Set options separately
+    /// - [`render_to_svg`](Self::render_to_svg) - Render to SVG
+    pub fn render_data(&mut self, data: &str, options: Option<&str>) -> Result<String> {
+        let c_data = CString::new(data)?;
+        let c_options = CString::new(options.unwrap_or("{}"))?;
+        if !self.has_resources() {
+            return Err(resources_required());
+        }
+
+        self.bump_generation();
+        self.source = None;
+        self.loaded = false;
+
+        // SAFETY: ptr is valid, c_data and c_options are valid null-terminated strings
+        let result_ptr = unsafe {
+            verovioxide_sys::vrvToolkit_renderData(self.ptr, c_data.as_ptr(), c_options.as_ptr())
+        };
+
+        // Verovio returns an empty string when the data cannot be loaded
+        let svg = self
+            .ptr_to_utf8(result_ptr, "rendered data")?
+            .filter(|svg| !svg.is_empty())
+            .ok_or_else(|| Error::RenderError("failed to render data".into()))?;
+
+        self.retain(data.as_bytes());
+        self.loaded = true;
+        self.notify_relayout();
+        Ok(svg)
+    }
+
+    /// Scales the MIDI playback tempo, leaving the layout untouched.
+    ///
+    /// This applies only Verovio's `midiTempoAdjustment` option, without
+    /// building a full [`Options`] or redoing the layout, so it is cheap enough
+    /// to call from a tempo slider. The next
+    /// [`render_to_midi`](Self::render_to_midi) uses the new tempo.
+    ///
+    /// The factor multiplies every tempo of the document: tempo markings
+    /// encoded in it, and Verovio's default of 120 BPM where there are none.
+    /// A factor of 0.5 plays the document at half speed and 1.0 restores the
+    /// encoded tempo.
+    ///
+    /// # Arguments
+    ///
+    /// * `factor` - The tempo factor, from 0.2 to 4.0
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `factor` is outside Verovio's range of 0.2..=4.0
+    /// or the option cannot be applied.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    ///
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// // ... load data ...
+    ///
+    /// toolkit.set_midi_tempo_factor(0.8).expect("Failed to set tempo");
+    /// let midi = toolkit.render_to_midi().expect("Failed to render MIDI");
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`midi_tempo`](crate::OptionsBuilder::midi_tempo) - Set the tempo with other options
+    /// - [`render_to_midi`](Self::render_to_midi) - Render MIDI
+    pub fn set_midi_tempo_factor(&mut self, factor: f64) -> Result<()> {
+        if !(0.2..=4.0).contains(&factor) {
+            return Err(Error::OptionsError(format!(
+                "MIDI tempo factor must be between 0.2 and 4.0, got {}",
+                factor
+            )));
+        }
+
+        self.bump_generation();
+        self.set_options_json(&serde_json::json!({ "midiTempoAdjustment": factor }).to_string())
+    }
+
+    /// Renders the loaded document to MIDI as base64-encoded data.
+    ///
+    /// # Performance
+    ///
+    /// MIDI generation traverses the entire score to extract timing and pitch
+    /// information, then base64-encodes the binary MIDI data. For large scores,
+    /// the base64 encoding adds a small overhead. The returned string is
+    /// approximately 33% larger than the raw MIDI binary data.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no document is loaded or rendering fails.
+    ///
+    /// # See also
+    ///
+    /// - [`get_mei`](Self::get_mei) - Export as MEI
+    /// - [`get_humdrum`](Self::get_humdrum) - Export as Humdrum
+    /// - [`render_to_pae`](Self::render_to_pae) - Export as Plaine & Easie
+    /// - [`render_to_timemap`](Self::render_to_timemap) - Get timing information
+    pub fn render_to_midi(&self) -> Result<String> {
+        self.check_loaded()?;
+
+        // SAFETY: ptr is valid, data is loaded
+        let midi_ptr = unsafe { verovioxide_sys::vrvToolkit_renderToMIDI(self.ptr) };
+
+        self.ptr_to_utf8(midi_ptr, "MIDI output")?
+            .ok_or_else(|| Error::RenderError("failed to render MIDI".into()))
+    }
+
+    /// Renders the loaded document to MIDI with each staff on its own channel,
+    /// returning the decoded Standard MIDI File bytes.
+    ///
+    /// Verovio writes one track per staff and takes the channel from the
+    /// staff's `<instrDef midi.channel>`, so without instrument definitions
+    /// every staff plays on channel 0. This method renders from a copy of the
+    /// document in which each staff without an explicit channel is given one:
+    ///
+    /// - Staves are numbered in the order of their first `<staffDef>`.
+    /// - Explicit `@midi.channel` values are kept.
+    /// - Every other staff gets the lowest channel not yet in use, skipping
+    ///   channel 9 (10 in 1-based numbering), which General MIDI reserves for
+    ///   percussion.
+    /// - Beyond 15 staves, channels are reused round-robin.
+    ///
+    /// The loaded document itself is not modified.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no data has been loaded or rendering fails.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    ///
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// // ... load data ...
+    ///
+    /// let midi = toolkit
+    ///     .render_to_midi_multichannel()
+    ///     .expect("Failed to render MIDI");
+    /// std::fs::write("score.mid", midi).expect("Failed to write MIDI");
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`render_to_midi`](Self::render_to_midi) - Base64 MIDI with the encoded channels
+    pub fn render_to_midi_multichannel(&self) -> Result<Vec<u8>> {
+        self.check_loaded()?;
+
+        let mei = crate::mei::assign_midi_channels(&self.get_mei()?)?;
+
+        let mut scratch = self.scratch_toolkit()?;
+        scratch.load_mei_internal(&mei)?;
+        crate::midi::decode_base64(&scratch.render_to_midi()?)
+    }
+
+    /// Renders the loaded document to Plaine & Easie code.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no document is loaded or rendering fails.
+    ///
+    /// # See also
+    ///
+    /// - [`get_mei`](Self::get_mei) - Export as MEI
+    /// - [`get_humdrum`](Self::get_humdrum) - Export as Humdrum
+    /// - [`render_to_midi`](Self::render_to_midi) - Export as MIDI
+    pub fn render_to_pae(&self) -> Result<String> {
+        self.check_loaded()?;
+
+        // SAFETY: ptr is valid, data is loaded
+        let pae_ptr = unsafe { verovioxide_sys::vrvToolkit_renderToPAE(self.ptr) };
+
+        self.ptr_to_utf8(pae_ptr, "PAE output")?
+            .ok_or_else(|| Error::RenderError("failed to render PAE".into()))
+    }
+
+    /// Gets the timemap as JSON.
+    ///
+    /// The timemap provides timing information for elements in the score,
+    /// mapping musical time to milliseconds.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no document is loaded or export fails.
+    ///
+    /// # See also
+    ///
+    /// - [`render_to_timemap_with_options`](Self::render_to_timemap_with_options) - Get timemap with custom options
+    /// - [`get_elements_at_time`](Self::get_elements_at_time) - Get elements at a specific time
+    /// - [`get_time_for_element`](Self::get_time_for_element) - Get time for a specific element
+    /// - [`render_to_midi`](Self::render_to_midi) - Export as MIDI (includes timing)
+    pub fn render_to_timemap(&self) -> Result<String> {
+        self.render_to_timemap_with_options("{}")
+    }
+
+    /// Gets the timemap as JSON with options.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - JSON string with timemap options
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no document is loaded or export fails.
+    ///
+    /// # See also
+    ///
+    /// - [`render_to_timemap`](Self::render_to_timemap) - Get timemap with default options
+    /// - [`get_elements_at_time`](Self::get_elements_at_time) - Get elements at a specific time
+    /// - [`get_time_for_element`](Self::get_time_for_element) - Get time for a specific element
+    pub fn render_to_timemap_with_options(&self, options: &str) -> Result<String> {
+        let c_options = CString::new(options)?;
+
+        // SAFETY: ptr is valid, c_options is a valid null-terminated string
+        let timemap_ptr =
+            unsafe { verovioxide_sys::vrvToolkit_renderToTimemap(self.ptr, c_options.as_ptr()) };
+
+        self.ptr_to_utf8(timemap_ptr, "timemap")?
+            .ok_or_else(|| Error::RenderError("failed to render timemap".into()))
+    }
+
+    /// Gets the expansion map as JSON.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no document is loaded or export fails.
+    pub fn render_to_expansion_map(&self) -> Result<String> {
+        // SAFETY: ptr is valid
+        let map_ptr = unsafe { verovioxide_sys::vrvToolkit_renderToExpansionMap(self.ptr) };
+
+        self.ptr_to_utf8(map_ptr, "expansion map")?
+            .ok_or_else(|| Error::RenderError("failed to render expansion map".into()))
+    }
+
+    // =========================================================================
+    // File Output Functions
+    // =========================================================================
+
+    /// Renders a page to SVG and saves to a file.
+    ///
+    /// This is a convenience method that combines rendering and file writing
+    /// in a single operation.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path to the output file
+    /// * `page` - The page number to render (1-based)
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - No data has been loaded
+    /// - The page number is out of range
+    /// - The path contains invalid UTF-8
+    /// - Writing the file fails
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    /// use std::path::Path;
+    ///
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// // ... load data ...
+    /// toolkit.render_to_svg_file(Path::new("output.svg"), 1)
+    ///     .expect("Failed to save SVG");
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`render_to_svg`](Self::render_to_svg) - Render to string
+    /// - [`render_to_midi_file`](Self::render_to_midi_file) - Save MIDI to file
+    pub fn render_to_svg_file(&self, path: &Path, page: u32) -> Result<()> {
+        let path_str = path.to_str().ok_or_else(|| invalid_path("file path"))?;
+
+        let c_path = CString::new(path_str)?;
+        self.check_svg_page(page)?;
+
+        // SAFETY: ptr is valid, c_path is a valid null-terminated string
+        let success = unsafe {
+            verovioxide_sys::vrvToolkit_renderToSVGFile(self.ptr, c_path.as_ptr(), page as i32)
+        };
+
+        if success {
+            Ok(())
+        } else {
+            Err(Error::RenderError(format!(
+                "failed to save SVG to file: {}",
+                path.display()
+            )))
+        }
+    }
+
+    /// Renders the document to MIDI and saves to a file.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path to the output MIDI file
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - No data has been loaded
+    /// - The path contains invalid UTF-8
+    /// - Writing the file fails
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    /// use std::path::Path;
+    ///
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// // ... load data ...
+    /// toolkit.render_to_midi_file(Path::new("output.mid"))
+    ///     .expect("Failed to save MIDI");
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`render_to_midi`](Self::render_to_midi) - Render to base64 string
+    /// - [`render_to_svg_file`](Self::render_to_svg_file) - Save SVG to file
+    pub fn render_to_midi_file(&self, path: &Path) -> Result<()> {
+        let path_str = path.to_str().ok_or_else(|| invalid_path("file path"))?;
+
+        let c_path = CString::new(path_str)?;
+
+        // SAFETY: ptr is valid, c_path is a valid null-terminated string
+        let success =
+            unsafe { verovioxide_sys::vrvToolkit_renderToMIDIFile(self.ptr, c_path.as_ptr()) };
+
+        if success {
+            Ok(())
+        } else {
+            Err(Error::RenderError(format!(
+                "failed to save MIDI to file: {}",
+                path.display()
+            )))
+        }
+    }
+
+    /// Renders the document to PAE and saves to a file.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path to the output PAE file
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - No data has been loaded
+    /// - The path contains invalid UTF-8
+    /// - Writing the file fails
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    /// use std::path::Path;
+    ///
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// // ... load data ...
+    /// toolkit.render_to_pae_file(Path::new("output.pae"))
+    ///     .expect("Failed to save PAE");
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`render_to_pae`](Self::render_to_pae) - Render to string
+    /// - [`validate_pae`](Self::validate_pae) - Validate PAE code
+    pub fn render_to_pae_file(&self, path: &Path) -> Result<()> {
+        let path_str = path.to_str().ok_or_else(|| invalid_path("file path"))?;
+
+        let c_path = CString::new(path_str)?;
+
+        // SAFETY: ptr is valid, c_path is a valid null-terminated string
+        let success =
+            unsafe { verovioxide_sys::vrvToolkit_renderToPAEFile(self.ptr, c_path.as_ptr()) };
+
+        if success {
+            Ok(())
+        } else {
+            Err(Error::RenderError(format!(
+                "failed to save PAE to file: {}",
+                path.display()
+            )))
+        }
+    }
+
+    /// Renders the expansion map and saves to a file.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path to the output file
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - No data has been loaded
+    /// - The path contains invalid UTF-8
+    /// - Writing the file fails
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    /// use std::path::Path;
+    ///
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// // ... load data ...
+    /// toolkit.render_to_expansion_map_file(Path::new("expansion_map.json"))
+    ///     .expect("Failed to save expansion map");
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`render_to_expansion_map`](Self::render_to_expansion_map) - Render to string
+    pub fn render_to_expansion_map_file(&self, path: &Path) -> Result<()> {
+        let path_str = path.to_str().ok_or_else(|| invalid_path("file path"))?;
+
+        let c_path = CString::new(path_str)?;
+
+        // SAFETY: ptr is valid, c_path is a valid null-terminated string
+        let success = unsafe {
+            verovioxide_sys::vrvToolkit_renderToExpansionMapFile(self.ptr, c_path.as_ptr())
+        };
+
+        if success {
+            Ok(())
+        } else {
+            Err(Error::RenderError(format!(
+                "failed to save expansion map to file: {}",
+                path.display()
+            )))
+        }
+    }
+
+    /// Renders the timemap and saves to a file.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path to the output file
+    /// * `options` - Optional JSON string with timemap options
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - No data has been loaded
+    /// - The path contains invalid UTF-8
+    /// - Writing the file fails
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    /// use std::path::Path;
+    ///
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// // ... load data ...
+    /// toolkit.render_to_timemap_file(Path::new("timemap.json"), None)
+    ///     .expect("Failed to save timemap");
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`render_to_timemap`](Self::render_to_timemap) - Render to string
+    /// - [`render_to_timemap_with_options`](Self::render_to_timemap_with_options) - Render with options
+    pub fn render_to_timemap_file(&self, path: &Path, options: Option<&str>) -> Result<()> {
+        let path_str = path.to_str().ok_or_else(|| invalid_path("file path"))?;
+
+        let c_path = CString::new(path_str)?;
+        let c_options = CString::new(options.unwrap_or("{}"))?;
+
+        // SAFETY: ptr is valid, c_path and c_options are valid null-terminated strings
+        let success = unsafe {
+            verovioxide_sys::vrvToolkit_renderToTimemapFile(
+                self.ptr,
+                c_path.as_ptr(),
+                c_options.as_ptr(),
+            )
+        };
+
+        if success {
+            Ok(())
+        } else {
+            Err(Error::RenderError(format!(
+                "failed to save timemap to file: {}",
+                path.display()
+            )))
+        }
+    }
+
+    /// Saves the document to a file with options.
+    ///
+    /// This method saves the currently loaded document to a file. The output
+    /// format depends on the options and the configured output format.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path to the output file
+    /// * `options` - Optional JSON string with save options
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - No data has been loaded
+    /// - The path contains invalid UTF-8
+    /// - Writing the file fails
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    /// use std::path::Path;
+    ///
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// // ... load data ...
+    /// toolkit.save_file(Path::new("output.mei"), None)
+    ///     .expect("Failed to save file");
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`get_mei`](Self::get_mei) - Get MEI as string
+    /// - [`set_output_to`](Self::set_output_to) - Set output format
+    pub fn save_file(&self, path: &Path, options: Option<&str>) -> Result<()> {
+        let path_str = path.to_str().ok_or_else(|| invalid_path("file path"))?;
+
+        let c_path = CString::new(path_str)?;
+        let c_options = CString::new(options.unwrap_or("{}"))?;
+
+        // SAFETY: ptr is valid, c_path and c_options are valid null-terminated strings
+        let success = unsafe {
+            verovioxide_sys::vrvToolkit_saveFile(self.ptr, c_path.as_ptr(), c_options.as_ptr())
+        };
+
+        if success {
+            Ok(())
+        } else {
+            Err(Error::RenderError(format!(
+                "failed to save to file: {}",
+                path.display()
+            )))
+        }
+    }
+
+    /// Saves the Humdrum representation to a file.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path to the output file
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - No data has been loaded
+    /// - The path contains invalid UTF-8
+    /// - Writing the file fails
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    /// use std::path::Path;
+    ///
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// // ... load data ...
+    /// toolkit.save_humdrum_to_file(Path::new("output.krn"))
+    ///     .expect("Failed to save Humdrum");
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`get_humdrum`](Self::get_humdrum) - Get Humdrum as string
+    pub fn save_humdrum_to_file(&self, path: &Path) -> Result<()> {
+        let path_str = path.to_str().ok_or_else(|| invalid_path("file path"))?;
+
+        let c_path = CString::new(path_str)?;
+
+        // SAFETY: ptr is valid, c_path is a valid null-terminated string
+        let success =
+            unsafe { verovioxide_sys::vrvToolkit_getHumdrumFile(self.ptr, c_path.as_ptr()) };
+
+        if success {
+            Ok(())
+        } else {
+            Err(Error::RenderError(format!(
+                "failed to save Humdrum to file: {}",
+                path.display()
+            )))
+        }
+    }
+
+    /// Gets the current rendering scale as a percentage.
+    ///
+    /// The scale is never negative; a negative value reported by Verovio is
+    /// returned as 0.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    ///
+    /// let toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// let scale = toolkit.scale();
+    /// println!("Current scale: {}%", scale);
+    ///
+    /// // The scale affects the rendered output size
+    /// if scale < 100 {
+    ///     println!("Rendering at reduced size");
+    /// }
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`set_scale`](Self::set_scale) - Set the scale percentage
+    #[must_use]
+    pub fn scale(&self) -> u32 {
+        // SAFETY: ptr is valid
+        let scale = unsafe { verovioxide_sys::vrvToolkit_getScale(self.ptr) };
+        u32::try_from(scale).unwrap_or(0)
+    }
+
+    /// Gets the current rendering scale as a percentage.
+    #[deprecated(since = "0.4.0", note = "use `scale`, which returns a `u32`")]
+    #[must_use]
+    pub fn get_scale(&self) -> i32 {
+        // SAFETY: ptr is valid
+        unsafe { verovioxide_sys::vrvToolkit_getScale(self.ptr) }
+    }
+
+    /// Sets the rendering scale as a percentage.
+    ///
+    /// # Arguments
+    ///
+    /// * `scale` - Scale percentage (e.g., 100 for 100%)
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the scale is negative or Verovio rejects it.
+    ///
+    /// # Performance
+    ///
+    /// If the scale changes, the [`generation`](Self::generation) changes, and
+    /// if a document is loaded the layout is redone right away, as for
+    /// [`set_options`](Self::set_options).
+    pub fn set_scale(&mut self, scale: i32) -> Result<()> {
+        if scale < 0 {
+            return Err(Error::OptionsError(format!("invalid scale: {}", scale)));
+        }
+
+        let previous = self.scale();
+        // SAFETY: ptr is valid
+        let success = unsafe { verovioxide_sys::vrvToolkit_setScale(self.ptr, scale) };
+
+        if !success {
+            return Err(Error::OptionsError(format!("invalid scale: {}", scale)));
+        }
+        if u32::try_from(scale) != Ok(previous) {
+            self.bump_generation();
+            self.redo_layout_if_loaded()?;
+        }
+        Ok(())
+    }
+
+    /// Sets the rendering scale from a [`ScaleMode`] and redoes the layout if a
+    /// document is loaded.
+    ///
+    /// For [`ScaleMode::FitWidth`] and [`ScaleMode::FitHeight`], the scale is
+    /// the percentage at which the current `page_width` or `page_height` spans
+    /// the given number of pixels, rounded and clamped to Verovio's range of
+    /// 1 to 1000. The page dimensions themselves are kept, so the layout (the
+    /// measures per system) does not change, only the size it is drawn at.
+    /// With [`adjust_page_width`](crate::OptionsBuilder::adjust_page_width) or
+    /// [`adjust_page_height`](crate::OptionsBuilder::adjust_page_height), pages
+    /// are cropped to their content and come out smaller than the target.
+    ///
+    /// # Arguments
+    ///
+    /// * `mode` - How to choose the scale
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the percentage or target size is 0, or the options
+    /// cannot be read or applied.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::{ScaleMode, Toolkit};
+    ///
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// // ... load data ...
+    ///
+    /// // Fit pages to an 800 px wide container
+    /// toolkit
+    ///     .apply_scale_mode(ScaleMode::FitWidth(800))
+    ///     .expect("Failed to apply scale");
+    /// println!("scale: {}%", toolkit.scale());
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`set_scale`](Self::set_scale) - Set the scale percentage directly
+    /// - [`page_view_box`](Self::page_view_box) - Inspect the resulting page size
+    pub fn apply_scale_mode(&mut self, mode: ScaleMode) -> Result<()> {
+        const MIN_SCALE: f64 = 1.0;
+        const MAX_SCALE: f64 = 1000.0;
+
+        let fit = |pixels: u32, page_size: Option<u32>| -> Result<u32> {
+            let page_size = page_size
+                .filter(|&size| size > 0)
+                .ok_or_else(|| Error::OptionsError("options have no page size".into()))?;
+            let scale = f64::from(pixels) * 100.0 / f64::from(page_size);
+            Ok(scale.round().clamp(MIN_SCALE, MAX_SCALE) as u32)
+        };
+
+        let scale = match mode {
+            ScaleMode::Percent(0) | ScaleMode::FitWidth(0) | ScaleMode::FitHeight(0) => {
+                return Err(Error::OptionsError(format!(
+                    "invalid scale mode: {:?}",
+                    mode
+                )));
+            }
+            ScaleMode::Percent(percent) => percent,
+            ScaleMode::FitWidth(width) => fit(width, self.current_options()?.page_width)?,
+            ScaleMode::FitHeight(height) => fit(height, self.current_options()?.page_height)?,
+        };
+
+        self.apply_options(&Options::builder().scale(scale).build())?;
+        self.redo_layout_if_loaded()
+    }
+
+    /// Sets the break mode and redoes the layout if a document is loaded.
+    ///
+    /// This is a shortcut for building an [`Options`] with only
+    /// [`breaks`](crate::OptionsBuilder::breaks) set, applying it, and calling
+    /// [`redo_layout`](Self::redo_layout). Other options are left untouched.
+    /// With [`BreakMode::Encoded`], the page and system breaks of the input are
+    /// kept: `<pb>` and `<sb>` in MEI, and `<print new-page="yes">` and
+    /// `<print new-system="yes">` in MusicXML. The mode can be set before or
+    /// after loading.
+    ///
+    /// # Arguments
+    ///
+    /// * `mode` - The break mode to use
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the option cannot be applied.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::{Toolkit, BreakMode};
+    ///
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// // ... load data ...
+    /// toolkit.set_breaks(BreakMode::None).expect("Failed to set breaks");
+    /// println!("Now {} page(s)", toolkit.page_count());
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`set_condense`](Self::set_condense) - Set the condense mode
+    /// - [`set_options`](Self::set_options) - Set several options at once
+    pub fn set_breaks(&mut self, mode: BreakMode) -> Result<()> {
+        self.apply_options(&Options::builder().breaks(mode).build())?;
+        self.redo_layout_if_loaded()
+    }
+
+    /// Sets the condense mode and redoes the layout if a document is loaded.
+    ///
+    /// This is a shortcut for building an [`Options`] with only
+    /// [`condense`](crate::OptionsBuilder::condense) set, applying it, and calling
+    /// [`redo_layout`](Self::redo_layout). Other options are left untouched.
+    ///
+    /// # Arguments
+    ///
+    /// * `mode` - The condense mode to use
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the option cannot be applied.
+    ///
+    /// # See also
+    ///
+    /// - [`set_breaks`](Self::set_breaks) - Set the break mode
+    /// - [`set_options`](Self::set_options) - Set several options at once
+    pub fn set_condense(&mut self, mode: CondenseMode) -> Result<()> {
+        self.apply_options(&Options::builder().condense(mode).build())?;
+        self.redo_layout_if_loaded()
+    }
+
+    /// Lays the document out with at most `n` systems per page and redoes the
+    /// layout if a document is loaded.
+    ///
+    /// This sets Verovio's `systemMaxPerPage` option. It is best-effort: a page
+    /// gets fewer systems when `n` do not fit on it, and the systems themselves
+    /// are still laid out by the break mode. If breaks are disabled
+    /// ([`BreakMode::None`]) they are switched to [`BreakMode::Auto`], since
+    /// the limit has no effect otherwise; other break modes are kept.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The maximum number of systems per page, at least 1
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `n` is 0 or the options cannot be applied.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    ///
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// // ... load data ...
+    ///
+    /// toolkit.set_systems_per_page(4).expect("Failed to set systems per page");
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`set_breaks`](Self::set_breaks) - Set the break mode
+    /// - [`layout_summary`](Self::layout_summary) - Count pages and systems
+    pub fn set_systems_per_page(&mut self, n: u32) -> Result<()> {
+        if n == 0 {
+            return Err(Error::OptionsError(
+                "systems per page must be at least 1".into(),
+            ));
+        }
+
+        let mut options = Options::builder().system_max_per_page(n);
+        if self.current_options()?.breaks == Some(BreakMode::None) {
+            options = options.breaks(BreakMode::Auto);
+        }
+        self.apply_options(&options.build())?;
+        self.redo_layout_if_loaded()
+    }
+
+    /// Searches for the page width that lays the document out with about
+    /// `target_systems_per_page` systems per page, and applies it.
+    ///
+    /// The page width is binary-searched between Verovio's bounds, redoing the
+    /// layout at every step, for at most 20 steps. The width whose average
+    /// number of systems per page comes closest to the target is kept. This
+    /// suits responsive embedding, where the container decides the page
+    /// height but the number of systems should stay tidy.
+    ///
+    /// Wider pages hold more measures per system, so fewer systems are needed.
+    /// The number of systems on a page is also limited by the page height:
+    /// for documents longer than one page, set a large `page_height` or use
+    /// [`fit_to_content`](Self::fit_to_content) so the height does not cap
+    /// the count. Like [`set_systems_per_page`](Self::set_systems_per_page),
+    /// this switches [`BreakMode::None`] to [`BreakMode::Auto`].
+    ///
+    /// # Performance
+    ///
+    /// Each search step performs a full layout and a page-based MEI export to
+    /// count the systems, so this costs up to 20 layouts of the document.
+    ///
+    /// # Arguments
+    ///
+    /// * `target_systems_per_page` - The desired number of systems per page, at least 1
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - `target_systems_per_page` is 0
+    /// - No data has been loaded
+    /// - The options cannot be applied or the layout cannot be inspected
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    ///
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// // ... load data ...
+    ///
+    /// toolkit.fit_width_to_systems(4).expect("Failed to fit page width");
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`set_systems_per_page`](Self::set_systems_per_page) - Cap systems per page
+    /// - [`layout_summary`](Self::layout_summary) - Count pages and systems
+    pub fn fit_width_to_systems(&mut self, target_systems_per_page: u32) -> Result<()> {
+        const MIN_PAGE_WIDTH: u32 = 100;
+        const MAX_PAGE_WIDTH: u32 = 100_000;
+        const MAX_STEPS: usize = 20;
+
+        if target_systems_per_page == 0 {
+            return Err(Error::OptionsError(
+                "systems per page must be at least 1".into(),
+            ));
+        }
+        self.check_loaded()?;
+        if self.current_options()?.breaks == Some(BreakMode::None) {
+            self.apply_options(&Options::builder().breaks(BreakMode::Auto).build())?;
+        }
+
+        let (mut low, mut high) = (MIN_PAGE_WIDTH, MAX_PAGE_WIDTH);
+        let mut best: Option<(u32, u32)> = None;
+        for _ in 0..MAX_STEPS {
+            if low > high {
+                break;
+            }
+            let width = low + (high - low) / 2;
+            self.apply_options(&Options::builder().page_width(width).build())?;
+            self.redo_layout(None)?;
+
+            let summary = self.layout_summary()?;
+            let systems = (f64::from(summary.systems) / f64::from(summary.pages)).round() as u32;
+            let distance = systems.abs_diff(target_systems_per_page);
+            if best.is_none_or(|(best_distance, _)| distance < best_distance) {
+                best = Some((distance, width));
+            }
+
+            match systems.cmp(&target_systems_per_page) {
+                std::cmp::Ordering::Greater => low = width + 1,
+                std::cmp::Ordering::Less => high = width - 1,
+                std::cmp::Ordering::Equal => break,
+            }
+        }
+
+        if let Some((_, width)) = best {
+            self.apply_options(&Options::builder().page_width(width).build())?;
+            self.redo_layout(None)?;
+        }
+        Ok(())
+    }
+
+    /// Crops pages to their content and redoes the layout if a document is
+    /// loaded.
+    ///
+    /// This sets both [`adjust_page_height`](crate::OptionsBuilder::adjust_page_height)
+    /// and [`adjust_page_width`](crate::OptionsBuilder::adjust_page_width), so
+    /// each page is only as large as the music it contains. The result is well
+    /// suited to embedding short excerpts in web pages. Other options, including
+    /// `page_width` and `page_height`, are left untouched; they still bound the
+    /// layout before cropping.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the options cannot be applied.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    ///
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// // ... load a short excerpt ...
+    /// toolkit.fit_to_content().expect("Failed to fit page to content");
+    /// let svg = toolkit.render_to_svg(1).expect("Failed to render");
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`page_view_box`](Self::page_view_box) - Inspect the resulting page size
+    /// - [`set_options`](Self::set_options) - Set several options at once
+    pub fn fit_to_content(&mut self) -> Result<()> {
+        self.apply_options(
+            &Options::builder()
+                .adjust_page_height(true)
+                .adjust_page_width(true)
+                .build(),
+        )?;
+        self.redo_layout_if_loaded()
+    }
+
+    /// Applies a compact vertical spacing preset, as for lead sheets, and
+    /// redoes the layout if a document is loaded.
+    ///
+    /// The preset sets:
+    ///
+    /// - [`justify_vertically`](crate::OptionsBuilder::justify_vertically)
+    ///   `false`, so systems are not spread to fill the page
+    /// - [`spacing_system`](crate::OptionsBuilder::spacing_system) `0` (default
+    ///   4), so systems are only kept apart by their content
+    /// - [`spacing_staff`](crate::OptionsBuilder::spacing_staff) `2` (default
+    ///   12), leaving a small gap between the staves of a system
+    /// - [`adjust_page_height`](crate::OptionsBuilder::adjust_page_height)
+    ///   `true`, so the last page ends below its last system
+    ///
+    /// Verovio still keeps elements from colliding, so staves with notes far
+    /// above or below them stay further apart. Other options are left
+    /// untouched.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the options cannot be applied.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    ///
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// // ... load a lead sheet ...
+    ///
+    /// toolkit.compact_vertical().expect("Failed to compact spacing");
+    /// let svg = toolkit.render_to_svg(1).expect("Failed to render");
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`fit_to_content`](Self::fit_to_content) - Crop pages to their content
+    /// - [`page_view_box`](Self::page_view_box) - Inspect the resulting page size
+    pub fn compact_vertical(&mut self) -> Result<()> {
+        let options = Options::builder()
+            .justify_vertically(false)
+            .spacing_system(0)
+            .spacing_staff(2)
+            .adjust_page_height(true)
+            .build();
+        self.apply_options(&options)?;
+        self.redo_layout_if_loaded()
+    }
+
+    /// Returns the page count the loaded document would have under `options`,
+    /// without keeping them.
+    ///
+    /// The options are applied and the layout is redone to read the page
+    /// count; afterwards the previous options are restored and the layout is
+    /// redone again. The [`on_relayout`](Self::on_relayout) hook is not called
+    /// for either layout, since the page count ends up unchanged. This is
+    /// useful for previewing several break or condense settings before
+    /// committing to one.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - The options to estimate the page count with
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no data has been loaded or the options cannot be
+    /// applied.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::{BreakMode, Options, Toolkit};
+    ///
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// // ... load data ...
+    ///
+    /// for mode in [BreakMode::Auto, BreakMode::Line, BreakMode::None] {
+    ///     let options = Options::builder().breaks(mode).build();
+    ///     let pages = toolkit
+    ///         .estimate_page_count(&options)
+    ///         .expect("Failed to estimate page count");
+    ///     println!("{:?}: {} pages", mode, pages);
+    /// }
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`page_count`](Self::page_count) - Page count under the current options
+    /// - [`set_options`](Self::set_options) - Apply options permanently
+    pub fn estimate_page_count(&mut self, options: &Options) -> Result<u32> {
+        self.check_loaded()?;
+
+        let mut candidate = options.clone();
+        candidate.text_lang = None;
+        let json = candidate
+            .to_json()
+            .map_err(|e| Error::OptionsError(e.to_string()))?;
+
+        let previous = self.get_options();
+        let text_lang = self.text_lang.clone();
+        let hook = self.relayout_hook.take();
+
+        let estimate = self.set_options_json(&json).map(|()| {
+            self.relayout(c"{}");
+            self.page_count()
+        });
+
+        let restored = self.set_options_json(&previous);
+        self.text_lang = text_lang;
+        self.relayout(c"{}");
+        self.relayout_hook = hook;
+        restored?;
+        estimate
+    }
+
+    /// Returns the page count the loaded document would have under each of
+    /// several option sets, without keeping any of them.
+    ///
+    /// Each option set is passed to
+    /// [`estimate_page_count`](Self::estimate_page_count), so it is applied on
+    /// top of the current options, not on top of the previous set, and the
+    /// original options are restored after each one. This is the batch form
+    /// of `estimate_page_count`, suited to building a "choose your layout"
+    /// interface.
+    ///
+    /// # Performance
+    ///
+    /// This performs two full layouts per option set: one to read the page
+    /// count and one to restore the original layout.
+    ///
+    /// # Arguments
+    ///
+    /// * `option_sets` - The candidate options
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no data has been loaded or an option set cannot be
+    /// applied. The original options are restored in either case.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::{BreakMode, Options, Toolkit};
+    ///
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// // ... load data ...
+    ///
+    /// let candidates = [
+    ///     Options::builder().scale(60).build(),
+    ///     Options::builder().scale(80).build(),
+    ///     Options::builder().breaks(BreakMode::None).build(),
+    /// ];
+    /// let pages = toolkit
+    ///     .preview_layouts(&candidates)
+    ///     .expect("Failed to preview layouts");
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`estimate_page_count`](Self::estimate_page_count) - Preview a single option set
+    pub fn preview_layouts(&mut self, option_sets: &[Options]) -> Result<Vec<u32>> {
+        self.check_loaded()?;
+
+        option_sets
+            .iter()
+            .map(|options| self.estimate_page_count(options))
+            .collect()
+    }
+
+    /// Renders a PNG thumbnail of page 1 under each of several option sets,
+    /// without keeping any of them.
+    ///
+    /// Each option set is applied on top of the current options, the layout is
+    /// redone, and page 1 is rasterized on a white background to fit within
+    /// `max_px` x `max_px` pixels. Afterwards the original options are
+    /// restored and the layout is redone once more. Together with
+    /// [`preview_layouts`](Self::preview_layouts), this builds a "choose a
+    /// layout" gallery in one call.
+    ///
+    /// # Performance
+    ///
+    /// This performs one full layout and one rasterization per option set,
+    /// plus one layout to restore the original.
+    ///
+    /// # Arguments
+    ///
+    /// * `option_sets` - The candidate options
+    /// * `max_px` - The maximum width and height of a thumbnail, at least 1
+    ///
+    /// # Returns
+    ///
+    /// One PNG image per option set, in order.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `max_px` is 0, no data has been loaded, an option
+    /// set cannot be applied, or rendering fails. The original options are
+    /// restored in either case.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::{BreakMode, Options, Toolkit};
+    ///
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// // ... load data ...
+    ///
+    /// let candidates = [
+    ///     Options::builder().breaks(BreakMode::Auto).build(),
+    ///     Options::builder().breaks(BreakMode::None).build(),
+    /// ];
+    /// let thumbnails = toolkit
+    ///     .layout_thumbnails(&candidates, 200)
+    ///     .expect("Failed to render thumbnails");
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`preview_layouts`](Self::preview_layouts) - Page counts of several option sets
+    #[cfg(feature = "png")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "png")))]
+    pub fn layout_thumbnails(
+        &mut self,
+        option_sets: &[Options],
+        max_px: u32,
+    ) -> Result<Vec<Vec<u8>>> {
+        if max_px == 0 {
+            return Err(Error::RenderError(
+                "thumbnail size must be at least 1 pixel".into(),
+            ));
+        }
+        self.check_loaded()?;
+
+        let thumbnail = crate::Png::page(1)
+            .width(max_px)
+            .height(max_px)
+            .white_background();
+        let previous = self.get_options();
+        let thumbnails = option_sets
+            .iter()
+            .map(|options| {
+                self.set_options_json(&previous)?;
+                self.apply_options(options)?;
+                self.redo_layout(None)?;
+                self.render(thumbnail.clone())
+            })
+            .collect::<Result<Vec<_>>>();
+
+        self.set_options_json(&previous)?;
+        self.redo_layout(None)?;
+        thumbnails
+    }
+
+    /// Gets the toolkit instance ID.
+    ///
+    /// Each toolkit instance has a unique identifier assigned by Verovio.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    ///
+    /// let toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// let id = toolkit.get_id();
+    /// println!("Toolkit ID: {}", id);
+    /// ```
+    #[must_use]
+    pub fn get_id(&self) -> String {
+        // SAFETY: ptr is valid
+        let id_ptr = unsafe { verovioxide_sys::vrvToolkit_getID(self.ptr) };
+        self.ptr_to_string(id_ptr).unwrap_or_default()
+    }
+
+    /// Gets the current resource path.
+    ///
+    /// Returns the path to the directory containing Verovio resources (fonts, etc.).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    ///
+    /// let toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// let path = toolkit.get_resource_path();
+    /// println!("Resources located at: {}", path);
+    /// ```
+    #[must_use]
+    pub fn get_resource_path(&self) -> String {
+        // SAFETY: ptr is valid
+        let path_ptr = unsafe { verovioxide_sys::vrvToolkit_getResourcePath(self.ptr) };
+        self.ptr_to_string(path_ptr).unwrap_or_default()
+    }
+
+    /// Checks the resource directory for the files Verovio needs.
+    ///
+    /// Returns the required resources (as paths relative to the resource
+    /// directory) that are missing: the baseline Bravura font, the font
+    /// currently selected by the `font` option and the text font definitions.
+    /// Returns an empty list when everything is present or when the toolkit
+    /// has no resource path.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    ///
+    /// let toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// let missing = toolkit.verify_resources();
+    /// assert!(missing.is_empty(), "missing resources: {:?}", missing);
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`diagnostics`](Self::diagnostics) - Full diagnostics snapshot
+    #[must_use]
+    pub fn verify_resources(&self) -> Vec<String> {
+        let resource_path = self.get_resource_path();
+        if resource_path.is_empty() {
+            return Vec::new();
+        }
+
+        let font = Self::font_option(&self.get_options()).unwrap_or_default();
+        crate::diagnostics::missing_resources(Path::new(&resource_path), &font)
+    }
+
+    /// Returns the SMuFL glyphs the loaded document needs that the active font
+    /// does not provide.
+    ///
+    /// Verovio substitutes glyphs missing from the selected font with those of
+    /// its fallback font, or leaves them out, so the rendering looks mostly
+    /// right while mixing typefaces. This method renders every page, collects
+    /// the glyphs drawn with both the active font and the complete baseline
+    /// Bravura font, and reports those absent from the active font's
+    /// definition. Glyphs are identified by their SMuFL names (e.g.
+    /// `noteheadBlack`), in code point order.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no data has been loaded, if the toolkit has no
+    /// resource path, or if a page cannot be rendered or the font definition
+    /// cannot be read.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    ///
+    /// let toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// // ... load data ...
+    ///
+    /// for glyph in toolkit.missing_glyphs().expect("Failed to check glyphs") {
+    ///     eprintln!("missing glyph: {}", glyph);
+    /// }
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`verify_resources`](Self::verify_resources) - Check the resource directory
+    /// - [`active_font`](Self::active_font) - Get the selected font
+    pub fn missing_glyphs(&self) -> Result<Vec<String>> {
+        self.check_loaded()?;
+        let page_count = self.page_count();
+        let resource_path = self.get_resource_path();
+        if resource_path.is_empty() {
+            return Err(Error::RenderError("no resource path".into()));
+        }
+
+        let font = Self::font_option(&self.get_options())?;
+        let collect = |toolkit: &Self| -> Result<BTreeSet<String>> {
+            let mut codes = BTreeSet::new();
+            for page in 1..=page_count {
+                codes.extend(crate::svg::glyph_codes(&toolkit.render_to_svg(page)?)?);
+            }
+            Ok(codes)
+        };
+
+        let mut used = collect(self)?;
+        if font != crate::diagnostics::BASELINE_FONT {
+            let baseline =
+                serde_json::json!({ "font": crate::diagnostics::BASELINE_FONT }).to_string();
+            used.extend(self.with_temporary_options(&baseline, collect)??);
+        }
+
+        crate::diagnostics::missing_glyphs(Path::new(&resource_path), &font, &used)
+    }
+
+    /// Collects diagnostics about the toolkit for bug reports.
+    ///
+    /// The returned [`Diagnostics`](crate::Diagnostics) implements `Serialize`,
+    /// so it can be dumped as JSON and attached to a support request.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    ///
+    /// let toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// let diagnostics = toolkit.diagnostics();
+    /// println!("Verovio {}", diagnostics.verovio_version);
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`verify_resources`](Self::verify_resources) - Check the resource directory
+    /// - [`version`](Self::version) - Get the Verovio version
+    #[must_use]
+    pub fn diagnostics(&self) -> crate::Diagnostics {
+        let resource_path = Some(self.get_resource_path())
+            .filter(|path| !path.is_empty())
+            .map(std::path::PathBuf::from);
+        let available_fonts = resource_path
+            .as_deref()
+            .map(crate::diagnostics::installed_fonts)
+            .unwrap_or_default();
+        let page_count = self.page_count();
+
+        crate::Diagnostics {
+            verovio_version: self.version(),
+            resource_path,
+            available_fonts,
+            page_count,
+            is_loaded: page_count > 0,
+            missing_resources: self.verify_resources(),
+        }
+    }
+
+    /// Summarizes the current layout of the loaded document.
+    ///
+    /// Systems and measures are counted in a page-based MEI export, so the
+    /// counts reflect the layout produced by the current options. The scale and
+    /// break mode are read from the current options.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no data has been loaded or the MEI export fails.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    ///
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// // ... load data ...
+    ///
+    /// let summary = toolkit.layout_summary().expect("Failed to summarize layout");
+    /// println!(
+    ///     "{} pages, {} systems, {} measures at {}%",
+    ///     summary.pages, summary.systems, summary.measures, summary.scale
+    /// );
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`diagnostics`](Self::diagnostics) - Toolkit and resource diagnostics
+    /// - [`page_count`](Self::page_count) - Number of pages
+    pub fn layout_summary(&self) -> Result<crate::LayoutSummary> {
+        self.check_loaded()?;
+        let pages = self.page_count();
+
+        let (systems, measures) =
+            crate::mei::layout_counts(&self.get_mei_with_options(r#"{"scoreBased":false}"#)?)?;
+
+        Ok(crate::LayoutSummary {
+            pages,
+            systems: systems as u32,
+            measures: measures as u32,
+            scale: self.scale() as i32,
+            breaks: self
+                .current_options()
+                .ok()
+                .and_then(|options| options.breaks),
+        })
+    }
+
+    /// Sets the resource path.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path to the Verovio resources directory
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the path is invalid.
+    pub fn set_resource_path(&mut self, path: &Path) -> Result<()> {
+        self.bump_generation();
+        let path_str = path.to_str().ok_or_else(|| invalid_path("resource path"))?;
+
+        let c_path = CString::new(path_str)?;
+
+        // SAFETY: ptr is valid, c_path is a valid null-terminated string
+        let success =
+            unsafe { verovioxide_sys::vrvToolkit_setResourcePath(self.ptr, c_path.as_ptr()) };
+
+        if success {
+            // Verovio reloads its fonts and starts over with the default one
+            self.applied_font.replace(None);
+            Ok(())
+        } else {
+            Err(Error::OptionsError("failed to set resource path".into()))
+        }
+    }
+
+    /// Gets the page number containing a specific element.
+    ///
+    /// # Arguments
+    ///
+    /// * `xml_id` - The xml:id of the element
+    ///
+    /// # Returns
+    ///
+    /// The page number (1-based), or 0 if the element is not found.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    ///
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// // ... load MEI data ...
+    ///
+    /// let page = toolkit.get_page_with_element("note-0001").expect("Failed to get page");
+    /// if page > 0 {
+    ///     println!("Element is on page {}", page);
+    /// } else {
+    ///     println!("Element not found");
+    /// }
+    /// ```
+    pub fn get_page_with_element(&self, xml_id: &str) -> Result<u32> {
+        let c_id = CString::new(xml_id)?;
+
+        // SAFETY: ptr is valid, c_id is a valid null-terminated string
+        let page =
+            unsafe { verovioxide_sys::vrvToolkit_getPageWithElement(self.ptr, c_id.as_ptr()) };
+
+        Ok(page.max(0) as u32)
+    }
+
+    /// Returns each measure's xml:id and page number, in document order.
+    ///
+    /// The measure ids are read from the exported MEI and looked up in the
+    /// current layout, so the result reflects the options in effect when the
+    /// document was last laid out. This is useful for "go to measure" features.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no data has been loaded or the MEI export fails.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    ///
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// // ... load data ...
+    ///
+    /// for (id, page) in toolkit.measure_page_index().expect("Failed to index measures") {
+    ///     println!("Measure {} is on page {}", id, page);
+    /// }
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`get_page_with_element`](Self::get_page_with_element) - Page lookup for a single element
+    pub fn measure_page_index(&self) -> Result<Vec<(String, u32)>> {
+        self.check_loaded()?;
+
+        crate::mei::measure_ids(&self.get_mei()?)?
+            .into_iter()
+            .map(|id| {
+                let page = self.get_page_with_element(&id)?;
+                Ok((id, page))
+            })
+            .collect()
+    }
+
+    /// Returns the number of measures in the loaded document.
+    ///
+    /// Measures are counted in the exported MEI, so measures inside endings
+    /// and other containers are included.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no data has been loaded or the MEI export fails.
+    ///
+    /// # See also
+    ///
+    /// - [`measure_page_index`](Self::measure_page_index) - Measure ids with their pages
+    pub fn measure_count(&self) -> Result<usize> {
+        self.check_loaded()?;
+
+        crate::mei::measure_count(&self.get_mei()?)
+    }
+
+    /// Gets element attributes by xml:id.
+    ///
+    /// # Arguments
+    ///
+    /// * `xml_id` - The xml:id of the element
+    ///
+    /// # Returns
+    ///
+    /// A JSON string with the element's attributes.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    ///
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// // ... load MEI data with elements having xml:id attributes ...
+    ///
+    /// let attrs = toolkit.get_element_attr("note-0001").expect("Failed to get attributes");
+    /// println!("Note attributes: {}", attrs);
+    /// ```
+    pub fn get_element_attr(&self, xml_id: &str) -> Result<String> {
+        let c_id = CString::new(xml_id)?;
+
+        // SAFETY: ptr is valid, c_id is a valid null-terminated string
+        let attr_ptr =
+            unsafe { verovioxide_sys::vrvToolkit_getElementAttr(self.ptr, c_id.as_ptr()) };
+
+        self.ptr_to_utf8(attr_ptr, "element attributes")?
+            .ok_or_else(|| {
+                Error::RenderError(format!("failed to get attributes for element: {}", xml_id))
+            })
+    }
+
+    /// Gets the attributes of several elements by xml:id.
+    ///
+    /// Each id is looked up with [`get_element_attr`](Self::get_element_attr)
+    /// and its attributes are parsed into a map. Ids that do not resolve to an
+    /// element are skipped, so the result may be shorter than `ids`; the order
+    /// of the remaining ids is preserved.
+    ///
+    /// # Arguments
+    ///
+    /// * `ids` - The xml:ids of the elements
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an id contains a null byte or Verovio returns
+    /// attributes that are not a JSON object.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    ///
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// // ... load MEI data with elements having xml:id attributes ...
+    ///
+    /// for (id, attrs) in toolkit
+    ///     .element_attributes_batch(&["note-0001", "note-0002"])
+    ///     .expect("Failed to get attributes")
+    /// {
+    ///     println!("{}: pitch {:?}", id, attrs.get("pname"));
+    /// }
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`get_element_attr`](Self::get_element_attr) - Attributes of a single element as JSON
+    pub fn element_attributes_batch(
+        &self,
+        ids: &[&str],
+    ) -> Result<Vec<(String, BTreeMap<String, String>)>> {
+        let mut resolved = Vec::with_capacity(ids.len());
+        for &id in ids {
+            let json = self.get_element_attr(id)?;
+            let attrs: serde_json::Map<String, serde_json::Value> = serde_json::from_str(&json)
+                .map_err(|e| {
+                    Error::RenderError(format!("invalid attributes for element {}: {}", id, e))
+                })?;
+
+            // Verovio answers unknown ids with an empty object
+            if attrs.is_empty() && self.get_page_with_element(id)? == 0 {
+                continue;
+            }
+
+            let attrs = attrs
+                .into_iter()
+                .map(|(name, value)| match value {
+                    serde_json::Value::String(value) => (name, value),
+                    value => (name, value.to_string()),
+                })
+                .collect();
+            resolved.push((id.to_string(), attrs));
+        }
+        Ok(resolved)
+    }
+
+    /// Gets elements at a specific time in milliseconds.
+    ///
+    /// # Arguments
+    ///
+    /// * `millisec` - Time in milliseconds
+    ///
+    /// # Returns
+    ///
+    /// A JSON string with the element IDs at the specified time.
+    ///
+    /// # See also
+    ///
+    /// - [`get_time_for_element`](Self::get_time_for_element) - Get time for a specific element
+    /// - [`render_to_timemap`](Self::render_to_timemap) - Get the full timemap
+    pub fn get_elements_at_time(&self, millisec: i32) -> Result<String> {
+        // SAFETY: ptr is valid
+        let elements_ptr =
+            unsafe { verovioxide_sys::vrvToolkit_getElementsAtTime(self.ptr, millisec) };
+
+        self.ptr_to_utf8(elements_ptr, "elements at time")?
+            .ok_or_else(|| {
+                Error::RenderError(format!("failed to get elements at time: {}", millisec))
+            })
+    }
+
+    /// Returns the id of the measure sounding at a given time.
+    ///
+    /// Playback interfaces can use this to highlight the current measure. The
+    /// time is looked up in the measure markers of the timemap: a measure
+    /// lasts from its start to the start of the next one, and the last measure
+    /// until the score ends. Times follow the tempo of the document, as in
+    /// [`render_to_timemap`](Self::render_to_timemap).
+    ///
+    /// # Arguments
+    ///
+    /// * `millis` - Time in milliseconds
+    ///
+    /// # Returns
+    ///
+    /// `Ok(None)` if `millis` is at or after the end of the score.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no data has been loaded or the timemap cannot be
+    /// generated.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    ///
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// // ... load data ...
+    ///
+    /// if let Some(measure) = toolkit.measure_at_time(1500).expect("Failed to look up time") {
+    ///     println!("playing measure {}", measure);
+    /// }
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`get_elements_at_time`](Self::get_elements_at_time) - Elements active at a time
+    /// - [`render_time_window`](Self::render_time_window) - Render the measures of a time range
+    pub fn measure_at_time(&self, millis: u32) -> Result<Option<String>> {
+        self.check_loaded()?;
+
+        let timemap = self.render_to_timemap_with_options(r#"{"includeMeasures": true}"#)?;
+        measure_at(&timemap, f64::from(millis))
+    }
+
+    /// Returns a cursor overlay for the elements sounding at a given time.
+    ///
+    /// The overlay is a small standalone `<svg>` with the same size and
+    /// coordinate system as the rendered page, containing a single vertical
+    /// `<line class="cursor">` at the left edge of the notes, chords and rests
+    /// active at `millis`. Stacking it on top of the page moves a playback
+    /// cursor without re-rendering the score.
+    ///
+    /// # Arguments
+    ///
+    /// * `millis` - Time in milliseconds
+    /// * `page` - The page number (1-based) the overlay is for
+    ///
+    /// # Returns
+    ///
+    /// `Ok(None)` if no elements are active on `page` at that time.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - No data has been loaded
+    /// - The page number is out of range
+    /// - The time does not fit Verovio's time type
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    ///
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// // ... load data ...
+    ///
+    /// if let Some(overlay) = toolkit.cursor_svg_for_time(1500, 1).expect("Failed to build cursor") {
+    ///     println!("{}", overlay);
+    /// }
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`get_elements_at_time`](Self::get_elements_at_time) - Elements active at a time
+    /// - [`render_to_svg`](Self::render_to_svg) - Render the page itself
+    pub fn cursor_svg_for_time(&self, millis: u32, page: u32) -> Result<Option<String>> {
+        let millisec = i32::try_from(millis)
+            .map_err(|_| Error::RenderError(format!("time out of range: {}", millis)))?;
+        let svg = self.render_to_svg(page)?;
+
+        let elements: serde_json::Value =
+            serde_json::from_str(&self.get_elements_at_time(millisec)?)
+                .map_err(|e| Error::RenderError(format!("invalid elements at time: {}", e)))?;
+        if elements
+            .get("page")
+            .and_then(serde_json::Value::as_u64)
+            .is_some_and(|active_page| active_page != u64::from(page))
+        {
+            return Ok(None);
+        }
+
+        let ids: Vec<String> = ["notes", "chords", "rests"]
+            .iter()
+            .filter_map(|key| elements.get(*key)?.as_array())
+            .flatten()
+            .filter_map(|id| id.as_str().map(String::from))
+            .collect();
+        if ids.is_empty() {
+            return Ok(None);
+        }
+
+        match crate::svg::elements_min_x(&svg, &ids)? {
+            Some(x) => crate::svg::cursor_overlay(&svg, x).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the id of the element drawn at a point of a page.
+    ///
+    /// The page is rendered with Verovio's `svgBoundingBoxes` option, and the
+    /// innermost element whose bounding box contains the point is returned:
+    /// clicking a stem yields the stem rather than its note or measure. Use
+    /// this to map clicks on a rendered page back to the document.
+    ///
+    /// Coordinates are in the page's drawing coordinate system, i.e. the
+    /// `viewBox` of the inner `<svg class="definition-scale">`, with the page
+    /// margin included. Convert client coordinates with the SVG's screen
+    /// transform before calling this.
+    ///
+    /// # Arguments
+    ///
+    /// * `page` - The page number (1-based)
+    /// * `x` - The horizontal position
+    /// * `y` - The vertical position
+    ///
+    /// # Returns
+    ///
+    /// `Ok(None)` if no element's bounding box contains the point.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - No data has been loaded
+    /// - The page number is out of range
+    /// - Rendering fails
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    ///
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// // ... load data ...
+    ///
+    /// if let Some(id) = toolkit.element_at_point(1, 2400.0, 1800.0).expect("Failed to hit-test") {
+    ///     println!("Clicked {}", id);
+    /// }
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`get_element_attr`](Self::get_element_attr) - Attributes of the clicked element
+    /// - [`get_time_for_element`](Self::get_time_for_element) - Seek playback to the element
+    pub fn element_at_point(&self, page: u32, x: f64, y: f64) -> Result<Option<String>> {
+        let svg = self.with_temporary_options(r#"{"svgBoundingBoxes": true}"#, |toolkit| {
+            toolkit.render_to_svg(page)
+        })??;
+
+        crate::svg::element_at_point(&svg, x, y)
+    }
+
+    /// Returns the bounding box of each system on a page, top to bottom.
+    ///
+    /// The page is rendered with Verovio's `svgBoundingBoxes` option and the
+    /// box of every `<g class="system">` is read from it. Readers that show
+    /// one system per row can use the boxes to slice the page, e.g. by
+    /// setting each one, with some padding, as the `viewBox` of a copy of the
+    /// page's inner `<svg class="definition-scale">`. Bounds are in the
+    /// page's drawing coordinate system, as for
+    /// [`element_at_point`](Self::element_at_point).
+    ///
+    /// # Arguments
+    ///
+    /// * `page` - The page number (1-based)
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - No data has been loaded
+    /// - The page number is out of range
+    /// - Rendering fails
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    ///
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// // ... load data ...
+    ///
+    /// for bounds in toolkit.system_bounds(1).expect("Failed to get systems") {
+    ///     println!("system at y = {} ({} high)", bounds.min_y, bounds.height);
+    /// }
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`document_anchors`](Self::document_anchors) - Bounds of every element
+    /// - [`render_to_svg_cropped`](Self::render_to_svg_cropped) - Crop a page to its content
+    pub fn system_bounds(&self, page: u32) -> Result<Vec<crate::ViewBox>> {
+        let svg = self.with_temporary_options(r#"{"svgBoundingBoxes": true}"#, |toolkit| {
+            toolkit.render_to_svg(page)
+        })??;
+
+        Ok(crate::svg::element_anchors(&svg)?
+            .into_iter()
+            .filter(|anchor| anchor.kind == "system")
+            .map(|anchor| anchor.bounds)
+            .collect())
+    }
+
+    /// Returns the anchors of the elements on every page, for playback and
+    /// search tools that need the position of everything at once.
+    ///
+    /// Bounding boxes are enabled once, every page is rendered with them, and
+    /// each element with a box is anchored at it, in document order. This is
+    /// much cheaper than hit-testing or rendering pages one call at a time.
+    /// Bounds are in each page's drawing coordinate system, as for
+    /// [`element_at_point`](Self::element_at_point).
+    ///
+    /// # Returns
+    ///
+    /// One `(page, anchors)` pair per page, with 1-based page numbers.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no data has been loaded or rendering fails.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    ///
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// // ... load data ...
+    ///
+    /// for (page, anchors) in toolkit.document_anchors().expect("Failed to get anchors") {
+    ///     let notes = anchors.iter().filter(|anchor| anchor.kind == "note").count();
+    ///     println!("page {}: {} notes", page, notes);
+    /// }
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`element_at_point`](Self::element_at_point) - Hit-test a single point
+    pub fn document_anchors(&self) -> Result<Vec<(u32, Vec<crate::ElementAnchor>)>> {
+        self.check_loaded()?;
+        let count = self.page_count();
+
+        self.with_temporary_options(r#"{"svgBoundingBoxes": true}"#, |toolkit| {
+            (1..=count)
+                .map(|page| {
+                    let svg = toolkit.render_to_svg(page)?;
+                    Ok((page, crate::svg::element_anchors(&svg)?))
+                })
+                .collect()
+        })?
+    }
+
+    /// Gets the time (in milliseconds) for an element.
+    ///
+    /// # Arguments
+    ///
+    /// * `xml_id` - The xml:id of the element
+    ///
+    /// # Returns
+    ///
+    /// The time in milliseconds.
+    ///
+    /// # See also
+    ///
+    /// - [`get_elements_at_time`](Self::get_elements_at_time) - Get elements at a specific time
+    /// - [`render_to_timemap`](Self::render_to_timemap) - Get the full timemap
+    pub fn get_time_for_element(&self, xml_id: &str) -> Result<f64> {
+        let c_id = CString::new(xml_id)?;
+
+        // SAFETY: ptr is valid, c_id is a valid null-terminated string
+        let time =
+            unsafe { verovioxide_sys::vrvToolkit_getTimeForElement(self.ptr, c_id.as_ptr()) };
+
+        Ok(time)
+    }
+
+    /// Gets expansion IDs for an element.
+    ///
+    /// When working with documents that contain expansion elements (e.g., repeats),
+    /// this method returns the expansion IDs associated with a given element.
+    ///
+    /// # Arguments
+    ///
+    /// * `xml_id` - The xml:id of the element
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The xml_id contains a null byte
+    /// - The query fails
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    ///
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// // ... load data with expansion elements ...
+    ///
+    /// let expansion_ids = toolkit.get_expansion_ids_for_element("note-0001")
+    ///     .expect("Failed to get expansion IDs");
+    /// println!("Expansion IDs: {}", expansion_ids);
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`render_to_expansion_map`](Self::render_to_expansion_map) - Get the full expansion map
+    /// - [`get_notated_id_for_element`](Self::get_notated_id_for_element) - Get notated ID
+    pub fn get_expansion_ids_for_element(&self, xml_id: &str) -> Result<String> {
+        let c_id = CString::new(xml_id)?;
+
+        // SAFETY: ptr is valid, c_id is a valid null-terminated string
+        let result_ptr = unsafe {
+            verovioxide_sys::vrvToolkit_getExpansionIdsForElement(self.ptr, c_id.as_ptr())
+        };
+
+        self.ptr_to_utf8(result_ptr, "expansion IDs")?
+            .ok_or_else(|| {
+                Error::RenderError(format!(
+                    "failed to get expansion IDs for element: {}",
+                    xml_id
+                ))
+            })
+    }
+
+    /// Gets MIDI values for an element.
+    ///
+    /// Returns MIDI-related information (pitch, velocity, etc.) for a specific element.
+    ///
+    /// # Arguments
+    ///
+    /// * `xml_id` - The xml:id of the element
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The xml_id contains a null byte
+    /// - The query fails
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    ///
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// // ... load data ...
+    ///
+    /// let midi_values = toolkit.get_midi_values_for_element("note-0001")
+    ///     .expect("Failed to get MIDI values");
+    /// println!("MIDI values: {}", midi_values);
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`render_to_midi`](Self::render_to_midi) - Render full MIDI
+    /// - [`get_time_for_element`](Self::get_time_for_element) - Get timing for element
+    pub fn get_midi_values_for_element(&self, xml_id: &str) -> Result<String> {
+        let c_id = CString::new(xml_id)?;
+
+        // SAFETY: ptr is valid, c_id is a valid null-terminated string
+        let result_ptr =
+            unsafe { verovioxide_sys::vrvToolkit_getMIDIValuesForElement(self.ptr, c_id.as_ptr()) };
+
+        self.ptr_to_utf8(result_ptr, "MIDI values")?.ok_or_else(|| {
+            Error::RenderError(format!("failed to get MIDI values for element: {}", xml_id))
+        })
+    }
+
+    /// Gets the notated ID for an element.
+    ///
+    /// When working with expansions, elements may have different rendered IDs
+    /// than their notated IDs. This method returns the original notated ID
+    /// for a given element.
+    ///
+    /// # Arguments
+    ///
+    /// * `xml_id` - The xml:id of the element (possibly a rendered ID)
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The xml_id contains a null byte
+    /// - The query fails
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    ///
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// // ... load data ...
+    ///
+    /// let notated_id = toolkit.get_notated_id_for_element("rendered-note-0001")
+    ///     .expect("Failed to get notated ID");
+    /// println!("Notated ID: {}", notated_id);
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`get_expansion_ids_for_element`](Self::get_expansion_ids_for_element) - Get expansion IDs
+    /// - [`render_to_expansion_map`](Self::render_to_expansion_map) - Get the full expansion map
+    pub fn get_notated_id_for_element(&self, xml_id: &str) -> Result<String> {
+        let c_id = CString::new(xml_id)?;
+
+        // SAFETY: ptr is valid, c_id is a valid null-terminated string
+        let result_ptr =
+            unsafe { verovioxide_sys::vrvToolkit_getNotatedIdForElement(self.ptr, c_id.as_ptr()) };
+
+        self.ptr_to_utf8(result_ptr, "notated ID")?.ok_or_else(|| {
+            Error::RenderError(format!("failed to get notated ID for element: {}", xml_id))
+        })
+    }
+
+    /// Returns the notated ID of a rendered element, if it differs.
+    ///
+    /// [`get_notated_id_for_element`](Self::get_notated_id_for_element)
+    /// returns an empty string or the input ID unchanged when there is no
+    /// mapping. This method returns `None` in both cases, and `Some` only
+    /// when the element is a copy created by an expansion.
+    ///
+    /// # Arguments
+    ///
+    /// * `rendered_id` - The xml:id of the rendered element
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The rendered_id contains a null byte
+    /// - The query fails
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    ///
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// // ... load data with an expansion ...
+    ///
+    /// match toolkit.notated_id("rendered-note-0001").expect("Failed to query") {
+    ///     Some(id) => println!("Copy of {}", id),
+    ///     None => println!("Not a copy"),
+    /// }
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`get_notated_id_for_element`](Self::get_notated_id_for_element) - The raw query
+    pub fn notated_id(&self, rendered_id: &str) -> Result<Option<String>> {
+        let notated = self.get_notated_id_for_element(rendered_id)?;
+        Ok(Some(notated).filter(|notated| !notated.is_empty() && notated != rendered_id))
+    }
+
+    /// Gets timing information for an element.
+    ///
+    /// Returns detailed timing information including onset time, offset time,
+    /// and duration for a specific element.
+    ///
+    /// # Arguments
+    ///
+    /// * `xml_id` - The xml:id of the element
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The xml_id contains a null byte
+    /// - The query fails
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    ///
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// // ... load data ...
+    ///
+    /// let times = toolkit.get_times_for_element("note-0001")
+    ///     .expect("Failed to get times");
+    /// println!("Timing info: {}", times);
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`get_time_for_element`](Self::get_time_for_element) - Get simple time value
+    /// - [`render_to_timemap`](Self::render_to_timemap) - Get full timemap
+    pub fn get_times_for_element(&self, xml_id: &str) -> Result<String> {
+        let c_id = CString::new(xml_id)?;
+
+        // SAFETY: ptr is valid, c_id is a valid null-terminated string
+        let result_ptr =
+            unsafe { verovioxide_sys::vrvToolkit_getTimesForElement(self.ptr, c_id.as_ptr()) };
+
+        self.ptr_to_utf8(result_ptr, "element times")?
+            .ok_or_else(|| {
+                Error::RenderError(format!("failed to get times for element: {}", xml_id))
+            })
+    }
+
+    /// Gets descriptive features from the document.
+    ///
+    /// Extracts descriptive features and metadata from the loaded document,
+    /// useful for analysis and categorization.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - Optional JSON string with feature extraction options
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - No data has been loaded
+    /// - The options contain a null byte
+    /// - Feature extraction fails
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    ///
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// // ... load data ...
+    ///
+    /// let features = toolkit.get_descriptive_features(None)
+    ///     .expect("Failed to get features");
+    /// println!("Features: {}", features);
+    /// ```
+    pub fn get_descriptive_features(&self, options: Option<&str>) -> Result<String> {
+        let c_options = CString::new(options.unwrap_or("{}"))?;
+
+        // SAFETY: ptr is valid, c_options is a valid null-terminated string
+        let result_ptr = unsafe {
+            verovioxide_sys::vrvToolkit_getDescriptiveFeatures(self.ptr, c_options.as_ptr())
+        };
+
+        self.ptr_to_utf8(result_ptr, "descriptive features")?
+            .ok_or_else(|| Error::RenderError("failed to get descriptive features".into()))
+    }
+
+    /// Redoes the layout with optional new options.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - Optional JSON string with layout options
+    pub fn redo_layout(&mut self, options: Option<&str>) -> Result<()> {
+        let c_options = CString::new(options.unwrap_or("{}"))?;
+        self.relayout(&c_options);
+        Ok(())
+    }
+
+    /// Registers a hook called with the new page count after every layout.
+    ///
+    /// The hook runs exactly once after each successful load
+    /// ([`load_data`](Self::load_data), [`load_file`](Self::load_file),
+    /// [`render_data`](Self::render_data) and the ZIP loaders) and after each [`redo_layout`](Self::redo_layout) of a
+    /// loaded document. Methods that change layout options, such as
+    /// [`set_breaks`](Self::set_breaks), relayout through `redo_layout` and
+    /// therefore also call it. This lets user interfaces keep their page
+    /// navigation in sync. Registering a hook replaces the previous one.
+    ///
+    /// [`set_options`](Self::set_options), [`set_scale`](Self::set_scale)
+    /// and [`reset_options`](Self::reset_options) relayout a loaded document,
+    /// and call the hook, when they change an option that affects the layout.
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - Called with the page count after each layout
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    ///
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// toolkit.on_relayout(|pages| println!("Document now has {} pages", pages));
+    /// // ... load data ...
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`redo_layout`](Self::redo_layout) - Redo the layout
+    /// - [`page_count`](Self::page_count) - Number of pages
+    pub fn on_relayout(&mut self, f: impl FnMut(u32) + Send + 'static) {
+        self.relayout_hook = Some(Box::new(f));
+    }
+
+    /// Performs an editor action on the loaded document.
+    ///
+    /// # Arguments
+    ///
+    /// * `action` - JSON string describing the editor action
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the action fails.
+    pub fn edit(&mut self, action: &str) -> Result<()> {
+        self.bump_generation();
+        let c_action = CString::new(action)?;
+
+        // SAFETY: ptr is valid, c_action is a valid null-terminated string
+        let success = unsafe { verovioxide_sys::vrvToolkit_edit(self.ptr, c_action.as_ptr()) };
+
+        if success {
+            Ok(())
+        } else {
+            Err(Error::RenderError("editor action failed".into()))
+        }
+    }
+
+    /// Appends MEI measures to the end of the loaded document.
+    ///
+    /// `mei_fragment` must contain one or more `<measure>` elements, written in
+    /// the MEI namespace without a wrapper (a default namespace declaration is
+    /// supplied). The measures are inserted at the end of the outermost
+    /// section of the exported MEI, after any ending or nested section that
+    /// holds the last measure, and the document is reloaded and laid out
+    /// again, which makes this suitable for notation that arrives
+    /// progressively.
+    ///
+    /// Verovio's editor has no action for inserting measures, so the document is
+    /// round-tripped through MEI. Any page selection made with
+    /// [`select`](Self::select) is reset.
+    ///
+    /// # Arguments
+    ///
+    /// * `mei_fragment` - One or more MEI `<measure>` elements
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - No data has been loaded
+    /// - The fragment is not well-formed or contains anything besides measures
+    /// - The updated document fails to load
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    ///
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// // ... load MEI data ...
+    ///
+    /// toolkit
+    ///     .append_mei_measures(
+    ///         r#"<measure n="2"><staff n="1"><layer n="1">
+    ///              <note pname="g" oct="4" dur="1"/>
+    ///            </layer></staff></measure>"#,
+    ///     )
+    ///     .expect("Failed to append measures");
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`measure_count`](Self::measure_count) - Count measures in the document
+    pub fn append_mei_measures(&mut self, mei_fragment: &str) -> Result<()> {
+        self.check_loaded_as(Error::LoadError)?;
+
+        let mei = crate::mei::append_measures(&self.get_mei()?, mei_fragment)?;
+        self.load_mei_internal(&mei)
+    }
+
+    /// Inserts a system break (`<sb/>`) before a measure and lays the
+    /// document out again.
+    ///
+    /// Verovio's editor has no action for inserting breaks, so the break is
+    /// added to the exported MEI and the document is reloaded, as with
+    /// [`append_mei_measures`](Self::append_mei_measures). Encoded breaks only
+    /// affect the layout when the break mode honors them, e.g.
+    /// [`BreakMode::Encoded`] or [`BreakMode::Line`].
+    ///
+    /// # Arguments
+    ///
+    /// * `measure_id` - The xml:id of the measure that should start a new system
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - No data has been loaded
+    /// - No measure has the given xml:id
+    /// - The updated document fails to load
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::{BreakMode, Toolkit};
+    ///
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// // ... load data ...
+    ///
+    /// toolkit.set_breaks(BreakMode::Encoded).expect("Failed to set breaks");
+    /// toolkit
+    ///     .insert_system_break_before("measure-0005")
+    ///     .expect("Failed to insert break");
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`insert_page_break_before`](Self::insert_page_break_before) - Insert a page break
+    /// - [`measure_page_index`](Self::measure_page_index) - Look up measure ids
+    pub fn insert_system_break_before(&mut self, measure_id: &str) -> Result<()> {
+        self.insert_before_measure(measure_id, "<sb/>")
+    }
+
+    /// Inserts a page break (`<pb/>`) before a measure and lays the document
+    /// out again.
+    ///
+    /// See [`insert_system_break_before`](Self::insert_system_break_before)
+    /// for how the break is inserted and when it takes effect.
+    ///
+    /// # Arguments
+    ///
+    /// * `measure_id` - The xml:id of the measure that should start a new page
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - No data has been loaded
+    /// - No measure has the given xml:id
+    /// - The updated document fails to load
+    pub fn insert_page_break_before(&mut self, measure_id: &str) -> Result<()> {
+        self.insert_before_measure(measure_id, "<pb/>")
+    }
+
+    /// Sets the title in the header of the loaded document and lays it out
+    /// again.
+    ///
+    /// Verovio's editor cannot change the MEI header, so the title is set in
+    /// the exported MEI (`<fileDesc>/<titleStmt>/<title>`) and the document is
+    /// reloaded, which also regenerates the page header. A document without a
+    /// header gets a minimal one.
+    ///
+    /// # Arguments
+    ///
+    /// * `title` - The new title
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - No data has been loaded
+    /// - The header has no title statement to edit
+    /// - The updated document fails to load
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    ///
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// toolkit.load_data("@clef:G-2\n@data:8ABCDEFG").expect("Failed to load");
+    /// toolkit.set_title("Scale in A minor").expect("Failed to set title");
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`set_composer`](Self::set_composer) - Set the composer
+    /// - [`metadata`](Self::metadata) - Read the header
+    pub fn set_title(&mut self, title: &str) -> Result<()> {
+        self.check_loaded_as(Error::LoadError)?;
+
+        let mei = crate::mei::set_title(&self.get_mei()?, title)?;
+        self.load_mei_internal(&mei)
+    }
+
+    /// Sets the composer in the header of the loaded document and lays it out
+    /// again.
+    ///
+    /// An existing composer in the title statement is replaced; otherwise a
+    /// `<respStmt><persName role="composer">` is added. See
+    /// [`set_title`](Self::set_title) for how the header is edited.
+    ///
+    /// # Arguments
+    ///
+    /// * `composer` - The composer's name
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - No data has been loaded
+    /// - The header has no title statement to edit
+    /// - The updated document fails to load
+    pub fn set_composer(&mut self, composer: &str) -> Result<()> {
+        self.check_loaded_as(Error::LoadError)?;
+
+        let mei = crate::mei::set_person(&self.get_mei()?, "composer", composer)?;
+        self.load_mei_internal(&mei)
+    }
+
+    /// Transposes the loaded document and reports its new key.
+    ///
+    /// Verovio applies the `transpose` option when a document is loaded, so the
+    /// current MEI is reloaded with the option set to `interval`; the option is
+    /// restored afterwards so later loads are not transposed again. The new key
+    /// is read from the first key signature of the transposed document, which
+    /// lets a transposition UI display it.
+    ///
+    /// # Arguments
+    ///
+    /// * `interval` - The interval in Verovio's notation: a quality and size
+    ///   such as `P5` or `-m3`, a number of semitones such as `+4`, or a target
+    ///   key such as `G` or `Eb`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - No data has been loaded
+    /// - `interval` is empty
+    /// - The transposed document fails to load
+    ///
+    /// Verovio rejects malformed intervals with a log message and leaves the
+    /// document as it was.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    ///
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// // ... load data ...
+    ///
+    /// let result = toolkit.transpose("P5").expect("Failed to transpose");
+    /// if let Some(key) = result.new_key {
+    ///     println!("Now in {}", key);
+    /// }
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`OptionsBuilder::transpose`](crate::OptionsBuilder::transpose) - Transpose on every load
+    pub fn transpose(&mut self, interval: &str) -> Result<crate::TranspositionResult> {
+        self.check_loaded_as(Error::LoadError)?;
+        if interval.trim().is_empty() {
+            return Err(Error::OptionsError(
+                "transposition interval is empty".into(),
+            ));
+        }
+
+        let mei = self.get_mei()?;
+        let options: serde_json::Value = serde_json::from_str(&self.get_options())
+            .map_err(|e| Error::OptionsError(e.to_string()))?;
+        let previous = options
+            .get("transpose")
+            .cloned()
+            .unwrap_or_else(|| serde_json::json!(""));
+
+        self.set_options_json(&serde_json::json!({ "transpose": interval }).to_string())?;
+        let loaded = self.load_mei_internal(&mei);
+        self.set_options_json(&serde_json::json!({ "transpose": previous }).to_string())?;
+        loaded?;
+
+        let key = crate::mei::key_signature(&self.get_mei()?)?;
+        Ok(crate::TranspositionResult {
+            interval: interval.to_string(),
+            fifths: key.as_ref().map(|(fifths, _)| *fifths),
+            new_key: key.and_then(|(fifths, mode)| {
+                crate::mei::key_name(fifths, mode.as_deref() == Some("minor"))
+            }),
+        })
+    }
+
+    /// Gets information about the last edit operation.
+    ///
+    /// Returns a JSON string containing details about the most recent edit
+    /// performed via [`edit()`](Self::edit).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use verovioxide::Toolkit;
+    ///
+    /// let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    /// // ... load data and perform an edit ...
+    ///
+    /// let info = toolkit.edit_info();
+    /// println!("Last edit info: {}", info);
+    /// ```
+    #[must_use]
+    pub fn edit_info(&self) -> String {
+        // SAFETY: ptr is valid
+        let info_ptr = unsafe { verovioxide_sys::vrvToolkit_editInfo(self.ptr) };
+        self.ptr_to_string(info_ptr).unwrap_or_default()
+    }
+
+    /// Enables or disables logging to stderr.
+    ///
+    /// # Arguments
+    ///
+    /// * `enable` - `true` to enable logging, `false` to disable
+    pub fn enable_log(enable: bool) {
+        // SAFETY: This function has no preconditions
+        unsafe { verovioxide_sys::enableLog(enable) };
+    }
+
+    /// Enables or disables logging to an internal buffer.
+    ///
+    /// When enabled, log messages can be retrieved with [`get_log()`](Self::get_log).
+    ///
+    /// # Arguments
+    ///
+    /// * `enable` - `true` to enable buffer logging, `false` to disable
+    pub fn enable_log_to_buffer(enable: bool) {
+        // SAFETY: This function has no preconditions
+        unsafe { verovioxide_sys::enableLogToBuffer(enable) };
+    }
+
+    /// Runs `f` with the given options temporarily applied.
+    ///
+    /// `options` is a JSON object of Verovio options. The current value of each
+    /// key is read from [`get_options`](Self::get_options) before the options are
+    /// applied and written back when `f` returns or panics. Only render-time
+    /// options should be passed here, since no relayout is performed.
+    ///
+    /// The previous values were reported by Verovio, so writing them back does
+    /// not fail in practice; should it fail, the result of `f` is still
+    /// returned.
+    fn with_temporary_options<T>(&self, options: &str, f: impl FnOnce(&Self) -> T) -> Result<T> {
+        let temporary: serde_json::Map<String, serde_json::Value> =
+            serde_json::from_str(options).map_err(|e| Error::OptionsError(e.to_string()))?;
+        let current: serde_json::Value = serde_json::from_str(&self.get_options())
+            .map_err(|e| Error::OptionsError(e.to_string()))?;
+
+        let previous: serde_json::Map<String, serde_json::Value> = temporary
+            .keys()
+            .filter_map(|key| current.get(key).map(|value| (key.clone(), value.clone())))
+            .collect();
+
+        self.set_options_json(options)?;
+        let _restore = RestoreOptions {
+            toolkit: self,
+            previous: serde_json::Value::Object(previous).to_string(),
+        };
+        Ok(f(self))
+    }
+
+    /// Captures the settings a scratch toolkit needs to render like this one.
+    fn scratch_settings(&self) -> ScratchSettings {
+        ScratchSettings {
+            resource_path: self.get_resource_path(),
+            options: self.get_options(),
+            text_lang: self.text_lang.clone(),
+        }
+    }
+
+    /// Creates a toolkit with the resources, options and render state of this
+    /// one, for laying out a modified copy of the document.
+    fn scratch_toolkit(&self) -> Result<Toolkit> {
+        self.scratch_settings().toolkit()
+    }
+
+    /// Applies a JSON object of options directly through the FFI.
+    fn set_options_json(&self, json: &str) -> Result<()> {
+        let c_json = CString::new(json)?;
+
+        // SAFETY: ptr is valid, c_json is a valid null-terminated string
+        let success = unsafe { verovioxide_sys::vrvToolkit_setOptions(self.ptr, c_json.as_ptr()) };
+
+        if !success {
+            return Err(Error::OptionsError("failed to set options".into()));
+        }
+        if let Ok(font) = Self::font_option(json) {
+            self.track_font(&font);
+        }
+        Ok(())
+    }
+
+    /// Records `font` as the music font in use if Verovio could load it.
+    ///
+    /// Verovio keeps its current font when the requested one is missing from
+    /// the resources, so a missing font leaves the record unchanged.
+    fn track_font(&self, font: &str) {
+        if self.has_font(font) {
+            *self.applied_font.borrow_mut() = Some(font.to_string());
+        }
+    }
+
+    /// Redoes the layout with the given layout options and calls the
+    /// [`on_relayout`](Self::on_relayout) hook if a document is loaded.
+    ///
+    /// Every relayout goes through here, so the hook sees each one.
+    fn relayout(&mut self, options: &CStr) {
+        self.bump_generation();
+        // SAFETY: ptr is valid, options is a valid null-terminated string
+        unsafe { verovioxide_sys::vrvToolkit_redoLayout(self.ptr, options.as_ptr()) };
+
+        if self.page_count() > 0 {
+            self.notify_relayout();
+        }
+    }
+
+    /// Calls the [`on_relayout`](Self::on_relayout) hook, if any, with the
+    /// current page count.
+    fn notify_relayout(&mut self) {
+        let page_count = self.page_count();
+        if let Some(hook) = self.relayout_hook.as_mut() {
+            hook(page_count);
+        }
+    }
+
+    /// Marks the document, layout or options as changed.
+    fn bump_generation(&mut self) {
+        self.generation = self.generation.wrapping_add(1);
+    }
+
+    /// Keeps a copy of the data just loaded if source retention is enabled.
+    fn retain(&mut self, data: &[u8]) {
+        if self.retain_source {
+            self.source = Some(data.to_vec());
+        }
+    }
+
+    /// Returns the cached export selected by `slot`, or produces and caches it.
+    fn cached_export(
+        &self,
+        slot: impl Fn(&mut ExportCache) -> &mut Option<String>,
+        export: impl FnOnce() -> Result<String>,
+    ) -> Result<String> {
+        {
+            let mut cache = self.export_cache.borrow_mut();
+            if cache.generation != self.generation {
+                *cache = ExportCache {
+                    generation: self.generation,
+                    ..ExportCache::default()
+                };
+            }
+            if let Some(cached) = slot(&mut cache) {
+                return Ok(cached.clone());
+            }
+        }
+
+        let exported = export()?;
+        *slot(&mut self.export_cache.borrow_mut()) = Some(exported.clone());
+        Ok(exported)
+    }
+
+    /// Inserts an MEI element before a measure and reloads the document.
+    fn insert_before_measure(&mut self, measure_id: &str, element: &str) -> Result<()> {
+        self.check_loaded_as(Error::LoadError)?;
+
+        let mei = crate::mei::insert_before_measure(&self.get_mei()?, measure_id, element)?;
+        self.load_mei_internal(&mei)
+    }
+
+    /// Loads MEI exported from the loaded document, e.g. after editing it.
+    ///
+    /// The MEI is loaded with `inputFrom` set to `mei` and without the input
+    /// format check, so internal reloads work whatever input format is set
+    /// for the user's own data. The previous `inputFrom` is restored
+    /// afterwards.
+    fn load_mei_internal(&mut self, mei: &str) -> Result<()> {
+        let options: serde_json::Value = serde_json::from_str(&self.get_options())
+            .map_err(|e| Error::OptionsError(e.to_string()))?;
+        let previous = options
+            .get("inputFrom")
+            .cloned()
+            .unwrap_or_else(|| serde_json::json!("auto"));
+
+        self.set_options_json(r#"{"inputFrom": "mei"}"#)?;
+        let check = std::mem::replace(&mut self.check_input_format, false);
+        let loaded = self.load_data(mei);
+        self.check_input_format = check;
+        self.set_options_json(&serde_json::json!({ "inputFrom": previous }).to_string())?;
+        loaded
+    }
+
+    /// Redoes the layout with the current options, but only if a document is loaded.
+    fn redo_layout_if_loaded(&mut self) -> Result<()> {
+        if self.page_count() > 0 {
+            self.relayout(c"{}");
+        }
+        Ok(())
+    }
+
+    /// Converts a C string pointer to an owned Rust string.
+    ///
+    /// Returns `None` if the pointer is null or contains invalid UTF-8.
+    fn ptr_to_string(&self, ptr: *const i8) -> Option<String> {
+        self.ptr_to_utf8(ptr, "Verovio string").ok().flatten()
+    }
+
+    /// Converts a C string pointer to an owned Rust string, reporting invalid
+    /// UTF-8 as [`Error::InvalidUtf8`] with the given context.
+    ///
+    /// Returns `Ok(None)` if the pointer is null.
+    fn ptr_to_utf8(&self, ptr: *const i8, context: &str) -> Result<Option<String>> {
+        if ptr.is_null() {
+            return Ok(None);
+        }
+
+        // SAFETY: ptr is non-null and points to a valid C string owned by the toolkit
+        let c_str = unsafe { CStr::from_ptr(ptr) };
+
+        c_str
+            .to_str()
+            .map(|s| Some(s.to_string()))
+            .map_err(|_| Error::InvalidUtf8 {
+                context: context.to_string(),
+            })
+    }
+}
+
+impl std::fmt::Debug for Toolkit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Toolkit")
+            .field("version", &self.version())
+            .field("page_count", &self.page_count())
+            .field("resource_path", &self.get_resource_path())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_toolkit_without_resources() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        assert!(!toolkit.version().is_empty());
+    }
+
+    #[test]
+    fn test_toolkit_version() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let version = toolkit.version();
+        // Version should look like a version number
+        assert!(!version.is_empty());
+    }
+
+    #[test]
+    fn test_toolkit_page_count_empty() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        assert_eq!(toolkit.page_count(), 0);
+    }
+
+    #[test]
+    fn test_toolkit_get_options() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let options = toolkit.get_options();
+        let trimmed = options.trim();
+        assert!(trimmed.starts_with('{'));
+        assert!(trimmed.ends_with('}'));
+    }
+
+    #[test]
+    fn test_toolkit_get_default_options() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let options = toolkit.get_default_options();
+        assert!(options.starts_with('{'));
+    }
+
+    #[test]
+    fn test_toolkit_get_available_options() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let options = toolkit.get_available_options();
+        assert!(options.starts_with('{'));
+    }
+
+    #[test]
+    fn test_toolkit_available_options() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let options = toolkit
+            .available_options()
+            .expect("Failed to parse available options");
+
+        let scale = options
+            .iter()
+            .find(|option| option.name == "scale")
+            .expect("scale option missing");
+        assert_eq!(scale.kind, crate::OptionKind::Int);
+        assert_eq!(scale.default, serde_json::json!(100));
+    }
+
+    #[test]
+    fn test_toolkit_available_options_by_category() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let categories = toolkit
+            .available_options_by_category()
+            .expect("Failed to parse available options");
+        assert!(categories.len() > 1);
+
+        let (category, _) = categories
+            .iter()
+            .find(|(_, options)| options.iter().any(|option| option.name == "scale"))
+            .expect("scale option missing");
+        assert!(!category.is_empty());
+        assert!(
+            ["Base short options", "General", "General layout"].contains(&category.as_str()),
+            "unexpected category for scale: {}",
+            category
+        );
+
+        let total: usize = categories.values().map(Vec::len).sum();
+        assert_eq!(total, toolkit.available_options().unwrap().len());
+        for (category, options) in &categories {
+            assert!(options.iter().all(|option| &option.group == category));
+        }
+    }
+
+    #[test]
+    fn test_toolkit_options_schema_json() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let schema: serde_json::Value =
+            serde_json::from_str(&toolkit.options_schema_json()).expect("Invalid schema JSON");
+
+        assert_eq!(schema["type"], "object");
+        assert_eq!(schema["properties"]["scale"]["type"], "integer");
+        assert_eq!(schema["properties"]["breaks"]["type"], "string");
+        assert!(schema["properties"]["breaks"]["enum"].is_array());
+    }
+
+    #[test]
+    fn test_toolkit_reset_options() {
+        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        toolkit.reset_options();
+        // Should not panic
+    }
+
+    #[test]
+    fn test_toolkit_on_relayout_no_data() {
+        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let calls = std::sync::Arc::new(std::sync::Mutex::new(0));
+        let counter = std::sync::Arc::clone(&calls);
+        toolkit.on_relayout(move |_| *counter.lock().unwrap() += 1);
+
+        toolkit.redo_layout(None).expect("Failed to redo layout");
+        assert!(toolkit.load_data("not music").is_err());
+        assert_eq!(*calls.lock().unwrap(), 0);
+    }
+
+    #[cfg(feature = "bundled-data")]
+    #[test]
+    fn test_toolkit_on_relayout() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        let page_counts = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = std::sync::Arc::clone(&page_counts);
+        toolkit.on_relayout(move |pages| recorded.lock().unwrap().push(pages));
+
+        toolkit
+            .load_data(&multi_measure_mei(200))
+            .expect("Failed to load MEI");
+        let loaded_pages = toolkit.page_count();
+        assert_eq!(*page_counts.lock().unwrap(), vec![loaded_pages]);
+
+        toolkit
+            .set_breaks(BreakMode::None)
+            .expect("Failed to set breaks");
+        toolkit.redo_layout(None).expect("Failed to redo layout");
+        assert_eq!(*page_counts.lock().unwrap(), vec![loaded_pages, 1, 1]);
+
+        toolkit
+            .render_data(&multi_measure_mei(2), None)
+            .expect("Failed to render data");
+        assert_eq!(*page_counts.lock().unwrap(), vec![loaded_pages, 1, 1, 1]);
+    }
+
+    #[cfg(feature = "bundled-data")]
+    #[test]
+    fn test_toolkit_on_relayout_set_options() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit
+            .load_data(&multi_measure_mei(40))
+            .expect("Failed to load MEI");
+        let calls = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = std::sync::Arc::clone(&calls);
+        toolkit.on_relayout(move |pages| recorded.lock().unwrap().push(pages));
+
+        // Render-only options and unchanged values do not relayout
+        toolkit
+            .set_options(&Options::builder().svg_css("g { fill: red; }").build())
+            .expect("Failed to set options");
+        let page_width = toolkit.current_options().unwrap().page_width.unwrap();
+        toolkit
+            .set_options(&Options::builder().page_width(page_width).build())
+            .expect("Failed to set options");
+        assert!(calls.lock().unwrap().is_empty());
+
+        toolkit
+            .set_options(&Options::builder().page_width(1000).build())
+            .expect("Failed to set options");
+        let pages = toolkit.page_count();
+        assert_eq!(*calls.lock().unwrap(), vec![pages]);
+
+        toolkit.set_scale(60).expect("Failed to set scale");
+        toolkit.reset_options();
+        assert_eq!(calls.lock().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_toolkit_reset() {
+        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        toolkit.set_scale(80).expect("Failed to set scale");
+        let generation = toolkit.generation;
+
+        toolkit.reset().expect("Failed to reset toolkit");
+        assert_eq!(toolkit.page_count(), 0);
+        assert_eq!(toolkit.export_config().expect("Failed to export"), "{}");
+        assert_ne!(toolkit.generation, generation);
+    }
+
+    #[cfg(feature = "bundled-data")]
+    #[test]
+    fn test_toolkit_reset_unloads_document() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        let resource_path = toolkit.get_resource_path();
+        toolkit
+            .set_options(&Options::builder().scale(50).page_width(1000).build())
+            .expect("Failed to set options");
+        toolkit
+            .load_data(&multi_measure_mei(4))
+            .expect("Failed to load MEI");
+        assert!(toolkit.page_count() > 0);
+
+        toolkit.reset().expect("Failed to reset toolkit");
+        assert_eq!(toolkit.page_count(), 0);
+        assert_eq!(toolkit.export_config().expect("Failed to export"), "{}");
+        assert_eq!(toolkit.get_resource_path(), resource_path);
+
+        // The toolkit can be reused
+        toolkit
+            .load_data(&multi_measure_mei(1))
+            .expect("Failed to load MEI");
+        assert!(toolkit.render_to_svg(1).is_ok());
+    }
+
+    #[cfg(feature = "bundled-data")]
+    #[test]
+    fn test_toolkit_reset_drops_retained_source() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit.set_retain_source(true);
+        toolkit
+            .load_data(&multi_measure_mei(2))
+            .expect("Failed to load MEI");
+        assert!(toolkit.content_hash().is_ok());
+
+        toolkit.reset().expect("Failed to reset toolkit");
+        assert!(matches!(toolkit.content_hash(), Err(Error::LoadError(_))));
+
+        toolkit
+            .load_data(&multi_measure_mei(2))
+            .expect("Failed to load MEI");
+        assert!(toolkit.content_hash().is_ok());
+    }
+
+    #[test]
+    fn test_toolkit_scale() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let scale: u32 = toolkit.scale();
+        assert!(scale > 0);
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_toolkit_get_scale() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let scale = toolkit.get_scale();
+        assert!(scale > 0);
+        assert_eq!(scale as u32, toolkit.scale());
+    }
+
+    #[test]
+    fn test_toolkit_set_scale() {
+        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let generation = toolkit.generation();
+        toolkit.set_scale(80).expect("Failed to set scale");
+        assert_eq!(toolkit.scale(), 80);
+        assert_ne!(toolkit.generation(), generation);
+
+        let generation = toolkit.generation();
+        toolkit.set_scale(80).expect("Failed to set scale");
+        assert_eq!(toolkit.generation(), generation);
+    }
+
+    #[test]
+    fn test_toolkit_get_id() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let id = toolkit.get_id();
+        assert!(!id.is_empty());
+    }
+
+    #[test]
+    fn test_toolkit_debug() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let debug = format!("{:?}", toolkit);
+        assert!(debug.contains("Toolkit"));
+        assert!(debug.contains("version"));
+    }
+
+    #[test]
+    fn test_toolkit_render_to_svg_no_data() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.render_to_svg(1);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_toolkit_render_to_svg_without_resources() {
+        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+
+        let err = toolkit.render_to_svg(1).unwrap_err();
+        assert!(matches!(err, Error::InitializationError(_)));
+        assert!(
+            err.to_string()
+                .contains("rendering requires resources; use Toolkit::new()")
+        );
+        assert!(matches!(
+            toolkit.render_to_svg_with_declaration(1),
+            Err(Error::InitializationError(_))
+        ));
+        assert!(matches!(
+            toolkit.render_to_svg_timed(1),
+            Err(Error::InitializationError(_))
+        ));
+        assert!(matches!(
+            toolkit.render_data("@clef:G-2\n@data:4C", None),
+            Err(Error::InitializationError(_))
+        ));
+
+        // Non-rendering operations remain available
+        assert!(!toolkit.version().is_empty());
+        assert!(toolkit.get_options().contains("scale"));
+    }
+
+    #[test]
+    fn test_toolkit_render_to_svg_page_zero() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.render_to_svg(0);
+        assert!(result.is_err());
+        let err = result.unwrap_err();
+        assert!(err.to_string().contains("out of range"));
+    }
+
+    #[test]
+    fn test_toolkit_set_options() {
+        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let options = Options::builder().scale(80).build();
+        toolkit
+            .set_options(&options)
+            .expect("Failed to set options");
+    }
+
+    #[test]
+    fn test_toolkit_load_data_empty() {
+        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.load_data("");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_toolkit_load_file_not_found() {
+        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.load_file(Path::new("/nonexistent/path/to/file.mei"));
+        assert!(result.is_err());
+        let err = result.unwrap_err();
+        assert!(err.to_string().contains("file not found"));
+    }
+
+    #[test]
+    fn test_toolkit_set_svg_css_file_not_found() {
+        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let err = toolkit
+            .set_svg_css_file(Path::new("/nonexistent/path/to/theme.css"))
+            .unwrap_err();
+        assert!(matches!(err, Error::FileNotFound(_)));
+    }
+
+    #[cfg(feature = "bundled-data")]
+    #[test]
+    fn test_toolkit_set_svg_css_file() {
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let path = temp_dir.path().join("theme.css");
+        std::fs::write(&path, "g.note { fill: #336699; }\n").expect("Failed to write CSS");
+
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit
+            .load_data(&multi_measure_mei(1))
+            .expect("Failed to load MEI");
+        toolkit
+            .set_svg_css_file(&path)
+            .expect("Failed to set CSS file");
+
+        let svg = toolkit.render_to_svg(1).expect("Failed to render SVG");
+        assert!(svg.contains("g.note { fill: #336699; }"));
+    }
+
+    #[test]
+    fn test_toolkit_is_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<Toolkit>();
+    }
+
+    #[test]
+    fn test_toolkit_enable_log() {
+        Toolkit::enable_log(true);
+        Toolkit::enable_log(false);
+        // Should not panic
+    }
+
+    #[test]
+    fn test_toolkit_enable_log_to_buffer() {
+        Toolkit::enable_log_to_buffer(true);
+        Toolkit::enable_log_to_buffer(false);
+        // Should not panic
+    }
+
+    #[test]
+    fn test_toolkit_get_log() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let _log = toolkit.get_log();
+        // Log may be empty, that's fine
+    }
+
+    #[cfg(feature = "bundled-data")]
+    #[test]
+    fn test_toolkit_new_with_bundled_data() {
+        let toolkit = Toolkit::new().expect("Failed to create toolkit");
+        assert!(!toolkit.version().is_empty());
+        assert!(!toolkit.get_resource_path().is_empty());
+    }
+
+    #[cfg(feature = "bundled-data")]
+    #[test]
+    fn test_toolkit_load_simple_mei() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+
+        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
+<mei xmlns="http://www.music-encoding.org/ns/mei">
+  <music>
+    <body>
+      <mdiv>
+        <score>
+          <scoreDef>
+            <staffGrp>
+              <staffDef n="1" lines="5" clef.shape="G" clef.line="2"/>
+            </staffGrp>
+          </scoreDef>
+          <section>
+            <measure>
+              <staff n="1">
+                <layer n="1">
+                  <note pname="c" oct="4" dur="4"/>
+                </layer>
+              </staff>
+            </measure>
+          </section>
+        </score>
+      </mdiv>
+    </body>
+  </music>
+</mei>"#;
+
+        toolkit.load_data(mei).expect("Failed to load MEI");
+        assert!(toolkit.page_count() > 0);
+    }
+
+    #[cfg(feature = "bundled-data")]
+    #[test]
+    fn test_toolkit_render_simple_mei() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+
+        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
+<mei xmlns="http://www.music-encoding.org/ns/mei">
+  <music>
+    <body>
+      <mdiv>
+        <score>
+          <scoreDef>
+            <staffGrp>
+              <staffDef n="1" lines="5" clef.shape="G" clef.line="2"/>
+            </staffGrp>
+          </scoreDef>
+          <section>
+            <measure>
+              <staff n="1">
+                <layer n="1">
+                  <note pname="c" oct="4" dur="4"/>
+                </layer>
+              </staff>
+            </measure>
+          </section>
+        </score>
+      </mdiv>
+    </body>
+  </music>
+</mei>"#;
+
+        toolkit.load_data(mei).expect("Failed to load MEI");
+
+        let svg = toolkit.render_to_svg(1).expect("Failed to render SVG");
+        assert!(svg.contains("<svg"));
+        assert!(svg.contains("</svg>"));
+    }
+
+    #[cfg(feature = "bundled-data")]
+    #[test]
+    fn test_toolkit_render_all_pages() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+
+        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
+<mei xmlns="http://www.music-encoding.org/ns/mei">
+  <music>
+    <body>
+      <mdiv>
+        <score>
+          <scoreDef>
+            <staffGrp>
+              <staffDef n="1" lines="5" clef.shape="G" clef.line="2"/>
+            </staffGrp>
+          </scoreDef>
+          <section>
+            <measure>
+              <staff n="1">
+                <layer n="1">
+                  <note pname="c" oct="4" dur="4"/>
+                </layer>
+              </staff>
+            </measure>
+          </section>
+        </score>
+      </mdiv>
+    </body>
+  </music>
+</mei>"#;
+
+        toolkit.load_data(mei).expect("Failed to load MEI");
+
+        let pages = toolkit.render_all_pages().expect("Failed to render pages");
+        assert!(!pages.is_empty());
+        for page in &pages {
+            assert!(page.contains("<svg"));
+        }
+    }
+
+    #[cfg(feature = "bundled-data")]
+    #[test]
+    fn test_toolkit_get_mei() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+
+        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
+<mei xmlns="http://www.music-encoding.org/ns/mei">
+  <music>
+    <body>
+      <mdiv>
+        <score>
+          <scoreDef>
+            <staffGrp>
+              <staffDef n="1" lines="5" clef.shape="G" clef.line="2"/>
+            </staffGrp>
+          </scoreDef>
+          <section>
+            <measure>
+              <staff n="1">
+                <layer n="1">
+                  <note pname="c" oct="4" dur="4"/>
+                </layer>
+              </staff>
+            </measure>
+          </section>
+        </score>
+      </mdiv>
+    </body>
+  </music>
+</mei>"#;
+
+        toolkit.load_data(mei).expect("Failed to load MEI");
+
+        let exported_mei = toolkit.get_mei().expect("Failed to export MEI");
+        assert!(exported_mei.contains("mei"));
+    }
+
+    #[cfg(feature = "bundled-data")]
+    #[test]
+    fn test_toolkit_with_options() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+
+        let options = Options::builder()
+            .scale(50)
+            .page_width(1000)
+            .page_height(1000)
+            .adjust_page_height(true)
+            .build();
+
+        toolkit
+            .set_options(&options)
+            .expect("Failed to set options");
+
+        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
+<mei xmlns="http://www.music-encoding.org/ns/mei">
+  <music>
+    <body>
+      <mdiv>
+        <score>
+          <scoreDef>
+            <staffGrp>
+              <staffDef n="1" lines="5" clef.shape="G" clef.line="2"/>
+            </staffGrp>
+          </scoreDef>
+          <section>
+            <measure>
+              <staff n="1">
+                <layer n="1">
+                  <note pname="c" oct="4" dur="4"/>
+                </layer>
+              </staff>
+            </measure>
+          </section>
+        </score>
+      </mdiv>
+    </body>
+  </music>
+</mei>"#;
+
+        toolkit.load_data(mei).expect("Failed to load MEI");
+
+        let svg = toolkit.render_to_svg(1).expect("Failed to render SVG");
+        assert!(svg.contains("<svg"));
+    }
+
+    // =========================================================================
+    // Additional tests for improved coverage
+    // =========================================================================
+
+    #[test]
+    fn test_toolkit_render_to_svg_with_declaration_page_zero() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.render_to_svg_with_declaration(0);
+        assert!(result.is_err());
+        let err = result.unwrap_err();
+        assert!(err.to_string().contains("out of range"));
+    }
+
+    #[test]
+    fn test_toolkit_render_to_svg_with_declaration_no_data() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.render_to_svg_with_declaration(1);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_toolkit_get_mei_no_data() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.get_mei();
+        // With no data loaded, this may return an empty MEI or error depending on Verovio
+        // Just ensure it doesn't panic and returns a result
+        let _ = result;
+    }
+
+    #[test]
+    fn test_toolkit_get_mei_with_options_no_data() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.get_mei_with_options(r#"{"removeIds": true}"#);
+        // May succeed with empty result or error - just ensure no panic
+        let _ = result;
+    }
+
+    #[test]
+    fn test_toolkit_get_humdrum_no_data() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.get_humdrum();
+        // May return empty or error - just ensure no panic
+        let _ = result;
+    }
+
+    #[test]
+    fn test_toolkit_render_to_midi_no_data() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.render_to_midi();
+        assert!(result.is_err(), "render_to_midi should fail without data");
+        let err = result.unwrap_err();
+        assert!(
+            err.to_string().contains("no data loaded"),
+            "Error should mention no data loaded"
+        );
+    }
+
+    #[test]
+    fn test_toolkit_render_to_pae_no_data() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.render_to_pae();
+        assert!(result.is_err(), "render_to_pae should fail without data");
+        let err = result.unwrap_err();
+        assert!(
+            err.to_string().contains("no data loaded"),
+            "Error should mention no data loaded"
+        );
+    }
+
+    #[test]
+    fn test_toolkit_render_to_timemap_no_data() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.render_to_timemap();
+        // Returns JSON array (possibly empty) or error
+        let _ = result;
+    }
+
+    #[test]
+    fn test_toolkit_render_to_timemap_with_options_no_data() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.render_to_timemap_with_options(r#"{"includeMeasures": true}"#);
+        let _ = result;
+    }
+
+    #[test]
+    fn test_toolkit_render_to_expansion_map_no_data() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.render_to_expansion_map();
+        let _ = result;
+    }
+
+    #[test]
+    fn test_toolkit_set_scale_invalid() {
+        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        // Scale of 0 should be invalid
+        let result = toolkit.set_scale(0);
+        // Verovio may accept or reject this - test that we handle the result
+        let _ = result;
+    }
+
+    #[test]
+    fn test_toolkit_set_scale_negative() {
+        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let before = toolkit.scale();
+
+        for scale in [-1, -5] {
+            let err = toolkit.set_scale(scale).unwrap_err();
+            assert!(matches!(err, Error::OptionsError(_)));
+            assert!(err.to_string().contains("invalid scale"));
+        }
+        assert_eq!(toolkit.scale(), before);
+    }
+
+    #[test]
+    fn test_toolkit_set_scale_large() {
+        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        // Very large scale
+        let result = toolkit.set_scale(10000);
+        let _ = result;
+    }
+
+    #[test]
+    fn test_toolkit_get_resource_path() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let path = toolkit.get_resource_path();
+        // Without resources, path may be empty
+        let _ = path;
+    }
+
+    #[test]
+    fn test_toolkit_set_resource_path_nonexistent() {
+        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.set_resource_path(Path::new("/nonexistent/path/to/resources"));
+        // This may succeed (just sets the path) or fail depending on implementation
+        let _ = result;
+    }
+
+    #[test]
+    fn test_toolkit_get_page_with_element_not_found() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.get_page_with_element("nonexistent-id");
+        // Should return 0 when element not found
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), 0);
+    }
+
+    #[test]
+    fn test_toolkit_get_element_attr_not_found() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.get_element_attr("nonexistent-id");
+        // May return empty JSON or error
+        let _ = result;
+    }
+
+    #[test]
+    fn test_toolkit_get_elements_at_time_no_data() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.get_elements_at_time(0);
+        // May return empty array or error
+        let _ = result;
+    }
+
+    #[test]
+    fn test_toolkit_get_elements_at_time_negative() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.get_elements_at_time(-1000);
+        let _ = result;
+    }
+
+    #[test]
+    fn test_toolkit_get_time_for_element_not_found() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.get_time_for_element("nonexistent-id");
+        assert!(result.is_ok());
+        // Time may be 0 or negative when not found
+    }
+
+    #[test]
+    fn test_toolkit_redo_layout_no_data() {
+        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.redo_layout(None);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_toolkit_redo_layout_with_options() {
+        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.redo_layout(Some(r#"{"pageWidth": 2100}"#));
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_toolkit_edit_no_data() {
+        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.edit(r#"{"action": "commit"}"#);
+        // Edit without data may fail
+        let _ = result;
+    }
+
+    #[test]
+    fn test_toolkit_edit_invalid_json() {
+        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.edit("not valid json");
+        // Should handle invalid JSON gracefully
+        let _ = result;
+    }
+
+    #[test]
+    fn test_toolkit_edit_info() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let info = toolkit.edit_info();
+        // Should return JSON (possibly empty object)
+        let _ = info;
+    }
+
+    #[test]
+    fn test_toolkit_load_data_with_null_byte() {
+        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.load_data("test\0data");
+        assert!(result.is_err());
+        let err = result.unwrap_err();
+        assert!(err.to_string().contains("null byte"));
+    }
+
+    #[test]
+    fn test_toolkit_load_data_malformed_mei() {
+        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.load_data("<mei><invalid></mei>");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_toolkit_load_data_random_text() {
+        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.load_data("This is just random text, not valid music notation");
+        assert!(result.is_err());
+    }
+
+    #[cfg(feature = "bundled-data")]
+    #[test]
+    fn test_toolkit_load_data_with_bom() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        let data = format!("\u{feff}{}", multi_measure_mei(1));
+        toolkit
+            .load_data(&data)
+            .expect("Failed to load BOM-prefixed MEI");
+        assert_eq!(toolkit.page_count(), 1);
+    }
+
+    #[cfg(feature = "bundled-data")]
+    #[test]
+    fn test_toolkit_load_reader_with_bom() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        let data = format!("\u{feff}{}", multi_measure_mei(1));
+        toolkit
+            .load_reader(data.as_bytes())
+            .expect("Failed to load BOM-prefixed MEI");
+        assert_eq!(toolkit.page_count(), 1);
+    }
+
+    #[test]
+    fn test_toolkit_load_reader_invalid_utf8() {
+        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.load_reader(&[0xff, 0xfe, 0x00][..]);
+        assert!(matches!(result, Err(Error::InvalidUtf8 { .. })));
+    }
+
+    #[test]
+    fn test_toolkit_get_mei_with_options_null_byte() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.get_mei_with_options("test\0options");
+        assert!(result.is_err());
+        let err = result.unwrap_err();
+        assert!(err.to_string().contains("null byte"));
+    }
+
+    #[test]
+    fn test_toolkit_render_to_timemap_with_options_null_byte() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.render_to_timemap_with_options("test\0options");
+        assert!(result.is_err());
+        let err = result.unwrap_err();
+        assert!(err.to_string().contains("null byte"));
+    }
+
+    #[test]
+    fn test_toolkit_get_page_with_element_null_byte() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.get_page_with_element("test\0id");
+        assert!(result.is_err());
+        let err = result.unwrap_err();
+        assert!(err.to_string().contains("null byte"));
+    }
+
+    #[test]
+    fn test_toolkit_get_element_attr_null_byte() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.get_element_attr("test\0id");
+        assert!(result.is_err());
+        let err = result.unwrap_err();
+        assert!(err.to_string().contains("null byte"));
+    }
+
+    #[test]
+    fn test_toolkit_get_time_for_element_null_byte() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.get_time_for_element("test\0id");
+        assert!(result.is_err());
+        let err = result.unwrap_err();
+        assert!(err.to_string().contains("null byte"));
+    }
+
+    #[test]
+    fn test_toolkit_redo_layout_null_byte() {
+        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.redo_layout(Some("test\0options"));
+        assert!(result.is_err());
+        let err = result.unwrap_err();
+        assert!(err.to_string().contains("null byte"));
+    }
+
+    #[test]
+    fn test_toolkit_edit_null_byte() {
+        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.edit("test\0action");
+        assert!(result.is_err());
+        let err = result.unwrap_err();
+        assert!(err.to_string().contains("null byte"));
+    }
+
+    #[test]
+    fn test_toolkit_set_options_null_byte_in_font() {
+        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        // Create options with a null byte in a string field
+        let mut options = Options::builder().build();
+        options.font = Some("test\0font".to_string());
+        let result = toolkit.set_options(&options);
+        // The JSON serialization should succeed, but CString creation may fail
+        // or the toolkit may reject it
+        let _ = result;
+    }
+
+    #[test]
+    fn test_toolkit_render_all_pages_no_data() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.render_all_pages();
+        // With no data, page_count is 0, so we get empty vec
+        assert!(result.is_ok());
+        assert!(result.unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_toolkit_render_all_pages_parallel_no_data() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let pages = toolkit
+            .render_all_pages_parallel()
+            .expect("Rendering nothing should succeed");
+        assert!(pages.is_empty());
+    }
+
+    #[cfg(feature = "bundled-data")]
+    #[test]
+    fn test_toolkit_render_all_pages_parallel_matches_sequential() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit
+            .load_data(&multi_measure_mei(200))
+            .expect("Failed to load MEI");
+        let mei = toolkit.get_mei().expect("Failed to export MEI");
+
+        // Load MEI carrying its xml:ids right after seeding, as the workers do
+        toolkit.reset_xml_id_seed(42);
+        toolkit.load_data(&mei).expect("Failed to reload MEI");
+        assert!(toolkit.page_count() > 1);
+
+        let sequential = toolkit.render_all_pages().expect("Failed to render");
+        let parallel = toolkit
+            .render_all_pages_parallel()
+            .expect("Failed to render in parallel");
+        assert_eq!(parallel, sequential);
+    }
+
+    #[cfg(feature = "bundled-data")]
+    #[test]
+    fn test_toolkit_render_all_pages_parallel_text_lang() {
+        let mei = multi_measure_mei(200).replace(
+            r#"<note pname="c" oct="4" dur="4"/>"#,
+            r#"<note pname="c" oct="4" dur="4"><verse n="1"><syl>la</syl></verse></note>"#,
+        );
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit.load_data(&mei).expect("Failed to load MEI");
+        let mei = toolkit.get_mei().expect("Failed to export MEI");
+        toolkit.reset_xml_id_seed(42);
+        toolkit.load_data(&mei).expect("Failed to reload MEI");
+        toolkit
+            .set_text_lang(Some("he"))
+            .expect("Failed to set language");
+
+        let sequential = toolkit.render_all_pages().expect("Failed to render");
+        assert!(sequential[0].contains(r#"xml:lang="he""#));
+        let parallel = toolkit
+            .render_all_pages_parallel()
+            .expect("Failed to render in parallel");
+        assert_eq!(parallel, sequential);
+
+        let verse = toolkit.render_verse(1, 1).expect("Failed to render verse");
+        assert!(verse.contains(r#"xml:lang="he""#));
+    }
+
+    #[test]
+    fn test_toolkit_try_render_all_pages_no_data() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        assert!(toolkit.try_render_all_pages().is_empty());
+    }
+
+    #[cfg(feature = "bundled-data")]
+    #[test]
+    fn test_toolkit_try_render_all_pages() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit
+            .load_data(&multi_measure_mei(200))
+            .expect("Failed to load MEI");
+
+        let results = toolkit.try_render_all_pages();
+        assert!(results.len() > 1);
+        assert_eq!(results.len(), toolkit.page_count() as usize);
+        for result in results {
+            assert!(result.expect("Page should render").contains("<svg"));
+        }
+    }
+
+    #[cfg(feature = "bundled-data")]
+    #[test]
+    fn test_toolkit_render_to_svg_with_declaration() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+
+        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
+<mei xmlns="http://www.music-encoding.org/ns/mei">
+  <music>
+    <body>
+      <mdiv>
+        <score>
+          <scoreDef>
+            <staffGrp>
+              <staffDef n="1" lines="5" clef.shape="G" clef.line="2"/>
+            </staffGrp>
+          </scoreDef>
+          <section>
+            <measure>
+              <staff n="1">
+                <layer n="1">
+                  <note pname="c" oct="4" dur="4"/>
+                </layer>
+              </staff>
+            </measure>
+          </section>
+        </score>
+      </mdiv>
+    </body>
+  </music>
+</mei>"#;
+
+        toolkit.load_data(mei).expect("Failed to load MEI");
+
+        let svg = toolkit
+            .render_to_svg_with_declaration(1)
+            .expect("Failed to render SVG");
+        assert!(svg.contains("<?xml"));
+        assert!(svg.contains("<svg"));
+    }
+
+    #[cfg(feature = "bundled-data")]
+    #[test]
+    fn test_toolkit_render_to_midi() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+
+        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
+<mei xmlns="http://www.music-encoding.org/ns/mei">
+  <music>
+    <body>
+      <mdiv>
+        <score>
+          <scoreDef>
+            <staffGrp>
+              <staffDef n="1" lines="5" clef.shape="G" clef.line="2"/>
+            </staffGrp>
+          </scoreDef>
+          <section>
+            <measure>
+              <staff n="1">
+                <layer n="1">
+                  <note pname="c" oct="4" dur="4"/>
+                </layer>
+              </staff>
+            </measure>
+          </section>
+        </score>
+      </mdiv>
+    </body>
+  </music>
+</mei>"#;
+
+        toolkit.load_data(mei).expect("Failed to load MEI");
+
+        let result = toolkit.render_to_midi();
+        // MIDI rendering should succeed with valid MEI
+        assert!(result.is_ok());
+        let midi = result.unwrap();
+        // MIDI is base64-encoded
+        assert!(!midi.is_empty());
+    }
+
+    #[cfg(feature = "bundled-data")]
+    #[test]
+    fn test_toolkit_get_mei_with_options() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+
+        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
+<mei xmlns="http://www.music-encoding.org/ns/mei">
+  <music>
+    <body>
+      <mdiv>
+        <score>
+          <scoreDef>
+            <staffGrp>
+              <staffDef n="1" lines="5" clef.shape="G" clef.line="2"/>
+            </staffGrp>
+          </scoreDef>
+          <section>
+            <measure>
+              <staff n="1">
+                <layer n="1">
+                  <note pname="c" oct="4" dur="4"/>
+                </layer>
+              </staff>
+            </measure>
+          </section>
+        </score>
+      </mdiv>
+    </body>
+  </music>
+</mei>"#;
+
+        toolkit.load_data(mei).expect("Failed to load MEI");
+
+        let result = toolkit.get_mei_with_options(r#"{"removeIds": false}"#);
+        assert!(result.is_ok());
+        let exported = result.unwrap();
+        assert!(exported.contains("mei"));
+    }
+
+    #[cfg(feature = "bundled-data")]
+    #[test]
+    fn test_toolkit_render_to_timemap() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+
+        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
+<mei xmlns="http://www.music-encoding.org/ns/mei">
+  <music>
+    <body>
+      <mdiv>
+        <score>
+          <scoreDef>
+            <staffGrp>
+              <staffDef n="1" lines="5" clef.shape="G" clef.line="2"/>
+            </staffGrp>
+          </scoreDef>
+          <section>
+            <measure>
+              <staff n="1">
+                <layer n="1">
+                  <note pname="c" oct="4" dur="4"/>
+                </layer>
+              </staff>
+            </measure>
+          </section>
+        </score>
+      </mdiv>
+    </body>
+  </music>
+</mei>"#;
+
+        toolkit.load_data(mei).expect("Failed to load MEI");
+
+        let result = toolkit.render_to_timemap();
+        assert!(result.is_ok());
+        let timemap = result.unwrap();
+        // Timemap is JSON
+        assert!(timemap.starts_with('[') || timemap.starts_with('{'));
+    }
+
+    #[cfg(feature = "bundled-data")]
+    #[test]
+    fn test_toolkit_render_to_expansion_map() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+
+        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
+<mei xmlns="http://www.music-encoding.org/ns/mei">
+  <music>
+    <body>
+      <mdiv>
+        <score>
+          <scoreDef>
+            <staffGrp>
+              <staffDef n="1" lines="5" clef.shape="G" clef.line="2"/>
+            </staffGrp>
+          </scoreDef>
+          <section>
+            <measure>
+              <staff n="1">
+                <layer n="1">
+                  <note pname="c" oct="4" dur="4"/>
+                </layer>
+              </staff>
+            </measure>
+          </section>
+        </score>
+      </mdiv>
+    </body>
+  </music>
+</mei>"#;
+
+        toolkit.load_data(mei).expect("Failed to load MEI");
+
+        let result = toolkit.render_to_expansion_map();
+        assert!(result.is_ok());
+    }
+
+    #[cfg(feature = "bundled-data")]
+    #[test]
+    fn test_toolkit_redo_layout_after_load() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+
+        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
+<mei xmlns="http://www.music-encoding.org/ns/mei">
+  <music>
+    <body>
+      <mdiv>
+        <score>
+          <scoreDef>
+            <staffGrp>
+              <staffDef n="1" lines="5" clef.shape="G" clef.line="2"/>
+            </staffGrp>
+          </scoreDef>
+          <section>
+            <measure>
+              <staff n="1">
+                <layer n="1">
+                  <note pname="c" oct="4" dur="4"/>
+                </layer>
+              </staff>
+            </measure>
+          </section>
+        </score>
+      </mdiv>
+    </body>
+  </music>
+</mei>"#;
+
+        toolkit.load_data(mei).expect("Failed to load MEI");
+
+        // Redo layout with different options
+        let result = toolkit.redo_layout(Some(r#"{"pageWidth": 1500}"#));
+        assert!(result.is_ok());
+
+        // Should still be able to render
+        let svg = toolkit
+            .render_to_svg(1)
+            .expect("Failed to render after redo");
+        assert!(svg.contains("<svg"));
+    }
+
+    #[cfg(feature = "bundled-data")]
+    #[test]
+    fn test_toolkit_set_and_get_resource_path() {
+        let toolkit = Toolkit::new().expect("Failed to create toolkit");
+        let path = toolkit.get_resource_path();
+        // With bundled data, resource path should not be empty
+        assert!(!path.is_empty());
+    }
+
+    #[cfg(feature = "bundled-data")]
+    #[test]
+    fn test_toolkit_get_elements_at_time_with_data() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+
+        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
+<mei xmlns="http://www.music-encoding.org/ns/mei">
+  <music>
+    <body>
+      <mdiv>
+        <score>
+          <scoreDef>
+            <staffGrp>
+              <staffDef n="1" lines="5" clef.shape="G" clef.line="2"/>
+            </staffGrp>
+          </scoreDef>
+          <section>
+            <measure>
+              <staff n="1">
+                <layer n="1">
+                  <note pname="c" oct="4" dur="4"/>
+                </layer>
+              </staff>
+            </measure>
+          </section>
+        </score>
+      </mdiv>
+    </body>
+  </music>
+</mei>"#;
+
+        toolkit.load_data(mei).expect("Failed to load MEI");
+
+        let result = toolkit.get_elements_at_time(0);
+        assert!(result.is_ok());
+        let elements = result.unwrap();
+        // Should be valid JSON
+        assert!(elements.starts_with('{') || elements.starts_with('['));
+    }
+
+    #[test]
+    fn test_toolkit_with_resource_path_nonexistent() {
+        let result = Toolkit::with_resource_path(Path::new("/nonexistent/resources"));
+        // May fail or succeed depending on whether Verovio validates the path
+        let _ = result;
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_toolkit_with_resource_path_invalid_utf8() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let path = Path::new(std::ffi::OsStr::from_bytes(b"/tmp/verovio-\xff"));
+        let result = Toolkit::with_resource_path(path);
+        match result {
+            Err(Error::InvalidUtf8 { context }) => assert_eq!(context, "resource path"),
+            other => panic!("expected InvalidUtf8, got {:?}", other),
+        }
+    }
+
+    #[cfg(feature = "bundled-data")]
+    #[test]
+    fn test_toolkit_load_file_with_tempfile() {
+        use std::io::Write;
+
+        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+
+        // Create a temp file with invalid content
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let file_path = temp_dir.path().join("test.mei");
+        let mut file = std::fs::File::create(&file_path).expect("Failed to create file");
+        file.write_all(b"<invalid>not valid mei</invalid>")
+            .expect("Failed to write");
+
+        let result = toolkit.load_file(&file_path);
+        // Should fail to load invalid MEI
+        assert!(result.is_err());
+    }
+
+    #[cfg(feature = "bundled-data")]
+    #[test]
+    fn test_toolkit_load_file_with_valid_mei() {
+        use std::io::Write;
+
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+
+        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
+<mei xmlns="http://www.music-encoding.org/ns/mei">
+  <music>
+    <body>
+      <mdiv>
+        <score>
+          <scoreDef>
+            <staffGrp>
+              <staffDef n="1" lines="5" clef.shape="G" clef.line="2"/>
+            </staffGrp>
+          </scoreDef>
+          <section>
+            <measure>
+              <staff n="1">
+                <layer n="1">
+                  <note pname="c" oct="4" dur="4"/>
+                </layer>
+              </staff>
+            </measure>
+          </section>
+        </score>
+      </mdiv>
+    </body>
+  </music>
+</mei>"#;
+
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let file_path = temp_dir.path().join("test.mei");
+        let mut file = std::fs::File::create(&file_path).expect("Failed to create file");
+        file.write_all(mei.as_bytes()).expect("Failed to write");
+
+        let result = toolkit.load_file(&file_path);
+        assert!(result.is_ok());
+        assert!(toolkit.page_count() > 0);
+    }
+
+    #[test]
+    fn test_toolkit_not_sync() {
+        // This is a compile-time check - Toolkit should NOT implement Sync
+        // We can't easily test this at runtime, but we document it here
+        // fn assert_sync<T: Sync>() {}
+        // assert_sync::<Toolkit>(); // This would fail to compile
+    }
+
+    #[test]
+    fn test_toolkit_debug_format_detailed() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let debug = format!("{:?}", toolkit);
+        assert!(debug.contains("Toolkit"));
+        assert!(debug.contains("version"));
+        assert!(debug.contains("page_count"));
+        assert!(debug.contains("resource_path"));
+    }
+
+    #[test]
+    fn test_toolkit_enable_log_toggle() {
+        // Test toggling log on and off multiple times
+        Toolkit::enable_log(true);
+        Toolkit::enable_log(true);
+        Toolkit::enable_log(false);
+        Toolkit::enable_log(false);
+        Toolkit::enable_log(true);
+        Toolkit::enable_log(false);
+    }
+
+    #[test]
+    fn test_toolkit_enable_log_to_buffer_toggle() {
+        // Test toggling buffer log on and off multiple times
+        Toolkit::enable_log_to_buffer(true);
+        Toolkit::enable_log_to_buffer(true);
+        Toolkit::enable_log_to_buffer(false);
+        Toolkit::enable_log_to_buffer(false);
+        Toolkit::enable_log_to_buffer(true);
+        Toolkit::enable_log_to_buffer(false);
+    }
+
+    #[cfg(feature = "bundled-data")]
+    #[test]
+    fn test_toolkit_logging_with_data() {
+        Toolkit::enable_log_to_buffer(true);
+
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+
+        // Load some data to generate log messages
+        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
+<mei xmlns="http://www.music-encoding.org/ns/mei">
+  <music>
+    <body>
+      <mdiv>
+        <score>
+          <scoreDef>
+            <staffGrp>
+              <staffDef n="1" lines="5" clef.shape="G" clef.line="2"/>
+            </staffGrp>
+          </scoreDef>
+          <section>
+            <measure>
+              <staff n="1">
+                <layer n="1">
+                  <note pname="c" oct="4" dur="4"/>
+                </layer>
+              </staff>
+            </measure>
+          </section>
+        </score>
+      </mdiv>
+    </body>
+  </music>
+</mei>"#;
+
+        toolkit.load_data(mei).expect("Failed to load MEI");
+
+        let log = toolkit.get_log();
+        // Log may or may not have content depending on Verovio's behavior
+        let _ = log;
+
+        Toolkit::enable_log_to_buffer(false);
+    }
+
+    #[cfg(feature = "bundled-data")]
+    #[test]
+    fn test_toolkit_render_to_svg_page_exceeds_count() {
+        let toolkit = Toolkit::new().expect("Failed to create toolkit");
+        // With no data, page_count is 0, so page 1 should be out of range
+        let result = toolkit.render_to_svg(1);
+        assert!(result.is_err());
+        let err = result.unwrap_err();
+        assert!(err.to_string().contains("out of range"));
+        assert!(err.to_string().contains("0 pages"));
+    }
+
+    #[cfg(feature = "bundled-data")]
+    #[test]
+    fn test_toolkit_render_to_svg_after_set_options_without_relayout() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit
+            .load_data(&multi_measure_mei(40))
+            .expect("Failed to load data");
+        let pages_before = toolkit.page_count();
+
+        // Narrower pages change the page count; no explicit relayout follows
+        toolkit
+            .set_options(&Options::builder().page_width(1000).build())
+            .expect("Failed to set options");
+
+        let svg = toolkit.render_to_svg(1).expect("Failed to render page 1");
+        assert!(svg.contains("<svg"));
+
+        let pages_after = toolkit.page_count();
+        assert!(pages_after > pages_before);
+        assert!(toolkit.render_to_svg(pages_after).is_ok());
+        assert!(toolkit.render_to_svg(pages_after + 1).is_err());
+    }
+
+    #[cfg(feature = "bundled-data")]
+    #[test]
+    fn test_toolkit_render_to_svg_with_declaration_page_exceeds_count() {
+        let toolkit = Toolkit::new().expect("Failed to create toolkit");
+        let result = toolkit.render_to_svg_with_declaration(100);
+        assert!(result.is_err());
+        let err = result.unwrap_err();
+        assert!(err.to_string().contains("out of range"));
+    }
+
+    // =========================================================================
+    // Tests for new API methods (26 functions for 100% coverage)
+    // =========================================================================
+
+    // Format Control Functions
+    #[test]
+    fn test_toolkit_set_input_from() {
+        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.set_input_from("mei");
+        // May succeed or fail depending on Verovio behavior
+        let _ = result;
+    }
+
+    #[test]
+    fn test_toolkit_set_input_from_various_formats() {
+        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        for format in &["mei", "musicxml", "humdrum", "pae", "abc"] {
+            let _ = toolkit.set_input_from(format);
+        }
+    }
+
+    #[test]
+    fn test_toolkit_set_input_from_null_byte() {
+        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.set_input_from("mei\0invalid");
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("null byte"));
+    }
+
+    #[test]
+    fn test_toolkit_detect_format() {
+        assert_eq!(
+            Toolkit::detect_format(
+                "<?xml version=\"1.0\"?>\n<!-- score -->\n<mei xmlns=\"http://www.music-encoding.org/ns/mei\">"
+            ),
+            Some("mei")
+        );
+        assert_eq!(
+            Toolkit::detect_format(
+                "\u{feff}<?xml version=\"1.0\"?>\n<!DOCTYPE score-partwise>\n<score-partwise version=\"4.0\">"
+            ),
+            Some("musicxml")
+        );
+        assert_eq!(
+            Toolkit::detect_format("**kern\n*clefG2\n4c\n*-"),
+            Some("humdrum")
+        );
+        assert_eq!(
+            Toolkit::detect_format("@clef:G-2\n@data:4CDEF"),
+            Some("pae")
+        );
+        assert_eq!(Toolkit::detect_format("%abc\nX:1\nK:C\nCDEF|"), Some("abc"));
+        assert_eq!(Toolkit::detect_format("<html><body/></html>"), None);
+        assert_eq!(Toolkit::detect_format("random text"), None);
+        assert_eq!(Toolkit::detect_format(""), None);
+    }
+
+    #[test]
+    fn test_toolkit_load_data_input_format_mismatch() {
+        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        toolkit
+            .set_input_from("mei")
+            .expect("Failed to set input format");
+
+        let abc = "X:1\nT:Scale\nM:4/4\nL:1/4\nK:C\nCDEF|\n";
+        let err = toolkit.load_data(abc).unwrap_err();
+        assert!(matches!(err, Error::LoadError(_)));
+        assert!(err.to_string().contains("\"mei\""));
+        assert!(err.to_string().contains("looks like abc"));
+
+        toolkit.set_input_format_check(false);
+        if let Err(err) = toolkit.load_data(abc) {
+            assert!(!err.to_string().contains("looks like"));
+        }
+    }
+
+    #[test]
+    fn test_toolkit_load_data_input_format_matches() {
+        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        toolkit
+            .set_input_from("abc")
+            .expect("Failed to set input format");
+
+        if let Err(err) = toolkit.load_data("X:1\nK:C\nCDEF|\n") {
+            assert!(!err.to_string().contains("looks like"));
+        }
+    }
+
+    #[test]
+    fn test_toolkit_set_output_to() {
+        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.set_output_to("mei");
+        let _ = result;
+    }
+
+    #[test]
+    fn test_toolkit_set_output_to_various_formats() {
+        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        for format in &["mei", "svg", "midi", "humdrum", "pae"] {
+            let _ = toolkit.set_output_to(format);
+        }
+    }
+
+    #[test]
+    fn test_toolkit_set_output_to_null_byte() {
+        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.set_output_to("mei\0invalid");
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("null byte"));
+    }
+
+    // ZIP Loading Functions
+    // Note: load_zip_data functions can throw C++ exceptions on invalid input
+    // so we only test the null byte handling (which is caught by Rust before FFI)
+
+    #[test]
+    fn test_toolkit_load_zip_data_base64_null_byte() {
+        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.load_zip_data_base64("data\0invalid");
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("null byte"));
+    }
+
+    // PAE Validation Functions
+    #[test]
+    fn test_toolkit_validate_pae() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        // Simple PAE code
+        let result = toolkit.validate_pae("@clef:G-2 @key:bBEA @time:4/4 ''4C/8DE");
+        // Returns JSON validation result
+        let _ = result;
+    }
+
+    #[test]
+    fn test_toolkit_validate_pae_empty() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.validate_pae("");
+        let _ = result;
+    }
+
+    #[test]
+    fn test_toolkit_validate_pae_null_byte() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.validate_pae("@clef:G-2\0invalid");
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("null byte"));
+    }
+
+    #[test]
+    fn test_toolkit_validate_pae_file_not_found() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.validate_pae_file(Path::new("/nonexistent/path.pae"));
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("file not found"));
+    }
+
+    #[test]
+    fn test_toolkit_validate_pae_file_null_byte() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        // Path with null byte in it - this should fail at CString conversion
+        // We need to construct this carefully since Path won't accept null bytes on some systems
+        // This test verifies the path string conversion handles this case
+        let _ = toolkit.validate_pae_file(Path::new("/some/path.pae"));
+    }
+
+    // Selection Function
+    #[test]
+    fn test_toolkit_select_no_data() {
+        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.select(r#"{"measureRange": "1-2"}"#);
+        // Select without data may fail
+        let _ = result;
+    }
+
+    #[test]
+    fn test_toolkit_select_empty() {
+        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.select("{}");
+        let _ = result;
+    }
+
+    #[test]
+    fn test_toolkit_select_null_byte() {
+        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.select("{\0}");
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("null byte"));
+    }
+
+    // Layout Functions
+    #[test]
+    fn test_toolkit_redo_page_pitch_pos_layout() {
+        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        toolkit.redo_page_pitch_pos_layout();
+        // Should not panic
+    }
+
+    #[test]
+    fn test_toolkit_reset_xml_id_seed() {
+        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        toolkit.reset_xml_id_seed(42);
+        // Should not panic
+    }
+
+    #[test]
+    fn test_toolkit_reset_xml_id_seed_zero() {
+        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        toolkit.reset_xml_id_seed(0);
+    }
+
+    #[test]
+    fn test_toolkit_reset_xml_id_seed_negative() {
+        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        toolkit.reset_xml_id_seed(-1);
+    }
+
+    #[test]
+    fn test_toolkit_get_option_usage_string() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let usage = toolkit.get_option_usage_string();
+        // Should return a non-empty string with option documentation
+        assert!(!usage.is_empty());
+    }
+
+    // Conversion Functions
+    // Note: Empty input to conversion functions can cause Verovio to hang,
+    // so we only test null byte handling here. Actual functionality is tested
+    // with valid data in the bundled-data tests below.
+
+    #[test]
+    fn test_toolkit_convert_humdrum_to_humdrum_null_byte() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.convert_humdrum_to_humdrum("data\0invalid");
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("null byte"));
+    }
+
+    #[test]
+    fn test_toolkit_convert_humdrum_to_midi_null_byte() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.convert_humdrum_to_midi("data\0invalid");
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("null byte"));
+    }
+
+    #[test]
+    fn test_toolkit_convert_mei_to_humdrum_null_byte() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.convert_mei_to_humdrum("<mei>\0</mei>");
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("null byte"));
+    }
+
+    #[test]
+    fn test_toolkit_render_data_null_byte() {
+        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.render_data("data\0invalid", None);
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("null byte"));
+    }
+
+    #[test]
+    fn test_toolkit_render_data_options_null_byte() {
+        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.render_data("", Some("{\0}"));
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("null byte"));
+    }
+
+    // Element Query Functions
+    #[test]
+    fn test_toolkit_get_expansion_ids_for_element_not_found() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.get_expansion_ids_for_element("nonexistent-id");
+        let _ = result;
+    }
+
+    #[test]
+    fn test_toolkit_get_expansion_ids_for_element_null_byte() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.get_expansion_ids_for_element("id\0invalid");
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("null byte"));
+    }
+
+    #[test]
+    fn test_toolkit_get_midi_values_for_element_not_found() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.get_midi_values_for_element("nonexistent-id");
+        let _ = result;
+    }
+
+    #[test]
+    fn test_toolkit_get_midi_values_for_element_null_byte() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.get_midi_values_for_element("id\0invalid");
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("null byte"));
+    }
+
+    #[test]
+    fn test_toolkit_get_notated_id_for_element_not_found() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.get_notated_id_for_element("nonexistent-id");
+        let _ = result;
+    }
+
+    #[test]
+    fn test_toolkit_get_notated_id_for_element_null_byte() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.get_notated_id_for_element("id\0invalid");
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("null byte"));
+    }
+
+    #[test]
+    fn test_toolkit_get_times_for_element_not_found() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.get_times_for_element("nonexistent-id");
+        let _ = result;
+    }
+
+    #[test]
+    fn test_toolkit_get_times_for_element_null_byte() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.get_times_for_element("id\0invalid");
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("null byte"));
+    }
+
+    #[test]
+    fn test_toolkit_get_descriptive_features_no_data() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.get_descriptive_features(None);
+        let _ = result;
+    }
+
+    #[test]
+    fn test_toolkit_get_descriptive_features_with_options() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.get_descriptive_features(Some("{}"));
+        let _ = result;
+    }
+
+    #[test]
+    fn test_toolkit_get_descriptive_features_null_byte() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.get_descriptive_features(Some("{\0}"));
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("null byte"));
+    }
+
+    // File Output Functions
+    // Note: Many file output functions assert/crash in Verovio when called without data,
+    // so we only test null byte handling here. Actual file output functionality is tested
+    // in the bundled-data tests below.
+
+    #[test]
+    fn test_toolkit_render_to_timemap_file_null_byte() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let path = temp_dir.path().join("test.json");
+        let result = toolkit.render_to_timemap_file(&path, Some("{\0}"));
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("null byte"));
+    }
+
+    #[test]
+    fn test_toolkit_save_file_null_byte() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let path = temp_dir.path().join("test.mei");
+        let result = toolkit.save_file(&path, Some("{\0}"));
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("null byte"));
+    }
+
+    // Tests with bundled data for file output functions
+    #[test]
+    fn test_toolkit_export_all_no_data() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let result = toolkit.export_all(temp_dir.path(), "score", &[crate::OutputFormat::Mei]);
+        assert!(result.unwrap_err().to_string().contains("no data loaded"));
+        assert!(!temp_dir.path().join("score.mei").exists());
+    }
+
+    #[cfg(feature = "bundled-data")]
+    #[test]
+    fn test_toolkit_render_to_svg_file_with_data() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+
+        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
+<mei xmlns="http://www.music-encoding.org/ns/mei">
+  <music><body><mdiv><score>
+    <scoreDef><staffGrp><staffDef n="1" lines="5" clef.shape="G" clef.line="2"/></staffGrp></scoreDef>
+    <section><measure><staff n="1"><layer n="1"><note pname="c" oct="4" dur="4"/></layer></staff></measure></section>
+  </score></mdiv></body></music>
+</mei>"#;
+
+        toolkit.load_data(mei).expect("Failed to load MEI");
+
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let path = temp_dir.path().join("test.svg");
+        let result = toolkit.render_to_svg_file(&path, 1);
+        assert!(result.is_ok());
+        assert!(path.exists());
+    }
+
+    #[cfg(feature = "bundled-data")]
+    #[test]
+    fn test_toolkit_render_to_midi_file_with_data() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+
+        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
+<mei xmlns="http://www.music-encoding.org/ns/mei">
+  <music><body><mdiv><score>
+    <scoreDef><staffGrp><staffDef n="1" lines="5" clef.shape="G" clef.line="2"/></staffGrp></scoreDef>
+    <section><measure><staff n="1"><layer n="1"><note pname="c" oct="4" dur="4"/></layer></staff></measure></section>
+  </score></mdiv></body></music>
+</mei>"#;
+
+        toolkit.load_data(mei).expect("Failed to load MEI");
+
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let path = temp_dir.path().join("test.mid");
+        let result = toolkit.render_to_midi_file(&path);
+        assert!(result.is_ok());
+    }
+
+    #[cfg(feature = "bundled-data")]
+    #[test]
+    fn test_toolkit_render_to_pae_file_with_data() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+
+        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
+<mei xmlns="http://www.music-encoding.org/ns/mei">
+  <music><body><mdiv><score>
+    <scoreDef><staffGrp><staffDef n="1" lines="5" clef.shape="G" clef.line="2"/></staffGrp></scoreDef>
+    <section><measure><staff n="1"><layer n="1"><note pname="c" oct="4" dur="4"/></layer></staff></measure></section>
+  </score></mdiv></body></music>
+</mei>"#;
+
+        toolkit.load_data(mei).expect("Failed to load MEI");
+
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let path = temp_dir.path().join("test.pae");
+        let result = toolkit.render_to_pae_file(&path);
+        // PAE rendering may or may not succeed depending on content
+        let _ = result;
+    }
+
+    #[cfg(feature = "bundled-data")]
+    #[test]
+    fn test_toolkit_render_to_expansion_map_file_with_data() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+
+        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
+<mei xmlns="http://www.music-encoding.org/ns/mei">
+  <music><body><mdiv><score>
+    <scoreDef><staffGrp><staffDef n="1" lines="5" clef.shape="G" clef.line="2"/></staffGrp></scoreDef>
+    <section><measure><staff n="1"><layer n="1"><note pname="c" oct="4" dur="4"/></layer></staff></measure></section>
+  </score></mdiv></body></music>
+</mei>"#;
+
+        toolkit.load_data(mei).expect("Failed to load MEI");
+
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let path = temp_dir.path().join("test.json");
+        let result = toolkit.render_to_expansion_map_file(&path);
+        assert!(result.is_ok());
+    }
+
+    #[cfg(feature = "bundled-data")]
+    #[test]
+    fn test_toolkit_render_to_timemap_file_with_data() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+
+        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
+<mei xmlns="http://www.music-encoding.org/ns/mei">
+  <music><body><mdiv><score>
+    <scoreDef><staffGrp><staffDef n="1" lines="5" clef.shape="G" clef.line="2"/></staffGrp></scoreDef>
+    <section><measure><staff n="1"><layer n="1"><note pname="c" oct="4" dur="4"/></layer></staff></measure></section>
+  </score></mdiv></body></music>
+</mei>"#;
+
+        toolkit.load_data(mei).expect("Failed to load MEI");
 
-        c_str.to_str().ok().map(String::from)
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let path = temp_dir.path().join("test.json");
+        let result = toolkit.render_to_timemap_file(&path, None);
+        assert!(result.is_ok());
     }
-}
 
-impl std::fmt::Debug for Toolkit {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("Toolkit")
-            .field("version", &self.version())
-            .field("page_count", &self.page_count())
-            .field("resource_path", &self.get_resource_path())
-            .finish_non_exhaustive()
+    #[cfg(feature = "bundled-data")]
+    #[test]
+    fn test_toolkit_save_file_with_data() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+
+        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
+<mei xmlns="http://www.music-encoding.org/ns/mei">
+  <music><body><mdiv><score>
+    <scoreDef><staffGrp><staffDef n="1" lines="5" clef.shape="G" clef.line="2"/></staffGrp></scoreDef>
+    <section><measure><staff n="1"><layer n="1"><note pname="c" oct="4" dur="4"/></layer></staff></measure></section>
+  </score></mdiv></body></music>
+</mei>"#;
+
+        toolkit.load_data(mei).expect("Failed to load MEI");
+
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let path = temp_dir.path().join("test.mei");
+        let result = toolkit.save_file(&path, None);
+        assert!(result.is_ok());
+        assert!(path.exists());
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[cfg(feature = "bundled-data")]
+    #[test]
+    fn test_toolkit_save_humdrum_to_file_with_data() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+
+        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
+<mei xmlns="http://www.music-encoding.org/ns/mei">
+  <music><body><mdiv><score>
+    <scoreDef><staffGrp><staffDef n="1" lines="5" clef.shape="G" clef.line="2"/></staffGrp></scoreDef>
+    <section><measure><staff n="1"><layer n="1"><note pname="c" oct="4" dur="4"/></layer></staff></measure></section>
+  </score></mdiv></body></music>
+</mei>"#;
+
+        toolkit.load_data(mei).expect("Failed to load MEI");
+
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let path = temp_dir.path().join("test.krn");
+        let result = toolkit.save_humdrum_to_file(&path);
+        // Humdrum export may or may not succeed depending on content
+        let _ = result;
+    }
+
+    #[cfg(feature = "bundled-data")]
+    #[test]
+    fn test_toolkit_select_with_data() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+
+        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
+<mei xmlns="http://www.music-encoding.org/ns/mei">
+  <music><body><mdiv><score>
+    <scoreDef><staffGrp><staffDef n="1" lines="5" clef.shape="G" clef.line="2"/></staffGrp></scoreDef>
+    <section><measure><staff n="1"><layer n="1"><note pname="c" oct="4" dur="4"/></layer></staff></measure></section>
+  </score></mdiv></body></music>
+</mei>"#;
+
+        toolkit.load_data(mei).expect("Failed to load MEI");
+        let result = toolkit.select(r#"{"measureRange": "1"}"#);
+        let _ = result;
+    }
+
+    #[cfg(feature = "bundled-data")]
+    #[test]
+    fn test_toolkit_get_descriptive_features_with_data() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+
+        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
+<mei xmlns="http://www.music-encoding.org/ns/mei">
+  <music><body><mdiv><score>
+    <scoreDef><staffGrp><staffDef n="1" lines="5" clef.shape="G" clef.line="2"/></staffGrp></scoreDef>
+    <section><measure><staff n="1"><layer n="1"><note pname="c" oct="4" dur="4"/></layer></staff></measure></section>
+  </score></mdiv></body></music>
+</mei>"#;
+
+        toolkit.load_data(mei).expect("Failed to load MEI");
+        let result = toolkit.get_descriptive_features(None);
+        assert!(result.is_ok());
+    }
+
+    #[cfg(feature = "bundled-data")]
+    #[test]
+    fn test_toolkit_render_data_with_mei() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+
+        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
+<mei xmlns="http://www.music-encoding.org/ns/mei">
+  <music><body><mdiv><score>
+    <scoreDef><staffGrp><staffDef n="1" lines="5" clef.shape="G" clef.line="2"/></staffGrp></scoreDef>
+    <section><measure><staff n="1"><layer n="1"><note pname="c" oct="4" dur="4"/></layer></staff></measure></section>
+  </score></mdiv></body></music>
+</mei>"#;
+
+        let result = toolkit.render_data(mei, None);
+        // render_data should process the data
+        let _ = result;
+    }
+
+    #[cfg(feature = "bundled-data")]
+    #[test]
+    fn test_toolkit_render_data_with_mei_and_options() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+
+        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
+<mei xmlns="http://www.music-encoding.org/ns/mei">
+  <music><body><mdiv><score>
+    <scoreDef><staffGrp><staffDef n="1" lines="5" clef.shape="G" clef.line="2"/></staffGrp></scoreDef>
+    <section><measure><staff n="1"><layer n="1"><note pname="c" oct="4" dur="4"/></layer></staff></measure></section>
+  </score></mdiv></body></music>
+</mei>"#;
+
+        let result = toolkit.render_data(mei, Some(r#"{"scale": 50}"#));
+        let _ = result;
+    }
+
+    #[cfg(feature = "bundled-data")]
+    #[test]
+    fn test_toolkit_get_descriptive_features_with_custom_options() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+
+        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
+<mei xmlns="http://www.music-encoding.org/ns/mei">
+  <music><body><mdiv><score>
+    <scoreDef><staffGrp><staffDef n="1" lines="5" clef.shape="G" clef.line="2"/></staffGrp></scoreDef>
+    <section><measure><staff n="1"><layer n="1"><note pname="c" oct="4" dur="4"/></layer></staff></measure></section>
+  </score></mdiv></body></music>
+</mei>"#;
+
+        toolkit.load_data(mei).expect("Failed to load MEI");
+        let result = toolkit.get_descriptive_features(Some(r#"{"includeMeasures": true}"#));
+        let _ = result;
+    }
+
+    #[cfg(feature = "bundled-data")]
+    #[test]
+    fn test_toolkit_render_to_timemap_with_options_loaded_data() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+
+        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
+<mei xmlns="http://www.music-encoding.org/ns/mei">
+  <music><body><mdiv><score>
+    <scoreDef><staffGrp><staffDef n="1" lines="5" clef.shape="G" clef.line="2"/></staffGrp></scoreDef>
+    <section><measure><staff n="1"><layer n="1"><note pname="c" oct="4" dur="4"/></layer></staff></measure></section>
+  </score></mdiv></body></music>
+</mei>"#;
+
+        toolkit.load_data(mei).expect("Failed to load MEI");
+        let result = toolkit.render_to_timemap_with_options(r#"{"includeMeasures": true}"#);
+        assert!(result.is_ok());
+    }
+
+    #[cfg(feature = "bundled-data")]
+    #[test]
+    fn test_toolkit_convert_humdrum_to_humdrum_with_data() {
+        let toolkit = Toolkit::new().expect("Failed to create toolkit");
+
+        let humdrum = r#"**kern
+*clefG2
+*k[]
+*M4/4
+4c
+4d
+4e
+4f
+*-"#;
+
+        let result = toolkit.convert_humdrum_to_humdrum(humdrum);
+        // May succeed or fail depending on Verovio's Humdrum support
+        let _ = result;
+    }
+
+    #[cfg(feature = "bundled-data")]
+    #[test]
+    fn test_toolkit_convert_humdrum_to_midi_with_data() {
+        let toolkit = Toolkit::new().expect("Failed to create toolkit");
+
+        let humdrum = r#"**kern
+*clefG2
+*k[]
+*M4/4
+4c
+4d
+4e
+4f
+*-"#;
+
+        let result = toolkit.convert_humdrum_to_midi(humdrum);
+        let _ = result;
+    }
+
+    #[cfg(feature = "bundled-data")]
+    #[test]
+    fn test_toolkit_convert_mei_to_humdrum_with_data() {
+        let toolkit = Toolkit::new().expect("Failed to create toolkit");
+
+        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
+<mei xmlns="http://www.music-encoding.org/ns/mei">
+  <music><body><mdiv><score>
+    <scoreDef><staffGrp><staffDef n="1" lines="5" clef.shape="G" clef.line="2"/></staffGrp></scoreDef>
+    <section><measure><staff n="1"><layer n="1"><note pname="c" oct="4" dur="4"/></layer></staff></measure></section>
+  </score></mdiv></body></music>
+</mei>"#;
+
+        let result = toolkit.convert_mei_to_humdrum(mei);
+        let _ = result;
+    }
+
+    #[cfg(feature = "bundled-data")]
+    #[test]
+    fn test_toolkit_set_input_output_format_with_data() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+
+        // Set input format before loading
+        let _ = toolkit.set_input_from("mei");
+        let _ = toolkit.set_output_to("svg");
+
+        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
+<mei xmlns="http://www.music-encoding.org/ns/mei">
+  <music><body><mdiv><score>
+    <scoreDef><staffGrp><staffDef n="1" lines="5" clef.shape="G" clef.line="2"/></staffGrp></scoreDef>
+    <section><measure><staff n="1"><layer n="1"><note pname="c" oct="4" dur="4"/></layer></staff></measure></section>
+  </score></mdiv></body></music>
+</mei>"#;
+
+        toolkit.load_data(mei).expect("Failed to load MEI");
+        assert_eq!(toolkit.page_count(), 1);
+    }
+
+    #[cfg(feature = "bundled-data")]
+    #[test]
+    fn test_toolkit_redo_page_pitch_pos_layout_with_data() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+
+        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
+<mei xmlns="http://www.music-encoding.org/ns/mei">
+  <music><body><mdiv><score>
+    <scoreDef><staffGrp><staffDef n="1" lines="5" clef.shape="G" clef.line="2"/></staffGrp></scoreDef>
+    <section><measure><staff n="1"><layer n="1"><note pname="c" oct="4" dur="4"/></layer></staff></measure></section>
+  </score></mdiv></body></music>
+</mei>"#;
 
-    #[test]
-    fn test_toolkit_without_resources() {
-        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        assert!(!toolkit.version().is_empty());
+        toolkit.load_data(mei).expect("Failed to load MEI");
+        toolkit.redo_page_pitch_pos_layout();
+        // Should complete without panic
     }
 
+    #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_version() {
-        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let version = toolkit.version();
-        // Version should look like a version number
-        assert!(!version.is_empty());
-    }
+    fn test_toolkit_reset_xml_id_seed_with_data() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
 
-    #[test]
-    fn test_toolkit_page_count_empty() {
-        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        assert_eq!(toolkit.page_count(), 0);
+        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
+<mei xmlns="http://www.music-encoding.org/ns/mei">
+  <music><body><mdiv><score>
+    <scoreDef><staffGrp><staffDef n="1" lines="5" clef.shape="G" clef.line="2"/></staffGrp></scoreDef>
+    <section><measure><staff n="1"><layer n="1"><note pname="c" oct="4" dur="4"/></layer></staff></measure></section>
+  </score></mdiv></body></music>
+</mei>"#;
+
+        toolkit.load_data(mei).expect("Failed to load MEI");
+        toolkit.reset_xml_id_seed(12345);
+        // Should complete without panic
     }
 
+    #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_get_options() {
-        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let options = toolkit.get_options();
-        let trimmed = options.trim();
-        assert!(trimmed.starts_with('{'));
-        assert!(trimmed.ends_with('}'));
+    fn test_toolkit_render_to_timemap_file_with_options() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+
+        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
+<mei xmlns="http://www.music-encoding.org/ns/mei">
+  <music><body><mdiv><score>
+    <scoreDef><staffGrp><staffDef n="1" lines="5" clef.shape="G" clef.line="2"/></staffGrp></scoreDef>
+    <section><measure><staff n="1"><layer n="1"><note pname="c" oct="4" dur="4"/></layer></staff></measure></section>
+  </score></mdiv></body></music>
+</mei>"#;
+
+        toolkit.load_data(mei).expect("Failed to load MEI");
+
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let path = temp_dir.path().join("test.json");
+
+        let result = toolkit.render_to_timemap_file(&path, Some(r#"{"includeMeasures": true}"#));
+        assert!(result.is_ok());
     }
 
+    #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_get_default_options() {
-        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let options = toolkit.get_default_options();
-        assert!(options.starts_with('{'));
+    fn test_toolkit_save_file_with_options() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+
+        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
+<mei xmlns="http://www.music-encoding.org/ns/mei">
+  <music><body><mdiv><score>
+    <scoreDef><staffGrp><staffDef n="1" lines="5" clef.shape="G" clef.line="2"/></staffGrp></scoreDef>
+    <section><measure><staff n="1"><layer n="1"><note pname="c" oct="4" dur="4"/></layer></staff></measure></section>
+  </score></mdiv></body></music>
+</mei>"#;
+
+        toolkit.load_data(mei).expect("Failed to load MEI");
+
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let path = temp_dir.path().join("test.mei");
+
+        let result = toolkit.save_file(&path, Some(r#"{"pageWidth": 2100}"#));
+        assert!(result.is_ok());
     }
 
+    #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_get_available_options() {
-        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let options = toolkit.get_available_options();
-        assert!(options.starts_with('{'));
+    fn test_toolkit_validate_pae_with_valid_code() {
+        let toolkit = Toolkit::new().expect("Failed to create toolkit");
+        // Valid PAE code
+        let result = toolkit.validate_pae("@clef:G-2\n@keysig:bBE\n@timesig:4/4\n'4C/8DE");
+        // Should return validation result
+        let _ = result;
     }
 
+    #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_reset_options() {
-        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        toolkit.reset_options();
-        // Should not panic
+    fn test_toolkit_element_queries_with_loaded_data() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+
+        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
+<mei xmlns="http://www.music-encoding.org/ns/mei">
+  <music><body><mdiv><score>
+    <scoreDef><staffGrp><staffDef n="1" lines="5" clef.shape="G" clef.line="2"/></staffGrp></scoreDef>
+    <section><measure xml:id="m1"><staff n="1"><layer n="1"><note xml:id="n1" pname="c" oct="4" dur="4"/></layer></staff></measure></section>
+  </score></mdiv></body></music>
+</mei>"#;
+
+        toolkit.load_data(mei).expect("Failed to load MEI");
+
+        // These queries return JSON even if element not found
+        let _ = toolkit.get_expansion_ids_for_element("n1");
+        let _ = toolkit.get_midi_values_for_element("n1");
+        let _ = toolkit.get_notated_id_for_element("n1");
+        let _ = toolkit.get_times_for_element("n1");
     }
 
+    // =========================================================================
+    // LoadSource Trait and Unified Load Method Tests
+    // =========================================================================
+
     #[test]
-    fn test_toolkit_get_scale() {
-        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let scale = toolkit.get_scale();
-        assert!(scale > 0);
+    fn test_zip_base64_marker_debug() {
+        let marker = ZipBase64("test");
+        let debug = format!("{:?}", marker);
+        assert!(debug.contains("ZipBase64"));
+        assert!(debug.contains("test"));
     }
 
     #[test]
-    fn test_toolkit_set_scale() {
-        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        toolkit.set_scale(80).expect("Failed to set scale");
-        assert_eq!(toolkit.get_scale(), 80);
+    fn test_zip_base64_marker_clone() {
+        let marker = ZipBase64("test");
+        let cloned = marker;
+        assert_eq!(cloned.0, "test");
     }
 
     #[test]
-    fn test_toolkit_get_id() {
-        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let id = toolkit.get_id();
-        assert!(!id.is_empty());
+    fn test_zip_buffer_marker_debug() {
+        let data = b"test";
+        let marker = ZipBuffer(data);
+        let debug = format!("{:?}", marker);
+        assert!(debug.contains("ZipBuffer"));
     }
 
     #[test]
-    fn test_toolkit_debug() {
-        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let debug = format!("{:?}", toolkit);
-        assert!(debug.contains("Toolkit"));
-        assert!(debug.contains("version"));
+    fn test_zip_buffer_marker_clone() {
+        let data = b"test";
+        let marker = ZipBuffer(data);
+        let cloned = marker;
+        assert_eq!(cloned.0, data);
     }
 
     #[test]
-    fn test_toolkit_render_to_svg_no_data() {
-        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.render_to_svg(1);
+    fn test_load_source_str_empty() {
+        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.load("");
         assert!(result.is_err());
     }
 
     #[test]
-    fn test_toolkit_render_to_svg_page_zero() {
-        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.render_to_svg(0);
+    fn test_load_source_path_not_found() {
+        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.load(Path::new("/nonexistent/path/to/file.mei"));
         assert!(result.is_err());
         let err = result.unwrap_err();
-        assert!(err.to_string().contains("out of range"));
+        assert!(err.to_string().contains("file not found"));
     }
 
     #[test]
-    fn test_toolkit_set_options() {
+    fn test_load_source_pathbuf_not_found() {
         let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let options = Options::builder().scale(80).build();
-        toolkit
-            .set_options(&options)
-            .expect("Failed to set options");
+        let path = std::path::PathBuf::from("/nonexistent/path/to/file.mei");
+        let result = toolkit.load(&path);
+        assert!(result.is_err());
     }
 
+    // NOTE: Tests for invalid ZIP data are intentionally omitted because
+    // Verovio crashes with a C++ exception rather than returning an error
+    // when given invalid ZIP data. This is documented behavior.
+
     #[test]
-    fn test_toolkit_load_data_empty() {
+    fn test_load_source_str_null_byte() {
         let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.load_data("");
+        let result = toolkit.load("data with \0 null byte");
         assert!(result.is_err());
     }
 
     #[test]
-    fn test_toolkit_load_file_not_found() {
+    fn test_load_source_zip_base64_null_byte() {
         let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.load_file(Path::new("/nonexistent/path/to/file.mei"));
+        let result = toolkit.load(ZipBase64("data with \0 null byte"));
         assert!(result.is_err());
-        let err = result.unwrap_err();
-        assert!(err.to_string().contains("file not found"));
     }
 
+    #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_is_send() {
-        fn assert_send<T: Send>() {}
-        assert_send::<Toolkit>();
+    fn test_load_source_str_valid_mei() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+
+        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
+<mei xmlns="http://www.music-encoding.org/ns/mei">
+  <music><body><mdiv><score>
+    <scoreDef><staffGrp><staffDef n="1" lines="5" clef.shape="G" clef.line="2"/></staffGrp></scoreDef>
+    <section><measure><staff n="1"><layer n="1"><note pname="c" oct="4" dur="4"/></layer></staff></measure></section>
+  </score></mdiv></body></music>
+</mei>"#;
+
+        let result = toolkit.load(mei);
+        assert!(result.is_ok());
+        assert!(toolkit.page_count() > 0);
     }
 
+    #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_enable_log() {
-        Toolkit::enable_log(true);
-        Toolkit::enable_log(false);
-        // Should not panic
+    fn test_load_source_str_valid_abc() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+
+        let abc = r#"X:1
+T:Test
+M:4/4
+K:C
+CDEF|"#;
+
+        let result = toolkit.load(abc);
+        assert!(result.is_ok());
+    }
+
+    #[cfg(feature = "bundled-data")]
+    #[test]
+    fn test_load_dispatches_correctly_str() {
+        // Verify that load(&str) dispatches to load_data
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+
+        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
+<mei xmlns="http://www.music-encoding.org/ns/mei">
+  <music><body><mdiv><score>
+    <scoreDef><staffGrp><staffDef n="1" lines="5" clef.shape="G" clef.line="2"/></staffGrp></scoreDef>
+    <section><measure><staff n="1"><layer n="1"><note pname="c" oct="4" dur="4"/></layer></staff></measure></section>
+  </score></mdiv></body></music>
+</mei>"#;
+
+        // Load using unified method
+        toolkit.load(mei).expect("Failed to load via load()");
+        let page_count_load = toolkit.page_count();
+
+        // Load using direct method
+        let mut toolkit2 = Toolkit::new().expect("Failed to create toolkit");
+        toolkit2
+            .load_data(mei)
+            .expect("Failed to load via load_data()");
+        let page_count_direct = toolkit2.page_count();
+
+        assert_eq!(page_count_load, page_count_direct);
     }
 
     #[test]
-    fn test_toolkit_enable_log_to_buffer() {
-        Toolkit::enable_log_to_buffer(true);
-        Toolkit::enable_log_to_buffer(false);
-        // Should not panic
+    fn test_load_source_trait_is_object_safe() {
+        // This test verifies the trait can be used as a trait object (if needed)
+        fn accepts_load_source(_source: &dyn std::any::Any) {}
+        let source: &str = "test";
+        accepts_load_source(&source);
     }
 
-    #[test]
-    fn test_toolkit_get_log() {
-        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let _log = toolkit.get_log();
-        // Log may be empty, that's fine
+    // =========================================================================
+    // Convenience Setter Tests
+    // =========================================================================
+
+    /// Builds a single-staff MEI document with `measures` measures of quarter notes.
+    #[cfg(feature = "bundled-data")]
+    fn multi_measure_mei(measures: usize) -> String {
+        let measure = r#"<measure><staff n="1"><layer n="1"><note pname="c" oct="4" dur="4"/><note pname="d" oct="4" dur="4"/><note pname="e" oct="4" dur="4"/><note pname="f" oct="4" dur="4"/></layer></staff></measure>"#;
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<mei xmlns="http://www.music-encoding.org/ns/mei">
+  <music><body><mdiv><score>
+    <scoreDef meter.count="4" meter.unit="4"><staffGrp><staffDef n="1" lines="5" clef.shape="G" clef.line="2"/></staffGrp></scoreDef>
+    <section>{}</section>
+  </score></mdiv></body></music>
+</mei>"#,
+            measure.repeat(measures)
+        )
     }
 
     #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_new_with_bundled_data() {
-        let toolkit = Toolkit::new().expect("Failed to create toolkit");
-        assert!(!toolkit.version().is_empty());
-        assert!(!toolkit.get_resource_path().is_empty());
+    fn test_toolkit_set_breaks_changes_page_count() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit
+            .load_data(&multi_measure_mei(200))
+            .expect("Failed to load MEI");
+
+        toolkit
+            .set_breaks(BreakMode::Auto)
+            .expect("Failed to set breaks");
+        let auto_pages = toolkit.page_count();
+
+        toolkit
+            .set_breaks(BreakMode::None)
+            .expect("Failed to set breaks");
+        let none_pages = toolkit.page_count();
+
+        assert_eq!(none_pages, 1);
+        assert!(auto_pages > none_pages);
+
+        toolkit
+            .set_breaks(BreakMode::Auto)
+            .expect("Failed to set breaks");
+        assert_eq!(toolkit.page_count(), auto_pages);
     }
 
     #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_load_simple_mei() {
+    fn test_toolkit_estimate_page_count_break_modes() {
+        let mei = multi_measure_mei(200).replace("</measure>", "</measure><sb/>");
         let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit.load_data(&mei).expect("Failed to load MEI");
+        let pages = toolkit.page_count();
 
-        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
-<mei xmlns="http://www.music-encoding.org/ns/mei">
-  <music>
-    <body>
-      <mdiv>
-        <score>
-          <scoreDef>
-            <staffGrp>
-              <staffDef n="1" lines="5" clef.shape="G" clef.line="2"/>
-            </staffGrp>
-          </scoreDef>
-          <section>
-            <measure>
-              <staff n="1">
-                <layer n="1">
-                  <note pname="c" oct="4" dur="4"/>
-                </layer>
-              </staff>
-            </measure>
-          </section>
-        </score>
-      </mdiv>
-    </body>
-  </music>
-</mei>"#;
+        let none_pages = toolkit
+            .estimate_page_count(&Options::builder().breaks(BreakMode::None).build())
+            .expect("Failed to estimate page count");
+        let line_pages = toolkit
+            .estimate_page_count(&Options::builder().breaks(BreakMode::Line).build())
+            .expect("Failed to estimate page count");
 
-        toolkit.load_data(mei).expect("Failed to load MEI");
-        assert!(toolkit.page_count() > 0);
+        assert_eq!(none_pages, 1);
+        assert!(line_pages > none_pages);
+        assert_eq!(toolkit.page_count(), pages);
+        let options: serde_json::Value =
+            serde_json::from_str(&toolkit.get_options()).expect("Options should be JSON");
+        assert_eq!(options["breaks"], "auto");
     }
 
     #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_render_simple_mei() {
+    fn test_toolkit_estimate_page_count_restores_state() {
         let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit
+            .load_data(&multi_measure_mei(200))
+            .expect("Failed to load MEI");
+        toolkit
+            .set_text_lang(Some("he"))
+            .expect("Failed to set language");
+        let calls = std::sync::Arc::new(std::sync::Mutex::new(0));
+        let counter = std::sync::Arc::clone(&calls);
+        toolkit.on_relayout(move |_| *counter.lock().unwrap() += 1);
+        let generation = toolkit.generation();
+
+        let pages = toolkit
+            .estimate_page_count(
+                &Options::builder()
+                    .breaks(BreakMode::None)
+                    .text_lang("ar")
+                    .build(),
+            )
+            .expect("Failed to estimate page count");
 
-        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
-<mei xmlns="http://www.music-encoding.org/ns/mei">
-  <music>
-    <body>
-      <mdiv>
-        <score>
-          <scoreDef>
-            <staffGrp>
-              <staffDef n="1" lines="5" clef.shape="G" clef.line="2"/>
-            </staffGrp>
-          </scoreDef>
-          <section>
-            <measure>
-              <staff n="1">
-                <layer n="1">
-                  <note pname="c" oct="4" dur="4"/>
-                </layer>
-              </staff>
-            </measure>
-          </section>
-        </score>
-      </mdiv>
-    </body>
-  </music>
-</mei>"#;
-
-        toolkit.load_data(mei).expect("Failed to load MEI");
+        assert_eq!(pages, 1);
+        assert_eq!(toolkit.text_lang.as_deref(), Some("he"));
+        assert_eq!(toolkit.generation(), generation.wrapping_add(2));
+        assert_eq!(*calls.lock().unwrap(), 0);
 
-        let svg = toolkit.render_to_svg(1).expect("Failed to render SVG");
-        assert!(svg.contains("<svg"));
-        assert!(svg.contains("</svg>"));
+        toolkit.redo_layout(None).expect("Failed to redo layout");
+        assert_eq!(*calls.lock().unwrap(), 1);
     }
 
     #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_render_all_pages() {
+    fn test_toolkit_preview_layouts() {
         let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit
+            .load_data(&multi_measure_mei(200))
+            .expect("Failed to load MEI");
+        let pages = toolkit.page_count();
+        let options = toolkit.get_options();
 
-        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
-<mei xmlns="http://www.music-encoding.org/ns/mei">
-  <music>
-    <body>
-      <mdiv>
-        <score>
-          <scoreDef>
-            <staffGrp>
-              <staffDef n="1" lines="5" clef.shape="G" clef.line="2"/>
-            </staffGrp>
-          </scoreDef>
-          <section>
-            <measure>
-              <staff n="1">
-                <layer n="1">
-                  <note pname="c" oct="4" dur="4"/>
-                </layer>
-              </staff>
-            </measure>
-          </section>
-        </score>
-      </mdiv>
-    </body>
-  </music>
-</mei>"#;
+        let counts = toolkit
+            .preview_layouts(&[
+                Options::builder().breaks(BreakMode::None).build(),
+                Options::builder().breaks(BreakMode::Auto).build(),
+            ])
+            .expect("Failed to preview layouts");
 
-        toolkit.load_data(mei).expect("Failed to load MEI");
+        assert_eq!(counts.len(), 2);
+        assert_eq!(counts[0], 1);
+        assert!(counts[1] > 1);
+        assert_eq!(toolkit.page_count(), pages);
+        assert_eq!(toolkit.get_options(), options);
+    }
 
-        let pages = toolkit.render_all_pages().expect("Failed to render pages");
-        assert!(!pages.is_empty());
-        for page in &pages {
-            assert!(page.contains("<svg"));
+    #[test]
+    fn test_toolkit_preview_layouts_no_data() {
+        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.preview_layouts(&[Options::default()]);
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("no data loaded"));
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    fn test_toolkit_layout_thumbnails_invalid() {
+        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let err = toolkit
+            .layout_thumbnails(&[Options::default()], 0)
+            .unwrap_err();
+        assert!(err.to_string().contains("at least 1 pixel"));
+
+        let err = toolkit
+            .layout_thumbnails(&[Options::default()], 100)
+            .unwrap_err();
+        assert!(err.to_string().contains("no data loaded"));
+    }
+
+    #[cfg(all(feature = "bundled-data", feature = "png"))]
+    #[test]
+    fn test_toolkit_layout_thumbnails() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit
+            .load_data(&multi_measure_mei(8))
+            .expect("Failed to load MEI");
+        let options_before = toolkit.get_options();
+
+        let thumbnails = toolkit
+            .layout_thumbnails(
+                &[
+                    Options::builder().breaks(BreakMode::Auto).build(),
+                    Options::builder().scale(20).build(),
+                ],
+                160,
+            )
+            .expect("Failed to render thumbnails");
+        assert_eq!(thumbnails.len(), 2);
+        for png in &thumbnails {
+            let image = image::load_from_memory(png).expect("thumbnail should be a valid PNG");
+            // The longer side fits the bound, up to rounding
+            assert!(image.width().max(image.height()).abs_diff(160) <= 1);
         }
+        assert_eq!(toolkit.get_options(), options_before);
+    }
+
+    #[test]
+    fn test_toolkit_estimate_page_count_no_data() {
+        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.estimate_page_count(&Options::default());
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("no data loaded"));
     }
 
     #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_get_mei() {
+    fn test_toolkit_layout_summary() {
         let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit
+            .load_data(&multi_measure_mei(200))
+            .expect("Failed to load MEI");
 
-        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
-<mei xmlns="http://www.music-encoding.org/ns/mei">
-  <music>
-    <body>
-      <mdiv>
-        <score>
-          <scoreDef>
-            <staffGrp>
-              <staffDef n="1" lines="5" clef.shape="G" clef.line="2"/>
-            </staffGrp>
-          </scoreDef>
-          <section>
-            <measure>
-              <staff n="1">
-                <layer n="1">
-                  <note pname="c" oct="4" dur="4"/>
-                </layer>
-              </staff>
-            </measure>
-          </section>
-        </score>
-      </mdiv>
-    </body>
-  </music>
-</mei>"#;
+        let summary = toolkit
+            .layout_summary()
+            .expect("Failed to summarize layout");
+        assert_eq!(summary.pages, toolkit.page_count());
+        assert!(summary.pages > 1);
+        assert!(summary.systems >= summary.pages);
+        assert_eq!(summary.measures, 200);
+        assert_eq!(summary.scale, toolkit.scale() as i32);
+        assert_eq!(summary.breaks, Some(BreakMode::Auto));
+    }
 
-        toolkit.load_data(mei).expect("Failed to load MEI");
+    #[test]
+    fn test_toolkit_layout_summary_no_data() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.layout_summary();
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("no data loaded"));
+    }
 
-        let exported_mei = toolkit.get_mei().expect("Failed to export MEI");
-        assert!(exported_mei.contains("mei"));
+    #[test]
+    fn test_toolkit_document_anchors_no_data() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.document_anchors();
+        assert!(result.unwrap_err().to_string().contains("no data loaded"));
     }
 
     #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_with_options() {
+    fn test_toolkit_document_anchors() {
         let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
-
-        let options = Options::builder()
-            .scale(50)
-            .page_width(1000)
-            .page_height(1000)
-            .adjust_page_height(true)
-            .build();
-
         toolkit
-            .set_options(&options)
-            .expect("Failed to set options");
-
-        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
-<mei xmlns="http://www.music-encoding.org/ns/mei">
-  <music>
-    <body>
-      <mdiv>
-        <score>
-          <scoreDef>
-            <staffGrp>
-              <staffDef n="1" lines="5" clef.shape="G" clef.line="2"/>
-            </staffGrp>
-          </scoreDef>
-          <section>
-            <measure>
-              <staff n="1">
-                <layer n="1">
-                  <note pname="c" oct="4" dur="4"/>
-                </layer>
-              </staff>
-            </measure>
-          </section>
-        </score>
-      </mdiv>
-    </body>
-  </music>
-</mei>"#;
+            .load_data(&multi_measure_mei(4))
+            .expect("Failed to load MEI");
 
-        toolkit.load_data(mei).expect("Failed to load MEI");
+        let anchors = toolkit.document_anchors().expect("Failed to get anchors");
+        assert_eq!(anchors.len() as u32, toolkit.page_count());
+        let (page, first) = &anchors[0];
+        assert_eq!(*page, 1);
+        assert!(!first.is_empty());
+        assert!(first.iter().any(|anchor| anchor.kind == "note"));
 
-        let svg = toolkit.render_to_svg(1).expect("Failed to render SVG");
-        assert!(svg.contains("<svg"));
+        // Bounding boxes were only enabled temporarily
+        assert!(!toolkit.render_to_svg(1).unwrap().contains("bounding-box"));
     }
 
-    // =========================================================================
-    // Additional tests for improved coverage
-    // =========================================================================
-
     #[test]
-    fn test_toolkit_render_to_svg_with_declaration_page_zero() {
+    fn test_toolkit_page_cursor_no_data() {
         let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.render_to_svg_with_declaration(0);
-        assert!(result.is_err());
-        let err = result.unwrap_err();
-        assert!(err.to_string().contains("out of range"));
+        let mut cursor = toolkit.page_cursor();
+        assert!(!cursor.has_more());
+        assert!(cursor.next_page(&toolkit).is_none());
+        assert_eq!(cursor.page(), 0);
     }
 
+    #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_render_to_svg_with_declaration_no_data() {
-        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.render_to_svg_with_declaration(1);
-        assert!(result.is_err());
+    fn test_toolkit_page_cursor() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit
+            .load_data(&multi_measure_mei(60))
+            .expect("Failed to load MEI");
+        toolkit
+            .set_systems_per_page(2)
+            .expect("Failed to set systems per page");
+        let count = toolkit.page_count();
+        assert!(count > 1);
+
+        let mut cursor = toolkit.page_cursor();
+        for page in 1..=count {
+            assert!(cursor.has_more());
+            let svg = cursor
+                .next_page(&toolkit)
+                .expect("cursor should have a page")
+                .expect("Failed to render page");
+            assert_eq!(cursor.page(), page);
+            assert_eq!(svg, toolkit.render_to_svg(page).unwrap());
+        }
+        assert!(!cursor.has_more());
+        assert!(cursor.next_page(&toolkit).is_none());
+        assert_eq!(cursor.page(), count);
+    }
+
+    #[cfg(feature = "bundled-data")]
+    #[test]
+    fn test_toolkit_page_cursor_resets_after_relayout() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit
+            .load_data(&multi_measure_mei(60))
+            .expect("Failed to load MEI");
+        toolkit
+            .set_systems_per_page(2)
+            .expect("Failed to set systems per page");
+
+        let mut cursor = toolkit.page_cursor();
+        cursor.next_page(&toolkit).unwrap().unwrap();
+        cursor.next_page(&toolkit).unwrap().unwrap();
+        assert_eq!(cursor.page(), 2);
+
+        toolkit
+            .set_systems_per_page(1)
+            .expect("Failed to set systems per page");
+        let svg = cursor.next_page(&toolkit).unwrap().unwrap();
+        assert_eq!(cursor.page(), 1);
+        assert_eq!(svg, toolkit.render_to_svg(1).unwrap());
+        assert_eq!(cursor.has_more(), toolkit.page_count() > 1);
     }
 
     #[test]
-    fn test_toolkit_get_mei_no_data() {
-        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.get_mei();
-        // With no data loaded, this may return an empty MEI or error depending on Verovio
-        // Just ensure it doesn't panic and returns a result
-        let _ = result;
+    fn test_toolkit_apply_scale_mode_zero() {
+        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        for mode in [
+            ScaleMode::Percent(0),
+            ScaleMode::FitWidth(0),
+            ScaleMode::FitHeight(0),
+        ] {
+            let err = toolkit.apply_scale_mode(mode).unwrap_err();
+            assert!(err.to_string().contains("invalid scale mode"));
+        }
     }
 
+    #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_get_mei_with_options_no_data() {
-        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.get_mei_with_options(r#"{"removeIds": true}"#);
-        // May succeed with empty result or error - just ensure no panic
-        let _ = result;
+    fn test_toolkit_apply_scale_mode_percent() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit
+            .load_data(&multi_measure_mei(4))
+            .expect("Failed to load MEI");
+
+        toolkit
+            .apply_scale_mode(ScaleMode::Percent(60))
+            .expect("Failed to apply scale");
+        assert_eq!(toolkit.scale(), 60);
     }
 
+    #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_get_humdrum_no_data() {
-        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.get_humdrum();
-        // May return empty or error - just ensure no panic
-        let _ = result;
+    fn test_toolkit_apply_scale_mode_fit_width() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit
+            .set_options(
+                &Options::builder()
+                    .page_width(2000)
+                    .page_height(3000)
+                    .build(),
+            )
+            .expect("Failed to set options");
+        toolkit
+            .load_data(&multi_measure_mei(4))
+            .expect("Failed to load MEI");
+
+        toolkit
+            .apply_scale_mode(ScaleMode::FitWidth(500))
+            .expect("Failed to apply scale");
+        assert_eq!(toolkit.scale(), 25);
+
+        toolkit
+            .apply_scale_mode(ScaleMode::FitHeight(1500))
+            .expect("Failed to apply scale");
+        assert_eq!(toolkit.scale(), 50);
+
+        // Far beyond Verovio's maximum scale
+        toolkit
+            .apply_scale_mode(ScaleMode::FitWidth(1_000_000))
+            .expect("Failed to apply scale");
+        assert_eq!(toolkit.scale(), 1000);
     }
 
+    #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_render_to_midi_no_data() {
-        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.render_to_midi();
-        assert!(result.is_err(), "render_to_midi should fail without data");
-        let err = result.unwrap_err();
-        assert!(
-            err.to_string().contains("no data loaded"),
-            "Error should mention no data loaded"
-        );
+    fn test_toolkit_set_systems_per_page() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit
+            .load_data(&multi_measure_mei(60))
+            .expect("Failed to load MEI");
+        toolkit
+            .set_breaks(BreakMode::None)
+            .expect("Failed to set breaks");
+        assert_eq!(toolkit.page_count(), 1);
+
+        toolkit
+            .set_systems_per_page(2)
+            .expect("Failed to set systems per page");
+        assert!(toolkit.page_count() > 1);
+
+        let summary = toolkit
+            .layout_summary()
+            .expect("Failed to summarize layout");
+        assert!(summary.systems <= summary.pages * 2);
     }
 
+    #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_render_to_pae_no_data() {
-        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.render_to_pae();
-        assert!(result.is_err(), "render_to_pae should fail without data");
-        let err = result.unwrap_err();
-        assert!(
-            err.to_string().contains("no data loaded"),
-            "Error should mention no data loaded"
-        );
+    fn test_toolkit_system_max_per_page_with_adjusted_height() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        let options = Options::builder()
+            .breaks(BreakMode::Auto)
+            .system_max_per_page(3)
+            .adjust_page_height(true)
+            .build();
+        options.validate().expect("Options should be valid");
+        toolkit
+            .set_options(&options)
+            .expect("Failed to set options");
+        toolkit
+            .load_data(&multi_measure_mei(80))
+            .expect("Failed to load MEI");
+
+        let pages = toolkit.render_all_pages().expect("Failed to render pages");
+        assert!(pages.len() > 1);
+        for (page, svg) in pages.iter().enumerate() {
+            let systems = svg.matches(r#"class="system""#).count();
+            assert!(
+                (1..=3).contains(&systems),
+                "page {} has {} systems",
+                page + 1,
+                systems
+            );
+        }
     }
 
     #[test]
-    fn test_toolkit_render_to_timemap_no_data() {
-        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.render_to_timemap();
-        // Returns JSON array (possibly empty) or error
-        let _ = result;
+    fn test_toolkit_fit_width_to_systems_invalid() {
+        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.fit_width_to_systems(0);
+        assert!(matches!(result, Err(Error::OptionsError(_))));
+
+        let result = toolkit.fit_width_to_systems(3);
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("no data loaded"));
     }
 
+    #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_render_to_timemap_with_options_no_data() {
-        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.render_to_timemap_with_options(r#"{"includeMeasures": true}"#);
-        let _ = result;
+    fn test_toolkit_fit_width_to_systems() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit
+            .set_options(&Options::builder().page_height(60000).build())
+            .expect("Failed to set options");
+        toolkit
+            .load_data(&multi_measure_mei(24))
+            .expect("Failed to load MEI");
+
+        toolkit
+            .fit_width_to_systems(3)
+            .expect("Failed to fit page width");
+
+        let summary = toolkit
+            .layout_summary()
+            .expect("Failed to summarize layout");
+        assert_eq!(summary.pages, 1);
+        assert!(
+            summary.systems.abs_diff(3) <= 1,
+            "expected about 3 systems, got {}",
+            summary.systems
+        );
     }
 
     #[test]
-    fn test_toolkit_render_to_expansion_map_no_data() {
-        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.render_to_expansion_map();
-        let _ = result;
+    fn test_toolkit_set_systems_per_page_zero() {
+        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.set_systems_per_page(0);
+        assert!(matches!(result, Err(Error::OptionsError(_))));
     }
 
     #[test]
-    fn test_toolkit_set_scale_invalid() {
+    fn test_toolkit_set_breaks_no_data() {
         let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        // Scale of 0 should be invalid
-        let result = toolkit.set_scale(0);
-        // Verovio may accept or reject this - test that we handle the result
-        let _ = result;
+        toolkit
+            .set_breaks(BreakMode::None)
+            .expect("Failed to set breaks");
+        assert_eq!(toolkit.page_count(), 0);
+        let options: serde_json::Value =
+            serde_json::from_str(&toolkit.get_options()).expect("Options should be JSON");
+        assert_eq!(options["breaks"], "none");
     }
 
     #[test]
-    fn test_toolkit_set_scale_negative() {
+    fn test_toolkit_set_condense_no_data() {
         let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.set_scale(-1);
-        // Verovio may accept or reject this - test that we handle the result
-        let _ = result;
+        toolkit
+            .set_condense(CondenseMode::Auto)
+            .expect("Failed to set condense");
+        assert_eq!(toolkit.page_count(), 0);
     }
 
     #[test]
-    fn test_toolkit_set_scale_large() {
+    fn test_toolkit_fit_to_content_no_data() {
         let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        // Very large scale
-        let result = toolkit.set_scale(10000);
-        let _ = result;
+        toolkit.fit_to_content().expect("Failed to fit to content");
+
+        let options: serde_json::Value =
+            serde_json::from_str(&toolkit.get_options()).expect("Options should be JSON");
+        assert_eq!(options["adjustPageHeight"], true);
+        assert_eq!(options["adjustPageWidth"], true);
     }
 
+    #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_get_resource_path() {
-        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let path = toolkit.get_resource_path();
-        // Without resources, path may be empty
-        let _ = path;
+    fn test_toolkit_fit_to_content_shrinks_page() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit
+            .load_data(&multi_measure_mei(2))
+            .expect("Failed to load MEI");
+        let a4 = toolkit.page_view_box(1).expect("Failed to get viewBox");
+
+        toolkit.fit_to_content().expect("Failed to fit to content");
+        let fitted = toolkit.page_view_box(1).expect("Failed to get viewBox");
+
+        assert!(fitted.width < a4.width);
+        assert!(fitted.height < a4.height);
     }
 
     #[test]
-    fn test_toolkit_set_resource_path_nonexistent() {
+    fn test_toolkit_compact_vertical_no_data() {
         let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.set_resource_path(Path::new("/nonexistent/path/to/resources"));
-        // This may succeed (just sets the path) or fail depending on implementation
-        let _ = result;
-    }
+        toolkit
+            .compact_vertical()
+            .expect("Failed to compact spacing");
 
-    #[test]
-    fn test_toolkit_get_page_with_element_not_found() {
-        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.get_page_with_element("nonexistent-id");
-        // Should return 0 when element not found
-        assert!(result.is_ok());
-        assert_eq!(result.unwrap(), 0);
+        let options: serde_json::Value =
+            serde_json::from_str(&toolkit.get_options()).expect("Options should be JSON");
+        assert_eq!(options["justifyVertically"], false);
+        assert_eq!(options["adjustPageHeight"], true);
+        assert_eq!(options["spacingSystem"].as_f64(), Some(0.0));
+        assert_eq!(options["spacingStaff"].as_f64(), Some(2.0));
     }
 
+    #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_get_element_attr_not_found() {
-        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.get_element_attr("nonexistent-id");
-        // May return empty JSON or error
-        let _ = result;
+    fn test_toolkit_compact_vertical_reduces_height() {
+        let mei = multi_measure_mei(16)
+            .replace(
+                r#"</staffGrp>"#,
+                r#"<staffDef n="2" lines="5" clef.shape="F" clef.line="4"/></staffGrp>"#,
+            )
+            .replace(
+                "</staff></measure>",
+                r#"</staff><staff n="2"><layer n="1"><note pname="c" oct="3" dur="1"/></layer></staff></measure>"#,
+            );
+        let total_height = |toolkit: &Toolkit| -> f64 {
+            (1..=toolkit.page_count())
+                .map(|page| {
+                    toolkit
+                        .page_view_box(page)
+                        .expect("Failed to get viewBox")
+                        .height
+                })
+                .sum()
+        };
+
+        // Compare against default spacing on pages cropped the same way
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit
+            .set_options(&Options::builder().adjust_page_height(true).build())
+            .expect("Failed to set options");
+        toolkit.load_data(&mei).expect("Failed to load MEI");
+        let default_height = total_height(&toolkit);
+
+        toolkit
+            .compact_vertical()
+            .expect("Failed to compact spacing");
+        let compact_height = total_height(&toolkit);
+        assert!(
+            compact_height < default_height,
+            "compact {} should be less than default {}",
+            compact_height,
+            default_height
+        );
     }
 
+    // =========================================================================
+    // ViewBox Tests
+    // =========================================================================
+
     #[test]
-    fn test_toolkit_get_elements_at_time_no_data() {
+    fn test_toolkit_page_view_box_no_data() {
         let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.get_elements_at_time(0);
-        // May return empty array or error
-        let _ = result;
+        let result = toolkit.page_view_box(1);
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("out of range"));
     }
 
+    #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_get_elements_at_time_negative() {
-        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.get_elements_at_time(-1000);
-        let _ = result;
+    fn test_toolkit_page_view_box() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit
+            .load_data(&multi_measure_mei(4))
+            .expect("Failed to load MEI");
+
+        let view_box = toolkit.page_view_box(1).expect("Failed to get viewBox");
+        assert!(view_box.width > 0.0);
+        assert!(view_box.height > 0.0);
+
+        // The svgViewBox option is restored afterwards
+        let options: serde_json::Value =
+            serde_json::from_str(&toolkit.get_options()).expect("Options should be JSON");
+        assert_eq!(options["svgViewBox"], false);
+        let svg = toolkit.render_to_svg(1).expect("Failed to render SVG");
+        assert!(crate::svg::root_view_box(&svg).is_err());
     }
 
+    // =========================================================================
+    // Measure Index Tests
+    // =========================================================================
+
     #[test]
-    fn test_toolkit_get_time_for_element_not_found() {
+    fn test_toolkit_measure_page_index_no_data() {
         let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.get_time_for_element("nonexistent-id");
-        assert!(result.is_ok());
-        // Time may be 0 or negative when not found
+        let result = toolkit.measure_page_index();
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("no data loaded"));
     }
 
+    #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_redo_layout_no_data() {
-        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.redo_layout(None);
-        assert!(result.is_ok());
-    }
+    fn test_toolkit_measure_page_index() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit
+            .load_data(&multi_measure_mei(200))
+            .expect("Failed to load MEI");
 
-    #[test]
-    fn test_toolkit_redo_layout_with_options() {
-        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.redo_layout(Some(r#"{"pageWidth": 2100}"#));
-        assert!(result.is_ok());
+        let index = toolkit
+            .measure_page_index()
+            .expect("Failed to index measures");
+        assert_eq!(index.len(), 200);
+
+        let pages: Vec<u32> = index.iter().map(|(_, page)| *page).collect();
+        assert!(pages.windows(2).all(|w| w[0] <= w[1]));
+        assert_eq!(pages.first(), Some(&1));
+        assert_eq!(pages.last(), Some(&toolkit.page_count()));
     }
 
     #[test]
-    fn test_toolkit_edit_no_data() {
-        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.edit(r#"{"action": "commit"}"#);
-        // Edit without data may fail
-        let _ = result;
+    fn test_toolkit_measure_count_no_data() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.measure_count();
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("no data loaded"));
     }
 
+    // =========================================================================
+    // Append Measures Tests
+    // =========================================================================
+
+    const APPENDED_MEASURE: &str = r#"<measure><staff n="1"><layer n="1"><note pname="g" oct="4" dur="1"/></layer></staff></measure>"#;
+
     #[test]
-    fn test_toolkit_edit_invalid_json() {
+    fn test_toolkit_append_mei_measures_no_data() {
         let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.edit("not valid json");
-        // Should handle invalid JSON gracefully
-        let _ = result;
+        let result = toolkit.append_mei_measures(APPENDED_MEASURE);
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("no data loaded"));
     }
 
+    #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_edit_info() {
-        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let info = toolkit.edit_info();
-        // Should return JSON (possibly empty object)
-        let _ = info;
-    }
+    fn test_toolkit_append_mei_measures_increments_measure_count() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit
+            .load_data(&multi_measure_mei(1))
+            .expect("Failed to load MEI");
+        assert_eq!(toolkit.measure_count().expect("Failed to count"), 1);
 
-    #[test]
-    fn test_toolkit_load_data_with_null_byte() {
-        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.load_data("test\0data");
-        assert!(result.is_err());
-        let err = result.unwrap_err();
-        assert!(err.to_string().contains("null byte"));
+        toolkit
+            .append_mei_measures(APPENDED_MEASURE)
+            .expect("Failed to append measure");
+        assert_eq!(toolkit.measure_count().expect("Failed to count"), 2);
+        assert!(
+            toolkit
+                .render_to_svg(1)
+                .expect("Failed to render")
+                .contains("<svg")
+        );
     }
 
+    #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_load_data_malformed_mei() {
-        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.load_data("<mei><invalid></mei>");
-        assert!(result.is_err());
+    fn test_toolkit_append_mei_measures_invalid_fragment() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit
+            .load_data(&multi_measure_mei(1))
+            .expect("Failed to load MEI");
+
+        let result = toolkit.append_mei_measures("<staff n=\"1\"/>");
+        assert!(matches!(result, Err(Error::LoadError(_))));
+        assert_eq!(toolkit.measure_count().expect("Failed to count"), 1);
     }
 
+    // =========================================================================
+    // Break Insertion Tests
+    // =========================================================================
+
     #[test]
-    fn test_toolkit_load_data_random_text() {
+    fn test_toolkit_insert_system_break_before_no_data() {
         let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.load_data("This is just random text, not valid music notation");
+        let result = toolkit.insert_system_break_before("measure-1");
         assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("no data loaded"));
     }
 
+    #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_get_mei_with_options_null_byte() {
-        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.get_mei_with_options("test\0options");
+    fn test_toolkit_insert_system_break_before_unknown_measure() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit
+            .load_data(&multi_measure_mei(4))
+            .expect("Failed to load MEI");
+
+        let result = toolkit.insert_system_break_before("no-such-measure");
         assert!(result.is_err());
-        let err = result.unwrap_err();
-        assert!(err.to_string().contains("null byte"));
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("measure not found")
+        );
     }
 
+    #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_render_to_timemap_with_options_null_byte() {
-        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.render_to_timemap_with_options("test\0options");
-        assert!(result.is_err());
-        let err = result.unwrap_err();
-        assert!(err.to_string().contains("null byte"));
+    fn test_toolkit_insert_system_break_before_adds_system() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit
+            .set_options(&Options::builder().breaks(BreakMode::Encoded).build())
+            .expect("Failed to set options");
+        toolkit
+            .load_data(&multi_measure_mei(4))
+            .expect("Failed to load MEI");
+
+        let count_systems = |toolkit: &Toolkit| {
+            toolkit
+                .render_all_pages()
+                .expect("Failed to render")
+                .iter()
+                .map(|svg| svg.matches(r#"class="system""#).count())
+                .sum::<usize>()
+        };
+        assert_eq!(count_systems(&toolkit), 1);
+
+        let index = toolkit.measure_page_index().expect("Failed to index");
+        toolkit
+            .insert_system_break_before(&index[2].0)
+            .expect("Failed to insert break");
+        assert_eq!(count_systems(&toolkit), 2);
     }
 
+    #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_get_page_with_element_null_byte() {
-        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.get_page_with_element("test\0id");
-        assert!(result.is_err());
-        let err = result.unwrap_err();
-        assert!(err.to_string().contains("null byte"));
+    fn test_toolkit_insert_page_break_before_adds_page() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit
+            .set_options(&Options::builder().breaks(BreakMode::Encoded).build())
+            .expect("Failed to set options");
+        toolkit
+            .load_data(&multi_measure_mei(4))
+            .expect("Failed to load MEI");
+        assert_eq!(toolkit.page_count(), 1);
+
+        let index = toolkit.measure_page_index().expect("Failed to index");
+        toolkit
+            .insert_page_break_before(&index[2].0)
+            .expect("Failed to insert break");
+        assert_eq!(toolkit.page_count(), 2);
     }
 
+    // =========================================================================
+    // Element Attributes Batch Tests
+    // =========================================================================
+
+    #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_get_element_attr_null_byte() {
-        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.get_element_attr("test\0id");
-        assert!(result.is_err());
-        let err = result.unwrap_err();
-        assert!(err.to_string().contains("null byte"));
+    fn test_toolkit_element_attributes_batch() {
+        let mei = multi_measure_mei(1)
+            .replacen(
+                r#"<note pname="c""#,
+                r#"<note xml:id="note-c" pname="c""#,
+                1,
+            )
+            .replacen(
+                r#"<note pname="d""#,
+                r#"<note xml:id="note-d" pname="d""#,
+                1,
+            );
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit.load_data(&mei).expect("Failed to load MEI");
+
+        let attrs = toolkit
+            .element_attributes_batch(&["note-c", "missing-note", "note-d"])
+            .expect("Failed to get attributes");
+
+        assert_eq!(attrs.len(), 2);
+        assert_eq!(attrs[0].0, "note-c");
+        assert_eq!(attrs[0].1.get("pname").map(String::as_str), Some("c"));
+        assert_eq!(attrs[0].1.get("oct").map(String::as_str), Some("4"));
+        assert_eq!(attrs[1].0, "note-d");
+        assert_eq!(attrs[1].1.get("pname").map(String::as_str), Some("d"));
     }
 
     #[test]
-    fn test_toolkit_get_time_for_element_null_byte() {
+    fn test_toolkit_element_attributes_batch_no_data() {
         let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.get_time_for_element("test\0id");
-        assert!(result.is_err());
-        let err = result.unwrap_err();
-        assert!(err.to_string().contains("null byte"));
+        let attrs = toolkit
+            .element_attributes_batch(&["note-c"])
+            .expect("Failed to get attributes");
+        assert!(attrs.is_empty());
     }
 
-    #[test]
-    fn test_toolkit_redo_layout_null_byte() {
-        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.redo_layout(Some("test\0options"));
-        assert!(result.is_err());
-        let err = result.unwrap_err();
-        assert!(err.to_string().contains("null byte"));
-    }
+    // =========================================================================
+    // Current Options Tests
+    // =========================================================================
 
     #[test]
-    fn test_toolkit_edit_null_byte() {
+    fn test_toolkit_current_options() {
         let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.edit("test\0action");
-        assert!(result.is_err());
-        let err = result.unwrap_err();
-        assert!(err.to_string().contains("null byte"));
+        toolkit
+            .set_options(&Options::builder().scale(55).build())
+            .expect("Failed to set options");
+
+        let options = toolkit.current_options().expect("Failed to read options");
+        assert_eq!(options.scale, Some(55));
+        assert!(!options.extra.is_empty());
+
+        // The full set of options can be applied back unchanged
+        toolkit
+            .set_options(&options)
+            .expect("Failed to re-apply options");
+        assert_eq!(toolkit.scale(), 55);
     }
 
     #[test]
-    fn test_toolkit_set_options_null_byte_in_font() {
+    fn test_toolkit_current_options_validate() {
         let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        // Create options with a null byte in a string field
-        let mut options = Options::builder().build();
-        options.font = Some("test\0font".to_string());
-        let result = toolkit.set_options(&options);
-        // The JSON serialization should succeed, but CString creation may fail
-        // or the toolkit may reject it
-        let _ = result;
+        let options = toolkit.current_options().expect("Failed to read options");
+        assert_eq!(options.validate(), Ok(()));
+
+        toolkit
+            .set_options(&Options::builder().adjust_page_height(true).build())
+            .expect("Failed to set options");
+        let options = toolkit.current_options().expect("Failed to read options");
+        assert_eq!(options.validate(), Ok(()));
     }
 
+    // =========================================================================
+    // Active Font Tests
+    // =========================================================================
+
     #[test]
-    fn test_toolkit_render_all_pages_no_data() {
+    fn test_toolkit_active_font_no_resources() {
         let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.render_all_pages();
-        // With no data, page_count is 0, so we get empty vec
-        assert!(result.is_ok());
-        assert!(result.unwrap().is_empty());
+        let font = toolkit.active_font().expect("Failed to get font");
+        assert!(!font.is_empty());
     }
 
     #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_render_to_svg_with_declaration() {
+    fn test_toolkit_active_font_available() {
         let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit
+            .set_options(&Options::builder().font("Bravura").build())
+            .expect("Failed to set options");
+        assert_eq!(toolkit.active_font().unwrap(), "Bravura");
+    }
 
-        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
-<mei xmlns="http://www.music-encoding.org/ns/mei">
-  <music>
-    <body>
-      <mdiv>
-        <score>
-          <scoreDef>
-            <staffGrp>
-              <staffDef n="1" lines="5" clef.shape="G" clef.line="2"/>
-            </staffGrp>
-          </scoreDef>
-          <section>
-            <measure>
-              <staff n="1">
-                <layer n="1">
-                  <note pname="c" oct="4" dur="4"/>
-                </layer>
-              </staff>
-            </measure>
-          </section>
-        </score>
-      </mdiv>
-    </body>
-  </music>
-</mei>"#;
-
-        toolkit.load_data(mei).expect("Failed to load MEI");
+    #[cfg(feature = "bundled-data")]
+    #[test]
+    fn test_toolkit_active_font_unavailable() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        // Verovio may or may not reject the option; either way the font is not used
+        let _ = toolkit.set_options(&Options::builder().font("NoSuchFont").build());
 
-        let svg = toolkit
-            .render_to_svg_with_declaration(1)
-            .expect("Failed to render SVG");
-        assert!(svg.contains("<?xml"));
-        assert!(svg.contains("<svg"));
+        let font = toolkit.active_font().expect("Failed to get font");
+        assert_ne!(font, "NoSuchFont");
+        assert!(!font.is_empty());
     }
 
     #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_render_to_midi() {
+    fn test_toolkit_active_font_keeps_applied_font() {
         let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit
+            .set_options(&Options::builder().font("Bravura").build())
+            .expect("Failed to set options");
+        let _ = toolkit.set_options(&Options::builder().font("NoSuchFont").build());
+        assert_eq!(toolkit.active_font().unwrap(), "Bravura");
+
+        toolkit.reset().expect("Failed to reset");
+        let _ = toolkit.set_options(&Options::builder().font("NoSuchFont").build());
+        assert_eq!(
+            toolkit.active_font().unwrap(),
+            Toolkit::font_option(&toolkit.get_default_options()).unwrap()
+        );
+    }
 
-        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
-<mei xmlns="http://www.music-encoding.org/ns/mei">
-  <music>
-    <body>
-      <mdiv>
-        <score>
-          <scoreDef>
-            <staffGrp>
-              <staffDef n="1" lines="5" clef.shape="G" clef.line="2"/>
-            </staffGrp>
-          </scoreDef>
-          <section>
-            <measure>
-              <staff n="1">
-                <layer n="1">
-                  <note pname="c" oct="4" dur="4"/>
-                </layer>
-              </staff>
-            </measure>
-          </section>
-        </score>
-      </mdiv>
-    </body>
-  </music>
-</mei>"#;
+    #[test]
+    fn test_toolkit_set_fonts_invalid_fallback() {
+        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let err = toolkit
+            .set_fonts(crate::Font::Leipzig, Some(crate::Font::Petaluma))
+            .unwrap_err();
+        assert!(matches!(err, Error::OptionsError(_)));
+        assert!(err.to_string().contains("Leipzig or Bravura"));
+    }
 
-        toolkit.load_data(mei).expect("Failed to load MEI");
+    #[cfg(feature = "bundled-data")]
+    #[test]
+    fn test_toolkit_set_fonts_unavailable() {
+        let bundled = Toolkit::new().expect("Failed to create toolkit");
+        let resources = resources_with_mini_font(Path::new(&bundled.get_resource_path()));
+        let _ = std::fs::remove_file(resources.path().join("Leland.xml"));
 
-        let result = toolkit.render_to_midi();
-        // MIDI rendering should succeed with valid MEI
-        assert!(result.is_ok());
-        let midi = result.unwrap();
-        // MIDI is base64-encoded
-        assert!(!midi.is_empty());
+        let mut toolkit =
+            Toolkit::with_resource_path(resources.path()).expect("Failed to create toolkit");
+        let err = toolkit
+            .set_fonts(crate::Font::Leland, Some(crate::Font::Bravura))
+            .unwrap_err();
+        assert!(err.to_string().contains("font not available: Leland"));
     }
 
     #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_get_mei_with_options() {
+    fn test_toolkit_set_fonts_leipzig_with_bravura_fallback() {
         let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit
+            .load_data(&multi_measure_mei(2))
+            .expect("Failed to load MEI");
+        toolkit
+            .set_fonts(crate::Font::Leipzig, Some(crate::Font::Bravura))
+            .expect("Failed to set fonts");
 
-        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
-<mei xmlns="http://www.music-encoding.org/ns/mei">
-  <music>
-    <body>
-      <mdiv>
-        <score>
-          <scoreDef>
-            <staffGrp>
-              <staffDef n="1" lines="5" clef.shape="G" clef.line="2"/>
-            </staffGrp>
-          </scoreDef>
-          <section>
-            <measure>
-              <staff n="1">
-                <layer n="1">
-                  <note pname="c" oct="4" dur="4"/>
-                </layer>
-              </staff>
-            </measure>
-          </section>
-        </score>
-      </mdiv>
-    </body>
-  </music>
-</mei>"#;
+        let options: serde_json::Value =
+            serde_json::from_str(&toolkit.get_options()).expect("Options should be JSON");
+        assert_eq!(options["font"], "Leipzig");
+        assert_eq!(options["fontFallback"], "Bravura");
+        assert_eq!(toolkit.active_font().unwrap(), "Leipzig");
 
-        toolkit.load_data(mei).expect("Failed to load MEI");
+        let svg = toolkit.render_to_svg(1).expect("Failed to render SVG");
+        assert!(svg.contains(r#"class="note""#));
+    }
 
-        let result = toolkit.get_mei_with_options(r#"{"removeIds": false}"#);
-        assert!(result.is_ok());
-        let exported = result.unwrap();
-        assert!(exported.contains("mei"));
+    // =========================================================================
+    // Config Export Tests
+    // =========================================================================
+
+    #[test]
+    fn test_declared_encoding() {
+        let range = |data: &[u8]| declared_encoding(data).map(|r| data[r].to_vec());
+        assert_eq!(
+            range(br#"<?xml version="1.0" encoding="ISO-8859-1"?><a/>"#),
+            Some(b"ISO-8859-1".to_vec())
+        );
+        assert_eq!(
+            range(b"\xEF\xBB\xBF<?xml version='1.0' encoding = 'utf-8' ?>"),
+            Some(b"utf-8".to_vec())
+        );
+        assert_eq!(range(br#"<?xml version="1.0"?><a encoding="x"/>"#), None);
+        assert_eq!(range(b"X:1\nK:C\nCDEF|"), None);
+        assert_eq!(range(br#"<?xml encoding="unterminated?>"#), None);
     }
 
-    #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_render_to_timemap() {
-        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    fn test_decode_data_utf8() {
+        let data = "<?xml version=\"1.0\" encoding=\"UTF-8\"?><a>\u{e9}</a>";
+        assert!(matches!(
+            decode_data(data.as_bytes()).unwrap(),
+            std::borrow::Cow::Borrowed(decoded) if decoded == data
+        ));
+        assert!(decode_data(b"<a>\xE9</a>").is_err());
+    }
 
-        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
-<mei xmlns="http://www.music-encoding.org/ns/mei">
-  <music>
-    <body>
-      <mdiv>
-        <score>
-          <scoreDef>
-            <staffGrp>
-              <staffDef n="1" lines="5" clef.shape="G" clef.line="2"/>
-            </staffGrp>
-          </scoreDef>
-          <section>
-            <measure>
-              <staff n="1">
-                <layer n="1">
-                  <note pname="c" oct="4" dur="4"/>
-                </layer>
-              </staff>
-            </measure>
-          </section>
-        </score>
-      </mdiv>
-    </body>
-  </music>
-</mei>"#;
+    #[test]
+    fn test_decode_data_latin1() {
+        let data = b"<?xml version=\"1.0\" encoding=\"ISO-8859-1\"?><a>Fr\xE9d\xE9ric</a>";
+        let result = decode_data(data);
 
-        toolkit.load_data(mei).expect("Failed to load MEI");
+        #[cfg(feature = "encoding")]
+        assert_eq!(
+            result.unwrap(),
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?><a>Fr\u{e9}d\u{e9}ric</a>"
+        );
+        #[cfg(not(feature = "encoding"))]
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("`encoding` feature")
+        );
+    }
 
-        let result = toolkit.render_to_timemap();
-        assert!(result.is_ok());
-        let timemap = result.unwrap();
-        // Timemap is JSON
-        assert!(timemap.starts_with('[') || timemap.starts_with('{'));
+    #[test]
+    fn test_window_measures() {
+        let timemap = r#"[
+            {"tstamp": 0, "on": ["n1"], "measureOn": "m1"},
+            {"tstamp": 500, "on": ["n2"], "off": ["n1"]},
+            {"tstamp": 2000, "on": ["n3"], "off": ["n2"], "measureOn": "m2"},
+            {"tstamp": 4000, "on": ["n4"], "off": ["n3"], "measureOn": "m3"},
+            {"tstamp": 6000, "off": ["n4"]}
+        ]"#;
+        let window = |start, end| window_measures(timemap, start, end).unwrap();
+
+        assert_eq!(window(0.0, 400.0), Some(("m1".into(), "m1".into())));
+        assert_eq!(window(1500.0, 2500.0), Some(("m1".into(), "m2".into())));
+        assert_eq!(window(2000.0, 6000.0), Some(("m2".into(), "m3".into())));
+        assert_eq!(window(5000.0, 9000.0), Some(("m3".into(), "m3".into())));
+        assert_eq!(window(6000.0, 9000.0), None);
+        assert_eq!(window_measures("[]", 0.0, 1000.0).unwrap(), None);
+        assert!(window_measures("not json", 0.0, 1000.0).is_err());
+    }
+
+    #[test]
+    fn test_measure_at() {
+        let timemap = r#"[
+            {"tstamp": 0, "on": ["n1"], "measureOn": "m1"},
+            {"tstamp": 2000, "on": ["n2"], "off": ["n1"], "measureOn": "m2"},
+            {"tstamp": 4000, "off": ["n2"]}
+        ]"#;
+        let at = |millis| measure_at(timemap, millis).unwrap();
+
+        assert_eq!(at(0.0), Some("m1".into()));
+        assert_eq!(at(1999.0), Some("m1".into()));
+        assert_eq!(at(2000.0), Some("m2".into()));
+        assert_eq!(at(4000.0), None);
+        assert_eq!(measure_at("[]", 0.0).unwrap(), None);
+        assert!(measure_at("not json", 0.0).is_err());
+    }
+
+    #[test]
+    fn test_fnv1a() {
+        assert_eq!(fnv1a(&[]), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a(&[b"a"]), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(fnv1a(&[b"foo", b"bar"]), fnv1a(&[b"foobar"]));
+    }
+
+    #[test]
+    fn test_toolkit_content_hash_without_retained_source() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let err = toolkit.content_hash().unwrap_err();
+        assert!(matches!(err, Error::LoadError(_)));
+        assert!(err.to_string().contains("set_retain_source"));
     }
 
     #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_render_to_expansion_map() {
-        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    fn test_toolkit_content_hash() {
+        let mei = multi_measure_mei(2);
+        let toolkit_with = |scale: u32| {
+            let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+            toolkit.set_retain_source(true);
+            toolkit.load_data(&mei).expect("Failed to load MEI");
+            toolkit
+                .set_options(&Options::builder().scale(scale).build())
+                .expect("Failed to set options");
+            toolkit
+        };
 
-        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
-<mei xmlns="http://www.music-encoding.org/ns/mei">
-  <music>
-    <body>
-      <mdiv>
-        <score>
-          <scoreDef>
-            <staffGrp>
-              <staffDef n="1" lines="5" clef.shape="G" clef.line="2"/>
-            </staffGrp>
-          </scoreDef>
-          <section>
-            <measure>
-              <staff n="1">
-                <layer n="1">
-                  <note pname="c" oct="4" dur="4"/>
-                </layer>
-              </staff>
-            </measure>
-          </section>
-        </score>
-      </mdiv>
-    </body>
-  </music>
-</mei>"#;
+        let a = toolkit_with(60).content_hash().expect("Failed to hash");
+        let b = toolkit_with(60).content_hash().expect("Failed to hash");
+        let c = toolkit_with(80).content_hash().expect("Failed to hash");
+        assert_eq!(a, b);
+        assert_ne!(a, c);
 
-        toolkit.load_data(mei).expect("Failed to load MEI");
+        let mut toolkit = toolkit_with(60);
+        toolkit
+            .load_data(&multi_measure_mei(3))
+            .expect("Failed to load MEI");
+        assert_ne!(toolkit.content_hash().expect("Failed to hash"), a);
 
-        let result = toolkit.render_to_expansion_map();
-        assert!(result.is_ok());
+        toolkit.set_retain_source(false);
+        assert!(toolkit.content_hash().is_err());
     }
 
     #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_redo_layout_after_load() {
+    fn test_toolkit_rejected_load_keeps_document() {
         let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit.set_retain_source(true);
+        toolkit
+            .set_input_from("mei")
+            .expect("Failed to set input format");
+        toolkit
+            .load_data(&multi_measure_mei(2))
+            .expect("Failed to load MEI");
+        let hash = toolkit.content_hash().expect("Failed to hash");
+        let generation = toolkit.generation();
 
-        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
-<mei xmlns="http://www.music-encoding.org/ns/mei">
-  <music>
-    <body>
-      <mdiv>
-        <score>
-          <scoreDef>
-            <staffGrp>
-              <staffDef n="1" lines="5" clef.shape="G" clef.line="2"/>
-            </staffGrp>
-          </scoreDef>
-          <section>
-            <measure>
-              <staff n="1">
-                <layer n="1">
-                  <note pname="c" oct="4" dur="4"/>
-                </layer>
-              </staff>
-            </measure>
-          </section>
-        </score>
-      </mdiv>
-    </body>
-  </music>
-</mei>"#;
+        assert!(
+            toolkit
+                .load_file(Path::new("/nonexistent/path/to/file.mei"))
+                .is_err()
+        );
+        assert!(toolkit.load_data("X:1\nK:C\nCDEF|\n").is_err());
 
-        toolkit.load_data(mei).expect("Failed to load MEI");
+        assert_eq!(toolkit.generation(), generation);
+        assert_eq!(toolkit.content_hash().expect("Failed to hash"), hash);
+        assert!(toolkit.render_to_svg(1).is_ok());
+    }
 
-        // Redo layout with different options
-        let result = toolkit.redo_layout(Some(r#"{"pageWidth": 1500}"#));
-        assert!(result.is_ok());
+    #[cfg(feature = "bundled-data")]
+    #[test]
+    fn test_toolkit_content_hash_after_render_data() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit.set_retain_source(true);
+        toolkit
+            .load_data(&multi_measure_mei(2))
+            .expect("Failed to load MEI");
+        let loaded = toolkit.content_hash().expect("Failed to hash");
 
-        // Should still be able to render
-        let svg = toolkit
-            .render_to_svg(1)
-            .expect("Failed to render after redo");
-        assert!(svg.contains("<svg"));
+        toolkit
+            .render_data(&multi_measure_mei(3), None)
+            .expect("Failed to render data");
+        let rendered = toolkit.content_hash().expect("Failed to hash");
+        assert_ne!(rendered, loaded);
+
+        toolkit
+            .load_data(&multi_measure_mei(3))
+            .expect("Failed to load MEI");
+        assert_eq!(toolkit.content_hash().expect("Failed to hash"), rendered);
     }
 
-    #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_set_and_get_resource_path() {
-        let toolkit = Toolkit::new().expect("Failed to create toolkit");
-        let path = toolkit.get_resource_path();
-        // With bundled data, resource path should not be empty
-        assert!(!path.is_empty());
+    fn test_toolkit_export_config_defaults() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        assert_eq!(toolkit.export_config().expect("Failed to export"), "{}");
     }
 
-    #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_get_elements_at_time_with_data() {
-        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    fn test_toolkit_config_round_trip() {
+        let mut source = Toolkit::without_resources().expect("Failed to create toolkit");
+        source
+            .set_options(&Options::builder().scale(55).breaks(BreakMode::None).build())
+            .expect("Failed to set options");
+        let config = source.export_config().expect("Failed to export");
 
-        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
-<mei xmlns="http://www.music-encoding.org/ns/mei">
-  <music>
-    <body>
-      <mdiv>
-        <score>
-          <scoreDef>
-            <staffGrp>
-              <staffDef n="1" lines="5" clef.shape="G" clef.line="2"/>
-            </staffGrp>
-          </scoreDef>
-          <section>
-            <measure>
-              <staff n="1">
-                <layer n="1">
-                  <note pname="c" oct="4" dur="4"/>
-                </layer>
-              </staff>
-            </measure>
-          </section>
-        </score>
-      </mdiv>
-    </body>
-  </music>
-</mei>"#;
+        let exported: serde_json::Value =
+            serde_json::from_str(&config).expect("Config should be JSON");
+        assert_eq!(exported["scale"], 55);
+        assert_eq!(exported["breaks"], "none");
+        assert!(exported.get("pageWidth").is_none());
 
-        toolkit.load_data(mei).expect("Failed to load MEI");
+        let mut target = Toolkit::without_resources().expect("Failed to create toolkit");
+        target.import_config(&config).expect("Failed to import");
+        assert_eq!(target.scale(), 55);
+        assert_eq!(target.scale(), source.scale());
+    }
 
-        let result = toolkit.get_elements_at_time(0);
-        assert!(result.is_ok());
-        let elements = result.unwrap();
-        // Should be valid JSON
-        assert!(elements.starts_with('{') || elements.starts_with('['));
+    #[test]
+    fn test_toolkit_import_config_invalid() {
+        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.import_config("[1, 2]");
+        assert!(matches!(result, Err(Error::OptionsError(_))));
     }
 
+    // =========================================================================
+    // Multichannel MIDI Tests
+    // =========================================================================
+
     #[test]
-    fn test_toolkit_with_resource_path_nonexistent() {
-        let result = Toolkit::with_resource_path(Path::new("/nonexistent/resources"));
-        // May fail or succeed depending on whether Verovio validates the path
-        let _ = result;
+    fn test_toolkit_render_to_midi_multichannel_no_data() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        assert!(toolkit.render_to_midi_multichannel().is_err());
     }
 
     #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_load_file_with_tempfile() {
-        use std::io::Write;
+    fn test_toolkit_render_to_midi_multichannel() {
+        let mei = multi_measure_mei(2)
+            .replace(
+                r#"</staffGrp>"#,
+                r#"<staffDef n="2" lines="5" clef.shape="F" clef.line="4"/></staffGrp>"#,
+            )
+            .replace(
+                "</staff></measure>",
+                r#"</staff><staff n="2"><layer n="1"><note pname="c" oct="3" dur="1"/></layer></staff></measure>"#,
+            );
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit.load_data(&mei).expect("Failed to load MEI");
 
-        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let midi = toolkit
+            .render_to_midi_multichannel()
+            .expect("Failed to render MIDI");
+        assert!(midi.starts_with(b"MThd"));
 
-        // Create a temp file with invalid content
-        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
-        let file_path = temp_dir.path().join("test.mei");
-        let mut file = std::fs::File::create(&file_path).expect("Failed to create file");
-        file.write_all(b"<invalid>not valid mei</invalid>")
-            .expect("Failed to write");
+        let channels = crate::midi::note_channels(&midi).expect("MIDI should parse");
+        assert!(channels.len() >= 2, "channels used: {:?}", channels);
+    }
 
-        let result = toolkit.load_file(&file_path);
-        // Should fail to load invalid MEI
+    // =========================================================================
+    // Metadata Tests
+    // =========================================================================
+
+    #[test]
+    fn test_toolkit_metadata_no_data() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.metadata();
         assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("no data loaded"));
     }
 
     #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_load_file_with_valid_mei() {
-        use std::io::Write;
-
+    fn test_toolkit_metadata() {
+        let mei = multi_measure_mei(2).replace(
+            "<music>",
+            concat!(
+                "<meiHead><fileDesc><titleStmt><title>Little Fugue</title>",
+                "<respStmt><persName role=\"composer\">J. S. Bach</persName></respStmt>",
+                "</titleStmt><pubStmt/></fileDesc></meiHead><music>"
+            ),
+        );
         let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit.load_data(&mei).expect("Failed to load MEI");
 
-        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
-<mei xmlns="http://www.music-encoding.org/ns/mei">
-  <music>
-    <body>
-      <mdiv>
-        <score>
-          <scoreDef>
-            <staffGrp>
-              <staffDef n="1" lines="5" clef.shape="G" clef.line="2"/>
-            </staffGrp>
-          </scoreDef>
-          <section>
-            <measure>
-              <staff n="1">
-                <layer n="1">
-                  <note pname="c" oct="4" dur="4"/>
-                </layer>
-              </staff>
-            </measure>
-          </section>
-        </score>
-      </mdiv>
-    </body>
-  </music>
-</mei>"#;
+        let metadata = toolkit.metadata().expect("Failed to read metadata");
+        assert_eq!(metadata.title.as_deref(), Some("Little Fugue"));
+        assert_eq!(metadata.composer.as_deref(), Some("J. S. Bach"));
+        assert_eq!(metadata.lyricist, None);
+        assert_eq!(metadata.copyright, None);
+    }
 
-        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
-        let file_path = temp_dir.path().join("test.mei");
-        let mut file = std::fs::File::create(&file_path).expect("Failed to create file");
-        file.write_all(mei.as_bytes()).expect("Failed to write");
+    #[cfg(feature = "bundled-data")]
+    #[test]
+    fn test_toolkit_metadata_without_header() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit
+            .load_data(&multi_measure_mei(1))
+            .expect("Failed to load MEI");
 
-        let result = toolkit.load_file(&file_path);
-        assert!(result.is_ok());
-        assert!(toolkit.page_count() > 0);
+        let metadata = toolkit.metadata().expect("Failed to read metadata");
+        assert_eq!(metadata.composer, None);
+        assert_eq!(metadata.lyricist, None);
     }
 
     #[test]
-    fn test_toolkit_not_sync() {
-        // This is a compile-time check - Toolkit should NOT implement Sync
-        // We can't easily test this at runtime, but we document it here
-        // fn assert_sync<T: Sync>() {}
-        // assert_sync::<Toolkit>(); // This would fail to compile
+    fn test_toolkit_render_to_svg_hiding_invalid_type() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let err = toolkit.render_to_svg_hiding(1, &["verse>"]).unwrap_err();
+        assert!(err.to_string().contains("invalid element type"));
     }
 
+    #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_debug_format_detailed() {
+    fn test_toolkit_render_to_svg_hiding_verses() {
+        let mei = multi_measure_mei(1).replace(
+            r#"<note pname="c" oct="4" dur="4"/>"#,
+            r#"<note pname="c" oct="4" dur="4"><verse n="1"><syl>Kyrie</syl></verse></note>"#,
+        );
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit.load_data(&mei).expect("Failed to load MEI");
+        let svg = toolkit.render_to_svg(1).expect("Failed to render SVG");
+        assert!(svg.contains(r#"class="verse""#));
+
+        let hidden = toolkit
+            .render_to_svg_hiding(1, &["verse"])
+            .expect("Failed to render SVG");
+        let doc = roxmltree::Document::parse(&hidden).expect("SVG should be well-formed");
+        assert!(!doc.descendants().any(|node| {
+            node.attribute("class")
+                .is_some_and(|class| class.split_whitespace().any(|c| c == "verse"))
+        }));
+        assert!(!hidden.contains("Kyrie"));
+        assert!(hidden.contains(r#"class="note""#));
+    }
+
+    #[test]
+    fn test_toolkit_render_to_svg_with_data_attrs_invalid_name() {
         let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let debug = format!("{:?}", toolkit);
-        assert!(debug.contains("Toolkit"));
-        assert!(debug.contains("version"));
-        assert!(debug.contains("page_count"));
-        assert!(debug.contains("resource_path"));
+        let err = toolkit
+            .render_to_svg_with_data_attrs(1, &[("onclick", "x")])
+            .unwrap_err();
+        assert!(err.to_string().contains("invalid data attribute name"));
+
+        let err = toolkit
+            .render_to_svg_with_element_data_attrs(1, "note>", &[("data-foo", "bar")])
+            .unwrap_err();
+        assert!(err.to_string().contains("invalid element type"));
     }
 
+    #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_enable_log_toggle() {
-        // Test toggling log on and off multiple times
-        Toolkit::enable_log(true);
-        Toolkit::enable_log(true);
-        Toolkit::enable_log(false);
-        Toolkit::enable_log(false);
-        Toolkit::enable_log(true);
-        Toolkit::enable_log(false);
+    fn test_toolkit_render_to_svg_with_data_attrs() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit
+            .load_data(&multi_measure_mei(1))
+            .expect("Failed to load MEI");
+
+        let svg = toolkit
+            .render_to_svg_with_data_attrs(1, &[("data-foo", "bar")])
+            .expect("Failed to render SVG");
+        let doc = roxmltree::Document::parse(&svg).expect("SVG should be well-formed");
+        assert_eq!(doc.root_element().attribute("data-foo"), Some("bar"));
+
+        let svg = toolkit
+            .render_to_svg_with_element_data_attrs(1, "note", &[("data-foo", "bar")])
+            .expect("Failed to render SVG");
+        let doc = roxmltree::Document::parse(&svg).expect("SVG should be well-formed");
+        let notes: Vec<_> = doc
+            .descendants()
+            .filter(|node| node.attribute("class") == Some("note"))
+            .collect();
+        assert_eq!(notes.len(), 4);
+        assert!(
+            notes
+                .iter()
+                .all(|note| note.attribute("data-foo") == Some("bar"))
+        );
+        assert_eq!(doc.root_element().attribute("data-foo"), None);
+    }
+
+    #[cfg(feature = "bundled-data")]
+    #[test]
+    fn test_toolkit_coordinate_mapper() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit
+            .load_data(&multi_measure_mei(1))
+            .expect("Failed to load MEI");
+        let view_box = crate::svg::page_drawing_view_box(&toolkit.render_to_svg(1).unwrap())
+            .expect("page should have a drawing viewBox");
+
+        let mapper = toolkit
+            .coordinate_mapper(1, view_box.width / 10.0, view_box.height / 10.0)
+            .expect("Failed to create mapper");
+        assert!((mapper.scale() - 0.1).abs() < 1e-9);
+        let (x, y) = mapper.user_to_pixel(view_box.min_x, view_box.min_y);
+        assert!(x.abs() < 1e-9 && y.abs() < 1e-9);
+
+        assert!(toolkit.coordinate_mapper(1, 0.0, 100.0).is_err());
     }
 
     #[test]
-    fn test_toolkit_enable_log_to_buffer_toggle() {
-        // Test toggling buffer log on and off multiple times
-        Toolkit::enable_log_to_buffer(true);
-        Toolkit::enable_log_to_buffer(true);
-        Toolkit::enable_log_to_buffer(false);
-        Toolkit::enable_log_to_buffer(false);
-        Toolkit::enable_log_to_buffer(true);
-        Toolkit::enable_log_to_buffer(false);
+    fn test_toolkit_render_time_window_invalid() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let err = toolkit.render_time_window(1000, 1000).unwrap_err();
+        assert!(err.to_string().contains("invalid time window"));
+
+        let err = toolkit.render_time_window(0, 1000).unwrap_err();
+        assert!(err.to_string().contains("no data loaded"));
     }
 
     #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_logging_with_data() {
-        Toolkit::enable_log_to_buffer(true);
-
+    fn test_toolkit_render_time_window() {
         let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit
+            .load_data(&multi_measure_mei(4))
+            .expect("Failed to load MEI");
+        let full = toolkit.render_to_svg(1).expect("Failed to render SVG");
+        let notes = |svg: &str| svg.matches(r#"class="note""#).count();
 
-        // Load some data to generate log messages
-        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
-<mei xmlns="http://www.music-encoding.org/ns/mei">
-  <music>
-    <body>
-      <mdiv>
-        <score>
-          <scoreDef>
-            <staffGrp>
-              <staffDef n="1" lines="5" clef.shape="G" clef.line="2"/>
-            </staffGrp>
-          </scoreDef>
-          <section>
-            <measure>
-              <staff n="1">
-                <layer n="1">
-                  <note pname="c" oct="4" dur="4"/>
-                </layer>
-              </staff>
-            </measure>
-          </section>
-        </score>
-      </mdiv>
-    </body>
-  </music>
-</mei>"#;
-
-        toolkit.load_data(mei).expect("Failed to load MEI");
+        // The first note sounds from 0 to 500 ms at the default tempo
+        let svg = toolkit
+            .render_time_window(0, 400)
+            .expect("Failed to render time window");
+        roxmltree::Document::parse(&svg).expect("SVG should be well-formed");
+        assert!(notes(&svg) > 0);
+        assert!(notes(&svg) < notes(&full));
 
-        let log = toolkit.get_log();
-        // Log may or may not have content depending on Verovio's behavior
-        let _ = log;
+        // This toolkit's layout is unchanged
+        assert_eq!(toolkit.render_to_svg(1).unwrap(), full);
 
-        Toolkit::enable_log_to_buffer(false);
+        let err = toolkit.render_time_window(600_000, 700_000).unwrap_err();
+        assert!(err.to_string().contains("no measures in time window"));
     }
 
+    #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_render_to_svg_page_exceeds_count() {
-        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        // With no data, page_count is 0, so page 1 should be out of range
-        let result = toolkit.render_to_svg(1);
-        assert!(result.is_err());
-        let err = result.unwrap_err();
-        assert!(err.to_string().contains("out of range"));
-        assert!(err.to_string().contains("0 pages"));
+    fn test_toolkit_write_mei() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit
+            .load_data(&multi_measure_mei(2))
+            .expect("Failed to load MEI");
+
+        let mut buffer = Vec::new();
+        toolkit
+            .write_mei(&mut buffer, &crate::Mei::with_options())
+            .expect("Failed to write MEI");
+        let written = String::from_utf8(buffer).expect("MEI should be UTF-8");
+        assert!(written.contains("<mei"));
+        assert_eq!(written, toolkit.get_mei().unwrap());
+
+        let mut buffer = Vec::new();
+        let options = crate::Mei::with_options().remove_ids(true);
+        toolkit
+            .write_mei(&mut buffer, &options)
+            .expect("Failed to write MEI");
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            toolkit
+                .get_mei_with_options(r#"{"removeIds": true}"#)
+                .unwrap()
+        );
     }
 
+    #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_render_to_svg_with_declaration_page_exceeds_count() {
+    fn test_toolkit_render_page_staff_colors() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit
+            .load_data(&multi_measure_mei(2))
+            .expect("Failed to load MEI");
+
+        let svg = toolkit
+            .render_page_staff_colors(1, &[(1, "#b22222")])
+            .expect("Failed to render SVG");
+        let doc = roxmltree::Document::parse(&svg).expect("SVG should be well-formed");
+        let staves: Vec<_> = doc
+            .descendants()
+            .filter(|node| node.attribute("class") == Some("staff"))
+            .collect();
+        assert_eq!(staves.len(), 2);
+        assert!(staves.iter().all(|staff| {
+            staff.attribute("data-n") == Some("1") && staff.attribute("fill") == Some("#b22222")
+        }));
+
+        // The additional attribute was only added for that render
+        assert!(!toolkit.render_to_svg(1).unwrap().contains("data-n"));
+
+        let err = toolkit
+            .render_page_staff_colors(1, &[(2, "red")])
+            .unwrap_err();
+        assert!(err.to_string().contains("no staff 2 on page 1"));
+        let err = toolkit
+            .render_page_staff_colors(1, &[(1, "red\"")])
+            .unwrap_err();
+        assert!(err.to_string().contains("invalid color"));
+    }
+
+    #[test]
+    fn test_toolkit_extract_text_no_data() {
         let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.render_to_svg_with_declaration(100);
-        assert!(result.is_err());
-        let err = result.unwrap_err();
-        assert!(err.to_string().contains("out of range"));
+        assert!(toolkit.extract_text().is_err());
     }
 
-    // =========================================================================
-    // Tests for new API methods (26 functions for 100% coverage)
-    // =========================================================================
-
-    // Format Control Functions
+    #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_set_input_from() {
-        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.set_input_from("mei");
-        // May succeed or fail depending on Verovio behavior
-        let _ = result;
+    fn test_toolkit_extract_text_lyric() {
+        let mei = multi_measure_mei(1).replace(
+            r#"<note pname="c" oct="4" dur="4"/>"#,
+            r#"<note pname="c" oct="4" dur="4"><verse n="1"><syl>Kyrie</syl></verse></note>"#,
+        );
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit.load_data(&mei).expect("Failed to load MEI");
+
+        let spans = toolkit.extract_text().expect("Failed to extract text");
+        let lyric = spans
+            .iter()
+            .find(|span| span.kind == crate::TextKind::Lyric)
+            .expect("lyric should be extracted");
+        assert_eq!(lyric.text, "Kyrie");
+        assert!(!lyric.element_id.is_empty());
     }
 
     #[test]
-    fn test_toolkit_set_input_from_various_formats() {
-        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        for format in &["mei", "musicxml", "humdrum", "pae", "abc"] {
-            let _ = toolkit.set_input_from(format);
-        }
+    fn test_toolkit_render_verse_no_data() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        assert!(toolkit.render_verse(2, 1).is_err());
     }
 
+    #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_set_input_from_null_byte() {
-        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.set_input_from("mei\0invalid");
-        assert!(result.is_err());
-        assert!(result.unwrap_err().to_string().contains("null byte"));
+    fn test_toolkit_render_verse() {
+        let mei = multi_measure_mei(1).replace(
+            r#"<note pname="c" oct="4" dur="4"/>"#,
+            concat!(
+                r#"<note pname="c" oct="4" dur="4">"#,
+                r#"<verse n="1"><syl>Kyrie</syl></verse><verse n="2"><syl>Gloria</syl></verse>"#,
+                "</note>"
+            ),
+        );
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit.load_data(&mei).expect("Failed to load MEI");
+        toolkit
+            .set_options(&Options::builder().lyric_verse_collapse(true).build())
+            .expect("Failed to set options");
+
+        let svg = toolkit.render_verse(2, 1).expect("Failed to render verse");
+        assert!(svg.contains("Gloria"));
+        assert!(!svg.contains("Kyrie"));
+
+        let svg = toolkit.render_verse(1, 1).expect("Failed to render verse");
+        assert!(svg.contains("Kyrie"));
+        assert!(!svg.contains("Gloria"));
+
+        let svg = toolkit.render_to_svg(1).expect("Failed to render SVG");
+        assert!(svg.contains("Kyrie") && svg.contains("Gloria"));
+
+        assert!(toolkit.render_verse(3, 1).is_err());
     }
 
     #[test]
-    fn test_toolkit_set_output_to() {
+    fn test_toolkit_set_options_text_lang_invalid() {
         let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.set_output_to("mei");
-        let _ = result;
+        let options = Options::builder().text_lang("not a tag").build();
+        let err = toolkit.set_options(&options).unwrap_err();
+        assert!(err.to_string().contains("invalid language tag"));
     }
 
+    #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_set_output_to_various_formats() {
-        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        for format in &["mei", "svg", "midi", "humdrum", "pae"] {
-            let _ = toolkit.set_output_to(format);
+    fn test_toolkit_set_options_text_lang() {
+        let mei = multi_measure_mei(1).replace(
+            r#"<note pname="c" oct="4" dur="4"/>"#,
+            r#"<note pname="c" oct="4" dur="4"><verse n="1"><syl>שלום</syl></verse></note>"#,
+        );
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit.load_data(&mei).expect("Failed to load MEI");
+        let svg = toolkit.render_to_svg(1).expect("Failed to render SVG");
+        assert!(!svg.contains("xml:lang"));
+
+        toolkit
+            .set_options(&Options::builder().text_lang("he").build())
+            .expect("Failed to set options");
+        let svg = toolkit.render_to_svg(1).expect("Failed to render SVG");
+        let doc = roxmltree::Document::parse(&svg).expect("SVG should be well-formed");
+        let texts: Vec<_> = doc
+            .descendants()
+            .filter(|node| node.has_tag_name("text"))
+            .collect();
+        assert!(!texts.is_empty());
+        for text in &texts {
+            assert_eq!(
+                text.attribute(("http://www.w3.org/XML/1998/namespace", "lang")),
+                Some("he")
+            );
+            assert_eq!(text.attribute("direction"), Some("rtl"));
         }
+        assert!(svg.contains("שלום"));
+
+        // Other options keep the language; resetting options clears it
+        toolkit
+            .set_options(&Options::builder().scale(50).build())
+            .expect("Failed to set options");
+        assert!(
+            toolkit
+                .render_to_svg(1)
+                .unwrap()
+                .contains(r#"direction="rtl""#)
+        );
+        toolkit.reset_options();
+        assert!(!toolkit.render_to_svg(1).unwrap().contains("xml:lang"));
     }
 
     #[test]
-    fn test_toolkit_set_output_to_null_byte() {
+    fn test_toolkit_text_lang_round_trips() {
         let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.set_output_to("mei\0invalid");
-        assert!(result.is_err());
-        assert!(result.unwrap_err().to_string().contains("null byte"));
-    }
+        toolkit
+            .set_options(&Options::builder().text_lang("ar-EG").build())
+            .expect("Failed to set options");
+        let options = toolkit.current_options().expect("Failed to read options");
+        assert_eq!(options.text_lang.as_deref(), Some("ar-EG"));
+        let config = toolkit.export_config().expect("Failed to export");
+        assert!(config.contains(r#""textLang":"ar-EG""#));
 
-    // ZIP Loading Functions
-    // Note: load_zip_data functions can throw C++ exceptions on invalid input
-    // so we only test the null byte handling (which is caught by Rust before FFI)
+        // An empty language clears it
+        toolkit
+            .set_options(&Options::builder().text_lang("").build())
+            .expect("Failed to set options");
+        assert_eq!(toolkit.current_options().unwrap().text_lang, None);
+        assert_eq!(toolkit.export_config().unwrap(), "{}");
 
-    #[test]
-    fn test_toolkit_load_zip_data_base64_null_byte() {
-        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.load_zip_data_base64("data\0invalid");
-        assert!(result.is_err());
-        assert!(result.unwrap_err().to_string().contains("null byte"));
+        toolkit.import_config(&config).expect("Failed to import");
+        assert_eq!(toolkit.text_lang.as_deref(), Some("ar-EG"));
+        assert!(toolkit.import_config(r#"{"textLang": 1}"#).is_err());
     }
 
-    // PAE Validation Functions
     #[test]
-    fn test_toolkit_validate_pae() {
-        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        // Simple PAE code
-        let result = toolkit.validate_pae("@clef:G-2 @key:bBEA @time:4/4 ''4C/8DE");
-        // Returns JSON validation result
-        let _ = result;
-    }
+    fn test_toolkit_set_text_lang() {
+        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let generation = toolkit.generation();
+        toolkit
+            .set_text_lang(Some("he"))
+            .expect("Failed to set language");
+        assert_eq!(toolkit.text_lang.as_deref(), Some("he"));
+        assert_ne!(toolkit.generation(), generation);
 
-    #[test]
-    fn test_toolkit_validate_pae_empty() {
-        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.validate_pae("");
-        let _ = result;
-    }
+        assert!(toolkit.set_text_lang(Some("not a tag")).is_err());
+        assert_eq!(toolkit.text_lang.as_deref(), Some("he"));
 
-    #[test]
-    fn test_toolkit_validate_pae_null_byte() {
-        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.validate_pae("@clef:G-2\0invalid");
-        assert!(result.is_err());
-        assert!(result.unwrap_err().to_string().contains("null byte"));
+        toolkit
+            .set_text_lang(None)
+            .expect("Failed to clear language");
+        assert_eq!(toolkit.text_lang, None);
     }
 
     #[test]
-    fn test_toolkit_validate_pae_file_not_found() {
+    fn test_toolkit_has_musical_content_no_data() {
         let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.validate_pae_file(Path::new("/nonexistent/path.pae"));
-        assert!(result.is_err());
-        assert!(result.unwrap_err().to_string().contains("file not found"));
+        assert!(toolkit.has_musical_content().is_err());
     }
 
+    #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_validate_pae_file_null_byte() {
-        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        // Path with null byte in it - this should fail at CString conversion
-        // We need to construct this carefully since Path won't accept null bytes on some systems
-        // This test verifies the path string conversion handles this case
-        let _ = toolkit.validate_pae_file(Path::new("/some/path.pae"));
+    fn test_toolkit_has_musical_content() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit
+            .load_data(&multi_measure_mei(1))
+            .expect("Failed to load MEI");
+        assert!(
+            toolkit
+                .has_musical_content()
+                .expect("Failed to inspect document")
+        );
     }
 
-    // Selection Function
+    #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_select_no_data() {
-        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.select(r#"{"measureRange": "1-2"}"#);
-        // Select without data may fail
-        let _ = result;
-    }
+    fn test_toolkit_has_musical_content_empty_skeleton() {
+        let mei = multi_measure_mei(1).replace(
+            r#"<layer n="1"><note pname="c" oct="4" dur="4"/><note pname="d" oct="4" dur="4"/><note pname="e" oct="4" dur="4"/><note pname="f" oct="4" dur="4"/></layer>"#,
+            r#"<layer n="1"/>"#,
+        );
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit.load_data(&mei).expect("Failed to load MEI");
 
-    #[test]
-    fn test_toolkit_select_empty() {
-        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.select("{}");
-        let _ = result;
+        assert!(toolkit.page_count() > 0);
+        assert!(
+            !toolkit
+                .has_musical_content()
+                .expect("Failed to inspect document")
+        );
     }
 
     #[test]
-    fn test_toolkit_select_null_byte() {
+    fn test_toolkit_set_title_no_data() {
         let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.select("{\0}");
-        assert!(result.is_err());
-        assert!(result.unwrap_err().to_string().contains("null byte"));
+        assert!(toolkit.set_title("Title").is_err());
+        assert!(toolkit.set_composer("Composer").is_err());
     }
 
-    // Layout Functions
+    #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_redo_page_pitch_pos_layout() {
-        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        toolkit.redo_page_pitch_pos_layout();
-        // Should not panic
-    }
+    fn test_toolkit_set_title_and_composer() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit
+            .load_data(&multi_measure_mei(2))
+            .expect("Failed to load MEI");
 
-    #[test]
-    fn test_toolkit_reset_xml_id_seed() {
-        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        toolkit.reset_xml_id_seed(42);
-        // Should not panic
+        toolkit
+            .set_title("Morning Song")
+            .expect("Failed to set title");
+        toolkit
+            .set_composer("Fanny Hensel")
+            .expect("Failed to set composer");
+
+        let metadata = toolkit.metadata().expect("Failed to read metadata");
+        assert_eq!(metadata.title.as_deref(), Some("Morning Song"));
+        assert_eq!(metadata.composer.as_deref(), Some("Fanny Hensel"));
+
+        let svg = toolkit.render_to_svg(1).expect("Failed to render SVG");
+        assert!(svg.contains("Morning Song"));
     }
 
+    #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_reset_xml_id_seed_zero() {
-        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        toolkit.reset_xml_id_seed(0);
+    fn test_toolkit_internal_reloads_with_pinned_input_format() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit
+            .load_data(&multi_measure_mei(2))
+            .expect("Failed to load MEI");
+        toolkit
+            .set_input_from("musicxml")
+            .expect("Failed to set input format");
+
+        toolkit
+            .set_title("Morning Song")
+            .expect("Failed to set title");
+        toolkit.transpose("M2").expect("Failed to transpose");
+        toolkit.ensure_ids().expect("Failed to ensure ids");
+        toolkit
+            .render_staves(&[1], 1)
+            .expect("Failed to render staves");
+        toolkit
+            .render_to_midi_multichannel()
+            .expect("Failed to render MIDI");
+        toolkit
+            .render_all_pages_parallel()
+            .expect("Failed to render pages");
+
+        let options: serde_json::Value =
+            serde_json::from_str(&toolkit.get_options()).expect("Options should be JSON");
+        assert_eq!(options["inputFrom"], "musicxml");
+        assert!(toolkit.load_data(&multi_measure_mei(1)).is_err());
     }
 
     #[test]
-    fn test_toolkit_reset_xml_id_seed_negative() {
+    fn test_toolkit_transpose_no_data() {
         let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        toolkit.reset_xml_id_seed(-1);
+        assert!(toolkit.transpose("P5").is_err());
     }
 
+    #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_get_option_usage_string() {
-        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let usage = toolkit.get_option_usage_string();
-        // Should return a non-empty string with option documentation
-        assert!(!usage.is_empty());
+    fn test_toolkit_transpose_c_major_up_fifth() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        let mei = multi_measure_mei(2).replace(r#"clef.line="2""#, r#"clef.line="2" keysig="0""#);
+        toolkit.load_data(&mei).expect("Failed to load MEI");
+
+        assert!(matches!(
+            toolkit.transpose(" "),
+            Err(Error::OptionsError(_))
+        ));
+
+        let result = toolkit.transpose("P5").expect("Failed to transpose");
+        assert_eq!(result.interval, "P5");
+        assert_eq!(result.fifths, Some(1));
+        assert_eq!(result.new_key.as_deref(), Some("G"));
+
+        let transposed = toolkit.get_mei().expect("Failed to export MEI");
+        assert!(transposed.contains(r#"pname="g""#));
+        assert!(!transposed.contains(r#"pname="f""#));
+
+        let options = toolkit.current_options().expect("Failed to read options");
+        assert!(options.transpose.unwrap_or_default().is_empty());
     }
 
-    // Conversion Functions
-    // Note: Empty input to conversion functions can cause Verovio to hang,
-    // so we only test null byte handling here. Actual functionality is tested
-    // with valid data in the bundled-data tests below.
+    // =========================================================================
+    // Document Validation Tests
+    // =========================================================================
 
     #[test]
-    fn test_toolkit_convert_humdrum_to_humdrum_null_byte() {
+    fn test_toolkit_validate_loaded_no_data() {
         let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.convert_humdrum_to_humdrum("data\0invalid");
+        let result = toolkit.validate_loaded();
         assert!(result.is_err());
-        assert!(result.unwrap_err().to_string().contains("null byte"));
+        assert!(result.unwrap_err().to_string().contains("no data loaded"));
+    }
+
+    #[cfg(feature = "bundled-data")]
+    #[test]
+    fn test_toolkit_validate_loaded_clean_document() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit
+            .load_data(&multi_measure_mei(2))
+            .expect("Failed to load MEI");
+
+        let issues = toolkit.validate_loaded().expect("Failed to validate");
+        assert!(issues.is_empty(), "unexpected issues: {:?}", issues);
     }
 
+    #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_convert_humdrum_to_midi_null_byte() {
-        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.convert_humdrum_to_midi("data\0invalid");
-        assert!(result.is_err());
-        assert!(result.unwrap_err().to_string().contains("null byte"));
+    fn test_toolkit_validate_loaded_reports_missing_clef() {
+        let mei = multi_measure_mei(2).replace(r#" clef.shape="G" clef.line="2""#, "");
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit.load_data(&mei).expect("Failed to load MEI");
+
+        let issues = toolkit.validate_loaded().expect("Failed to validate");
+        assert!(
+            issues.iter().any(|issue| issue.message.contains("no clef")),
+            "missing clef not reported: {:?}",
+            issues
+        );
     }
 
+    // =========================================================================
+    // Single SVG Tests
+    // =========================================================================
+
     #[test]
-    fn test_toolkit_convert_mei_to_humdrum_null_byte() {
+    fn test_toolkit_render_to_single_svg_no_data() {
         let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.convert_mei_to_humdrum("<mei>\0</mei>");
+        let result = toolkit.render_to_single_svg();
         assert!(result.is_err());
-        assert!(result.unwrap_err().to_string().contains("null byte"));
+        assert!(result.unwrap_err().to_string().contains("no data loaded"));
     }
 
+    #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_render_data_null_byte() {
-        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.render_data("data\0invalid", None);
-        assert!(result.is_err());
-        assert!(result.unwrap_err().to_string().contains("null byte"));
+    fn test_toolkit_render_to_single_svg_deduplicates_symbols() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit
+            .load_data(&multi_measure_mei(200))
+            .expect("Failed to load MEI");
+        assert!(toolkit.page_count() > 1);
+
+        let pages = toolkit.render_all_pages().expect("Failed to render");
+        let combined = toolkit
+            .render_to_single_svg()
+            .expect("Failed to render single SVG");
+        roxmltree::Document::parse(&combined).expect("SVG should be well-formed");
+
+        let per_page: usize = pages.iter().map(|svg| svg.matches("<symbol").count()).sum();
+        assert!(combined.matches("<symbol").count() < per_page);
     }
 
+    // =========================================================================
+    // Timed Render Tests
+    // =========================================================================
+
+    #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_render_data_options_null_byte() {
-        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.render_data("", Some("{\0}"));
+    fn test_toolkit_render_to_svg_timed_no_data() {
+        let toolkit = Toolkit::new().expect("Failed to create toolkit");
+        let result = toolkit.render_to_svg_timed(1);
         assert!(result.is_err());
-        assert!(result.unwrap_err().to_string().contains("null byte"));
+        assert!(result.unwrap_err().to_string().contains("out of range"));
     }
 
-    // Element Query Functions
+    #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_get_expansion_ids_for_element_not_found() {
-        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.get_expansion_ids_for_element("nonexistent-id");
-        let _ = result;
+    fn test_toolkit_render_to_svg_timed() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit
+            .load_data(&multi_measure_mei(4))
+            .expect("Failed to load MEI");
+
+        let (svg, elapsed) = toolkit
+            .render_to_svg_timed(1)
+            .expect("Failed to render SVG");
+        assert!(elapsed >= Duration::ZERO);
+        assert!(svg.contains("<svg"));
+        assert!(svg.contains("</svg>"));
+        roxmltree::Document::parse(&svg).expect("SVG should be well-formed");
     }
 
+    // =========================================================================
+    // Prefixed SVG Tests
+    // =========================================================================
+
     #[test]
-    fn test_toolkit_get_expansion_ids_for_element_null_byte() {
+    fn test_toolkit_render_to_svg_dark_no_data() {
         let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.get_expansion_ids_for_element("id\0invalid");
-        assert!(result.is_err());
-        assert!(result.unwrap_err().to_string().contains("null byte"));
+        assert!(toolkit.render_to_svg_dark(1).is_err());
     }
 
     #[test]
-    fn test_toolkit_get_midi_values_for_element_not_found() {
+    fn test_toolkit_render_to_svg_annotated_no_data() {
         let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.get_midi_values_for_element("nonexistent-id");
-        let _ = result;
+        let err = toolkit.render_to_svg_annotated(1).unwrap_err();
+        assert!(err.to_string().contains("no data loaded"));
     }
 
+    #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_get_midi_values_for_element_null_byte() {
-        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.get_midi_values_for_element("id\0invalid");
-        assert!(result.is_err());
-        assert!(result.unwrap_err().to_string().contains("null byte"));
+    fn test_toolkit_render_to_svg_annotated() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit
+            .load_data(&multi_measure_mei(1))
+            .expect("Failed to load MEI");
+
+        let svg = toolkit
+            .render_to_svg_annotated(1)
+            .expect("Failed to render SVG");
+        assert!(svg.contains("<title>noteheadBlack</title>"));
+        assert!(svg.contains(r#"class="bounding-box""#));
+        assert!(toolkit.render_to_svg_annotated(2).is_err());
+
+        // Bounding boxes are only drawn for that render
+        let svg = toolkit.render_to_svg(1).expect("Failed to render SVG");
+        assert!(!svg.contains("<title>noteheadBlack</title>"));
+        assert!(!svg.contains(r#"class="bounding-box""#));
     }
 
+    #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_get_notated_id_for_element_not_found() {
-        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.get_notated_id_for_element("nonexistent-id");
-        let _ = result;
+    fn test_toolkit_render_to_svg_dark() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit
+            .set_options(
+                &Options::builder()
+                    .svg_css("g.note { opacity: 0.9; }")
+                    .build(),
+            )
+            .expect("Failed to set options");
+        toolkit
+            .load_data(&multi_measure_mei(1))
+            .expect("Failed to load MEI");
+
+        let svg = toolkit.render_to_svg_dark(1).expect("Failed to render SVG");
+        assert!(svg.contains("svg.definition-scale { color: #f0f0f0; }"));
+        assert!(svg.contains("g.page-margin text, g.page-margin tspan { fill: #f0f0f0; }"));
+        assert!(svg.contains("g.note { opacity: 0.9; }"));
+
+        // The theme is only applied to that render
+        let svg = toolkit.render_to_svg(1).expect("Failed to render SVG");
+        assert!(!svg.contains("#f0f0f0"));
+        assert!(svg.contains("g.note { opacity: 0.9; }"));
     }
 
     #[test]
-    fn test_toolkit_get_notated_id_for_element_null_byte() {
-        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.get_notated_id_for_element("id\0invalid");
+    fn test_toolkit_render_to_svg_stable_no_data() {
+        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.render_to_svg_stable(1, 42);
         assert!(result.is_err());
-        assert!(result.unwrap_err().to_string().contains("null byte"));
+        assert!(result.unwrap_err().to_string().contains("no data loaded"));
     }
 
+    #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_get_times_for_element_not_found() {
-        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.get_times_for_element("nonexistent-id");
-        let _ = result;
+    fn test_toolkit_render_to_svg_stable() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit
+            .load_data(&multi_measure_mei(40))
+            .expect("Failed to load MEI");
+
+        let first = toolkit
+            .render_to_svg_stable(1, 42)
+            .expect("Failed to render SVG");
+        toolkit
+            .render_to_svg_stable(1, 7)
+            .expect("Failed to render SVG");
+        let second = toolkit
+            .render_to_svg_stable(1, 42)
+            .expect("Failed to render SVG");
+        assert_eq!(first, second);
     }
 
     #[test]
-    fn test_toolkit_get_times_for_element_null_byte() {
+    fn test_toolkit_highlight_measure_number_no_data() {
         let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.get_times_for_element("id\0invalid");
+        let result = toolkit.highlight_measure_number(1, 1, "highlight");
         assert!(result.is_err());
-        assert!(result.unwrap_err().to_string().contains("null byte"));
+        assert!(result.unwrap_err().to_string().contains("no data loaded"));
     }
 
+    #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_get_descriptive_features_no_data() {
-        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.get_descriptive_features(None);
-        let _ = result;
+    fn test_toolkit_highlight_measure_number() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit
+            .load_data(&multi_measure_mei(3))
+            .expect("Failed to load MEI");
+
+        let svg = toolkit
+            .highlight_measure_number(1, 1, "highlight")
+            .expect("Failed to highlight measure");
+        let doc = roxmltree::Document::parse(&svg).expect("SVG should be well-formed");
+        let highlighted: Vec<_> = doc
+            .descendants()
+            .filter(|node| {
+                node.attribute("class")
+                    .is_some_and(|class| class.split_whitespace().any(|c| c == "highlight"))
+            })
+            .collect();
+        assert_eq!(highlighted.len(), 1);
+        assert_eq!(highlighted[0].attribute("class"), Some("measure highlight"));
+
+        let first_measure = toolkit.measure_page_index().expect("Failed to index")[0]
+            .0
+            .clone();
+        assert_eq!(highlighted[0].attribute("id"), Some(first_measure.as_str()));
+
+        assert!(
+            toolkit
+                .highlight_measure_number(1, 99, "highlight")
+                .is_err()
+        );
+        assert!(toolkit.highlight_measure_number(2, 1, "highlight").is_err());
     }
 
     #[test]
-    fn test_toolkit_get_descriptive_features_with_options() {
+    fn test_toolkit_render_accessible_svg_no_data() {
         let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.get_descriptive_features(Some("{}"));
-        let _ = result;
+        assert!(toolkit.render_accessible_svg(1).is_err());
     }
 
+    #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_get_descriptive_features_null_byte() {
-        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.get_descriptive_features(Some("{\0}"));
-        assert!(result.is_err());
-        assert!(result.unwrap_err().to_string().contains("null byte"));
-    }
+    fn test_toolkit_render_accessible_svg() {
+        let mei = multi_measure_mei(200).replace(
+            "<music>",
+            "<meiHead><fileDesc><titleStmt><title>Little Fugue</title></titleStmt><pubStmt/></fileDesc></meiHead><music>",
+        );
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit.load_data(&mei).expect("Failed to load MEI");
 
-    // File Output Functions
-    // Note: Many file output functions assert/crash in Verovio when called without data,
-    // so we only test null byte handling here. Actual file output functionality is tested
-    // in the bundled-data tests below.
+        let svg = toolkit
+            .render_accessible_svg(1)
+            .expect("Failed to render SVG");
+        let doc = roxmltree::Document::parse(&svg).expect("SVG should be well-formed");
+        let root = doc.root_element();
+        assert_eq!(root.attribute("role"), Some("img"));
+
+        let title = root
+            .first_element_child()
+            .expect("Root should have children");
+        assert!(title.has_tag_name("title"));
+        assert!(title.text().unwrap_or_default().contains("Little Fugue"));
+        assert_eq!(title.attribute("id"), Some("page-1-title"));
+        assert_eq!(
+            root.attribute("aria-labelledby"),
+            Some("page-1-title page-1-desc")
+        );
 
-    #[test]
-    fn test_toolkit_render_to_timemap_file_null_byte() {
-        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
-        let path = temp_dir.path().join("test.json");
-        let result = toolkit.render_to_timemap_file(&path, Some("{\0}"));
-        assert!(result.is_err());
-        assert!(result.unwrap_err().to_string().contains("null byte"));
+        let page_2 = toolkit
+            .render_accessible_svg(2)
+            .expect("Failed to render SVG");
+        assert!(page_2.contains(r#"<title id="page-2-title">"#));
     }
 
     #[test]
-    fn test_toolkit_save_file_null_byte() {
+    fn test_toolkit_render_to_svg_optimized_no_data() {
         let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
-        let path = temp_dir.path().join("test.mei");
-        let result = toolkit.save_file(&path, Some("{\0}"));
-        assert!(result.is_err());
-        assert!(result.unwrap_err().to_string().contains("null byte"));
+        assert!(
+            toolkit
+                .render_to_svg_optimized(1, crate::OptimizeLevel::Basic)
+                .is_err()
+        );
     }
 
-    // Tests with bundled data for file output functions
     #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_render_to_svg_file_with_data() {
+    fn test_toolkit_render_to_svg_with_precision() {
+        // A scaled staff draws its glyphs with scale factors such as 0.5976
+        let mei =
+            multi_measure_mei(4).replace(r#"<staffDef n="1""#, r#"<staffDef n="1" scale="83%""#);
         let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit.load_data(&mei).expect("Failed to load MEI");
+
+        let max_decimals = |svg: &str| {
+            let doc = roxmltree::Document::parse(svg).expect("SVG should be well-formed");
+            doc.descendants()
+                .filter_map(|node| node.attribute("transform"))
+                .flat_map(|transform| {
+                    transform
+                        .split(|c: char| !(c.is_ascii_digit() || c == '.'))
+                        .map(|number| number.split_once('.').map_or(0, |(_, f)| f.len()))
+                        .collect::<Vec<_>>()
+                })
+                .max()
+                .unwrap_or(0)
+        };
 
-        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
-<mei xmlns="http://www.music-encoding.org/ns/mei">
-  <music><body><mdiv><score>
-    <scoreDef><staffGrp><staffDef n="1" lines="5" clef.shape="G" clef.line="2"/></staffGrp></scoreDef>
-    <section><measure><staff n="1"><layer n="1"><note pname="c" oct="4" dur="4"/></layer></staff></measure></section>
-  </score></mdiv></body></music>
-</mei>"#;
-
-        toolkit.load_data(mei).expect("Failed to load MEI");
-
-        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
-        let path = temp_dir.path().join("test.svg");
-        let result = toolkit.render_to_svg_file(&path, 1);
-        assert!(result.is_ok());
-        assert!(path.exists());
+        let full = toolkit.render_to_svg(1).expect("Failed to render SVG");
+        assert!(max_decimals(&full) > 2);
+        let two = toolkit
+            .render_to_svg_with_precision(1, crate::OptimizeLevel::None, 2)
+            .expect("Failed to render SVG");
+        let one = toolkit
+            .render_to_svg_with_precision(1, crate::OptimizeLevel::None, 1)
+            .expect("Failed to render SVG");
+        assert!(two.len() < full.len());
+        assert!(one.len() <= two.len());
+        assert!(max_decimals(&two) <= 2);
+
+        let doc = roxmltree::Document::parse(&two).expect("SVG should be well-formed");
+        let original = roxmltree::Document::parse(&full).expect("SVG should be well-formed");
+        assert_eq!(
+            doc.descendants().filter(|node| node.is_element()).count(),
+            original
+                .descendants()
+                .filter(|node| node.is_element())
+                .count()
+        );
     }
 
     #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_render_to_midi_file_with_data() {
+    fn test_toolkit_render_to_svg_optimized() {
         let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit
+            .load_data(&multi_measure_mei(4))
+            .expect("Failed to load MEI");
 
-        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
-<mei xmlns="http://www.music-encoding.org/ns/mei">
-  <music><body><mdiv><score>
-    <scoreDef><staffGrp><staffDef n="1" lines="5" clef.shape="G" clef.line="2"/></staffGrp></scoreDef>
-    <section><measure><staff n="1"><layer n="1"><note pname="c" oct="4" dur="4"/></layer></staff></measure></section>
-  </score></mdiv></body></music>
-</mei>"#;
+        let plain = toolkit.render_to_svg(1).expect("Failed to render SVG");
+        let none = toolkit
+            .render_to_svg_optimized(1, crate::OptimizeLevel::None)
+            .expect("Failed to render SVG");
+        assert_eq!(none, plain);
 
-        toolkit.load_data(mei).expect("Failed to load MEI");
+        let basic = toolkit
+            .render_to_svg_optimized(1, crate::OptimizeLevel::Basic)
+            .expect("Failed to render SVG");
+        let aggressive = toolkit
+            .render_to_svg_optimized(1, crate::OptimizeLevel::Aggressive)
+            .expect("Failed to render SVG");
+        assert!(basic.len() < plain.len());
+        assert!(aggressive.len() <= basic.len());
+
+        let doc = roxmltree::Document::parse(&aggressive).expect("SVG should be well-formed");
+        assert!(doc.root_element().has_tag_name("svg"));
+        let notes = |doc: &roxmltree::Document| {
+            doc.descendants()
+                .filter(|node| node.attribute("class") == Some("note"))
+                .count()
+        };
+        let original = roxmltree::Document::parse(&plain).expect("SVG should be well-formed");
+        assert_eq!(notes(&doc), notes(&original));
+    }
 
-        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
-        let path = temp_dir.path().join("test.mid");
-        let result = toolkit.render_to_midi_file(&path);
-        assert!(result.is_ok());
+    #[test]
+    fn test_page_timemap_filters_entries() {
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg"><g id="n1"/><g id="n2"/></svg>"#;
+        let timemap = r#"[
+            {"tstamp": 0, "qstamp": 0, "on": ["n1"]},
+            {"tstamp": 500, "qstamp": 1, "off": ["n1"], "on": ["n2"]},
+            {"tstamp": 1000, "qstamp": 2, "off": ["n2"], "on": ["n3"]},
+            {"tstamp": 1500, "qstamp": 3, "off": ["n3"]}
+        ]"#;
+
+        let (json, duration_ms) = page_timemap(timemap, svg).unwrap();
+        let entries: Vec<serde_json::Value> = serde_json::from_str(&json).unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[2]["tstamp"], 1000);
+        assert_eq!(duration_ms, 1000.0);
+
+        let (json, duration_ms) = page_timemap("[]", svg).unwrap();
+        assert_eq!(json, "[]");
+        assert_eq!(duration_ms, 0.0);
+    }
+
+    #[test]
+    fn test_toolkit_render_page_bundle_no_data() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        assert!(toolkit.render_page_bundle(1).is_err());
     }
 
     #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_render_to_pae_file_with_data() {
+    fn test_toolkit_render_page_bundle() {
         let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit
+            .load_data(&multi_measure_mei(2))
+            .expect("Failed to load MEI");
 
-        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
-<mei xmlns="http://www.music-encoding.org/ns/mei">
-  <music><body><mdiv><score>
-    <scoreDef><staffGrp><staffDef n="1" lines="5" clef.shape="G" clef.line="2"/></staffGrp></scoreDef>
-    <section><measure><staff n="1"><layer n="1"><note pname="c" oct="4" dur="4"/></layer></staff></measure></section>
-  </score></mdiv></body></music>
-</mei>"#;
-
-        toolkit.load_data(mei).expect("Failed to load MEI");
-
-        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
-        let path = temp_dir.path().join("test.pae");
-        let result = toolkit.render_to_pae_file(&path);
-        // PAE rendering may or may not succeed depending on content
-        let _ = result;
+        let bundle = toolkit.render_page_bundle(1).expect("Failed to render");
+        assert!(bundle.svg.contains("<svg"));
+        let entries: Vec<serde_json::Value> =
+            serde_json::from_str(&bundle.timemap).expect("Timemap should be JSON");
+        assert!(!entries.is_empty());
+        assert!(bundle.duration_ms > 0.0);
     }
 
     #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_render_to_expansion_map_file_with_data() {
+    fn test_toolkit_render_to_svg_prefixed_with_map() {
+        let mei = multi_measure_mei(1).replacen("<note ", r#"<note xml:id="note-c" "#, 1);
         let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit.load_data(&mei).expect("Failed to load MEI");
 
-        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
-<mei xmlns="http://www.music-encoding.org/ns/mei">
-  <music><body><mdiv><score>
-    <scoreDef><staffGrp><staffDef n="1" lines="5" clef.shape="G" clef.line="2"/></staffGrp></scoreDef>
-    <section><measure><staff n="1"><layer n="1"><note pname="c" oct="4" dur="4"/></layer></staff></measure></section>
-  </score></mdiv></body></music>
-</mei>"#;
+        let (svg, ids) = toolkit
+            .render_to_svg_prefixed_with_map(1, "score1-")
+            .expect("Failed to render SVG");
+        assert_eq!(ids.get("note-c").map(String::as_str), Some("score1-note-c"));
+        assert!(svg.contains(r#"id="score1-note-c""#));
+        assert!(ids.values().all(|id| id.starts_with("score1-")));
+    }
 
-        toolkit.load_data(mei).expect("Failed to load MEI");
+    #[test]
+    fn test_toolkit_render_to_svg_prefixed_with_map_no_data() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        assert!(toolkit.render_to_svg_prefixed_with_map(1, "a-").is_err());
+    }
 
-        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
-        let path = temp_dir.path().join("test.json");
-        let result = toolkit.render_to_expansion_map_file(&path);
-        assert!(result.is_ok());
+    #[test]
+    fn test_toolkit_render_page_colored_no_data() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        assert!(toolkit.render_page_colored(1, &[("a", "red")]).is_err());
     }
 
     #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_render_to_timemap_file_with_data() {
+    fn test_toolkit_render_page_colored() {
+        let mei = multi_measure_mei(2).replacen("<note ", r#"<note xml:id="note-red" "#, 1);
         let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit.load_data(&mei).expect("Failed to load MEI");
 
-        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
-<mei xmlns="http://www.music-encoding.org/ns/mei">
-  <music><body><mdiv><score>
-    <scoreDef><staffGrp><staffDef n="1" lines="5" clef.shape="G" clef.line="2"/></staffGrp></scoreDef>
-    <section><measure><staff n="1"><layer n="1"><note pname="c" oct="4" dur="4"/></layer></staff></measure></section>
-  </score></mdiv></body></music>
-</mei>"#;
+        let svg = toolkit
+            .render_page_colored(1, &[("note-red", "red")])
+            .expect("Failed to render SVG");
+        let doc = roxmltree::Document::parse(&svg).expect("SVG should be well-formed");
 
-        toolkit.load_data(mei).expect("Failed to load MEI");
+        let note = doc
+            .descendants()
+            .find(|node| node.attribute("id") == Some("note-red"))
+            .expect("Colored note should be in the SVG");
+        assert_eq!(note.attribute("fill"), Some("red"));
+        assert_eq!(note.attribute("stroke"), Some("red"));
 
-        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
-        let path = temp_dir.path().join("test.json");
-        let result = toolkit.render_to_timemap_file(&path, None);
-        assert!(result.is_ok());
+        let other_fills = doc
+            .descendants()
+            .filter(|node| node.attribute("fill") == Some("red"))
+            .count();
+        assert_eq!(other_fills, 1);
+    }
+
+    #[test]
+    fn test_toolkit_render_to_svg_prefixed_no_data() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        assert!(toolkit.render_to_svg_prefixed(1, "a-").is_err());
     }
 
     #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_save_file_with_data() {
+    fn test_toolkit_render_to_svg_prefixed() {
         let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit
+            .load_data(&multi_measure_mei(4))
+            .expect("Failed to load MEI");
 
-        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
-<mei xmlns="http://www.music-encoding.org/ns/mei">
-  <music><body><mdiv><score>
-    <scoreDef><staffGrp><staffDef n="1" lines="5" clef.shape="G" clef.line="2"/></staffGrp></scoreDef>
-    <section><measure><staff n="1"><layer n="1"><note pname="c" oct="4" dur="4"/></layer></staff></measure></section>
-  </score></mdiv></body></music>
-</mei>"#;
+        let svg = toolkit
+            .render_to_svg_prefixed(1, "score1-")
+            .expect("Failed to render SVG");
+        let doc = roxmltree::Document::parse(&svg).expect("SVG should be well-formed");
+
+        let ids: std::collections::HashSet<&str> = doc
+            .descendants()
+            .filter_map(|node| node.attribute("id"))
+            .collect();
+        assert!(!ids.is_empty());
+        assert!(ids.iter().all(|id| id.starts_with("score1-")));
+
+        // Every internal reference still points at an element in the document
+        let references: Vec<&str> = doc
+            .descendants()
+            .flat_map(|node| node.attributes())
+            .filter(|attr| attr.name() == "href")
+            .filter_map(|attr| attr.value().strip_prefix('#'))
+            .collect();
+        assert!(!references.is_empty());
+        for reference in references {
+            assert!(ids.contains(reference), "dangling reference #{}", reference);
+        }
+    }
 
-        toolkit.load_data(mei).expect("Failed to load MEI");
+    // =========================================================================
+    // PDF Tests
+    // =========================================================================
 
-        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
-        let path = temp_dir.path().join("test.mei");
-        let result = toolkit.save_file(&path, None);
-        assert!(result.is_ok());
-        assert!(path.exists());
+    #[cfg(feature = "pdf")]
+    #[test]
+    fn test_toolkit_render_to_pdf_no_data() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.render_to_pdf();
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("no data loaded"));
     }
 
-    #[cfg(feature = "bundled-data")]
+    #[cfg(all(feature = "bundled-data", feature = "pdf"))]
     #[test]
-    fn test_toolkit_save_humdrum_to_file_with_data() {
+    fn test_toolkit_render_to_pdf() {
         let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit
+            .load_data(&multi_measure_mei(200))
+            .expect("Failed to load MEI");
 
-        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
-<mei xmlns="http://www.music-encoding.org/ns/mei">
-  <music><body><mdiv><score>
-    <scoreDef><staffGrp><staffDef n="1" lines="5" clef.shape="G" clef.line="2"/></staffGrp></scoreDef>
-    <section><measure><staff n="1"><layer n="1"><note pname="c" oct="4" dur="4"/></layer></staff></measure></section>
-  </score></mdiv></body></music>
-</mei>"#;
+        let pdf = toolkit.render_to_pdf().expect("Failed to render PDF");
+        assert!(pdf.starts_with(b"%PDF"));
+        let text = String::from_utf8_lossy(&pdf);
+        assert!(text.contains(&format!("/Count {}", toolkit.page_count())));
+    }
 
-        toolkit.load_data(mei).expect("Failed to load MEI");
+    // =========================================================================
+    // Generation and Export Cache Tests
+    // =========================================================================
 
-        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
-        let path = temp_dir.path().join("test.krn");
-        let result = toolkit.save_humdrum_to_file(&path);
-        // Humdrum export may or may not succeed depending on content
-        let _ = result;
+    #[test]
+    fn test_toolkit_generation_changes_on_mutation() {
+        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let start = toolkit.generation();
+
+        toolkit.reset_options();
+        let after_reset = toolkit.generation();
+        assert_ne!(start, after_reset);
+
+        // Read-only calls leave the generation alone
+        let _ = toolkit.get_options();
+        let _ = toolkit.page_count();
+        assert_eq!(after_reset, toolkit.generation());
+
+        let _ = toolkit.load_data("not music");
+        assert_ne!(after_reset, toolkit.generation());
     }
 
     #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_select_with_data() {
+    fn test_toolkit_get_humdrum_cached() {
         let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit
+            .load_data(&multi_measure_mei(2))
+            .expect("Failed to load MEI");
+
+        let first = toolkit.get_humdrum().expect("Failed to export Humdrum");
+        let second = toolkit.get_humdrum().expect("Failed to export Humdrum");
+        assert_eq!(first, second);
+        assert_eq!(
+            toolkit.export_cache.borrow().humdrum.as_deref(),
+            Some(first.as_str())
+        );
 
-        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
-<mei xmlns="http://www.music-encoding.org/ns/mei">
-  <music><body><mdiv><score>
-    <scoreDef><staffGrp><staffDef n="1" lines="5" clef.shape="G" clef.line="2"/></staffGrp></scoreDef>
-    <section><measure><staff n="1"><layer n="1"><note pname="c" oct="4" dur="4"/></layer></staff></measure></section>
-  </score></mdiv></body></music>
-</mei>"#;
-
-        toolkit.load_data(mei).expect("Failed to load MEI");
-        let result = toolkit.select(r#"{"measureRange": "1"}"#);
-        let _ = result;
+        // Reloading invalidates the cache
+        toolkit
+            .load_data(&multi_measure_mei(4))
+            .expect("Failed to load MEI");
+        assert!(toolkit.export_cache.borrow().generation != toolkit.generation());
+        let reloaded = toolkit.get_humdrum().expect("Failed to export Humdrum");
+        assert_ne!(first, reloaded);
     }
 
     #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_get_descriptive_features_with_data() {
+    fn test_toolkit_get_mei_cached() {
         let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit
+            .load_data(&multi_measure_mei(2))
+            .expect("Failed to load MEI");
 
-        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
-<mei xmlns="http://www.music-encoding.org/ns/mei">
-  <music><body><mdiv><score>
-    <scoreDef><staffGrp><staffDef n="1" lines="5" clef.shape="G" clef.line="2"/></staffGrp></scoreDef>
-    <section><measure><staff n="1"><layer n="1"><note pname="c" oct="4" dur="4"/></layer></staff></measure></section>
-  </score></mdiv></body></music>
-</mei>"#;
+        let first = toolkit.get_mei().expect("Failed to export MEI");
+        assert_eq!(first, toolkit.get_mei().expect("Failed to export MEI"));
+        assert!(toolkit.export_cache.borrow().mei.is_some());
 
-        toolkit.load_data(mei).expect("Failed to load MEI");
-        let result = toolkit.get_descriptive_features(None);
-        assert!(result.is_ok());
+        toolkit
+            .set_options(&Options::builder().remove_ids(true).build())
+            .expect("Failed to set options");
+        assert!(toolkit.export_cache.borrow().generation != toolkit.generation());
+        let _ = toolkit.get_mei().expect("Failed to export MEI");
+        assert_eq!(
+            toolkit.export_cache.borrow().generation,
+            toolkit.generation()
+        );
     }
 
     #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_render_data_with_mei() {
+    fn test_toolkit_get_mei_output_formatting() {
         let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit
+            .load_data(&multi_measure_mei(2))
+            .expect("Failed to load MEI");
 
-        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
+        toolkit
+            .set_options(&Options::builder().output_indent(4).build())
+            .expect("Failed to set options");
+        let indented = toolkit.get_mei().expect("Failed to export MEI");
+        assert!(indented.lines().count() > 10);
+        assert!(indented.contains("\n    <"));
+
+        toolkit
+            .set_options(&Options::builder().output_format_raw(true).build())
+            .expect("Failed to set options");
+        let compact = toolkit.get_mei().expect("Failed to export MEI");
+        assert!(compact.trim().lines().count() <= 2);
+        assert!(compact.len() < indented.len());
+    }
+
+    // =========================================================================
+    // Expansion Tests
+    // =========================================================================
+
+    #[cfg(feature = "bundled-data")]
+    const EXPANSION_MEI: &str = r##"<?xml version="1.0" encoding="UTF-8"?>
 <mei xmlns="http://www.music-encoding.org/ns/mei">
-  <music><body><mdiv><score>
-    <scoreDef><staffGrp><staffDef n="1" lines="5" clef.shape="G" clef.line="2"/></staffGrp></scoreDef>
-    <section><measure><staff n="1"><layer n="1"><note pname="c" oct="4" dur="4"/></layer></staff></measure></section>
-  </score></mdiv></body></music>
-</mei>"#;
+  <music>
+    <body>
+      <mdiv>
+        <score>
+          <scoreDef meter.count="4" meter.unit="4">
+            <staffGrp>
+              <staffDef n="1" lines="5" clef.shape="G" clef.line="2"/>
+            </staffGrp>
+          </scoreDef>
+          <section>
+            <expansion xml:id="expansion-repeat" plist="#section-a #section-a #section-b"/>
+            <section xml:id="section-a">
+              <measure n="1">
+                <staff n="1"><layer n="1"><note pname="c" oct="4" dur="1"/></layer></staff>
+              </measure>
+              <measure n="2">
+                <staff n="1"><layer n="1"><note pname="d" oct="4" dur="1"/></layer></staff>
+              </measure>
+            </section>
+            <section xml:id="section-b">
+              <measure n="3">
+                <staff n="1"><layer n="1"><note pname="e" oct="4" dur="1"/></layer></staff>
+              </measure>
+            </section>
+          </section>
+        </score>
+      </mdiv>
+    </body>
+  </music>
+</mei>"##;
 
-        let result = toolkit.render_data(mei, None);
-        // render_data should process the data
-        let _ = result;
+    #[test]
+    fn test_toolkit_render_expansion_no_data() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.render_expansion("expansion-repeat");
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("no data loaded"));
     }
 
     #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_render_data_with_mei_and_options() {
+    fn test_toolkit_render_expansion_unknown_id() {
         let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit
+            .load_data(EXPANSION_MEI)
+            .expect("Failed to load MEI");
 
-        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
-<mei xmlns="http://www.music-encoding.org/ns/mei">
-  <music><body><mdiv><score>
-    <scoreDef><staffGrp><staffDef n="1" lines="5" clef.shape="G" clef.line="2"/></staffGrp></scoreDef>
-    <section><measure><staff n="1"><layer n="1"><note pname="c" oct="4" dur="4"/></layer></staff></measure></section>
-  </score></mdiv></body></music>
-</mei>"#;
-
-        let result = toolkit.render_data(mei, Some(r#"{"scale": 50}"#));
-        let _ = result;
+        let result = toolkit.render_expansion("no-such-expansion");
+        assert!(result.is_err());
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("expansion not found")
+        );
     }
 
     #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_get_descriptive_features_with_custom_options() {
+    fn test_toolkit_render_expansion() {
         let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit
+            .load_data(EXPANSION_MEI)
+            .expect("Failed to load MEI");
+
+        let count_measures = |pages: &[String]| {
+            pages
+                .iter()
+                .map(|svg| svg.matches(r#"class="measure""#).count())
+                .sum::<usize>()
+        };
 
-        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
-<mei xmlns="http://www.music-encoding.org/ns/mei">
-  <music><body><mdiv><score>
-    <scoreDef><staffGrp><staffDef n="1" lines="5" clef.shape="G" clef.line="2"/></staffGrp></scoreDef>
-    <section><measure><staff n="1"><layer n="1"><note pname="c" oct="4" dur="4"/></layer></staff></measure></section>
-  </score></mdiv></body></music>
-</mei>"#;
+        let default_pages = toolkit.render_all_pages().expect("Failed to render");
+        let generation = toolkit.generation();
+        let expanded_pages = toolkit
+            .render_expansion("expansion-repeat")
+            .expect("Failed to render expansion");
 
-        toolkit.load_data(mei).expect("Failed to load MEI");
-        let result = toolkit.get_descriptive_features(Some(r#"{"includeMeasures": true}"#));
-        let _ = result;
+        assert!(expanded_pages.len() >= default_pages.len());
+        assert!(count_measures(&expanded_pages) > count_measures(&default_pages));
+
+        // The loaded document is left untouched
+        assert_eq!(toolkit.generation(), generation);
+        assert_eq!(
+            toolkit.render_all_pages().expect("Failed to render"),
+            default_pages
+        );
+    }
+
+    #[test]
+    fn test_toolkit_notated_id_null_byte() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        assert!(toolkit.notated_id("note\0id").is_err());
     }
 
     #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_render_to_timemap_with_options_loaded_data() {
+    fn test_toolkit_notated_id() {
+        let mei = EXPANSION_MEI.replace(
+            r#"<note pname="c" oct="4" dur="1"/>"#,
+            r#"<note xml:id="note-c" pname="c" oct="4" dur="1"/>"#,
+        );
         let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit
+            .set_options_json(r#"{"expand": "expansion-repeat"}"#)
+            .expect("Failed to set options");
+        toolkit.load_data(&mei).expect("Failed to load MEI");
+
+        let pages = toolkit.render_all_pages().expect("Failed to render");
+        let note_ids: Vec<String> = pages
+            .iter()
+            .flat_map(|svg| {
+                let doc = roxmltree::Document::parse(svg).expect("SVG should be well-formed");
+                doc.descendants()
+                    .filter(|node| node.attribute("class") == Some("note"))
+                    .filter_map(|node| node.attribute("id").map(String::from))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
+        // Section A is repeated, so the C is also drawn as a copy with its own id
+        let copies: Vec<&String> = note_ids
+            .iter()
+            .filter(|id| {
+                toolkit.notated_id(id).expect("Failed to query notated ID")
+                    == Some("note-c".to_string())
+            })
+            .collect();
+        assert!(!copies.is_empty());
+        assert!(copies.iter().all(|id| *id != "note-c"));
+
+        assert_eq!(toolkit.notated_id("note-c").expect("Failed to query"), None);
+        assert_eq!(
+            toolkit.notated_id("no-such-id").expect("Failed to query"),
+            None
+        );
+    }
 
-        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
+    // =========================================================================
+    // Render Staves Tests
+    // =========================================================================
+
+    #[cfg(feature = "bundled-data")]
+    const TWO_STAFF_MEI: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
 <mei xmlns="http://www.music-encoding.org/ns/mei">
   <music><body><mdiv><score>
-    <scoreDef><staffGrp><staffDef n="1" lines="5" clef.shape="G" clef.line="2"/></staffGrp></scoreDef>
-    <section><measure><staff n="1"><layer n="1"><note pname="c" oct="4" dur="4"/></layer></staff></measure></section>
+    <scoreDef><staffGrp>
+      <staffDef n="1" lines="5" clef.shape="G" clef.line="2"/>
+      <staffDef n="2" lines="5" clef.shape="F" clef.line="4"/>
+    </staffGrp></scoreDef>
+    <section><measure>
+      <staff n="1"><layer n="1"><note pname="c" oct="5" dur="1"/></layer></staff>
+      <staff n="2"><layer n="1"><note pname="c" oct="3" dur="1"/></layer></staff>
+    </measure></section>
   </score></mdiv></body></music>
 </mei>"#;
 
-        toolkit.load_data(mei).expect("Failed to load MEI");
-        let result = toolkit.render_to_timemap_with_options(r#"{"includeMeasures": true}"#);
-        assert!(result.is_ok());
+    #[test]
+    fn test_toolkit_render_staves_no_data() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let result = toolkit.render_staves(&[1], 1);
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("no data loaded"));
     }
 
     #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_convert_humdrum_to_humdrum_with_data() {
-        let toolkit = Toolkit::new().expect("Failed to create toolkit");
-
-        let humdrum = r#"**kern
-*clefG2
-*k[]
-*M4/4
-4c
-4d
-4e
-4f
-*-"#;
+    fn test_toolkit_render_staves_unknown_staff() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit
+            .load_data(TWO_STAFF_MEI)
+            .expect("Failed to load MEI");
 
-        let result = toolkit.convert_humdrum_to_humdrum(humdrum);
-        // May succeed or fail depending on Verovio's Humdrum support
-        let _ = result;
+        let result = toolkit.render_staves(&[3], 1);
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("staff not found"));
     }
 
     #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_convert_humdrum_to_midi_with_data() {
-        let toolkit = Toolkit::new().expect("Failed to create toolkit");
+    fn test_toolkit_render_staves_single_staff() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit
+            .load_data(TWO_STAFF_MEI)
+            .expect("Failed to load MEI");
 
-        let humdrum = r#"**kern
-*clefG2
-*k[]
-*M4/4
-4c
-4d
-4e
-4f
-*-"#;
+        let full = toolkit.render_to_svg(1).expect("Failed to render");
+        let svg = toolkit
+            .render_staves(&[1], 1)
+            .expect("Failed to render staves");
 
-        let result = toolkit.convert_humdrum_to_midi(humdrum);
-        let _ = result;
-    }
+        assert!(svg.contains("<svg"));
+        assert!(svg.contains("</svg>"));
+        assert_eq!(full.matches(r#"class="staff""#).count(), 2);
+        assert_eq!(svg.matches(r#"class="staff""#).count(), 1);
 
-    #[cfg(feature = "bundled-data")]
-    #[test]
-    fn test_toolkit_convert_mei_to_humdrum_with_data() {
-        let toolkit = Toolkit::new().expect("Failed to create toolkit");
+        // The toolkit's own document is untouched
+        assert_eq!(toolkit.render_to_svg(1).expect("Failed to render"), full);
+    }
 
-        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
-<mei xmlns="http://www.music-encoding.org/ns/mei">
-  <music><body><mdiv><score>
-    <scoreDef><staffGrp><staffDef n="1" lines="5" clef.shape="G" clef.line="2"/></staffGrp></scoreDef>
-    <section><measure><staff n="1"><layer n="1"><note pname="c" oct="4" dur="4"/></layer></staff></measure></section>
-  </score></mdiv></body></music>
-</mei>"#;
+    // =========================================================================
+    // Diagnostics Tests
+    // =========================================================================
 
-        let result = toolkit.convert_mei_to_humdrum(mei);
-        let _ = result;
+    #[test]
+    fn test_toolkit_diagnostics_without_resources() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let diagnostics = toolkit.diagnostics();
+        assert!(!diagnostics.verovio_version.is_empty());
+        assert_eq!(diagnostics.page_count, 0);
+        assert!(!diagnostics.is_loaded);
+        assert!(diagnostics.missing_resources.is_empty());
     }
 
     #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_set_input_output_format_with_data() {
+    fn test_toolkit_diagnostics_bundled() {
         let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        let diagnostics = toolkit.diagnostics();
+
+        let bundled = toolkit._temp_dir.as_ref().unwrap().path();
+        let reported = diagnostics
+            .resource_path
+            .expect("Resource path should be set");
+        assert_eq!(
+            reported.canonicalize().unwrap(),
+            bundled.canonicalize().unwrap()
+        );
+        assert!(diagnostics.available_fonts.contains(&"Bravura".to_string()));
+        assert!(diagnostics.missing_resources.is_empty());
+        assert!(toolkit.verify_resources().is_empty());
 
-        // Set input format before loading
-        let _ = toolkit.set_input_from("mei");
-        let _ = toolkit.set_output_to("svg");
-
-        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
-<mei xmlns="http://www.music-encoding.org/ns/mei">
-  <music><body><mdiv><score>
-    <scoreDef><staffGrp><staffDef n="1" lines="5" clef.shape="G" clef.line="2"/></staffGrp></scoreDef>
-    <section><measure><staff n="1"><layer n="1"><note pname="c" oct="4" dur="4"/></layer></staff></measure></section>
-  </score></mdiv></body></music>
-</mei>"#;
-
-        toolkit.load_data(mei).expect("Failed to load MEI");
-        assert_eq!(toolkit.page_count(), 1);
+        toolkit
+            .load_data(&multi_measure_mei(2))
+            .expect("Failed to load MEI");
+        let diagnostics = toolkit.diagnostics();
+        assert!(diagnostics.is_loaded);
+        assert_eq!(diagnostics.page_count, toolkit.page_count());
+        assert!(serde_json::to_string(&diagnostics).is_ok());
     }
 
-    #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_redo_page_pitch_pos_layout_with_data() {
-        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    fn test_toolkit_missing_glyphs_no_data() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        assert!(toolkit.missing_glyphs().is_err());
+    }
 
-        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
-<mei xmlns="http://www.music-encoding.org/ns/mei">
-  <music><body><mdiv><score>
-    <scoreDef><staffGrp><staffDef n="1" lines="5" clef.shape="G" clef.line="2"/></staffGrp></scoreDef>
-    <section><measure><staff n="1"><layer n="1"><note pname="c" oct="4" dur="4"/></layer></staff></measure></section>
-  </score></mdiv></body></music>
-</mei>"#;
+    /// Copies the bundled resources into a temp dir and adds a "Mini" font: a
+    /// copy of Leipzig without the black notehead (E0A4).
+    #[cfg(feature = "bundled-data")]
+    fn resources_with_mini_font(bundled: &Path) -> tempfile::TempDir {
+        fn copy_dir(from: &Path, to: &Path) {
+            std::fs::create_dir_all(to).unwrap();
+            for entry in std::fs::read_dir(from).unwrap() {
+                let path = entry.unwrap().path();
+                let target = to.join(path.file_name().unwrap());
+                if path.is_dir() {
+                    copy_dir(&path, &target);
+                } else {
+                    std::fs::copy(&path, &target).unwrap();
+                }
+            }
+        }
 
-        toolkit.load_data(mei).expect("Failed to load MEI");
-        toolkit.redo_page_pitch_pos_layout();
-        // Should complete without panic
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let dir = temp_dir.path();
+        copy_dir(bundled, dir);
+
+        let leipzig = std::fs::read_to_string(dir.join("Leipzig.xml")).unwrap();
+        let doc = roxmltree::Document::parse(&leipzig).unwrap();
+        let notehead = doc
+            .descendants()
+            .find(|node| node.has_tag_name("g") && node.attribute("c") == Some("E0A4"))
+            .expect("Leipzig should define E0A4");
+        let mini = format!(
+            "{}{}",
+            &leipzig[..notehead.range().start],
+            &leipzig[notehead.range().end..]
+        )
+        .replace(r#"font-family="Leipzig""#, r#"font-family="Mini""#);
+        std::fs::write(dir.join("Mini.xml"), mini).unwrap();
+        std::fs::copy(dir.join("Leipzig.css"), dir.join("Mini.css")).unwrap();
+
+        copy_dir(&dir.join("Leipzig"), &dir.join("Mini"));
+        std::fs::remove_file(dir.join("Mini").join("E0A4.xml")).unwrap();
+        temp_dir
     }
 
     #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_reset_xml_id_seed_with_data() {
-        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    fn test_toolkit_missing_glyphs_reports_missing_notehead() {
+        let bundled = Toolkit::new().expect("Failed to create toolkit");
+        let resources = resources_with_mini_font(Path::new(&bundled.get_resource_path()));
 
-        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
-<mei xmlns="http://www.music-encoding.org/ns/mei">
-  <music><body><mdiv><score>
-    <scoreDef><staffGrp><staffDef n="1" lines="5" clef.shape="G" clef.line="2"/></staffGrp></scoreDef>
-    <section><measure><staff n="1"><layer n="1"><note pname="c" oct="4" dur="4"/></layer></staff></measure></section>
-  </score></mdiv></body></music>
-</mei>"#;
+        let mut toolkit =
+            Toolkit::with_resource_path(resources.path()).expect("Failed to create toolkit");
+        toolkit
+            .set_options(&Options::builder().font("Mini").build())
+            .expect("Failed to set options");
+        toolkit
+            .load_data(&multi_measure_mei(2))
+            .expect("Failed to load MEI");
 
-        toolkit.load_data(mei).expect("Failed to load MEI");
-        toolkit.reset_xml_id_seed(12345);
-        // Should complete without panic
+        let missing = toolkit.missing_glyphs().expect("Failed to check glyphs");
+        assert_eq!(missing, vec!["noteheadBlack"]);
+        assert_eq!(toolkit.active_font().unwrap(), "Mini");
     }
 
     #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_render_to_timemap_file_with_options() {
+    fn test_toolkit_missing_glyphs_complete_font() {
         let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit
+            .load_data(&multi_measure_mei(2))
+            .expect("Failed to load MEI");
+        assert!(toolkit.missing_glyphs().unwrap().is_empty());
+    }
 
-        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
-<mei xmlns="http://www.music-encoding.org/ns/mei">
-  <music><body><mdiv><score>
-    <scoreDef><staffGrp><staffDef n="1" lines="5" clef.shape="G" clef.line="2"/></staffGrp></scoreDef>
-    <section><measure><staff n="1"><layer n="1"><note pname="c" oct="4" dur="4"/></layer></staff></measure></section>
-  </score></mdiv></body></music>
-</mei>"#;
-
-        toolkit.load_data(mei).expect("Failed to load MEI");
-
-        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
-        let path = temp_dir.path().join("test.json");
+    // =========================================================================
+    // Cursor Overlay Tests
+    // =========================================================================
 
-        let result = toolkit.render_to_timemap_file(&path, Some(r#"{"includeMeasures": true}"#));
-        assert!(result.is_ok());
+    #[test]
+    fn test_toolkit_render_to_svg_json_no_data() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        assert!(toolkit.render_to_svg_json(1).is_err());
     }
 
     #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_save_file_with_options() {
+    fn test_toolkit_render_to_svg_json() {
         let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit
+            .load_data(&multi_measure_mei(2))
+            .expect("Failed to load MEI");
 
-        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
-<mei xmlns="http://www.music-encoding.org/ns/mei">
-  <music><body><mdiv><score>
-    <scoreDef><staffGrp><staffDef n="1" lines="5" clef.shape="G" clef.line="2"/></staffGrp></scoreDef>
-    <section><measure><staff n="1"><layer n="1"><note pname="c" oct="4" dur="4"/></layer></staff></measure></section>
-  </score></mdiv></body></music>
-</mei>"#;
-
-        toolkit.load_data(mei).expect("Failed to load MEI");
-
-        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
-        let path = temp_dir.path().join("test.mei");
+        let json = toolkit.render_to_svg_json(1).expect("Failed to render SVG");
+        let value: serde_json::Value = serde_json::from_str(&json).expect("Result should be JSON");
+        assert_eq!(value["page"], 1);
+        let svg = value["svg"].as_str().expect("svg should be a string");
+        assert!(svg.contains("<svg"));
+        assert!(roxmltree::Document::parse(svg).is_ok());
 
-        let result = toolkit.save_file(&path, Some(r#"{"pageWidth": 2100}"#));
-        assert!(result.is_ok());
+        let view_box = toolkit.page_view_box(1).expect("Failed to get viewBox");
+        assert_eq!(value["view_box"]["width"], view_box.width);
+        assert_eq!(value["view_box"]["height"], view_box.height);
     }
 
-    #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_validate_pae_with_valid_code() {
-        let toolkit = Toolkit::new().expect("Failed to create toolkit");
-        // Valid PAE code
-        let result = toolkit.validate_pae("@clef:G-2\n@keysig:bBE\n@timesig:4/4\n'4C/8DE");
-        // Should return validation result
-        let _ = result;
+    fn test_toolkit_diff_render_no_data() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        assert!(toolkit.diff_render(1, "<svg/>").is_err());
     }
 
     #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_toolkit_element_queries_with_loaded_data() {
+    fn test_toolkit_diff_render() {
+        let mei = multi_measure_mei(2).replacen(
+            r#"<note pname="e""#,
+            r#"<note xml:id="note-e" pname="e""#,
+            1,
+        );
         let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit.load_data(&mei).expect("Failed to load MEI");
+        let baseline = toolkit.render_to_svg(1).expect("Failed to render SVG");
+
+        let differences = toolkit
+            .diff_render(1, &baseline)
+            .expect("Failed to diff render");
+        assert!(differences.is_empty(), "{:?}", differences);
+
+        toolkit.transpose("M3").expect("Failed to transpose");
+        let differences = toolkit
+            .diff_render(1, &baseline)
+            .expect("Failed to diff render");
+        assert!(!differences.is_empty());
+        assert!(
+            differences
+                .iter()
+                .any(|difference| difference.contains("#note-e")),
+            "{:?}",
+            differences
+        );
+    }
 
+    #[cfg(feature = "bundled-data")]
+    #[test]
+    fn test_toolkit_render_to_svg_empty_document() {
         let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
 <mei xmlns="http://www.music-encoding.org/ns/mei">
   <music><body><mdiv><score>
     <scoreDef><staffGrp><staffDef n="1" lines="5" clef.shape="G" clef.line="2"/></staffGrp></scoreDef>
-    <section><measure xml:id="m1"><staff n="1"><layer n="1"><note xml:id="n1" pname="c" oct="4" dur="4"/></layer></staff></measure></section>
+    <section/>
   </score></mdiv></body></music>
 </mei>"#;
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        let err = toolkit.render_to_svg(1).unwrap_err();
+        assert!(err.to_string().contains("out of range"), "{}", err);
 
         toolkit.load_data(mei).expect("Failed to load MEI");
-
-        // These queries return JSON even if element not found
-        let _ = toolkit.get_expansion_ids_for_element("n1");
-        let _ = toolkit.get_midi_values_for_element("n1");
-        let _ = toolkit.get_notated_id_for_element("n1");
-        let _ = toolkit.get_times_for_element("n1");
+        assert_eq!(toolkit.page_count(), 0);
+        let err = toolkit.render_to_svg(1).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "failed to render: document loaded but produced no pages; check for empty content"
+        );
+        let no_pages = |err: Error| err.to_string().contains("produced no pages");
+        assert!(no_pages(toolkit.document_anchors().unwrap_err()));
+        assert!(no_pages(toolkit.measure_at_time(0).unwrap_err()));
+        assert!(no_pages(toolkit.render_time_window(0, 1000).unwrap_err()));
+        assert!(no_pages(toolkit.render_to_svg_annotated(1).unwrap_err()));
+        assert!(no_pages(toolkit.ensure_ids().unwrap_err()));
+        #[cfg(feature = "png")]
+        assert!(no_pages(
+            toolkit
+                .layout_thumbnails(&[Options::default()], 100)
+                .unwrap_err()
+        ));
+
+        toolkit.reset().expect("Failed to reset toolkit");
+        let err = toolkit.render_to_svg(1).unwrap_err();
+        assert!(err.to_string().contains("out of range"), "{}", err);
+    }
+
+    #[test]
+    fn test_toolkit_system_bounds_no_data() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        assert!(toolkit.system_bounds(1).is_err());
     }
 
-    // =========================================================================
-    // LoadSource Trait and Unified Load Method Tests
-    // =========================================================================
-
+    #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_zip_base64_marker_debug() {
-        let marker = ZipBase64("test");
-        let debug = format!("{:?}", marker);
-        assert!(debug.contains("ZipBase64"));
-        assert!(debug.contains("test"));
+    fn test_toolkit_system_bounds() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit
+            .load_data(&multi_measure_mei(24))
+            .expect("Failed to load MEI");
+
+        let svg = toolkit.render_to_svg(1).expect("Failed to render SVG");
+        let systems = svg.matches(r#"class="system""#).count();
+        assert!(systems > 1, "expected several systems, got {}", systems);
+
+        let bounds = toolkit
+            .system_bounds(1)
+            .expect("Failed to get system bounds");
+        assert_eq!(bounds.len(), systems);
+        for pair in bounds.windows(2) {
+            assert!(pair[0].min_y < pair[1].min_y);
+        }
+        assert!(bounds.iter().all(|b| b.width > 0.0 && b.height > 0.0));
     }
 
     #[test]
-    fn test_zip_base64_marker_clone() {
-        let marker = ZipBase64("test");
-        let cloned = marker;
-        assert_eq!(cloned.0, "test");
+    fn test_toolkit_ensure_ids_no_data() {
+        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        assert!(toolkit.ensure_ids().is_err());
     }
 
     #[test]
-    fn test_zip_buffer_marker_debug() {
-        let data = b"test";
-        let marker = ZipBuffer(data);
-        let debug = format!("{:?}", marker);
-        assert!(debug.contains("ZipBuffer"));
+    fn test_toolkit_compare_golden_no_data() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        assert!(
+            toolkit
+                .compare_golden(1, Path::new("missing-golden.svg"))
+                .is_err()
+        );
     }
 
+    #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_zip_buffer_marker_clone() {
-        let data = b"test";
-        let marker = ZipBuffer(data);
-        let cloned = marker;
-        assert_eq!(cloned.0, data);
+    fn test_toolkit_compare_golden() {
+        let mei = multi_measure_mei(2).replacen(
+            r#"<note pname="e""#,
+            r#"<note xml:id="note-e" pname="e""#,
+            1,
+        );
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit.load_data(&mei).expect("Failed to load MEI");
+
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let golden = temp_dir.path().join("page-1.svg");
+        let svg = toolkit.render_to_svg(1).expect("Failed to render SVG");
+        std::fs::write(&golden, &svg).expect("Failed to write golden file");
+
+        let result = toolkit
+            .compare_golden(1, &golden)
+            .expect("Failed to compare golden file");
+        assert_eq!(
+            result,
+            crate::GoldenResult {
+                passed: true,
+                differences: Vec::new(),
+                updated: false,
+            }
+        );
+
+        toolkit.transpose("M3").expect("Failed to transpose");
+        let result = toolkit
+            .compare_golden(1, &golden)
+            .expect("Failed to compare golden file");
+        assert!(!result.passed);
+        assert!(!result.updated);
+        assert!(
+            result
+                .differences
+                .iter()
+                .any(|difference| difference.contains("#note-e")),
+            "{:?}",
+            result.differences
+        );
+        assert_eq!(
+            std::fs::read_to_string(&golden).expect("Failed to read golden file"),
+            svg
+        );
     }
 
+    #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_load_source_str_empty() {
-        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.load("");
-        assert!(result.is_err());
+    fn test_toolkit_compare_golden_missing_file() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit
+            .load_data(&multi_measure_mei(1))
+            .expect("Failed to load MEI");
+
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let golden = temp_dir.path().join("missing.svg");
+        let err = toolkit.compare_golden(1, &golden).unwrap_err();
+        assert!(matches!(err, Error::FileNotFound(path) if path == golden));
     }
 
     #[test]
-    fn test_load_source_path_not_found() {
+    fn test_toolkit_set_midi_tempo_factor_out_of_range() {
         let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.load(Path::new("/nonexistent/path/to/file.mei"));
-        assert!(result.is_err());
-        let err = result.unwrap_err();
-        assert!(err.to_string().contains("file not found"));
+        for factor in [0.0, 0.19, 4.01, f64::NAN] {
+            let err = toolkit.set_midi_tempo_factor(factor).unwrap_err();
+            assert!(matches!(err, Error::OptionsError(_)));
+            assert!(err.to_string().contains("between 0.2 and 4.0"));
+        }
+        toolkit
+            .set_midi_tempo_factor(0.2)
+            .expect("Failed to set tempo");
+        toolkit
+            .set_midi_tempo_factor(4.0)
+            .expect("Failed to set tempo");
     }
 
+    #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_load_source_pathbuf_not_found() {
-        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let path = std::path::PathBuf::from("/nonexistent/path/to/file.mei");
-        let result = toolkit.load(&path);
-        assert!(result.is_err());
+    fn test_toolkit_set_midi_tempo_factor_changes_midi() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit
+            .load_data(&multi_measure_mei(2))
+            .expect("Failed to load MEI");
+        let pages = toolkit.page_count();
+
+        toolkit
+            .set_midi_tempo_factor(0.5)
+            .expect("Failed to set tempo");
+        let slow = toolkit.render_to_midi().expect("Failed to render MIDI");
+        toolkit
+            .set_midi_tempo_factor(1.5)
+            .expect("Failed to set tempo");
+        let fast = toolkit.render_to_midi().expect("Failed to render MIDI");
+
+        assert_ne!(slow, fast);
+        assert_eq!(toolkit.page_count(), pages);
     }
 
-    // NOTE: Tests for invalid ZIP data are intentionally omitted because
-    // Verovio crashes with a C++ exception rather than returning an error
-    // when given invalid ZIP data. This is documented behavior.
+    #[cfg(feature = "bundled-data")]
+    #[test]
+    fn test_toolkit_set_midi_tempo_factor_keeps_layout() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit
+            .load_data(&multi_measure_mei(4))
+            .expect("Failed to load MEI");
+        let calls = std::sync::Arc::new(std::sync::Mutex::new(0));
+        let counter = std::sync::Arc::clone(&calls);
+        toolkit.on_relayout(move |_| *counter.lock().unwrap() += 1);
+        let generation = toolkit.generation();
+        let pages = toolkit.page_count();
+        let svg = toolkit.render_to_svg(1).expect("Failed to render SVG");
+
+        toolkit
+            .set_midi_tempo_factor(0.75)
+            .expect("Failed to set tempo");
+
+        assert_ne!(toolkit.generation(), generation);
+        assert_eq!(*calls.lock().unwrap(), 0);
+        assert_eq!(toolkit.page_count(), pages);
+        assert_eq!(toolkit.render_to_svg(1).expect("Failed to render SVG"), svg);
+    }
 
     #[test]
-    fn test_load_source_str_null_byte() {
-        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.load("data with \0 null byte");
-        assert!(result.is_err());
+    fn test_toolkit_render_to_svg_cropped_no_data() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        assert!(toolkit.render_to_svg_cropped(1, 0.0).is_err());
     }
 
     #[test]
-    fn test_load_source_zip_base64_null_byte() {
-        let mut toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
-        let result = toolkit.load(ZipBase64("data with \0 null byte"));
-        assert!(result.is_err());
+    fn test_toolkit_render_to_svg_cropped_invalid_padding() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        for padding in [-1.0, f64::NAN, f64::INFINITY] {
+            let err = toolkit.render_to_svg_cropped(1, padding).unwrap_err();
+            assert!(err.to_string().contains("invalid crop padding"));
+        }
     }
 
     #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_load_source_str_valid_mei() {
+    fn test_toolkit_render_to_svg_cropped() {
         let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit
+            .load_data(&multi_measure_mei(2))
+            .expect("Failed to load MEI");
 
-        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
-<mei xmlns="http://www.music-encoding.org/ns/mei">
-  <music><body><mdiv><score>
-    <scoreDef><staffGrp><staffDef n="1" lines="5" clef.shape="G" clef.line="2"/></staffGrp></scoreDef>
-    <section><measure><staff n="1"><layer n="1"><note pname="c" oct="4" dur="4"/></layer></staff></measure></section>
-  </score></mdiv></body></music>
-</mei>"#;
+        let full = toolkit.page_view_box(1).expect("Failed to get viewBox");
+        let svg = toolkit
+            .render_to_svg_cropped(1, 20.0)
+            .expect("Failed to render SVG");
+        let cropped = crate::svg::root_view_box(&svg).expect("Cropped SVG should have a viewBox");
 
-        let result = toolkit.load(mei);
-        assert!(result.is_ok());
-        assert!(toolkit.page_count() > 0);
+        assert!(cropped.width > 0.0 && cropped.width < full.width);
+        assert!(cropped.height > 0.0 && cropped.height < full.height);
+        assert!(svg.contains(r#"class="note""#));
     }
 
-    #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_load_source_str_valid_abc() {
-        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    fn test_toolkit_element_at_point_no_data() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        assert!(toolkit.element_at_point(1, 0.0, 0.0).is_err());
+    }
 
-        let abc = r#"X:1
-T:Test
-M:4/4
-K:C
-CDEF|"#;
+    #[test]
+    fn test_toolkit_with_temporary_options_restores() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let before = toolkit.get_options();
 
-        let result = toolkit.load(abc);
-        assert!(result.is_ok());
+        let view_box = toolkit
+            .with_temporary_options(r#"{"svgViewBox": true}"#, |toolkit| {
+                toolkit.current_options().unwrap().svg_view_box
+            })
+            .expect("Failed to set options");
+        assert_eq!(view_box, Some(true));
+        assert_eq!(toolkit.get_options(), before);
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            toolkit.with_temporary_options::<()>(r#"{"svgViewBox": true}"#, |_| {
+                panic!("render failed")
+            })
+        }));
+        assert!(result.is_err());
+        assert_eq!(toolkit.get_options(), before);
     }
 
     #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_load_dispatches_correctly_str() {
-        // Verify that load(&str) dispatches to load_data
+    fn test_toolkit_element_at_point_hits_note_center() {
         let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        let mei = multi_measure_mei(2).replacen(
+            r#"<note pname="e""#,
+            r#"<note xml:id="note-e" pname="e""#,
+            1,
+        );
+        toolkit.load_data(&mei).expect("Failed to load MEI");
 
-        let mei = r#"<?xml version="1.0" encoding="UTF-8"?>
-<mei xmlns="http://www.music-encoding.org/ns/mei">
-  <music><body><mdiv><score>
-    <scoreDef><staffGrp><staffDef n="1" lines="5" clef.shape="G" clef.line="2"/></staffGrp></scoreDef>
-    <section><measure><staff n="1"><layer n="1"><note pname="c" oct="4" dur="4"/></layer></staff></measure></section>
-  </score></mdiv></body></music>
-</mei>"#;
+        let svg = toolkit
+            .with_temporary_options(r#"{"svgBoundingBoxes": true}"#, |toolkit| {
+                toolkit.render_to_svg(1)
+            })
+            .expect("Failed to set options")
+            .expect("Failed to render SVG");
+        let doc = roxmltree::Document::parse(&svg).expect("SVG should be well-formed");
+        let rect = doc
+            .descendants()
+            .find(|node| node.attribute("id") == Some("note-e"))
+            .and_then(|note| {
+                note.children().find(|node| {
+                    node.attribute("class")
+                        .is_some_and(|class| class.split_whitespace().any(|c| c == "bounding-box"))
+                })
+            })
+            .and_then(|bbox| bbox.descendants().find(|node| node.has_tag_name("rect")))
+            .expect("note should have a bounding box");
+        let number = |name: &str| -> f64 { rect.attribute(name).unwrap().parse().unwrap() };
+        let (dx, dy) = rect
+            .ancestors()
+            .take_while(|node| !node.has_tag_name("svg"))
+            .filter_map(|node| node.attribute("transform"))
+            .filter_map(|transform| {
+                let inner = transform.strip_prefix("translate(")?.strip_suffix(')')?;
+                let mut values = inner.split([',', ' ']).filter(|v| !v.is_empty());
+                let x: f64 = values.next()?.parse().ok()?;
+                let y: f64 = values.next().map_or(Some(0.0), |v| v.parse().ok())?;
+                Some((x, y))
+            })
+            .fold((0.0, 0.0), |(x, y), (dx, dy)| (x + dx, y + dy));
+        let x = number("x") + dx + number("width") / 2.0;
+        let y = number("y") + dy + number("height") / 2.0;
+
+        let hit = toolkit
+            .element_at_point(1, x, y)
+            .expect("Failed to hit-test");
+        assert_eq!(hit.as_deref(), Some("note-e"));
+        assert_eq!(
+            toolkit
+                .element_at_point(1, -1.0e6, -1.0e6)
+                .expect("Failed to hit-test"),
+            None
+        );
+    }
 
-        // Load using unified method
-        toolkit.load(mei).expect("Failed to load via load()");
-        let page_count_load = toolkit.page_count();
+    #[test]
+    fn test_toolkit_measure_at_time_no_data() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        let err = toolkit.measure_at_time(0).unwrap_err();
+        assert!(err.to_string().contains("no data loaded"));
+    }
 
-        // Load using direct method
-        let mut toolkit2 = Toolkit::new().expect("Failed to create toolkit");
-        toolkit2
-            .load_data(mei)
-            .expect("Failed to load via load_data()");
-        let page_count_direct = toolkit2.page_count();
+    #[cfg(feature = "bundled-data")]
+    #[test]
+    fn test_toolkit_measure_at_time() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit
+            .load_data(&multi_measure_mei(4))
+            .expect("Failed to load MEI");
+        let measures =
+            crate::mei::measure_ids(&toolkit.get_mei().expect("Failed to export MEI")).unwrap();
+        assert_eq!(measures.len(), 4);
+
+        let first = toolkit.measure_at_time(0).expect("Failed to look up time");
+        assert_eq!(first.as_ref(), Some(&measures[0]));
+        let later = toolkit
+            .measure_at_time(3000)
+            .expect("Failed to look up time")
+            .expect("A measure should sound at 3 s");
+        assert!(measures.contains(&later));
+        assert_ne!(Some(later), first);
+
+        assert_eq!(
+            toolkit
+                .measure_at_time(10_000_000)
+                .expect("Failed to look up time"),
+            None
+        );
+    }
 
-        assert_eq!(page_count_load, page_count_direct);
+    #[test]
+    fn test_toolkit_cursor_svg_for_time_no_data() {
+        let toolkit = Toolkit::without_resources().expect("Failed to create toolkit");
+        assert!(toolkit.cursor_svg_for_time(0, 1).is_err());
     }
 
+    #[cfg(feature = "bundled-data")]
     #[test]
-    fn test_load_source_trait_is_object_safe() {
-        // This test verifies the trait can be used as a trait object (if needed)
-        fn accepts_load_source(_source: &dyn std::any::Any) {}
-        let source: &str = "test";
-        accepts_load_source(&source);
+    fn test_toolkit_cursor_svg_for_time() {
+        let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+        toolkit
+            .load_data(&multi_measure_mei(4))
+            .expect("Failed to load MEI");
+
+        let overlay = toolkit
+            .cursor_svg_for_time(0, 1)
+            .expect("Failed to build cursor")
+            .expect("A note should be active at time 0");
+        let doc = roxmltree::Document::parse(&overlay).expect("Overlay should be well-formed");
+        let root = doc.root_element();
+        assert!(root.has_tag_name("svg"));
+        assert!(root.attribute("viewBox").is_some());
+        let line = root
+            .first_element_child()
+            .expect("Overlay should have a line");
+        assert_eq!(line.attribute("class"), Some("cursor"));
+
+        // Nothing is sounding long after the end of the score
+        let after_end = toolkit
+            .cursor_svg_for_time(10_000_000, 1)
+            .expect("Failed to build cursor");
+        assert!(after_end.is_none());
+
+        assert!(toolkit.cursor_svg_for_time(u32::MAX, 1).is_err());
     }
 }
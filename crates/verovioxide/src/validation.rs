@@ -0,0 +1,222 @@
+//! Structural checks on loaded documents.
+//!
+//! [`ValidationIssue`]s are collected from two sources: warnings and errors in
+//! Verovio's log, and structural checks on the MEI exported from the toolkit.
+
+use std::collections::HashSet;
+
+use serde::Serialize;
+
+use crate::Result;
+
+/// The XML namespace of the `xml:id` attribute.
+const XML_NS: &str = "http://www.w3.org/XML/1998/namespace";
+
+/// Control events that span from a start to an end element.
+const SPANNING_ELEMENTS: &[&str] = &[
+    "bracketSpan",
+    "gliss",
+    "hairpin",
+    "lv",
+    "octave",
+    "phrase",
+    "slur",
+    "tie",
+    "trill",
+];
+
+/// How serious a [`ValidationIssue`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// The document renders, but probably not as intended.
+    Warning,
+    /// Part of the document is broken or was dropped.
+    Error,
+}
+
+/// A problem found in a loaded document.
+///
+/// # See also
+///
+/// - [`Toolkit::validate_loaded`](crate::Toolkit::validate_loaded) - Validate the loaded document
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ValidationIssue {
+    /// How serious the issue is.
+    pub severity: Severity,
+    /// A human-readable description of the issue.
+    pub message: String,
+    /// The xml:id of the element the issue concerns, if known.
+    pub element_id: Option<String>,
+}
+
+impl ValidationIssue {
+    fn new(severity: Severity, message: String, element_id: Option<&str>) -> Self {
+        Self {
+            severity,
+            message,
+            element_id: element_id.map(String::from),
+        }
+    }
+}
+
+/// Returns the warnings and errors in a Verovio log buffer.
+///
+/// Log lines look like `[Warning] message`; other levels are ignored.
+pub(crate) fn log_issues(log: &str) -> Vec<ValidationIssue> {
+    log.lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            let (severity, message) = if let Some(message) = line.strip_prefix("[Warning]") {
+                (Severity::Warning, message)
+            } else if let Some(message) = line.strip_prefix("[Error]") {
+                (Severity::Error, message)
+            } else {
+                return None;
+            };
+            Some(ValidationIssue::new(
+                severity,
+                message.trim().to_string(),
+                None,
+            ))
+        })
+        .collect()
+}
+
+/// Checks an exported MEI document for structural problems.
+///
+/// Reported are spanning control events (slurs, ties, hairpins, ...) without an
+/// end, references to elements that do not exist, and staves whose first
+/// staff definition has no clef.
+pub(crate) fn mei_issues(mei: &str) -> Result<Vec<ValidationIssue>> {
+    let doc = crate::mei::parse(mei)?;
+    let mut issues = Vec::new();
+
+    let ids: HashSet<&str> = doc
+        .descendants()
+        .filter_map(|node| node.attribute((XML_NS, "id")))
+        .collect();
+
+    for node in doc.descendants().filter(|node| node.is_element()) {
+        let name = node.tag_name().name();
+        let id = node.attribute((XML_NS, "id"));
+
+        for attr in ["startid", "endid"] {
+            if let Some(target) = node.attribute(attr) {
+                let target = target.trim_start_matches('#');
+                if !ids.contains(target) {
+                    issues.push(ValidationIssue::new(
+                        Severity::Error,
+                        format!(
+                            "<{}> @{} references missing element #{}",
+                            name, attr, target
+                        ),
+                        id,
+                    ));
+                }
+            }
+        }
+
+        if SPANNING_ELEMENTS.contains(&name)
+            && node.has_attribute("startid")
+            && !node.has_attribute("endid")
+            && !node.has_attribute("tstamp2")
+        {
+            issues.push(ValidationIssue::new(
+                Severity::Warning,
+                format!("unterminated <{}>: no @endid or @tstamp2", name),
+                id,
+            ));
+        }
+    }
+
+    let mut seen_staves = HashSet::new();
+    for staff_def in doc
+        .descendants()
+        .filter(|node| node.has_tag_name("staffDef"))
+    {
+        let Some(n) = staff_def.attribute("n") else {
+            continue;
+        };
+        if !seen_staves.insert(n) {
+            continue;
+        }
+        let has_clef = staff_def.has_attribute("clef.shape")
+            || staff_def.children().any(|child| child.has_tag_name("clef"));
+        if !has_clef {
+            issues.push(ValidationIssue::new(
+                Severity::Warning,
+                format!("staff {} has no clef", n),
+                staff_def.attribute((XML_NS, "id")),
+            ));
+        }
+    }
+
+    Ok(issues)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_issues() {
+        let log = "[Info] Loading\n[Warning] MusicXML import: Dangling ending tag skipped\n\
+                   [Error] Unsupported element <foo>\n[Debug] done\n";
+        assert_eq!(
+            log_issues(log),
+            vec![
+                ValidationIssue {
+                    severity: Severity::Warning,
+                    message: "MusicXML import: Dangling ending tag skipped".into(),
+                    element_id: None,
+                },
+                ValidationIssue {
+                    severity: Severity::Error,
+                    message: "Unsupported element <foo>".into(),
+                    element_id: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_mei_issues_clean_document() {
+        let mei = r##"<mei><scoreDef><staffDef n="1"><clef shape="G" line="2"/></staffDef>
+            <staffDef n="1"/></scoreDef>
+            <note xml:id="n1"/><note xml:id="n2"/>
+            <slur xml:id="s1" startid="#n1" endid="#n2"/>
+            <hairpin xml:id="h1" startid="#n1" tstamp2="0m+3"/></mei>"##;
+        assert!(mei_issues(mei).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_mei_issues_unterminated_slur() {
+        let mei = r##"<mei><staffDef n="1" clef.shape="G"/>
+            <note xml:id="n1"/><slur xml:id="s1" startid="#n1"/></mei>"##;
+        assert_eq!(
+            mei_issues(mei).unwrap(),
+            vec![ValidationIssue {
+                severity: Severity::Warning,
+                message: "unterminated <slur>: no @endid or @tstamp2".into(),
+                element_id: Some("s1".into()),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_mei_issues_missing_reference_and_clef() {
+        let mei = r##"<mei><staffDef xml:id="sd1" n="1"/>
+            <note xml:id="n1"/><tie xml:id="t1" startid="#n1" endid="#gone"/></mei>"##;
+        let issues = mei_issues(mei).unwrap();
+        assert_eq!(issues.len(), 2);
+        assert_eq!(issues[0].severity, Severity::Error);
+        assert_eq!(
+            issues[0].message,
+            "<tie> @endid references missing element #gone"
+        );
+        assert_eq!(issues[0].element_id.as_deref(), Some("t1"));
+        assert_eq!(issues[1].message, "staff 1 has no clef");
+        assert_eq!(issues[1].element_id.as_deref(), Some("sd1"));
+    }
+}
@@ -8,7 +8,7 @@
 //! - Error handling for invalid input
 
 use serial_test::serial;
-use verovioxide::{Options, Toolkit};
+use verovioxide::{BreakMode, OptimizeLevel, Options, Toolkit};
 
 // =============================================================================
 // Test Fixtures
@@ -23,6 +23,16 @@ const SIMPLE_MEI: &str = include_str!("../../../test-fixtures/mei/simple.mei");
 /// Simple ABC notation file with "Twinkle Twinkle Little Star".
 const SIMPLE_ABC: &str = include_str!("../../../test-fixtures/abc/simple.abc");
 
+/// The simple MusicXML scale with an encoded page break before measure 3.
+const TWO_PAGE_MUSICXML: &str = include_str!("../../../test-fixtures/musicxml/two-pages.musicxml");
+
+/// A two-part MusicXML duet in the score-timewise layout (2 measures).
+const TIMEWISE_MUSICXML: &str = include_str!("../../../test-fixtures/musicxml/timewise.musicxml");
+
+/// The simple MusicXML scale encoded as ISO-8859-1, with an accented composer.
+#[cfg(feature = "encoding")]
+const LATIN1_MUSICXML: &[u8] = include_bytes!("../../../test-fixtures/musicxml/latin1.musicxml");
+
 // =============================================================================
 // Helper Functions
 // =============================================================================
@@ -136,6 +146,32 @@ fn test_render_mei() {
     assert_valid_svg(&svg);
 }
 
+/// Test that SVG optimization shrinks the rendered MEI fixture.
+#[test]
+#[serial]
+fn test_render_mei_optimized_sizes() {
+    let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    toolkit
+        .load_data(SIMPLE_MEI)
+        .expect("Failed to load MEI data");
+
+    let plain = toolkit.render_to_svg(1).expect("Failed to render SVG");
+    let basic = toolkit
+        .render_to_svg_optimized(1, OptimizeLevel::Basic)
+        .expect("Failed to render optimized SVG");
+    let aggressive = toolkit
+        .render_to_svg_optimized(1, OptimizeLevel::Aggressive)
+        .expect("Failed to render optimized SVG");
+    assert_valid_svg(&basic);
+    assert_valid_svg(&aggressive);
+
+    assert!(basic.len() < plain.len(), "Basic should shrink the SVG");
+    assert!(
+        aggressive.len() <= basic.len(),
+        "Aggressive should not grow the SVG beyond Basic"
+    );
+}
+
 // =============================================================================
 // ABC Notation Rendering Tests
 // =============================================================================
@@ -227,7 +263,7 @@ fn test_options() {
     assert_valid_svg(&svg);
 
     // Verify scale was set correctly
-    assert_eq!(toolkit.get_scale(), 80, "Scale should be 80");
+    assert_eq!(toolkit.scale(), 80, "Scale should be 80");
 }
 
 /// Test setting and retrieving scale independently.
@@ -237,16 +273,16 @@ fn test_options_scale() {
     let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
 
     // Get default scale
-    let default_scale = toolkit.get_scale();
+    let default_scale = toolkit.scale();
     assert!(default_scale > 0, "Default scale should be positive");
 
     // Set new scale
     toolkit.set_scale(50).expect("Failed to set scale to 50");
-    assert_eq!(toolkit.get_scale(), 50);
+    assert_eq!(toolkit.scale(), 50);
 
     // Set back to 100
     toolkit.set_scale(100).expect("Failed to set scale to 100");
-    assert_eq!(toolkit.get_scale(), 100);
+    assert_eq!(toolkit.scale(), 100);
 }
 
 /// Test that options can be retrieved as JSON.
@@ -277,12 +313,12 @@ fn test_options_reset() {
     let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
 
     // Get initial scale (should be 100)
-    let initial_scale = toolkit.get_scale();
+    let initial_scale = toolkit.scale();
     assert_eq!(initial_scale, 100, "Initial scale should be 100");
 
     // Change scale
     toolkit.set_scale(50).expect("Failed to set scale");
-    assert_eq!(toolkit.get_scale(), 50, "Scale should be 50 after setting");
+    assert_eq!(toolkit.scale(), 50, "Scale should be 50 after setting");
 
     // Reset options - this calls Verovio's resetOptions
     // Note: Verovio's reset behavior for scale may vary between versions
@@ -290,7 +326,7 @@ fn test_options_reset() {
 
     // Just verify reset_options doesn't crash and the toolkit is still usable
     // The exact reset behavior is Verovio-version dependent
-    let _scale = toolkit.get_scale();
+    let _scale = toolkit.scale();
     let _options = toolkit.get_options();
     assert!(
         !_options.is_empty(),
@@ -350,6 +386,102 @@ fn test_get_mei_export_with_options() {
     assert!(mei.contains("<mei"));
 }
 
+/// MEI with explicit `xml:id`s on every element of interest.
+const MEI_WITH_IDS: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<mei xmlns="http://www.music-encoding.org/ns/mei">
+  <music>
+    <body>
+      <mdiv xml:id="mdiv-main">
+        <score>
+          <scoreDef meter.count="4" meter.unit="4">
+            <staffGrp>
+              <staffDef n="1" lines="5" clef.shape="G" clef.line="2"/>
+            </staffGrp>
+          </scoreDef>
+          <section>
+            <measure xml:id="measure-one" n="1">
+              <staff n="1">
+                <layer n="1">
+                  <note xml:id="note-c4" pname="c" oct="4" dur="2"/>
+                  <note xml:id="note-e4" pname="e" oct="4" dur="2"/>
+                </layer>
+              </staff>
+            </measure>
+          </section>
+        </score>
+      </mdiv>
+    </body>
+  </music>
+</mei>"#;
+
+const PRESERVED_IDS: [&str; 4] = ["mdiv-main", "measure-one", "note-c4", "note-e4"];
+
+/// Test that `xml:id`s survive a default MEI export.
+#[test]
+#[serial]
+fn test_get_mei_preserves_ids_by_default() {
+    use verovioxide::Mei;
+
+    let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    toolkit
+        .load_data(MEI_WITH_IDS)
+        .expect("Failed to load MEI data");
+
+    let exports = [
+        toolkit.get_mei().expect("Failed to export MEI"),
+        toolkit.render(Mei).expect("Failed to render MEI"),
+        toolkit
+            .render(Mei::with_options().preserve_ids(true))
+            .expect("Failed to render MEI with options"),
+    ];
+
+    for mei in &exports {
+        for id in PRESERVED_IDS {
+            assert!(
+                mei.contains(&format!("xml:id=\"{}\"", id)),
+                "Exported MEI should keep xml:id {}",
+                id
+            );
+        }
+    }
+}
+
+/// Test that `ensure_ids` gives id-less MusicXML ids that element queries
+/// resolve.
+#[test]
+#[serial]
+fn test_ensure_ids_musicxml() {
+    let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    toolkit
+        .load_data(SIMPLE_MUSICXML)
+        .expect("Failed to load MusicXML");
+    toolkit.ensure_ids().expect("Failed to ensure ids");
+
+    let mei = toolkit.get_mei().expect("Failed to export MEI");
+    let note = &mei[mei.find("<note ").expect("MEI should contain a note")..];
+    let note = &note[..note.find('>').unwrap()];
+    let id = note
+        .split("xml:id=\"")
+        .nth(1)
+        .and_then(|rest| rest.split('"').next())
+        .expect("Note should have an xml:id");
+
+    assert_eq!(toolkit.get_page_with_element(id).unwrap(), 1);
+    assert!(
+        toolkit
+            .render_to_svg(1)
+            .expect("Failed to render page")
+            .contains(&format!("id=\"{}\"", id))
+    );
+    assert!(
+        toolkit
+            .get_mei()
+            .expect("Failed to export MEI")
+            .contains(&format!("xml:id=\"{}\"", id)),
+        "Ids should survive another export"
+    );
+}
+
 // =============================================================================
 // Error Handling Tests
 // =============================================================================
@@ -454,6 +586,78 @@ fn test_render_all_pages() {
     }
 }
 
+/// Test that encoded MusicXML page breaks are honored under `BreakMode::Encoded`.
+#[test]
+#[serial]
+fn test_musicxml_encoded_page_breaks() {
+    let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+
+    toolkit
+        .load_data(TWO_PAGE_MUSICXML)
+        .expect("Failed to load MusicXML data");
+    assert_eq!(toolkit.page_count(), 1, "Four measures fit on one page");
+
+    toolkit
+        .set_breaks(BreakMode::Encoded)
+        .expect("Failed to set breaks");
+    assert_eq!(toolkit.page_count(), 2);
+    for page in 1..=2 {
+        assert_valid_svg(&toolkit.render_to_svg(page).expect("Failed to render page"));
+    }
+
+    // Setting the break mode before loading works the same way
+    let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    toolkit
+        .set_breaks(BreakMode::Encoded)
+        .expect("Failed to set breaks");
+    toolkit
+        .load_data(TWO_PAGE_MUSICXML)
+        .expect("Failed to load MusicXML data");
+    assert_eq!(toolkit.page_count(), 2);
+}
+
+/// Test loading score-timewise MusicXML by converting it to partwise.
+#[test]
+#[serial]
+fn test_load_timewise_musicxml() {
+    let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    toolkit
+        .load_musicxml(TIMEWISE_MUSICXML)
+        .expect("Should load timewise MusicXML");
+    assert!(toolkit.page_count() > 0);
+
+    let mei = toolkit.get_mei().expect("Failed to export MEI");
+    assert!(mei.contains("Timewise Duet"));
+    assert_eq!(mei.matches("<measure ").count(), 2);
+    assert_eq!(mei.matches("<staff ").count(), 4);
+    assert_valid_svg(&toolkit.render_to_svg(1).expect("Failed to render page"));
+
+    // Partwise MusicXML loads unchanged
+    toolkit
+        .load_musicxml(SIMPLE_MUSICXML)
+        .expect("Should load partwise MusicXML");
+    assert!(toolkit.page_count() > 0);
+}
+
+/// Test loading ISO-8859-1 MusicXML keeps accented text intact.
+#[cfg(feature = "encoding")]
+#[test]
+#[serial]
+fn test_load_latin1_musicxml() {
+    let mut toolkit = Toolkit::new().expect("Failed to create toolkit");
+    assert!(
+        toolkit.load_data_bytes(LATIN1_MUSICXML).is_ok(),
+        "Should load Latin-1 MusicXML"
+    );
+    assert!(toolkit.page_count() > 0);
+
+    let mei = toolkit.get_mei().expect("Failed to export MEI");
+    assert!(
+        mei.contains("Frédéric Chopin"),
+        "Composer name should survive transcoding"
+    );
+}
+
 // =============================================================================
 // SVG Output Variation Tests
 // =============================================================================
@@ -625,8 +829,8 @@ fn test_readme_quick_start_pattern() {
 // =============================================================================
 
 use verovioxide::{
-    Attrs, Elements, ExpansionMap, Features, Humdrum, Mei, Midi, Pae, Page, Svg, Time, Timemap,
-    Times,
+    Attrs, Elements, ExpansionMap, Features, Humdrum, Mei, Midi, OutputFormat, Pae, Page, Svg,
+    Time, Timemap, Times,
 };
 
 /// Test the unified render() method with Svg::page().
@@ -788,6 +992,27 @@ fn test_render_mei_with_options() {
     assert!(mei.contains("<mei"));
 }
 
+/// Test that page-based MEI export differs from the score-based default.
+#[test]
+#[serial]
+fn test_render_mei_score_based_toggle() {
+    let mut voxide = Toolkit::new().expect("Failed to create toolkit");
+    voxide
+        .load(SIMPLE_MUSICXML)
+        .expect("Failed to load MusicXML");
+
+    let score_based: String = voxide
+        .render(Mei::with_options().score_based(true))
+        .expect("Failed to render score-based MEI");
+    let page_based: String = voxide
+        .render(Mei::with_options().score_based(false))
+        .expect("Failed to render page-based MEI");
+
+    assert_ne!(score_based, page_based);
+    assert!(!score_based.contains("<page"));
+    assert!(page_based.contains("<page"));
+}
+
 /// Test the unified render() method with Humdrum.
 #[test]
 #[serial]
@@ -1392,6 +1617,54 @@ fn test_render_to_as_svg_pages_creates_directory() {
     assert!(dir_path.is_dir(), "Should be a directory");
 }
 
+/// Test export_all() writes every requested format and returns the paths.
+#[test]
+#[serial]
+fn test_export_all_svg_and_midi() {
+    let mut voxide = Toolkit::new().expect("Failed to create toolkit");
+    voxide.load(SIMPLE_MEI).expect("Failed to load MEI");
+
+    let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let out_dir = temp_dir.path().join("exports");
+
+    let paths = voxide
+        .export_all(&out_dir, "score", &[OutputFormat::Svg, OutputFormat::Midi])
+        .expect("Failed to export");
+
+    let page1 = out_dir.join("score").join("page-001.svg");
+    let midi = out_dir.join("score.mid");
+    assert_eq!(paths.len(), voxide.page_count() as usize + 1);
+    assert_eq!(paths.first(), Some(&page1));
+    assert_eq!(paths.last(), Some(&midi));
+    for path in &paths {
+        assert!(path.is_file(), "{} should exist", path.display());
+    }
+
+    assert_valid_svg(&std::fs::read_to_string(&page1).expect("Failed to read SVG"));
+    let bytes = std::fs::read(&midi).expect("Failed to read MIDI");
+    assert!(bytes.starts_with(b"MThd"), "MIDI should start with MThd");
+}
+
+/// Test that export_all() rejects basenames that would escape the directory.
+#[test]
+#[serial]
+fn test_export_all_invalid_basename() {
+    let mut voxide = Toolkit::new().expect("Failed to create toolkit");
+    voxide.load(SIMPLE_MEI).expect("Failed to load MEI");
+
+    let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+    assert!(
+        voxide
+            .export_all(temp_dir.path(), "", &[OutputFormat::Mei])
+            .is_err()
+    );
+    assert!(
+        voxide
+            .export_all(temp_dir.path(), "../score", &[OutputFormat::Mei])
+            .is_err()
+    );
+}
+
 // =============================================================================
 // PNG Rendering Tests (feature-gated)
 // =============================================================================